@@ -39,6 +39,39 @@ fn build_dfa_with_setting(query: &str, setting: PrefixSetting) -> DFA {
     }
 }
 
+fn build_dfa_with_typos_setting(query: &str, typos: u8, setting: PrefixSetting) -> DFA {
+    use PrefixSetting::{NoPrefix, Prefix};
+
+    let builder = match typos {
+        0 => LEVDIST0.get_or_init(|| LevBuilder::new(0, true)),
+        1 => LEVDIST1.get_or_init(|| LevBuilder::new(1, true)),
+        _ => LEVDIST2.get_or_init(|| LevBuilder::new(2, true)),
+    };
+
+    match setting {
+        Prefix => builder.build_prefix_dfa(query),
+        NoPrefix => builder.build_dfa(query),
+    }
+}
+
+/// Returns the number of typos allowed for the word so that the words matching it
+/// are at least `min_similarity` similar, i.e. `(1 - min_similarity) * length`, rounded down
+/// so that a 3 characters word allows no typo at 0.8, and capped to two typos.
+pub fn typos_for_similarity(min_similarity: f32, word: &str) -> u8 {
+    let length = word.chars().count() as f32;
+    // `length - similarity * length` is exact for the round similarities, `1 - similarity` is not
+    let typos = (length - min_similarity.max(0.0).min(1.0) * length).floor();
+    typos.min(2.0) as u8
+}
+
+pub fn build_prefix_dfa_with_typos(query: &str, typos: u8) -> DFA {
+    build_dfa_with_typos_setting(query, typos, PrefixSetting::Prefix)
+}
+
+pub fn build_dfa_with_typos(query: &str, typos: u8) -> DFA {
+    build_dfa_with_typos_setting(query, typos, PrefixSetting::NoPrefix)
+}
+
 pub fn build_prefix_dfa(query: &str) -> DFA {
     build_dfa_with_setting(query, PrefixSetting::Prefix)
 }
@@ -51,3 +84,27 @@ pub fn build_exact_dfa(query: &str) -> DFA {
     let builder = LEVDIST0.get_or_init(|| LevBuilder::new(0, true));
     builder.build_dfa(query)
 }
+
+/// Builds a DFA accepting the words at a distance of, at most, two typos,
+/// one for the short words, regardless of the typo budget used by the search.
+pub fn build_spellcheck_dfa(query: &str) -> DFA {
+    let builder = match query.len() {
+        0..=3 => LEVDIST1.get_or_init(|| LevBuilder::new(1, true)),
+        _ => LEVDIST2.get_or_init(|| LevBuilder::new(2, true)),
+    };
+    builder.build_dfa(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typos_proportional_to_length() {
+        assert_eq!(typos_for_similarity(0.8, "abc"), 0);
+        assert_eq!(typos_for_similarity(0.8, "abcde"), 1);
+        assert_eq!(typos_for_similarity(0.8, "abcdefghij"), 2);
+        assert_eq!(typos_for_similarity(0.0, "abcdefghij"), 2);
+        assert_eq!(typos_for_similarity(1.0, "abcdefghij"), 0);
+    }
+}
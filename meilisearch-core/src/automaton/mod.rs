@@ -2,7 +2,14 @@ mod dfa;
 
 use meilisearch_tokenizer::is_cjk;
 
-pub use self::dfa::{build_dfa, build_prefix_dfa, build_exact_dfa};
+pub use self::dfa::{build_dfa, build_prefix_dfa, build_exact_dfa, build_spellcheck_dfa};
+pub use self::dfa::{build_dfa_with_typos, build_prefix_dfa_with_typos, typos_for_similarity};
+
+/// Normalizes the words synonyms are defined for like the words of the queries, their case is
+/// folded first so that the words only differing by their case are the same, e.g. `Straße` and `STRASSE`.
+pub fn normalize_synonym(string: &str) -> String {
+    normalize_str(&caseless::default_case_fold_str(string))
+}
 
 pub fn normalize_str(string: &str) -> String {
     let mut string = string.to_lowercase();
@@ -9,20 +9,23 @@ use std::time::Instant;
 use std::fmt;
 
 use compact_arena::{SmallArena, Idx32, mk_arena};
+use indexmap::IndexMap;
 use log::debug;
 use meilisearch_types::DocIndex;
 use sdset::{Set, SetBuf, exponential_search, SetOperation, Counter, duo::OpBuilder};
 use slice_group_by::{GroupBy, GroupByMut};
 
 use crate::error::Error;
-use crate::criterion::{Criteria, Context, ContextMut};
+use crate::criterion::{Criteria, Context, ContextMut, ExplainContext};
 use crate::distinct_map::{BufferedDistinctMap, DistinctMap};
+use crate::facets::FacetNode;
 use crate::raw_document::RawDocument;
 use crate::{database::MainT, reordered_attrs::ReorderedAttrs};
 use crate::{store, Document, DocumentId, MResult};
-use crate::query_tree::{create_query_tree, traverse_query_tree};
+use crate::query_tree::{create_query_tree, synonyms_expansions, traverse_query_tree};
 use crate::query_tree::{Operation, QueryResult, QueryKind, QueryId, PostingsKey};
 use crate::query_tree::Context as QTContext;
+use crate::stemming::query_stemmers;
 
 #[derive(Debug, Default)]
 pub struct SortResult {
@@ -31,6 +34,10 @@ pub struct SortResult {
     pub exhaustive_nb_hit: bool,
     pub facets: Option<HashMap<String, HashMap<String, usize>>>,
     pub exhaustive_facets_count: Option<bool>,
+    /// The count of the values of the hierarchical facets, level by level.
+    pub facet_tree: Option<IndexMap<String, FacetNode>>,
+    /// The wildcard prefixes that matched too many words, only the first ones were searched.
+    pub truncated_wildcards: Vec<String>,
 }
 
 pub fn bucket_sort<'c, FI>(
@@ -39,9 +46,13 @@ pub fn bucket_sort<'c, FI>(
     range: Range<usize>,
     facets_docids: Option<SetBuf<DocumentId>>,
     facet_count_docids: Option<HashMap<String, HashMap<String, Cow<Set<DocumentId>>>>>,
+    facet_tree_docids: Option<Vec<(String, HashMap<String, Cow<Set<DocumentId>>>)>>,
     filter: Option<FI>,
     criteria: Criteria<'c>,
     searchable_attrs: Option<ReorderedAttrs>,
+    min_similarity: Option<f32>,
+    max_wildcard_expansions: usize,
+    explain_ranking: bool,
     main_store: store::Main,
     postings_lists_store: store::PostingsLists,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
@@ -63,11 +74,15 @@ where
             range,
             facets_docids,
             facet_count_docids,
+            facet_tree_docids,
             filter,
             distinct,
             distinct_size,
             criteria,
             searchable_attrs,
+            min_similarity,
+            max_wildcard_expansions,
+            explain_ranking,
             main_store,
             postings_lists_store,
             documents_fields_counts_store,
@@ -86,8 +101,13 @@ where
         words_set,
         stop_words,
         synonyms: synonyms_store,
+        synonyms_expansions: synonyms_expansions(reader, main_store)?,
         postings_lists: postings_lists_store,
         prefix_postings_lists: prefix_postings_lists_cache_store,
+        phonetic_matching: main_store.phonetic_matching(reader)?.unwrap_or(false),
+        min_similarity,
+        stemmers: query_stemmers(reader, main_store, query)?,
+        max_wildcard_expansions,
     };
 
     let (operation, mapping) = create_query_tree(reader, &context, query)?;
@@ -105,7 +125,8 @@ where
     let mut queries_kinds = HashMap::new();
     recurs_operation(&mut queries_kinds, &operation);
 
-    let QueryResult { mut docids, queries } = traverse_query_tree(reader, &context, &operation)?;
+    let QueryResult { mut docids, queries, truncated_wildcards } = traverse_query_tree(reader, &context, &operation)?;
+    result.truncated_wildcards = truncated_wildcards;
     debug!("found {} documents", docids.len());
     debug!("number of postings {:?}", queries.len());
 
@@ -122,6 +143,10 @@ where
         result.facets = Some(facet_count(f, &docids));
     }
 
+    if let Some(levels) = facet_tree_docids {
+        result.facet_tree = Some(facet_tree(&levels, &docids));
+    }
+
     let before = Instant::now();
     mk_arena!(arena);
     let mut bare_matches = cleanup_bare_matches(&mut arena, &docids, queries);
@@ -145,7 +170,7 @@ where
 
     let mut groups = vec![raw_documents.as_mut_slice()];
 
-    'criteria: for criterion in criteria.as_ref() {
+    'criteria: for (criterion_index, criterion) in criteria.as_ref().iter().enumerate() {
         let tmp_groups = mem::replace(&mut groups, Vec::new());
         let mut documents_seen = 0;
 
@@ -171,7 +196,12 @@ where
             group.sort_unstable_by(|a, b| criterion.evaluate(&ctx, a, b));
             debug!("{:?} evaluation took {:.02?}", criterion.name(), before_criterion_sort.elapsed());
 
+            let group_len = group.len();
             for group in group.binary_group_by_mut(|a, b| criterion.eq(&ctx, a, b)) {
+                if explain_ranking && group.len() < group_len {
+                    group.iter_mut().for_each(|document| document.decisive_criteria.push(criterion_index));
+                }
+
                 debug!("{:?} produced a group of size {}", criterion.name(), group.len());
 
                 documents_seen += group.len();
@@ -191,7 +221,13 @@ where
 
     let schema = main_store.schema(reader)?.ok_or(Error::SchemaMissing)?;
     let iter = raw_documents.into_iter().skip(range.start).take(range.len());
-    let iter = iter.map(|rd| Document::from_raw(rd, &queries_kinds, &arena, searchable_attrs.as_ref(), &schema));
+    let explain_ctx = ExplainContext { schema: &schema, searchable_attrs: searchable_attrs.as_ref() };
+    let iter = iter.map(|rd| {
+        let explanation = if explain_ranking { ranking_explanation(&criteria, &explain_ctx, &rd) } else { Vec::new() };
+        let mut document = Document::from_raw(rd, &queries_kinds, &arena, searchable_attrs.as_ref(), &schema);
+        document.ranking_explanation = explanation;
+        document
+    });
     let documents = iter.collect();
 
     debug!("bucket sort took {:.02?}", before_bucket_sort.elapsed());
@@ -208,11 +244,15 @@ pub fn bucket_sort_with_distinct<'c, FI, FD>(
     range: Range<usize>,
     facets_docids: Option<SetBuf<DocumentId>>,
     facet_count_docids: Option<HashMap<String, HashMap<String, Cow<Set<DocumentId>>>>>,
+    facet_tree_docids: Option<Vec<(String, HashMap<String, Cow<Set<DocumentId>>>)>>,
     filter: Option<FI>,
     distinct: FD,
     distinct_size: usize,
     criteria: Criteria<'c>,
     searchable_attrs: Option<ReorderedAttrs>,
+    min_similarity: Option<f32>,
+    max_wildcard_expansions: usize,
+    explain_ranking: bool,
     main_store: store::Main,
     postings_lists_store: store::PostingsLists,
     documents_fields_counts_store: store::DocumentsFieldsCounts,
@@ -233,8 +273,13 @@ where
         words_set,
         stop_words,
         synonyms: synonyms_store,
+        synonyms_expansions: synonyms_expansions(reader, main_store)?,
         postings_lists: postings_lists_store,
         prefix_postings_lists: prefix_postings_lists_cache_store,
+        phonetic_matching: main_store.phonetic_matching(reader)?.unwrap_or(false),
+        min_similarity,
+        stemmers: query_stemmers(reader, main_store, query)?,
+        max_wildcard_expansions,
     };
 
     let (operation, mapping) = create_query_tree(reader, &context, query)?;
@@ -252,7 +297,8 @@ where
     let mut queries_kinds = HashMap::new();
     recurs_operation(&mut queries_kinds, &operation);
 
-    let QueryResult { mut docids, queries } = traverse_query_tree(reader, &context, &operation)?;
+    let QueryResult { mut docids, queries, truncated_wildcards } = traverse_query_tree(reader, &context, &operation)?;
+    result.truncated_wildcards = truncated_wildcards;
     debug!("found {} documents", docids.len());
     debug!("number of postings {:?}", queries.len());
 
@@ -269,6 +315,10 @@ where
         result.facets = Some(facet_count(f, &docids));
     }
 
+    if let Some(levels) = facet_tree_docids {
+        result.facet_tree = Some(facet_tree(&levels, &docids));
+    }
+
     let before = Instant::now();
     mk_arena!(arena);
     let mut bare_matches = cleanup_bare_matches(&mut arena, &docids, queries);
@@ -295,7 +345,7 @@ where
     let mut distinct_map = DistinctMap::new(distinct_size);
     let mut distinct_raw_offset = 0;
 
-    'criteria: for criterion in criteria.as_ref() {
+    'criteria: for (criterion_index, criterion) in criteria.as_ref().iter().enumerate() {
         let tmp_groups = mem::replace(&mut groups, Vec::new());
         let mut buf_distinct = BufferedDistinctMap::new(&mut distinct_map);
         let mut documents_seen = 0;
@@ -329,7 +379,12 @@ where
             group.sort_unstable_by(|a, b| criterion.evaluate(&ctx, a, b));
             debug!("{:?} evaluation took {:.02?}", criterion.name(), before_criterion_sort.elapsed());
 
+            let group_len = group.len();
             for group in group.binary_group_by_mut(|a, b| criterion.eq(&ctx, a, b)) {
+                if explain_ranking && group.len() < group_len {
+                    group.iter_mut().for_each(|document| document.decisive_criteria.push(criterion_index));
+                }
+
                 // we must compute the real distinguished len of this sub-group
                 for document in group.iter() {
                     let filter_accepted = match &filter {
@@ -395,7 +450,15 @@ where
             };
 
             if distinct_accepted && seen.len() > range.start {
-                documents.push(Document::from_raw(raw_document, &queries_kinds, &arena, searchable_attrs.as_ref(), &schema));
+                let explanation = if explain_ranking {
+                    let explain_ctx = ExplainContext { schema: &schema, searchable_attrs: searchable_attrs.as_ref() };
+                    ranking_explanation(&criteria, &explain_ctx, &raw_document)
+                } else {
+                    Vec::new()
+                };
+                let mut document = Document::from_raw(raw_document, &queries_kinds, &arena, searchable_attrs.as_ref(), &schema);
+                document.ranking_explanation = explanation;
+                documents.push(document);
                 if documents.len() == range.len() {
                     break;
                 }
@@ -408,6 +471,19 @@ where
     Ok(result)
 }
 
+/// Describes the criteria that ranked the document apart from the documents it was tied with,
+/// in the order they were applied.
+fn ranking_explanation(criteria: &Criteria, ctx: &ExplainContext, document: &RawDocument) -> Vec<String> {
+    document.decisive_criteria
+        .iter()
+        .filter_map(|index| criteria.as_ref().get(*index))
+        .map(|criterion| match criterion.explain(ctx, document) {
+            Some(explanation) => format!("{} ({})", criterion.name(), explanation),
+            None => criterion.name().to_string(),
+        })
+        .collect()
+}
+
 fn cleanup_bare_matches<'tag, 'txn>(
     arena: &mut SmallArena<'tag, PostingsListView<'txn>>,
     docids: &Set<DocumentId>,
@@ -601,6 +677,52 @@ impl Deref for PostingsListView<'_> {
     }
 }
 
+/// Returns the candidate documents, or all the documents when there are no candidates, that pass
+/// the filter, ordered by their id, i.e. in the order they were added, the query is not searched.
+pub fn all_documents_sort<FI>(
+    reader: &heed::RoTxn<MainT>,
+    range: Range<usize>,
+    facets_docids: Option<SetBuf<DocumentId>>,
+    facet_count_docids: Option<HashMap<String, HashMap<String, Cow<Set<DocumentId>>>>>,
+    facet_tree_docids: Option<Vec<(String, HashMap<String, Cow<Set<DocumentId>>>)>>,
+    filter: Option<FI>,
+    main_store: store::Main,
+) -> MResult<SortResult>
+where
+    FI: Fn(DocumentId) -> bool,
+{
+    let mut result = SortResult::default();
+
+    let docids = match facets_docids {
+        Some(docids) => docids,
+        None => main_store.internal_docids(reader)?.into_owned(),
+    };
+    let docids = match filter {
+        Some(filter) => SetBuf::new_unchecked(docids.iter().cloned().filter(|id| filter(*id)).collect()),
+        None => docids,
+    };
+
+    if let Some(f) = facet_count_docids {
+        result.exhaustive_facets_count = Some(true);
+        result.facets = Some(facet_count(f, &docids));
+    }
+
+    if let Some(levels) = facet_tree_docids {
+        result.facet_tree = Some(facet_tree(&levels, &docids));
+    }
+
+    result.nb_hits = docids.len();
+    result.exhaustive_nb_hit = true;
+    result.documents = docids
+        .iter()
+        .skip(range.start)
+        .take(range.end.saturating_sub(range.start))
+        .map(|id| Document::from_highlights(*id, &[]))
+        .collect();
+
+    Ok(result)
+}
+
 /// For each entry in facet_docids, calculates the number of documents in the intersection with candidate_docids.
 fn facet_count(
     facet_docids: HashMap<String, HashMap<String, Cow<Set<DocumentId>>>>,
@@ -619,3 +741,37 @@ fn facet_count(
     }
     facets_counts
 }
+
+/// Counts the candidate documents having each value of the first level of the hierarchy,
+/// then the ones of the next levels among the documents having the value of the level above.
+/// The values are ordered by count, the ones no candidate has are not returned.
+fn facet_tree(
+    levels: &[(String, HashMap<String, Cow<Set<DocumentId>>>)],
+    candidate_docids: &Set<DocumentId>,
+) -> IndexMap<String, FacetNode> {
+    let (values, next_levels) = match levels.split_first() {
+        Some(((_, values), next_levels)) => (values, next_levels),
+        None => return IndexMap::new(),
+    };
+
+    let mut nodes = Vec::with_capacity(values.len());
+    for (value, docids) in values {
+        let docids = OpBuilder::new(docids.as_ref(), candidate_docids).intersection().into_set_buf();
+        if docids.is_empty() {
+            continue;
+        }
+
+        let children = match next_levels.first() {
+            Some((attribute, _)) => {
+                let children = facet_tree(next_levels, &docids);
+                if children.is_empty() { None } else { Some((attribute.clone(), children)) }
+            }
+            None => None,
+        };
+
+        nodes.push((value.clone(), FacetNode { count: docids.len(), children }));
+    }
+
+    nodes.sort_unstable_by(|(a, a_node), (b, b_node)| b_node.count.cmp(&a_node.count).then_with(|| a.cmp(b)));
+    nodes.into_iter().collect()
+}
@@ -2,7 +2,7 @@ use std::cmp::Ordering;
 use slice_group_by::GroupBy;
 use crate::{RawDocument, MResult};
 use crate::bucket_sort::SimpleMatch;
-use super::{Criterion, Context, ContextMut, prepare_bare_matches};
+use super::{Criterion, Context, ContextMut, ExplainContext, prepare_bare_matches};
 
 pub struct Attribute;
 
@@ -34,4 +34,18 @@ impl Criterion for Attribute {
 
         lhs.cmp(&rhs)
     }
+
+    fn explain(&self, ctx: &ExplainContext, document: &RawDocument) -> Option<String> {
+        let attribute = document.processed_matches
+            .linear_group_by_key(|sm| sm.query_index)
+            .map(|group| group[0].attribute)
+            .min()?;
+
+        let attribute = ctx.searchable_attrs
+            .and_then(|sa| sa.reverse(attribute))
+            .unwrap_or(attribute);
+
+        let field_id = ctx.schema.indexed_pos_to_field_id(attribute)?;
+        ctx.schema.name(field_id).map(|name| format!("{} match", name))
+    }
 }
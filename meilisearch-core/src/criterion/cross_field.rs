@@ -0,0 +1,47 @@
+use std::cmp::Ordering;
+use slice_group_by::GroupBy;
+use crate::{RawDocument, MResult};
+use crate::bucket_sort::SimpleMatch;
+use super::{Criterion, Context, ContextMut, prepare_bare_matches};
+
+/// Ranks first the documents where the words of the query are found in the most
+/// fields, a word found in both the title and the description counts twice.
+///
+/// The documents are not scored, so instead of a bonus added to a score this criterion
+/// breaks the ties left by the words criterion, which it follows.
+pub struct CrossField;
+
+impl Criterion for CrossField {
+    fn name(&self) -> &str { "cross field" }
+
+    fn prepare<'h, 'p, 'tag, 'txn, 'q, 'r>(
+        &self,
+        ctx: ContextMut<'h, 'p, 'tag, 'txn, 'q>,
+        documents: &mut [RawDocument<'r, 'tag>],
+    ) -> MResult<()>
+    {
+        prepare_bare_matches(documents, ctx.postings_lists, ctx.query_mapping);
+        Ok(())
+    }
+
+    fn evaluate(&self, _ctx: &Context, lhs: &RawDocument, rhs: &RawDocument) -> Ordering {
+        #[inline]
+        fn number_of_fields(matches: &[SimpleMatch]) -> usize {
+            let mut number_of_fields = 0;
+            let mut attributes = Vec::new();
+            for group in matches.linear_group_by_key(|bm| bm.query_index) {
+                attributes.clear();
+                attributes.extend(group.iter().map(|sm| sm.attribute));
+                attributes.sort_unstable();
+                attributes.dedup();
+                number_of_fields += attributes.len();
+            }
+            number_of_fields
+        }
+
+        let lhs = number_of_fields(&lhs.processed_matches);
+        let rhs = number_of_fields(&rhs.processed_matches);
+
+        lhs.cmp(&rhs).reverse()
+    }
+}
@@ -0,0 +1,64 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use crate::{RawDocument, MResult};
+use super::{Criterion, Context, ContextMut, ExplainContext};
+
+/// Ranks first the documents with a field containing all the words
+/// of the query, without typos and in the same order.
+pub struct ExactMatch;
+
+impl Criterion for ExactMatch {
+    fn name(&self) -> &str { "exact match" }
+
+    fn prepare<'h, 'p, 'tag, 'txn, 'q, 'r>(
+        &self,
+        ctx: ContextMut<'h, 'p, 'tag, 'txn, 'q>,
+        documents: &mut [RawDocument<'r, 'tag>],
+    ) -> MResult<()>
+    {
+        // the words of the query are the first query ids, all the others are alternatives
+        let query_len = (0..).take_while(|i| ctx.query_mapping.contains_key(i)).count();
+        if query_len == 0 {
+            return Ok(())
+        }
+
+        for doc in documents {
+            // the attribute and the position of the query words matched without typos
+            let mut positions = HashSet::new();
+            for bm in doc.bare_matches.iter() {
+                if !bm.is_exact || bm.distance != 0 || bm.query_index >= query_len {
+                    continue
+                }
+
+                for di in ctx.postings_lists[bm.postings_list].as_ref() {
+                    positions.insert((di.attribute, di.word_index, bm.query_index));
+                }
+            }
+
+            doc.contains_exact_match = positions
+                .iter()
+                .filter(|(_, _, query_index)| *query_index == 0)
+                .any(|&(attribute, word_index, _)| {
+                    (1..query_len).all(|i| {
+                        let word_index = word_index as usize + i;
+                        word_index <= u16::max_value() as usize
+                            && positions.contains(&(attribute, word_index as u16, i))
+                    })
+                });
+        }
+
+        Ok(())
+    }
+
+    fn evaluate(&self, _ctx: &Context, lhs: &RawDocument, rhs: &RawDocument) -> Ordering {
+        lhs.contains_exact_match.cmp(&rhs.contains_exact_match).reverse()
+    }
+
+    fn explain(&self, _ctx: &ExplainContext, document: &RawDocument) -> Option<String> {
+        if document.contains_exact_match {
+            Some("all the query words in order".to_string())
+        } else {
+            Some("not all the query words in order".to_string())
+        }
+    }
+}
@@ -4,7 +4,7 @@ use meilisearch_schema::IndexedPos;
 use slice_group_by::GroupBy;
 use crate::{RawDocument, MResult};
 use crate::bucket_sort::BareMatch;
-use super::{Criterion, Context, ContextMut};
+use super::{Criterion, Context, ContextMut, ExplainContext};
 
 pub struct Exactness;
 
@@ -75,4 +75,20 @@ impl Criterion for Exactness {
             lhs.cmp(&rhs).reverse()
         })
     }
+
+    fn explain(&self, _ctx: &ExplainContext, document: &RawDocument) -> Option<String> {
+        if document.contains_one_word_field {
+            return Some("full match in a one word field".to_string());
+        }
+
+        let exact_words = document.bare_matches
+            .linear_group_by_key(|bm| bm.query_index)
+            .filter(|group| group[0].is_exact)
+            .count();
+
+        match exact_words {
+            1 => Some("1 exact word".to_string()),
+            exact_words => Some(format!("{} exact words", exact_words)),
+        }
+    }
 }
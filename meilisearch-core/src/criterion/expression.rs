@@ -0,0 +1,246 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+
+/// An arithmetic expression of attributes used to sort the documents, e.g. `price * discount_factor`.
+///
+/// The `+`, `-`, `*` and `/` operators and the parentheses are supported. Because the
+/// attribute names can contain dashes, the `-` operator must be followed by a space.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Field(String),
+    Number(f64),
+    Operation(Box<Expression>, Operator, Box<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpressionError {
+    message: String,
+    position: usize,
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at character {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+impl Expression {
+    /// Returns the name of the attributes the expression reads.
+    pub fn fields(&self) -> Vec<&str> {
+        match self {
+            Expression::Field(name) => vec![name.as_str()],
+            Expression::Number(_) => Vec::new(),
+            Expression::Operation(lhs, _, rhs) => {
+                let mut fields = lhs.fields();
+                fields.extend(rhs.fields());
+                fields
+            }
+        }
+    }
+
+    /// Computes the expression with the values of the attributes, `None` when one of the
+    /// attributes has no value or when the result is not a finite number (e.g. a division by zero).
+    pub fn evaluate<F>(&self, value: &F) -> Option<f64>
+    where F: Fn(&str) -> Option<f64>,
+    {
+        let result = match self {
+            Expression::Field(name) => value(name)?,
+            Expression::Number(number) => *number,
+            Expression::Operation(lhs, operator, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate(value)?, rhs.evaluate(value)?);
+                match operator {
+                    Operator::Add => lhs + rhs,
+                    Operator::Sub => lhs - rhs,
+                    Operator::Mul => lhs * rhs,
+                    Operator::Div => lhs / rhs,
+                }
+            }
+        };
+
+        if result.is_finite() { Some(result) } else { None }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = ExpressionError;
+
+    fn from_str(s: &str) -> Result<Expression, ExpressionError> {
+        let mut parser = Parser { input: s, chars: s.char_indices().peekable() };
+        let expression = parser.expression()?;
+        match parser.next_token()? {
+            None => Ok(expression),
+            Some((position, token)) => Err(parser.error(format!("unexpected {}", token), position)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    Operator(Operator),
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "`{}`", word),
+            Token::Operator(Operator::Add) => f.write_str("`+`"),
+            Token::Operator(Operator::Sub) => f.write_str("`-`"),
+            Token::Operator(Operator::Mul) => f.write_str("`*`"),
+            Token::Operator(Operator::Div) => f.write_str("`/`"),
+            Token::Open => f.write_str("`(`"),
+            Token::Close => f.write_str("`)`"),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: String, position: usize) -> ExpressionError {
+        ExpressionError { message, position }
+    }
+
+    fn next_token(&mut self) -> Result<Option<(usize, Token<'a>)>, ExpressionError> {
+        while let Some(&(_, c)) = self.chars.peek() {
+            if !c.is_whitespace() { break }
+            self.chars.next();
+        }
+
+        let (start, c) = match self.chars.next() {
+            Some(next) => next,
+            None => return Ok(None),
+        };
+
+        let token = match c {
+            '+' => Token::Operator(Operator::Add),
+            '*' => Token::Operator(Operator::Mul),
+            '/' => Token::Operator(Operator::Div),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '-' if self.chars.peek().map_or(true, |&(_, c)| c.is_whitespace()) => Token::Operator(Operator::Sub),
+            c if is_word_char(c) => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, c)) = self.chars.peek() {
+                    if !is_word_char(c) { break }
+                    end = i + c.len_utf8();
+                    self.chars.next();
+                }
+                let input = self.input;
+                Token::Word(&input[start..end])
+            }
+            c => return Err(self.error(format!("unexpected character `{}`", c), start)),
+        };
+
+        Ok(Some((start, token)))
+    }
+
+    fn peek_token(&mut self) -> Result<Option<Token<'a>>, ExpressionError> {
+        let chars = self.chars.clone();
+        let token = self.next_token()?.map(|(_, token)| token);
+        self.chars = chars;
+        Ok(token)
+    }
+
+    // expression = term (("+" | "-") term)*
+    fn expression(&mut self) -> Result<Expression, ExpressionError> {
+        let mut lhs = self.term()?;
+        while let Some(Token::Operator(op @ Operator::Add)) | Some(Token::Operator(op @ Operator::Sub)) = self.peek_token()? {
+            self.next_token()?;
+            let rhs = self.term()?;
+            lhs = Expression::Operation(Box::new(lhs), op, Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    // term = factor (("*" | "/") factor)*
+    fn term(&mut self) -> Result<Expression, ExpressionError> {
+        let mut lhs = self.factor()?;
+        while let Some(Token::Operator(op @ Operator::Mul)) | Some(Token::Operator(op @ Operator::Div)) = self.peek_token()? {
+            self.next_token()?;
+            let rhs = self.factor()?;
+            lhs = Expression::Operation(Box::new(lhs), op, Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    // factor = number | attribute | "(" expression ")"
+    fn factor(&mut self) -> Result<Expression, ExpressionError> {
+        match self.next_token()? {
+            Some((_, Token::Word(word))) => match word.parse::<f64>() {
+                Ok(number) => Ok(Expression::Number(number)),
+                Err(_) => Ok(Expression::Field(word.to_string())),
+            },
+            Some((position, Token::Open)) => {
+                let expression = self.expression()?;
+                match self.next_token()? {
+                    Some((_, Token::Close)) => Ok(expression),
+                    _ => Err(self.error("unclosed parenthesis".to_string(), position)),
+                }
+            }
+            Some((position, token)) => Err(self.error(format!("expected an attribute or a number, found {}", token), position)),
+            None => Err(self.error("expected an attribute or a number".to_string(), self.input.len())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_evaluate() {
+        let value = |name: &str| match name {
+            "price" => Some(20.0),
+            "discount_factor" => Some(0.5),
+            "shipping-cost" => Some(5.0),
+            _ => None,
+        };
+
+        let expression: Expression = "price".parse().unwrap();
+        assert_eq!(expression, Expression::Field("price".to_string()));
+
+        let expression: Expression = "price * discount_factor".parse().unwrap();
+        assert_eq!(expression.fields(), vec!["price", "discount_factor"]);
+        assert_eq!(expression.evaluate(&value), Some(10.0));
+
+        let expression: Expression = "(price + shipping-cost) * 2 - 1".parse().unwrap();
+        assert_eq!(expression.fields(), vec!["price", "shipping-cost"]);
+        assert_eq!(expression.evaluate(&value), Some(49.0));
+
+        let expression: Expression = "price / (discount_factor - 0.5)".parse().unwrap();
+        assert_eq!(expression.evaluate(&value), None);
+
+        let expression: Expression = "price * rating".parse().unwrap();
+        assert_eq!(expression.evaluate(&value), None);
+    }
+
+    #[test]
+    fn invalid_expressions() {
+        assert!("price *".parse::<Expression>().is_err());
+        assert!("(price + 1".parse::<Expression>().is_err());
+        assert!("price discount".parse::<Expression>().is_err());
+        assert!("price % 2".parse::<Expression>().is_err());
+        assert!("".parse::<Expression>().is_err());
+    }
+}
@@ -0,0 +1,42 @@
+use std::cmp::Ordering;
+use meilisearch_schema::FieldId;
+use crate::{DocumentId, Number, RankedMap, RawDocument};
+use super::{Criterion, Context};
+
+/// Sorts the documents by the Hamming distance between the 64-bit perceptual hash
+/// of their image, stored in a ranked attribute, and the hash of the query image.
+/// The closest images come first, the documents without a hash come last.
+pub struct ImageDistance<'a> {
+    ranked_map: &'a RankedMap,
+    field_id: FieldId,
+    hash: u64,
+}
+
+impl<'a> ImageDistance<'a> {
+    pub fn new(ranked_map: &'a RankedMap, field_id: FieldId, hash: u64) -> ImageDistance<'a> {
+        ImageDistance { ranked_map, field_id, hash }
+    }
+
+    /// Returns the number of bits that differ between the hash of the document image and the query one.
+    pub fn distance(&self, document_id: DocumentId) -> Option<u32> {
+        let hash = match self.ranked_map.get(document_id, self.field_id)? {
+            Number::Unsigned(hash) => hash,
+            Number::Signed(hash) => hash as u64,
+            Number::Float(_) | Number::Null => return None,
+        };
+        Some((hash ^ self.hash).count_ones())
+    }
+}
+
+impl Criterion for ImageDistance<'_> {
+    fn name(&self) -> &str { "image distance" }
+
+    fn evaluate(&self, _ctx: &Context, lhs: &RawDocument, rhs: &RawDocument) -> Ordering {
+        match (self.distance(lhs.id), self.distance(rhs.id)) {
+            (Some(lhs), Some(rhs)) => lhs.cmp(&rhs),
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
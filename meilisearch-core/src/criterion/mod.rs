@@ -9,24 +9,33 @@ use slice_group_by::GroupBy;
 use crate::bucket_sort::{SimpleMatch, PostingsListView};
 use crate::database::MainT;
 use crate::query_tree::QueryId;
-use crate::{store, RawDocument, MResult};
+use crate::reordered_attrs::ReorderedAttrs;
+use crate::{store, RawDocument, MResult, Schema};
 
 mod typo;
 mod words;
 mod proximity;
 mod attribute;
+mod cross_field;
 mod words_position;
 mod exactness;
+mod exact_match;
 mod document_id;
+mod expression;
+mod image_distance;
 mod sort_by_attr;
 
 pub use self::typo::Typo;
 pub use self::words::Words;
 pub use self::proximity::Proximity;
 pub use self::attribute::Attribute;
+pub use self::cross_field::CrossField;
 pub use self::words_position::WordsPosition;
 pub use self::exactness::Exactness;
+pub use self::exact_match::ExactMatch;
 pub use self::document_id::DocumentId;
+pub use self::expression::{Expression, ExpressionError, Operator};
+pub use self::image_distance::ImageDistance;
 pub use self::sort_by_attr::SortByAttr;
 
 pub trait Criterion {
@@ -58,6 +67,17 @@ pub trait Criterion {
     {
         self.evaluate(ctx, lhs, rhs) == Ordering::Equal
     }
+
+    /// Describes the score of the document on this criterion, it is used
+    /// to explain why a document is ranked before the others.
+    fn explain<'r, 'tag>(
+        &self,
+        _ctx: &ExplainContext,
+        _document: &RawDocument<'r, 'tag>,
+    ) -> Option<String>
+    {
+        None
+    }
 }
 
 pub struct ContextMut<'h, 'p, 'tag, 'txn, 'q> {
@@ -72,6 +92,11 @@ pub struct Context<'p, 'tag, 'txn, 'q> {
     pub query_mapping: &'q HashMap<QueryId, Range<usize>>,
 }
 
+pub struct ExplainContext<'a> {
+    pub schema: &'a Schema,
+    pub searchable_attrs: Option<&'a ReorderedAttrs>,
+}
+
 #[derive(Default)]
 pub struct CriteriaBuilder<'a> {
     inner: Vec<Box<dyn Criterion + 'a>>,
@@ -121,7 +146,7 @@ impl<'a> Default for Criteria<'a> {
         CriteriaBuilder::with_capacity(7)
             .add(Typo)
             .add(Words)
-            .add(Proximity)
+            .add(Proximity::default())
             .add(Attribute)
             .add(WordsPosition)
             .add(Exactness)
@@ -1,12 +1,29 @@
 use std::cmp::{self, Ordering};
+use std::convert::TryFrom;
 use slice_group_by::GroupBy;
 use crate::bucket_sort::{SimpleMatch};
+use crate::settings::{ProximityPrecision, DEFAULT_PROXIMITY_WINDOW};
 use crate::{RawDocument, MResult};
 use super::{Criterion, Context, ContextMut, prepare_bare_matches};
 
-const MAX_DISTANCE: u16 = 8;
+pub struct Proximity {
+    /// The distance past which the query words are considered as far from each other.
+    pub window: u16,
+    pub precision: ProximityPrecision,
+}
 
-pub struct Proximity;
+impl Proximity {
+    pub fn new(window: usize, precision: ProximityPrecision) -> Proximity {
+        let window = u16::try_from(window).unwrap_or(u16::max_value() - 1);
+        Proximity { window, precision }
+    }
+}
+
+impl Default for Proximity {
+    fn default() -> Proximity {
+        Proximity::new(DEFAULT_PROXIMITY_WINDOW, ProximityPrecision::default())
+    }
+}
 
 impl Criterion for Proximity {
     fn name(&self) -> &str { "proximity" }
@@ -22,20 +39,24 @@ impl Criterion for Proximity {
     }
 
     fn evaluate(&self, _ctx: &Context, lhs: &RawDocument, rhs: &RawDocument) -> Ordering {
-        fn index_proximity(lhs: u16, rhs: u16) -> u16 {
+        let window = self.window;
+        let precision = self.precision;
+
+        let index_proximity = |lhs: u16, rhs: u16| -> u16 {
             if lhs < rhs {
-                cmp::min(rhs - lhs, MAX_DISTANCE)
+                cmp::min(rhs - lhs, window)
             } else {
-                cmp::min(lhs - rhs, MAX_DISTANCE) + 1
+                cmp::min(lhs - rhs, window) + 1
             }
-        }
+        };
 
-        fn attribute_proximity(lhs: SimpleMatch, rhs: SimpleMatch) -> u16 {
-            if lhs.attribute != rhs.attribute { MAX_DISTANCE }
+        let attribute_proximity = |lhs: SimpleMatch, rhs: SimpleMatch| -> u16 {
+            if lhs.attribute != rhs.attribute { window }
+            else if precision == ProximityPrecision::ByAttribute { 1 }
             else { index_proximity(lhs.word_index, rhs.word_index) }
-        }
+        };
 
-        fn min_proximity(lhs: &[SimpleMatch], rhs: &[SimpleMatch]) -> u16 {
+        let min_proximity = |lhs: &[SimpleMatch], rhs: &[SimpleMatch]| -> u16 {
             let mut min_prox = u16::max_value();
             for a in lhs {
                 for b in rhs {
@@ -44,21 +65,21 @@ impl Criterion for Proximity {
                 }
             }
             min_prox
-        }
+        };
 
-        fn matches_proximity(matches: &[SimpleMatch],) -> u16 {
-            let mut proximity = 0;
+        let matches_proximity = |matches: &[SimpleMatch]| -> u16 {
+            let mut proximity: u16 = 0;
             let mut iter = matches.linear_group_by_key(|m| m.query_index);
 
             // iterate over groups by windows of size 2
             let mut last = iter.next();
             while let (Some(lhs), Some(rhs)) = (last, iter.next()) {
-                proximity += min_proximity(lhs, rhs);
+                proximity = proximity.saturating_add(min_proximity(lhs, rhs));
                 last = Some(rhs);
             }
 
             proximity
-        }
+        };
 
         let lhs = matches_proximity(&lhs.processed_matches);
         let rhs = matches_proximity(&rhs.processed_matches);
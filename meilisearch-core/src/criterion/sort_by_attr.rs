@@ -1,12 +1,16 @@
+use std::cell::RefCell;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use meilisearch_schema::{Schema, FieldId};
-use crate::{RankedMap, RawDocument};
-use super::{Criterion, Context};
+use ordered_float::OrderedFloat;
+use crate::{DocumentId, MResult, RankedMap, RawDocument};
+use super::{Criterion, Context, ContextMut, Expression};
 
 /// An helper struct that permit to sort documents by
-/// some of their stored attributes.
+/// some of their stored attributes, or by an arithmetic
+/// expression of them (e.g. `price * discount_factor`).
 ///
 /// # Note
 ///
@@ -29,7 +33,7 @@ use super::{Criterion, Context};
 /// let builder = CriteriaBuilder::with_capacity(8)
 ///        .add(Typo)
 ///        .add(Words)
-///        .add(Proximity)
+///        .add(Proximity::default())
 ///        .add(Attribute)
 ///        .add(WordsPosition)
 ///        .add(Exactness)
@@ -41,10 +45,17 @@ use super::{Criterion, Context};
 /// ```
 pub struct SortByAttr<'a> {
     ranked_map: &'a RankedMap,
-    field_id: FieldId,
+    rank: Rank,
     reversed: bool,
 }
 
+enum Rank {
+    Field(FieldId),
+    /// The expression, the ids of the attributes it reads and
+    /// its value for the documents it was computed for.
+    Expression(Expression, HashMap<String, FieldId>, RefCell<HashMap<DocumentId, Option<OrderedFloat<f64>>>>),
+}
+
 impl<'a> SortByAttr<'a> {
     pub fn lower_is_better(
         ranked_map: &'a RankedMap,
@@ -68,18 +79,28 @@ impl<'a> SortByAttr<'a> {
         attr_name: &str,
         reversed: bool,
     ) -> Result<SortByAttr<'a>, SortByAttrError> {
-        let field_id = match schema.id(attr_name) {
-            Some(field_id) => field_id,
-            None => return Err(SortByAttrError::AttributeNotFound),
+        let ranked_field_id = |name: &str| -> Result<FieldId, SortByAttrError> {
+            let field_id = schema.id(name).ok_or(SortByAttrError::AttributeNotFound)?;
+            if !schema.is_ranked(field_id) {
+                return Err(SortByAttrError::AttributeNotRegisteredForRanking);
+            }
+            Ok(field_id)
         };
 
-        if !schema.is_ranked(field_id) {
-            return Err(SortByAttrError::AttributeNotRegisteredForRanking);
-        }
+        let rank = match attr_name.parse::<Expression>() {
+            Ok(Expression::Field(_)) | Err(_) => Rank::Field(ranked_field_id(attr_name)?),
+            Ok(expression) => {
+                let mut field_ids = HashMap::new();
+                for name in expression.fields() {
+                    field_ids.insert(name.to_string(), ranked_field_id(name)?);
+                }
+                Rank::Expression(expression, field_ids, RefCell::default())
+            }
+        };
 
         Ok(SortByAttr {
             ranked_map,
-            field_id,
+            rank,
             reversed,
         })
     }
@@ -90,10 +111,57 @@ impl Criterion for SortByAttr<'_> {
         "sort by attribute"
     }
 
+    fn prepare<'h, 'p, 'tag, 'txn, 'q, 'r>(
+        &self,
+        _ctx: ContextMut<'h, 'p, 'tag, 'txn, 'q>,
+        documents: &mut [RawDocument<'r, 'tag>],
+    ) -> MResult<()>
+    {
+        // the expression is computed once per document instead of on every comparison
+        if let Rank::Expression(expression, field_ids, values) = &self.rank {
+            let mut values = values.borrow_mut();
+            for document in documents.iter() {
+                values.entry(document.id).or_insert_with(|| self.compute(expression, field_ids, document.id));
+            }
+        }
+        Ok(())
+    }
+
     fn evaluate(&self, _ctx: &Context, lhs: &RawDocument, rhs: &RawDocument) -> Ordering {
-        let lhs = self.ranked_map.get(lhs.id, self.field_id);
-        let rhs = self.ranked_map.get(rhs.id, self.field_id);
+        match &self.rank {
+            Rank::Field(field_id) => {
+                let lhs = self.ranked_map.get(lhs.id, *field_id);
+                let rhs = self.ranked_map.get(rhs.id, *field_id);
+                self.compare(lhs, rhs)
+            }
+            Rank::Expression(expression, field_ids, values) => {
+                let values = values.borrow();
+                let value = |document_id| match values.get(&document_id) {
+                    Some(value) => *value,
+                    None => self.compute(expression, field_ids, document_id),
+                };
+                self.compare(value(lhs.id), value(rhs.id))
+            }
+        }
+    }
+}
+
+impl SortByAttr<'_> {
+    fn compute(
+        &self,
+        expression: &Expression,
+        field_ids: &HashMap<String, FieldId>,
+        document_id: DocumentId,
+    ) -> Option<OrderedFloat<f64>>
+    {
+        let value = |name: &str| {
+            let field_id = *field_ids.get(name)?;
+            self.ranked_map.get(document_id, field_id)?.as_f64()
+        };
+        expression.evaluate(&value).map(OrderedFloat)
+    }
 
+    fn compare<T: Ord>(&self, lhs: Option<T>, rhs: Option<T>) -> Ordering {
         match (lhs, rhs) {
             (Some(lhs), Some(rhs)) => {
                 let order = lhs.cmp(&rhs);
@@ -1,6 +1,6 @@
 use std::cmp::Ordering;
 use crate::{RawDocument, MResult};
-use super::{Criterion, Context, ContextMut, prepare_query_distances};
+use super::{Criterion, Context, ContextMut, ExplainContext, prepare_query_distances};
 
 pub struct Typo;
 
@@ -52,4 +52,12 @@ impl Criterion for Typo {
 
         lhs.cmp(&rhs).reverse()
     }
+
+    fn explain(&self, _ctx: &ExplainContext, document: &RawDocument) -> Option<String> {
+        let typos: usize = document.processed_distances.iter().flatten().map(|d| *d as usize).sum();
+        match typos {
+            1 => Some("1 typo".to_string()),
+            typos => Some(format!("{} typos", typos)),
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use std::cmp::Ordering;
 use crate::{RawDocument, MResult};
-use super::{Criterion, Context, ContextMut, prepare_query_distances};
+use super::{Criterion, Context, ContextMut, ExplainContext, prepare_query_distances};
 
 pub struct Words;
 
@@ -28,4 +28,12 @@ impl Criterion for Words {
 
         lhs.cmp(&rhs).reverse()
     }
+
+    fn explain(&self, _ctx: &ExplainContext, document: &RawDocument) -> Option<String> {
+        let words = document.processed_distances.iter().filter(|d| d.is_some()).count();
+        match words {
+            1 => Some("1 query word matched".to_string()),
+            words => Some(format!("{} query words matched", words)),
+        }
+    }
 }
@@ -17,6 +17,16 @@ pub type BoxUpdateFn = Box<dyn Fn(&str, update::ProcessedUpdateResult) + Send +
 
 type ArcSwapFn = arc_swap::ArcSwapOption<BoxUpdateFn>;
 
+/// Downloads and parses the documents of an import, reporting the progress of the download.
+pub type BoxDocumentsImporter = Box<
+    dyn Fn(&str, &update::DocumentsImport, &update::UpdateProgress) -> Result<update::ImportedDocuments, String>
+        + Send
+        + Sync
+        + 'static,
+>;
+
+type ArcSwapImporter = arc_swap::ArcSwapOption<BoxDocumentsImporter>;
+
 type SerdeDatetime = SerdeBincode<DateTime<Utc>>;
 
 pub type MainWriter<'a> = heed::RwTxn<'a, MainT>;
@@ -38,6 +48,7 @@ pub struct Database {
     indexes_store: heed::Database<Str, Unit>,
     indexes: RwLock<HashMap<String, (Index, thread::JoinHandle<MResult<()>>)>>,
     update_fn: Arc<ArcSwapFn>,
+    importer: Arc<ArcSwapImporter>,
 }
 
 pub struct DatabaseOptions {
@@ -80,6 +91,7 @@ fn update_awaiter(
     update_env: heed::Env,
     index_uid: &str,
     update_fn: Arc<ArcSwapFn>,
+    importer: Arc<ArcSwapImporter>,
     index: Index,
 ) -> MResult<()> {
     let mut receiver = receiver.into_iter();
@@ -108,7 +120,7 @@ fn update_awaiter(
 
             // retrieve the update that needs to be processed
             let result = index.updates.first_update(&update_reader);
-            let (update_id, update) = match break_try!(result, "pop front update failed") {
+            let (update_id, mut update) = match break_try!(result, "pop front update failed") {
                 Some(value) => value,
                 None => {
                     debug!("no more updates");
@@ -119,13 +131,26 @@ fn update_awaiter(
             // do not keep the reader for too long
             break_try!(update_reader.abort(), "aborting update transaction failed");
 
+            // the documents of an import are downloaded before the main transaction is opened
+            update::download_documents(index_uid, update_id, &mut update, importer.load().as_deref(), &index.update_progress);
+
             // instantiate a transaction to touch to the main env
             let result = env.typed_write_txn::<MainT>();
             let mut main_writer = break_try!(result, "LMDB nested write transaction failed");
 
-            // try to apply the update to the database using the main transaction
-            let result = update::update_task(&mut main_writer, &index, update_id, update);
-            let status = break_try!(result, "update task failed");
+            // the update ids are given in ascending order and the updates are processed from the
+            // lowest one, an update is already applied when the process stopped before its result was
+            // written, it is then only moved to the updates-results store
+            let result = index.main.last_applied_update_id(&main_writer);
+            let last_applied = break_try!(result, "retrieving the last applied update failed");
+
+            let status = if last_applied.map_or(false, |id| id >= update_id) {
+                update::applied_update_result(update_id, update)
+            } else {
+                // try to apply the update to the database using the main transaction
+                let result = update::update_task(&mut main_writer, &index, update_id, update);
+                break_try!(result, "update task failed")
+            };
 
             // commit the main transaction if the update was successful, abort it otherwise
             if status.error.is_none() {
@@ -182,6 +207,7 @@ impl Database {
         let common_store = env.create_poly_database(Some("common"))?;
         let indexes_store = env.create_database::<Str, Unit>(Some("indexes"))?;
         let update_fn = Arc::new(ArcSwapFn::empty());
+        let importer = Arc::new(ArcSwapImporter::empty());
 
         // list all indexes that needs to be opened
         let mut must_open = Vec::new();
@@ -213,6 +239,7 @@ impl Database {
             let index_clone = index.clone();
             let name_clone = index_uid.clone();
             let update_fn_clone = update_fn.clone();
+            let importer_clone = importer.clone();
 
             let handle = thread::spawn(move || {
                 update_awaiter(
@@ -221,6 +248,7 @@ impl Database {
                     update_env_clone,
                     &name_clone,
                     update_fn_clone,
+                    importer_clone,
                     index_clone,
                 )
             });
@@ -243,6 +271,7 @@ impl Database {
             indexes_store,
             indexes: RwLock::new(indexes),
             update_fn,
+            importer,
         })
     }
 
@@ -284,6 +313,7 @@ impl Database {
                 let index_clone = index.clone();
                 let name_clone = name.to_owned();
                 let update_fn_clone = self.update_fn.clone();
+                let importer_clone = self.importer.clone();
 
                 let handle = thread::spawn(move || {
                     update_awaiter(
@@ -292,6 +322,7 @@ impl Database {
                         update_env_clone,
                         &name_clone,
                         update_fn_clone,
+                        importer_clone,
                         index_clone,
                     )
                 });
@@ -339,6 +370,11 @@ impl Database {
         self.update_fn.swap(None);
     }
 
+    /// Sets the importer downloading the documents of the imports, they fail without one.
+    pub fn set_documents_importer(&self, importer: BoxDocumentsImporter) {
+        self.importer.swap(Some(Arc::new(importer)));
+    }
+
     pub fn main_read_txn(&self) -> MResult<MainReader> {
         Ok(self.env.typed_read_txn::<MainT>()?)
     }
@@ -1058,6 +1094,76 @@ mod tests {
         assert_eq!(document, Some(new_doc2));
     }
 
+    #[test]
+    fn documents_embeddings_keep_the_other_fields() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let database = Database::open_or_create(dir.path(), DatabaseOptions::default()).unwrap();
+        let db = &database;
+
+        let (sender, receiver) = mpsc::sync_channel(100);
+        let update_fn = move |_name: &str, update: ProcessedUpdateResult| {
+            sender.send(update.update_id).unwrap()
+        };
+        let index = database.create_index("test").unwrap();
+
+        database.set_update_callback(Box::new(update_fn));
+
+        let mut writer = db.main_write_txn().unwrap();
+        index.main.put_schema(&mut writer, &Schema::with_primary_key("id")).unwrap();
+        index.main.put_document_versions(&mut writer, 2).unwrap();
+        writer.commit().unwrap();
+
+        let mut additions = index.documents_addition();
+        additions.update_document(serde_json::json!({ "id": 123, "name": "Marvin" }));
+
+        let mut writer = db.update_write_txn().unwrap();
+        let update_id = additions.finalize(&mut writer).unwrap();
+        writer.commit().unwrap();
+
+        // the document is replaced while its embedding is generated
+        let mut additions = index.documents_addition();
+        additions.update_document(serde_json::json!({ "id": 123, "name": "Marvin", "description": "My name is Marvin" }));
+
+        let mut writer = db.update_write_txn().unwrap();
+        let _update_id = additions.finalize(&mut writer).unwrap();
+        writer.commit().unwrap();
+
+        // the embeddings of the unknown documents are ignored
+        let embeddings = vec![
+            serde_json::from_value(serde_json::json!({ "id": 123, "_vectors": { "default": [0.5, 1.0] } })).unwrap(),
+            serde_json::from_value(serde_json::json!({ "id": 234, "_vectors": { "default": [0.5, 1.0] } })).unwrap(),
+        ];
+
+        let mut writer = db.update_write_txn().unwrap();
+        let update_id_embeddings = index.documents_embeddings(&mut writer, embeddings).unwrap();
+        writer.commit().unwrap();
+
+        // block until the transaction is processed
+        let _ = receiver.iter().find(|id| *id == update_id_embeddings);
+
+        let update_reader = db.update_read_txn().unwrap();
+        let result = index.update_status(&update_reader, update_id_embeddings).unwrap();
+        assert_matches!(result, Some(UpdateStatus::Processed { content }) if content.error.is_none());
+        assert!(update_id < update_id_embeddings);
+        update_reader.abort().unwrap();
+
+        let reader = db.main_read_txn().unwrap();
+        let document: Option<serde_json::Value> = index.document(&reader, None, DocumentId(0)).unwrap();
+        let expected = serde_json::json!({
+            "id": 123,
+            "name": "Marvin",
+            "description": "My name is Marvin",
+            "_vectors": { "default": [0.5, 1.0] },
+        });
+        assert_eq!(document, Some(expected));
+        assert_eq!(index.main.number_of_documents(&reader).unwrap(), 1);
+
+        // only the replaced document is archived, the embeddings are no new version
+        let versions = index.document_versions.versions(&reader, "123").unwrap();
+        assert_eq!(versions.len(), 1);
+    }
+
     #[test]
     fn delete_index() {
         let dir = tempfile::tempdir().unwrap();
@@ -1232,4 +1338,54 @@ mod tests {
         );
         assert_matches!(iter.next(), None);
     }
+
+    #[test]
+    fn applied_updates_are_not_applied_twice() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let database = Database::open_or_create(dir.path(), DatabaseOptions::default()).unwrap();
+        let db = &database;
+
+        let (sender, receiver) = mpsc::sync_channel(100);
+        let update_fn = move |_name: &str, update: ProcessedUpdateResult| {
+            sender.send(update.update_id).unwrap()
+        };
+        let index = database.create_index("test").unwrap();
+
+        database.set_update_callback(Box::new(update_fn));
+
+        // simulate a process that stopped after committing the first update
+        // to the main store but before writing its result
+        let mut writer = db.main_write_txn().unwrap();
+        index.main.put_schema(&mut writer, &Schema::with_primary_key("id")).unwrap();
+        index.main.put_last_applied_update_id(&mut writer, 0).unwrap();
+        writer.commit().unwrap();
+
+        let mut additions = index.documents_addition();
+        additions.update_document(serde_json::json!({ "id": 123, "name": "Marvin" }));
+
+        let mut update_writer = db.update_write_txn().unwrap();
+        let first_update_id = additions.finalize(&mut update_writer).unwrap();
+        update_writer.commit().unwrap();
+
+        let mut additions = index.documents_addition();
+        additions.update_document(serde_json::json!({ "id": 234, "name": "Kevin" }));
+
+        let mut update_writer = db.update_write_txn().unwrap();
+        let second_update_id = additions.finalize(&mut update_writer).unwrap();
+        update_writer.commit().unwrap();
+
+        // block until the transactions are processed
+        let _ = receiver.iter().find(|id| *id == second_update_id);
+        assert_eq!(first_update_id, 0);
+
+        let update_reader = db.update_read_txn().unwrap();
+        let result = index.update_status(&update_reader, first_update_id).unwrap();
+        assert_matches!(result, Some(UpdateStatus::Processed { content }) if content.error.is_none());
+
+        // only the second update has been applied, after the first one
+        let reader = db.main_read_txn().unwrap();
+        assert_eq!(index.main.number_of_documents(&reader).unwrap(), 1);
+        assert_eq!(index.main.last_applied_update_id(&reader).unwrap(), Some(second_update_id));
+    }
 }
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+/// The minimum number of characters of a part of a compound word.
+const MIN_PART_LENGTH: usize = 3;
+
+/// The maximum number of characters of a word that is split, the longer ones are not.
+const MAX_WORD_LENGTH: usize = 64;
+
+/// Splits a compound word into the known words it is made of, or returns `None`
+/// if the word cannot entirely be split into known words.
+///
+/// The longest known prefix is tried first and the German linking "s"
+/// is skipped, "arbeitszeit" is split into "arbeit" and "zeit".
+pub fn decompound<'a, F>(word: &'a str, is_known: &F) -> Option<Vec<&'a str>>
+where F: Fn(&str) -> bool,
+{
+    if word.chars().count() > MAX_WORD_LENGTH {
+        return None;
+    }
+
+    // the splits of the suffixes of the word, by their offset, each suffix is only split once
+    let mut splits = HashMap::new();
+    decompound_from(word, 0, is_known, &mut splits)
+}
+
+fn decompound_from<'a, F>(
+    word: &'a str,
+    offset: usize,
+    is_known: &F,
+    splits: &mut HashMap<usize, Option<Vec<&'a str>>>,
+) -> Option<Vec<&'a str>>
+where F: Fn(&str) -> bool,
+{
+    if let Some(parts) = splits.get(&offset) {
+        return parts.clone();
+    }
+
+    let suffix = &word[offset..];
+    let boundaries: Vec<_> = suffix.char_indices().map(|(i, _)| i).skip(MIN_PART_LENGTH).collect();
+
+    let mut result = None;
+    for &i in boundaries.iter().rev() {
+        let (left, right) = suffix.split_at(i);
+        if right.chars().count() < MIN_PART_LENGTH {
+            continue;
+        }
+
+        let left = if is_known(left) {
+            left
+        } else if left.ends_with('s') && left.chars().count() > MIN_PART_LENGTH && is_known(&left[..left.len() - 1]) {
+            &left[..left.len() - 1]
+        } else {
+            continue
+        };
+
+        let parts = if is_known(right) {
+            Some(vec![right])
+        } else {
+            decompound_from(word, offset + i, is_known, splits)
+        };
+
+        if let Some(mut parts) = parts {
+            parts.insert(0, left);
+            result = Some(parts);
+            break;
+        }
+    }
+
+    splits.insert(offset, result.clone());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn german_compounds() {
+        let dictionary = ["arbeit", "fußball", "training", "zeit"];
+        let is_known = |word: &str| dictionary.contains(&word);
+
+        assert_eq!(decompound("fußballtraining", &is_known), Some(vec!["fußball", "training"]));
+        assert_eq!(decompound("arbeitszeit", &is_known), Some(vec!["arbeit", "zeit"]));
+        assert_eq!(decompound("fußballtrainingszeit", &is_known), Some(vec!["fußball", "training", "zeit"]));
+        assert_eq!(decompound("fußball", &is_known), None);
+        assert_eq!(decompound("fußballspiel", &is_known), None);
+    }
+
+    #[test]
+    fn long_words_are_split_once_per_suffix() {
+        let calls = std::cell::Cell::new(0);
+        let is_known = |word: &str| {
+            calls.set(calls.get() + 1);
+            word == "aaa"
+        };
+
+        // this word has many ways to be split, none of them ending with a known word
+        let word = format!("{}b", "a".repeat(60));
+        assert_eq!(decompound(&word, &is_known), None);
+        assert!(calls.get() < 10_000);
+
+        // the words longer than the maximum length are not split, even made of known words
+        assert_eq!(decompound(&"aaa".repeat(21), &is_known).map(|parts| parts.len()), Some(21));
+        let word = "aaa".repeat(22);
+        assert_eq!(decompound(&word, &is_known), None);
+    }
+}
@@ -22,6 +22,13 @@ pub enum Error {
     WordIndexMissing,
     MissingDocumentId,
     MaxFieldsLimitExceeded,
+    IndexSizeLimitExceeded(usize),
+    /// The number of invalid documents and the errors of the first ones.
+    InvalidDocuments(usize, Vec<String>),
+    /// The file of an import could not be downloaded or parsed.
+    ImportFailed(String),
+    /// The document to patch was deleted before the patch was processed.
+    DocumentNotFound(String),
     Schema(meilisearch_schema::Error),
     Heed(heed::Error),
     Fst(fst::Error),
@@ -38,26 +45,46 @@ impl ErrorCode for Error {
         use Error::*;
 
         match self {
-            FacetError(_) => Code::Facet,
-            FilterParseError(_) => Code::Filter,
+            FacetError(e) => e.error_code(),
+            FilterParseError(_) => Code::FilterInvalidSyntax,
             IndexAlreadyExists => Code::IndexAlreadyExists,
-            MissingPrimaryKey => Code::InvalidState,
+            MissingPrimaryKey => Code::MissingPrimaryKey,
             MissingDocumentId => Code::MissingDocumentId,
+            InvalidDocuments(..) => Code::InvalidDocument,
+            ImportFailed(_) => Code::ImportFailed,
+            DocumentNotFound(_) => Code::DocumentNotFound,
             MaxFieldsLimitExceeded => Code::MaxFieldsLimitExceeded,
+            IndexSizeLimitExceeded(_) => Code::IndexSizeLimitExceeded,
             Schema(s) =>  s.error_code(),
-            WordIndexMissing
-            | SchemaMissing => Code::InvalidState,
+            WordIndexMissing => Code::WordIndexMissing,
+            SchemaMissing => Code::SchemaMissing,
+            Serializer(e) => serializer_error_code(e),
             Heed(_)
             | Fst(_)
             | SerdeJson(_)
             | Bincode(_)
-            | Serializer(_)
             | Deserializer(_)
             | Io(_) => Code::Internal,
         }
     }
 }
 
+/// The documents that cannot be serialized are invalid, unless the storage failed.
+fn serializer_error_code(error: &SerializerError) -> Code {
+    match error {
+        SerializerError::DocumentIdNotFound => Code::MissingDocumentId,
+        SerializerError::InvalidDocumentIdFormat => Code::InvalidDocumentId,
+        SerializerError::ParseNumber(_)
+        | SerializerError::UnserializableType { .. }
+        | SerializerError::UnindexableType { .. }
+        | SerializerError::UnrankableType { .. } => Code::InvalidDocument,
+        SerializerError::Schema(e) => e.error_code(),
+        SerializerError::Zlmdb(_)
+        | SerializerError::SerdeJson(_)
+        | SerializerError::Custom(_) => Code::Internal,
+    }
+}
+
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Error {
         Error::Io(error)
@@ -145,6 +172,13 @@ impl fmt::Display for Error {
             WordIndexMissing => write!(f, "this index does not have a word index"),
             MissingDocumentId => write!(f, "document id is missing"),
             MaxFieldsLimitExceeded => write!(f, "maximum number of fields in a document exceeded"),
+            IndexSizeLimitExceeded(limit) => write!(f, "the index would exceed its maximum size of {} bytes", limit),
+            InvalidDocuments(count, errors) if *count > errors.len() => {
+                write!(f, "{} documents are invalid; {}; and {} other documents", count, errors.join("; "), count - errors.len())
+            }
+            InvalidDocuments(count, errors) => write!(f, "{} documents are invalid; {}", count, errors.join("; ")),
+            ImportFailed(e) => write!(f, "{}", e),
+            DocumentNotFound(id) => write!(f, "document {} not found", id),
             Schema(e) => write!(f, "schema error; {}", e),
             Heed(e) => write!(f, "heed error; {}", e),
             Fst(e) => write!(f, "fst error; {}", e),
@@ -195,6 +229,22 @@ impl FacetError {
     }
 }
 
+impl FacetError {
+    pub fn error_code(&self) -> Code {
+        use FacetError::*;
+
+        match self {
+            EmptyArray
+            | ParsingError(_)
+            | UnexpectedToken { .. }
+            | InvalidFormat(_) => Code::Facet,
+            AttributeNotFound(_) => Code::FacetAttributeNotFound,
+            AttributeNotSet { .. } => Code::FacetAttributeNotSet,
+            InvalidDocumentAttribute(_) => Code::InvalidDocumentFacet,
+        }
+    }
+}
+
 impl fmt::Display for FacetError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use FacetError::*;
@@ -0,0 +1,110 @@
+use std::borrow::Cow;
+use std::ops::Deref;
+
+use fst::{IntoStreamer, Streamer};
+use meilisearch_tokenizer::split_query_string;
+use sdset::{SetBuf, SetOperation};
+
+use crate::automaton::{build_dfa, build_dfa_with_typos, build_prefix_dfa, build_prefix_dfa_with_typos, typos_for_similarity};
+use crate::database::MainT;
+use crate::{store, DocumentId, MResult};
+
+/// A word the documents must not contain, a word ending with a `*` also excludes
+/// the words it is a prefix of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExcludedWord {
+    pub word: String,
+    pub prefix: bool,
+}
+
+/// Splits the query into the query to search and the words prefixed by a `-` that the
+/// documents must not contain. An even number of `-` cancels out, `--word` is searched.
+pub fn split_excluded_words(query: &str) -> (String, Vec<ExcludedWord>) {
+    let mut searched = Vec::new();
+    let mut excluded = Vec::new();
+
+    for token in query.split_whitespace() {
+        let word = token.trim_start_matches('-');
+        let dashes = token.len() - word.len();
+        if word.is_empty() {
+            continue;
+        }
+
+        if dashes % 2 == 0 {
+            searched.push(word);
+        } else {
+            let words = split_query_string(word).map(|w| w.trim_start_matches('*'));
+            let words = words.map(|w| ExcludedWord { word: w.trim_end_matches('*').to_lowercase(), prefix: w.ends_with('*') });
+            excluded.extend(words.filter(|w| !w.word.is_empty()));
+        }
+    }
+
+    (searched.join(" "), excluded)
+}
+
+/// Returns the documents containing one of the words or a word within the typo budget
+/// of the search, the prefix words also exclude the words they are a prefix of.
+pub fn excluded_documents(
+    reader: &heed::RoTxn<MainT>,
+    index: &store::Index,
+    words: &[ExcludedWord],
+    min_similarity: Option<f32>,
+) -> MResult<SetBuf<DocumentId>> {
+    let words_set = index.main.words_fst(reader)?;
+    let mut docids = Vec::new();
+
+    for ExcludedWord { word, prefix } in words {
+        let dfa = match (min_similarity, prefix) {
+            (Some(min_similarity), true) => build_prefix_dfa_with_typos(word, typos_for_similarity(min_similarity, word)),
+            (Some(min_similarity), false) => build_dfa_with_typos(word, typos_for_similarity(min_similarity, word)),
+            (None, true) => build_prefix_dfa(word),
+            (None, false) => build_dfa(word),
+        };
+
+        // like the search, the first letter of the words must be the right one,
+        // it also skips the phonetic codes and the stems
+        let byte = word.as_bytes()[0];
+        let mut stream = if byte == u8::max_value() {
+            words_set.search(&dfa).ge(&[byte]).into_stream()
+        } else {
+            words_set.search(&dfa).ge(&[byte]).lt(&[byte + 1]).into_stream()
+        };
+
+        while let Some(input) = stream.next() {
+            if let Some(postings) = index.postings_lists.postings_list(reader, input)? {
+                docids.push(postings.docids);
+            }
+        }
+    }
+
+    let sets = docids.iter().map(Cow::deref).collect();
+    Ok(sdset::multi::OpBuilder::from_vec(sets).union().into_set_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exact(word: &str) -> ExcludedWord {
+        ExcludedWord { word: word.to_string(), prefix: false }
+    }
+
+    #[test]
+    fn excluded_words_are_split_from_the_query() {
+        let (query, excluded) = split_excluded_words("shoes -Leather");
+        assert_eq!(query, "shoes");
+        assert_eq!(excluded, vec![exact("leather")]);
+
+        let (query, excluded) = split_excluded_words("--leather t-shirt - -");
+        assert_eq!(query, "leather t-shirt");
+        assert!(excluded.is_empty());
+
+        let (query, excluded) = split_excluded_words("-red -blue");
+        assert_eq!(query, "");
+        assert_eq!(excluded, vec![exact("red"), exact("blue")]);
+
+        let (query, excluded) = split_excluded_words("shoes -shoe*");
+        assert_eq!(query, "shoes");
+        assert_eq!(excluded, vec![ExcludedWord { word: "shoe".to_string(), prefix: true }]);
+    }
+}
@@ -7,6 +7,7 @@ use cow_utils::CowUtils;
 use either::Either;
 use heed::types::{Str, OwnedType};
 use indexmap::IndexMap;
+use serde::ser::{Serialize, SerializeMap, Serializer};
 use serde_json::Value;
 
 use meilisearch_schema::{FieldId, Schema};
@@ -18,7 +19,7 @@ use crate::store::BEU16;
 
 /// Data structure used to represent a boolean expression in the form of nested arrays.
 /// Values in the outer array are and-ed together, values in the inner arrays are or-ed together.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FacetFilter(Vec<Either<Vec<FacetKey>, FacetKey>>);
 
 impl Deref for FacetFilter {
@@ -72,9 +73,35 @@ impl FacetFilter {
             bad_value => Err(FacetError::unexpected_token(&["Array"], bad_value).into()),
         }
     }
+
+    /// Returns whether the document has the facet values the filter asks for.
+    pub fn matches(&self, reader: &heed::RoTxn<MainT>, index: &crate::Index, document_id: DocumentId) -> MResult<bool> {
+        for expr in self.iter() {
+            let keys = match expr {
+                Either::Left(keys) => keys.as_slice(),
+                Either::Right(key) => std::slice::from_ref(key),
+            };
+
+            let mut matches = false;
+            for key in keys {
+                if let Some(docids) = index.facets.facet_document_ids(reader, key)? {
+                    if docids.binary_search(&document_id).is_ok() {
+                        matches = true;
+                        break;
+                    }
+                }
+            }
+
+            if !matches {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
 }
 
-#[derive(Debug, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 #[repr(C)]
 pub struct FacetKey(FieldId, String);
 
@@ -233,6 +260,26 @@ pub fn facet_map_from_docs(
     Ok(facet_map)
 }
 
+/// The number of hits having a value of a hierarchical facet, along with the values of
+/// the next level of the hierarchy these hits have.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FacetNode {
+    pub count: usize,
+    /// The name of the attribute of the next level and the count of its values.
+    pub children: Option<(String, IndexMap<String, FacetNode>)>,
+}
+
+impl Serialize for FacetNode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("_count", &self.count)?;
+        if let Some((attribute, children)) = &self.children {
+            map.serialize_entry(attribute, children)?;
+        }
+        map.end()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
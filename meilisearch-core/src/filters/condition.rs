@@ -2,12 +2,14 @@ use std::str::FromStr;
 use std::cmp::Ordering;
 
 use crate::error::Error;
+use crate::facets::FacetKey;
 use crate::{store::Index, DocumentId, MainT};
 use heed::RoTxn;
 use meilisearch_schema::{FieldId, Schema};
 use pest::error::{Error as PestError, ErrorVariant};
 use pest::iterators::Pair;
-use serde_json::{Value, Number};
+use sdset::SetBuf;
+use serde_json::{json, Value, Number};
 use super::parser::Rule;
 
 #[derive(Debug, PartialEq)]
@@ -153,6 +155,24 @@ impl<'a> Condition<'a> {
         Ok(Self { field, condition, value })
     }
 
+    /// Returns a json representation of this condition, used to debug filters.
+    pub fn to_json(&self, schema: &Schema) -> Value {
+        let operator = match self.condition {
+            ConditionType::Greater => ">",
+            ConditionType::Less => "<",
+            ConditionType::Equal => "=",
+            ConditionType::LessEqual => "<=",
+            ConditionType::GreaterEqual => ">=",
+            ConditionType::NotEqual => "!=",
+        };
+
+        json!({
+            "attribute": schema.name(self.field),
+            "operator": operator,
+            "value": self.value.as_str(),
+        })
+    }
+
     pub fn test(
         &self,
         reader: &RoTxn<MainT>,
@@ -165,6 +185,23 @@ impl<'a> Condition<'a> {
         }
     }
 
+    /// Returns the documents having the value in the facet index when the condition is an
+    /// equality on an attribute for faceting, the only values these attributes can have are strings.
+    pub fn candidates(&self, reader: &RoTxn<MainT>, index: &Index) -> Result<Option<SetBuf<DocumentId>>, Error> {
+        if self.condition != ConditionType::Equal {
+            return Ok(None);
+        }
+
+        match index.main.attributes_for_faceting(reader)? {
+            Some(attributes) if attributes.contains(&self.field) => (),
+            _ => return Ok(None),
+        }
+
+        let key = FacetKey::new(self.field, self.value.as_str().to_string());
+        let documents_ids = index.facets.facet_document_ids(reader, &key)?;
+        Ok(Some(documents_ids.map_or_else(|| SetBuf::new_unchecked(Vec::new()), |ids| ids.into_owned())))
+    }
+
     fn match_value(&self, value: Option<&Value>) -> bool {
         match value {
             Some(Value::String(s)) => {
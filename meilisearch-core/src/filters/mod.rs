@@ -13,6 +13,8 @@ use meilisearch_schema::Schema;
 use parser::{PREC_CLIMBER, FilterParser};
 use pest::iterators::{Pair, Pairs};
 use pest::Parser;
+use sdset::{duo::OpBuilder, SetBuf, SetOperation};
+use serde_json::{json, Value};
 
 type FilterResult<'a> = Result<Filter<'a>, Error>;
 
@@ -49,6 +51,37 @@ impl<'a> Filter<'a> {
         }
     }
 
+    /// Returns the documents that can match the filter, found in the facet index, or `None` when
+    /// it cannot be resolved by the facet index and every document must be tested. The documents
+    /// returned must be tested too, the strings of the facet index are only lowercased.
+    pub fn candidates(&self, reader: &RoTxn<MainT>, index: &Index) -> Result<Option<SetBuf<DocumentId>>, Error> {
+        use Filter::*;
+        match self {
+            Condition(c) => c.candidates(reader, index),
+            Or(lhs, rhs) => match (lhs.candidates(reader, index)?, rhs.candidates(reader, index)?) {
+                (Some(lhs), Some(rhs)) => Ok(Some(OpBuilder::new(&lhs, &rhs).union().into_set_buf())),
+                _ => Ok(None),
+            },
+            And(lhs, rhs) => match (lhs.candidates(reader, index)?, rhs.candidates(reader, index)?) {
+                (Some(lhs), Some(rhs)) => Ok(Some(OpBuilder::new(&lhs, &rhs).intersection().into_set_buf())),
+                (Some(candidates), None) | (None, Some(candidates)) => Ok(Some(candidates)),
+                (None, None) => Ok(None),
+            },
+            Not(_) => Ok(None),
+        }
+    }
+
+    /// Returns a json representation of the filter tree, used to debug filters.
+    pub fn to_json(&self, schema: &Schema) -> Value {
+        use Filter::*;
+        match self {
+            Condition(c) => json!({ "condition": c.to_json(schema) }),
+            Or(lhs, rhs) => json!({ "or": [lhs.to_json(schema), rhs.to_json(schema)] }),
+            And(lhs, rhs) => json!({ "and": [lhs.to_json(schema), rhs.to_json(schema)] }),
+            Not(op) => json!({ "not": op.to_json(schema) }),
+        }
+    }
+
     fn build(expression: Pairs<'a, Rule>, schema: &'a Schema) -> FilterResult<'a> {
         PREC_CLIMBER.climb(
             expression,
@@ -7,12 +7,16 @@ extern crate pest_derive;
 mod automaton;
 mod bucket_sort;
 mod database;
+mod decompound;
 mod distinct_map;
 mod error;
+mod excluded_words;
 mod filters;
 mod levenshtein;
 mod number;
+pub mod phonetic;
 mod query_builder;
+pub mod query_parser;
 mod query_tree;
 mod query_words_mapper;
 mod ranked_map;
@@ -23,10 +27,16 @@ pub mod facets;
 pub mod raw_indexer;
 pub mod serde;
 pub mod settings;
+pub mod similar;
+pub mod spellcheck;
+pub mod stemming;
 pub mod store;
+pub mod transformer;
 pub mod update;
+pub mod warm;
 
-pub use self::database::{BoxUpdateFn, Database, DatabaseOptions, MainT, UpdateT, MainWriter, MainReader, UpdateWriter, UpdateReader};
+pub use self::automaton::normalize_synonym;
+pub use self::database::{BoxDocumentsImporter, BoxUpdateFn, Database, DatabaseOptions, MainT, UpdateT, MainWriter, MainReader, UpdateWriter, UpdateReader};
 pub use self::error::{Error, HeedError, FstError, MResult, pest_error, FacetError};
 pub use self::filters::Filter;
 pub use self::number::{Number, ParseNumberError};
@@ -56,6 +66,11 @@ type FstMapCow<'a> = fst::Map<Cow<'a, [u8]>>;
 pub struct Document {
     pub id: DocumentId,
     pub highlights: Vec<Highlight>,
+    /// Whether a query word only matched this document by the way it sounds.
+    pub phonetic_match: bool,
+    /// The criteria that ranked this document apart from the documents it was tied with,
+    /// only filled when the explanation of the ranking is asked.
+    pub ranking_explanation: Vec<String>,
 
     #[cfg(test)]
     pub matches: Vec<crate::bucket_sort::SimpleMatch>,
@@ -78,7 +93,7 @@ fn highlights_from_raw_document<'a, 'tag, 'txn>(
 
         for di in postings_list.iter() {
             let covered_area = match kind {
-                Some(QueryKind::NonTolerant(query)) | Some(QueryKind::Tolerant(query)) => {
+                Some(QueryKind::NonTolerant(query)) | Some(QueryKind::Tolerant(query)) | Some(QueryKind::Wildcard(query)) => {
                     let len = if query.len() > input.len() {
                         input.len()
                     } else {
@@ -115,15 +130,32 @@ fn highlights_from_raw_document<'a, 'tag, 'txn>(
     highlights
 }
 
+fn is_phonetic_match(raw_document: &RawDocument, queries_kinds: &HashMap<QueryId, &QueryKind>) -> bool {
+    let matched_kinds: Vec<_> = raw_document.bare_matches
+        .iter()
+        .filter_map(|bm| queries_kinds.get(&bm.query_index))
+        .collect();
+
+    matched_kinds.iter().any(|kind| match kind {
+        QueryKind::Phonetic(word) => {
+            !matched_kinds.iter().any(|k| match k {
+                QueryKind::Tolerant(w) => w == word,
+                _ => false,
+            })
+        },
+        _ => false,
+    })
+}
+
 impl Document {
     #[cfg(not(test))]
     pub fn from_highlights(id: DocumentId, highlights: &[Highlight]) -> Document {
-        Document { id, highlights: highlights.to_owned() }
+        Document { id, highlights: highlights.to_owned(), phonetic_match: false, ranking_explanation: Vec::new() }
     }
 
     #[cfg(test)]
     pub fn from_highlights(id: DocumentId, highlights: &[Highlight]) -> Document {
-        Document { id, highlights: highlights.to_owned(), matches: Vec::new() }
+        Document { id, highlights: highlights.to_owned(), phonetic_match: false, ranking_explanation: Vec::new(), matches: Vec::new() }
     }
 
     #[cfg(not(test))]
@@ -143,7 +175,9 @@ impl Document {
             schema,
         );
 
-        Document { id: raw_document.id, highlights }
+        let phonetic_match = is_phonetic_match(&raw_document, queries_kinds);
+
+        Document { id: raw_document.id, highlights, phonetic_match, ranking_explanation: Vec::new() }
     }
 
     #[cfg(test)]
@@ -157,6 +191,8 @@ impl Document {
     {
         use crate::bucket_sort::SimpleMatch;
 
+        let phonetic_match = is_phonetic_match(&raw_document, queries_kinds);
+
         let highlights = highlights_from_raw_document(
             &raw_document,
             queries_kinds,
@@ -184,7 +220,7 @@ impl Document {
         }
         matches.sort_unstable();
 
-        Document { id: raw_document.id, highlights, matches }
+        Document { id: raw_document.id, highlights, phonetic_match, ranking_explanation: Vec::new(), matches }
     }
 }
 
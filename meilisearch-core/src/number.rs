@@ -94,6 +94,17 @@ impl Ord for Number {
     }
 }
 
+impl Number {
+    pub fn as_f64(self) -> Option<f64> {
+        match self {
+            Number::Unsigned(n) => Some(n as f64),
+            Number::Signed(n) => Some(n as f64),
+            Number::Float(n) => Some(n.into_inner()),
+            Number::Null => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParseNumberError {
     uint_error: ParseIntError,
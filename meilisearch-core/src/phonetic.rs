@@ -0,0 +1,45 @@
+use meilisearch_tokenizer::is_cjk;
+use once_cell::sync::Lazy;
+use rphonetic::{DoubleMetaphone, Encoder};
+
+/// Phonetic codes are stored in the same postings lists as the words, this prefix
+/// ensures that they can never be confused with a word of the documents or of a query.
+const PHONETIC_PREFIX: char = '\u{1}';
+
+static DOUBLE_METAPHONE: Lazy<DoubleMetaphone> = Lazy::new(DoubleMetaphone::default);
+
+/// Returns whether this indexed word is a phonetic code and not a word of the documents.
+pub fn is_phonetic_word(word: &[u8]) -> bool {
+    word.starts_with(&[PHONETIC_PREFIX as u8])
+}
+
+/// Returns the double metaphone code of the word in the form it is indexed,
+/// or `None` if the word does not have a phonetic representation.
+pub fn phonetic_word(word: &str) -> Option<String> {
+    if word.is_empty() || word.contains(is_cjk) {
+        return None;
+    }
+
+    let code = DOUBLE_METAPHONE.encode(word);
+    if code.is_empty() {
+        return None;
+    }
+
+    let mut phonetic = String::with_capacity(code.len() + 1);
+    phonetic.push(PHONETIC_PREFIX);
+    phonetic.push_str(&code.to_lowercase());
+    Some(phonetic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similar_sounding_words() {
+        assert_eq!(phonetic_word("smith"), phonetic_word("smyth"));
+        assert_ne!(phonetic_word("smith"), phonetic_word("jones"));
+        assert_eq!(phonetic_word(""), None);
+        assert_eq!(phonetic_word("東京"), None);
+    }
+}
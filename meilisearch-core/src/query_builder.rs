@@ -4,15 +4,17 @@ use std::ops::{Range, Deref};
 use std::time::Duration;
 
 use either::Either;
-use sdset::SetOperation;
+use sdset::{Set, SetBuf, SetOperation};
 
 use meilisearch_schema::FieldId;
 
 use crate::database::MainT;
-use crate::bucket_sort::{bucket_sort, bucket_sort_with_distinct, SortResult};
+use crate::bucket_sort::{all_documents_sort, bucket_sort, bucket_sort_with_distinct, SortResult};
 use crate::{criterion::Criteria, DocumentId};
 use crate::{reordered_attrs::ReorderedAttrs, store, MResult};
+use crate::excluded_words::{excluded_documents, split_excluded_words};
 use crate::facets::FacetFilter;
+use crate::query_tree::DEFAULT_MAX_WILDCARD_EXPANSIONS;
 
 pub struct QueryBuilder<'c, 'f, 'd, 'i> {
     criteria: Criteria<'c>,
@@ -23,6 +25,13 @@ pub struct QueryBuilder<'c, 'f, 'd, 'i> {
     index: &'i store::Index,
     facet_filter: Option<FacetFilter>,
     facets: Option<Vec<(FieldId, String)>>,
+    facet_tree: Option<Vec<(FieldId, String)>>,
+    candidates: Option<SetBuf<DocumentId>>,
+    excluded_documents: SetBuf<DocumentId>,
+    min_similarity: Option<f32>,
+    max_wildcard_expansions: usize,
+    explain_ranking: bool,
+    all_documents: bool,
 }
 
 impl<'c, 'f, 'd, 'i> QueryBuilder<'c, 'f, 'd, 'i> {
@@ -43,6 +52,44 @@ impl<'c, 'f, 'd, 'i> QueryBuilder<'c, 'f, 'd, 'i> {
         self.facets = facets;
     }
 
+    /// sets the levels of the hierarchical facet for which to return the count,
+    /// the count of each level is computed among the values of the level above
+    pub fn set_facet_tree(&mut self, levels: Option<Vec<(FieldId, String)>>) {
+        self.facet_tree = levels;
+    }
+
+    /// restricts the search to these documents
+    pub fn set_candidates(&mut self, documents_ids: Option<Vec<DocumentId>>) {
+        self.candidates = documents_ids.map(SetBuf::from_dirty);
+    }
+
+    /// removes these documents from the results, unlike the filter they are not counted in the hits
+    pub fn set_excluded_documents(&mut self, documents_ids: Vec<DocumentId>) {
+        self.excluded_documents = SetBuf::from_dirty(documents_ids);
+    }
+
+    /// sets the minimum similarity of the words matching a query word,
+    /// the typos allowed are then proportional to the length of the query word
+    pub fn set_min_similarity(&mut self, min_similarity: Option<f32>) {
+        self.min_similarity = min_similarity;
+    }
+
+    /// sets the maximum number of words a wildcard (e.g. `shoe*`) is expanded to
+    pub fn set_max_wildcard_expansions(&mut self, max_wildcard_expansions: usize) {
+        self.max_wildcard_expansions = max_wildcard_expansions;
+    }
+
+    /// records the criteria that ranked each returned document apart from the others
+    pub fn set_explain_ranking(&mut self, explain_ranking: bool) {
+        self.explain_ranking = explain_ranking;
+    }
+
+    /// returns all the documents in the order they were added instead of the ones matching
+    /// the query, the facet filters, the candidates and the filter still apply
+    pub fn set_all_documents(&mut self, all_documents: bool) {
+        self.all_documents = all_documents;
+    }
+
     pub fn with_criteria(
         index: &'i store::Index,
         criteria: Criteria<'c>,
@@ -56,6 +103,13 @@ impl<'c, 'f, 'd, 'i> QueryBuilder<'c, 'f, 'd, 'i> {
             index,
             facet_filter: None,
             facets: None,
+            facet_tree: None,
+            candidates: None,
+            excluded_documents: SetBuf::default(),
+            min_similarity: None,
+            max_wildcard_expansions: DEFAULT_MAX_WILDCARD_EXPANSIONS,
+            explain_ranking: false,
+            all_documents: false,
         }
     }
 
@@ -120,6 +174,31 @@ impl<'c, 'f, 'd, 'i> QueryBuilder<'c, 'f, 'd, 'i> {
             None => None
         };
 
+        let facets_docids = match (facets_docids, self.candidates) {
+            (Some(facets_docids), Some(candidates)) => {
+                Some(sdset::duo::OpBuilder::new(&facets_docids, &candidates).intersection().into_set_buf())
+            },
+            (facets_docids, candidates) => facets_docids.or(candidates),
+        };
+
+        // the excluded documents and the ones containing an excluded word are removed from the candidates
+        let (query, excluded_words) = split_excluded_words(query);
+        let query = query.as_str();
+        let mut excluded = self.excluded_documents;
+        if !excluded_words.is_empty() {
+            let excluded_by_words = excluded_documents(reader, self.index, &excluded_words, self.min_similarity)?;
+            excluded = sdset::duo::OpBuilder::new(&excluded, &excluded_by_words).union().into_set_buf();
+        }
+        let facets_docids = if excluded.is_empty() {
+            facets_docids
+        } else {
+            let candidates = match facets_docids {
+                Some(candidates) => candidates,
+                None => self.index.main.internal_docids(reader)?.into_owned(),
+            };
+            Some(sdset::duo::OpBuilder::new(&candidates, &excluded).difference().into_set_buf())
+        };
+
         // for each field to retrieve the count for, create an HashMap associating the attribute
         // value to a set of matching documents. The HashMaps are them collected in another
         // HashMap, associating each HashMap to it's field.
@@ -127,12 +206,7 @@ impl<'c, 'f, 'd, 'i> QueryBuilder<'c, 'f, 'd, 'i> {
             Some(field_ids) => {
                 let mut facet_count_map = HashMap::new();
                 for (field_id, field_name) in field_ids {
-                    let mut key_map = HashMap::new();
-                    for pair in self.index.facets.field_document_ids(reader, field_id)? {
-                        let (facet_key, document_ids) = pair?;
-                        let value = facet_key.value();
-                        key_map.insert(value.to_string(), document_ids);
-                    }
+                    let key_map = facet_values_docids(self.index, reader, field_id)?;
                     facet_count_map.insert(field_name, key_map);
                 }
                 Some(facet_count_map)
@@ -140,6 +214,30 @@ impl<'c, 'f, 'd, 'i> QueryBuilder<'c, 'f, 'd, 'i> {
             None => None,
         };
 
+        let facet_tree_docids = match self.facet_tree {
+            Some(levels) => {
+                let mut facet_tree_levels = Vec::with_capacity(levels.len());
+                for (field_id, field_name) in levels {
+                    let key_map = facet_values_docids(self.index, reader, field_id)?;
+                    facet_tree_levels.push((field_name, key_map));
+                }
+                Some(facet_tree_levels)
+            }
+            None => None,
+        };
+
+        if self.all_documents {
+            return all_documents_sort(
+                reader,
+                range,
+                facets_docids,
+                facet_count_docids,
+                facet_tree_docids,
+                self.filter,
+                self.index.main,
+            );
+        }
+
         match self.distinct {
             Some((distinct, distinct_size)) => bucket_sort_with_distinct(
                 reader,
@@ -147,11 +245,15 @@ impl<'c, 'f, 'd, 'i> QueryBuilder<'c, 'f, 'd, 'i> {
                 range,
                 facets_docids,
                 facet_count_docids,
+                facet_tree_docids,
                 self.filter,
                 distinct,
                 distinct_size,
                 self.criteria,
                 self.searchable_attrs,
+                self.min_similarity,
+                self.max_wildcard_expansions,
+                self.explain_ranking,
                 self.index.main,
                 self.index.postings_lists,
                 self.index.documents_fields_counts,
@@ -165,9 +267,13 @@ impl<'c, 'f, 'd, 'i> QueryBuilder<'c, 'f, 'd, 'i> {
                 range,
                 facets_docids,
                 facet_count_docids,
+                facet_tree_docids,
                 self.filter,
                 self.criteria,
                 self.searchable_attrs,
+                self.min_similarity,
+                self.max_wildcard_expansions,
+                self.explain_ranking,
                 self.index.main,
                 self.index.postings_lists,
                 self.index.documents_fields_counts,
@@ -179,6 +285,25 @@ impl<'c, 'f, 'd, 'i> QueryBuilder<'c, 'f, 'd, 'i> {
     }
 }
 
+/// Returns the documents having each value of the facet attribute.
+fn facet_values_docids<'txn>(
+    index: &store::Index,
+    reader: &'txn heed::RoTxn<MainT>,
+    field_id: FieldId,
+) -> MResult<HashMap<String, Cow<'txn, Set<DocumentId>>>> {
+    let mut key_map = HashMap::new();
+    for pair in index.facets.field_document_ids(reader, field_id)? {
+        let (facet_key, document_ids) = pair?;
+        // the values of the deleted documents are kept with an empty set
+        if document_ids.is_empty() {
+            continue;
+        }
+        let value = facet_key.value();
+        key_map.insert(value.to_string(), document_ids);
+    }
+    Ok(key_map)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,6 +319,7 @@ mod tests {
     use crate::DocIndex;
     use crate::Document;
     use crate::automaton::normalize_str;
+    use crate::criterion::{self, CriteriaBuilder};
     use crate::bucket_sort::SimpleMatch;
     use crate::database::{Database,DatabaseOptions};
     use crate::store::Index;
@@ -344,6 +470,9 @@ mod tests {
                     .unwrap();
             }
 
+            let docids = fields_counts.keys().map(|(docid, _, _)| *docid).collect();
+            index.main.put_internal_docids(&mut writer, &SetBuf::from_dirty(docids)).unwrap();
+
             for ((docid, attr, _), count) in fields_counts {
                 let prev = index
                     .documents_fields_counts
@@ -1306,4 +1435,122 @@ mod tests {
         });
         assert_matches!(iter.next(), None);
     }
+
+    #[test]
+    fn search_among_candidates() {
+        let store = TempDatabase::from_iter(vec![
+            ("hello", &[doc_index(0, 0)][..]),
+            ("hello", &[doc_index(1, 0)][..]),
+            ("hello", &[doc_index(2, 0)][..]),
+        ]);
+
+        let db = &store.database;
+        let reader = db.main_read_txn().unwrap();
+
+        let mut builder = store.query_builder();
+        builder.set_candidates(Some(vec![DocumentId(2), DocumentId(0)]));
+        let SortResult { documents, nb_hits, .. } = builder.query(&reader, "hello", 0..20).unwrap();
+        let mut iter = documents.into_iter();
+
+        assert_eq!(nb_hits, 2);
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), .. }));
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(2), .. }));
+        assert_matches!(iter.next(), None);
+    }
+
+    #[test]
+    fn excluded_documents_are_not_counted() {
+        let store = TempDatabase::from_iter(vec![
+            ("hello", &[doc_index(0, 0)][..]),
+            ("hello", &[doc_index(1, 0)][..]),
+            ("hello", &[doc_index(2, 0)][..]),
+        ]);
+
+        let db = &store.database;
+        let reader = db.main_read_txn().unwrap();
+
+        let mut builder = store.query_builder();
+        builder.set_excluded_documents(vec![DocumentId(1)]);
+        let SortResult { documents, nb_hits, .. } = builder.query(&reader, "hello", 0..20).unwrap();
+        let mut iter = documents.into_iter();
+
+        assert_eq!(nb_hits, 2);
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), .. }));
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(2), .. }));
+        assert_matches!(iter.next(), None);
+    }
+
+    #[test]
+    fn all_documents_in_insertion_order() {
+        let store = TempDatabase::from_iter(vec![
+            ("hello", &[doc_index(0, 0)][..]),
+            ("hello", &[doc_index(1, 0)][..]),
+            ("hello", &[doc_index(2, 0)][..]),
+        ]);
+
+        let db = &store.database;
+        let reader = db.main_read_txn().unwrap();
+
+        // the query does not matter, the documents are not searched
+        let mut builder = store.query_builder();
+        builder.set_candidates(Some(vec![DocumentId(2), DocumentId(0), DocumentId(1)]));
+        builder.with_filter(|id| id != DocumentId(1));
+        builder.set_all_documents(true);
+        let SortResult { documents, nb_hits, .. } = builder.query(&reader, "unknown", 0..20).unwrap();
+        let mut iter = documents.into_iter();
+
+        assert_eq!(nb_hits, 2);
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), .. }));
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(2), .. }));
+        assert_matches!(iter.next(), None);
+    }
+
+    #[test]
+    fn exact_match_criterion() {
+        // "york new" does not contain exactly "new york"
+        let store = TempDatabase::from_iter(vec![
+            ("new", &[doc_index(0, 1), doc_index(1, 0)][..]),
+            ("york", &[doc_index(0, 0), doc_index(1, 1)][..]),
+        ]);
+
+        let db = &store.database;
+        let reader = db.main_read_txn().unwrap();
+
+        let criteria = CriteriaBuilder::new().add(criterion::ExactMatch).build();
+        let builder = store.index.query_builder_with_criteria(criteria);
+        let SortResult { documents, .. } = builder.query(&reader, "new york", 0..20).unwrap();
+        let mut iter = documents.into_iter();
+
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(1), .. }));
+        assert_matches!(iter.next(), Some(Document { id: DocumentId(0), .. }));
+        assert_matches!(iter.next(), None);
+    }
+
+    #[test]
+    fn wildcard_prefix() {
+        let store = TempDatabase::from_iter(vec![
+            ("shoes", &[doc_index(0, 0)][..]),
+            ("shoelace", &[doc_index(1, 0)][..]),
+            ("shoemaker", &[doc_index(2, 0)][..]),
+            ("shop", &[doc_index(3, 0)][..]),
+        ]);
+
+        let db = &store.database;
+        let reader = db.main_read_txn().unwrap();
+
+        let builder = store.query_builder();
+        let SortResult { documents, truncated_wildcards, .. } = builder.query(&reader, "shoe*", 0..20).unwrap();
+        let mut ids: Vec<_> = documents.into_iter().map(|d| d.id).collect();
+        ids.sort_unstable();
+
+        assert_eq!(ids, vec![DocumentId(0), DocumentId(1), DocumentId(2)]);
+        assert!(truncated_wildcards.is_empty());
+
+        let mut builder = store.query_builder();
+        builder.set_max_wildcard_expansions(2);
+        let SortResult { nb_hits, truncated_wildcards, .. } = builder.query(&reader, "shoe*", 0..20).unwrap();
+
+        assert_eq!(nb_hits, 2);
+        assert_eq!(truncated_wildcards, vec!["shoe".to_string()]);
+    }
 }
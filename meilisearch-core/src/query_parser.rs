@@ -0,0 +1,258 @@
+use fst::{IntoStreamer, Streamer};
+use meilisearch_tokenizer::{split_query_string, Tokenizer};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::automaton::{build_dfa, build_dfa_with_typos, build_prefix_dfa, build_prefix_dfa_with_typos, typos_for_similarity};
+use crate::database::MainT;
+use crate::query_tree::{create_query_tree, synonyms_expansions, Context, Operation, QueryKind, DEFAULT_MAX_WILDCARD_EXPANSIONS};
+use crate::stemming::{query_stemmers, stem, stemmer};
+use crate::{store, DocumentId, MResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TokenKind {
+    Word,
+    StopWord,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryToken {
+    /// The word as it is written in the query string.
+    pub original: String,
+    /// The word as it is used by the engine to search.
+    pub word: String,
+    pub kind: TokenKind,
+    pub word_index: usize,
+    pub char_index: usize,
+    /// Whether the tokenizer changed the word (e.g. lowercasing).
+    pub modified: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedQuery {
+    pub tokens: Vec<QueryToken>,
+    pub tree: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StemmedToken {
+    pub original: String,
+    pub stemmed: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryAnalysis {
+    pub original_query: String,
+    /// The words of the query that are searched, separated by spaces.
+    pub processed_query: String,
+    pub stopped_words: Vec<String>,
+    /// The words that are also searched by their stem, with the stemming language of the index.
+    pub stemmed_tokens: Vec<StemmedToken>,
+}
+
+fn query_tokens(reader: &heed::RoTxn<MainT>, index: &store::Index, query: &str) -> MResult<Vec<QueryToken>> {
+    let stop_words = index.main.stop_words_fst(reader)?;
+
+    let tokens = Tokenizer::new(query)
+        .map(|token| {
+            let word = token.word.to_lowercase();
+            let kind = if stop_words.contains(&word) { TokenKind::StopWord } else { TokenKind::Word };
+            QueryToken {
+                original: token.word.to_string(),
+                modified: word != token.word,
+                word,
+                kind,
+                word_index: token.word_index,
+                char_index: token.char_index,
+            }
+        })
+        .collect();
+
+    Ok(tokens)
+}
+
+/// Tokenizes the query and builds its query tree the same way a search would,
+/// returning everything in a form that can be inspected.
+pub fn parse_query(
+    reader: &heed::RoTxn<MainT>,
+    index: &store::Index,
+    query: &str,
+) -> MResult<ParsedQuery> {
+    let tokens = query_tokens(reader, index, query)?;
+
+    let context = Context {
+        words_set: index.main.words_fst(reader)?,
+        stop_words: index.main.stop_words_fst(reader)?,
+        synonyms: index.synonyms,
+        synonyms_expansions: synonyms_expansions(reader, index.main)?,
+        postings_lists: index.postings_lists,
+        prefix_postings_lists: index.prefix_postings_lists_cache,
+        phonetic_matching: index.main.phonetic_matching(reader)?.unwrap_or(false),
+        min_similarity: None,
+        stemmers: query_stemmers(reader, index.main, query)?,
+        max_wildcard_expansions: DEFAULT_MAX_WILDCARD_EXPANSIONS,
+    };
+
+    let (operation, _mapping) = create_query_tree(reader, &context, query)?;
+    let tree = operation_to_json(&operation);
+
+    Ok(ParsedQuery { tokens, tree })
+}
+
+/// Returns the words of the query skipped as stop words and the ones searched by their stem.
+pub fn analyze_query(
+    reader: &heed::RoTxn<MainT>,
+    index: &store::Index,
+    query: &str,
+) -> MResult<QueryAnalysis> {
+    let tokens = query_tokens(reader, index, query)?;
+    let stemmer = index.main.stemming_language(reader)?.and_then(|l| stemmer(&l));
+
+    let mut words = Vec::new();
+    let mut stopped_words = Vec::new();
+    let mut stemmed_tokens = Vec::new();
+
+    for token in tokens {
+        match token.kind {
+            TokenKind::StopWord => stopped_words.push(token.word),
+            TokenKind::Word => {
+                if let Some(stemmed) = stemmer.as_ref().and_then(|stemmer| stem(stemmer, &token.word)) {
+                    if stemmed != token.word {
+                        stemmed_tokens.push(StemmedToken { original: token.word.clone(), stemmed });
+                    }
+                }
+                words.push(token.word);
+            }
+        }
+    }
+
+    Ok(QueryAnalysis {
+        original_query: query.to_string(),
+        processed_query: words.join(" "),
+        stopped_words,
+        stemmed_tokens,
+    })
+}
+
+/// Returns the words of the query, except the stop words, that the document contains with
+/// the typo tolerance of the search, the last word matches the words it is a prefix of.
+/// The words only matched by a synonym, a stem or the way they sound are not returned.
+pub fn matched_words(
+    reader: &heed::RoTxn<MainT>,
+    index: &store::Index,
+    document_id: DocumentId,
+    query: &str,
+    min_similarity: Option<f32>,
+) -> MResult<Vec<String>> {
+    let stop_words = index.main.stop_words_fst(reader)?;
+    let document_words = index.docs_words.doc_words(reader, document_id)?;
+
+    let words: Vec<_> = split_query_string(query)
+        .map(str::to_lowercase)
+        .filter(|word| !word.is_empty() && !stop_words.contains(word))
+        .collect();
+
+    let mut matched_words: Vec<String> = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+        let prefix = i + 1 == words.len();
+        let dfa = match min_similarity {
+            Some(min_similarity) => {
+                let typos = typos_for_similarity(min_similarity, word);
+                if prefix { build_prefix_dfa_with_typos(word, typos) } else { build_dfa_with_typos(word, typos) }
+            },
+            None => if prefix { build_prefix_dfa(word) } else { build_dfa(word) },
+        };
+
+        // like the search, the first letter of the words must be the right one
+        let byte = word.as_bytes()[0];
+        let mut stream = if byte == u8::max_value() {
+            document_words.search(&dfa).ge(&[byte]).into_stream()
+        } else {
+            document_words.search(&dfa).ge(&[byte]).lt(&[byte + 1]).into_stream()
+        };
+
+        if stream.next().is_some() && !matched_words.contains(word) {
+            matched_words.push(word.clone());
+        }
+    }
+
+    Ok(matched_words)
+}
+
+fn operation_to_json(operation: &Operation) -> Value {
+    match operation {
+        Operation::And(ops) => json!({ "and": ops.iter().map(operation_to_json).collect::<Vec<_>>() }),
+        Operation::Or(ops) => json!({ "or": ops.iter().map(operation_to_json).collect::<Vec<_>>() }),
+        Operation::Query(query) => {
+            let (kind, words) = match &query.kind {
+                QueryKind::Tolerant(word) => ("tolerant", vec![word.clone()]),
+                QueryKind::NonTolerant(word) => ("nonTolerant", vec![word.clone()]),
+                QueryKind::Phrase(words) => ("phrase", words.clone()),
+                QueryKind::Phonetic(word) => ("phonetic", vec![word.clone()]),
+                QueryKind::Stemmed(word) => ("stemmed", vec![word.clone()]),
+                QueryKind::Wildcard(word) => ("wildcard", vec![word.clone()]),
+            };
+
+            json!({
+                "id": query.id,
+                "kind": kind,
+                "prefix": query.prefix,
+                "exact": query.exact,
+                "words": words,
+            })
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::database::{Database, DatabaseOptions};
+
+    #[test]
+    fn tokens_are_lowercased_and_positioned() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = Database::open_or_create(dir.path(), DatabaseOptions::default()).unwrap();
+        let index = database.create_index("test").unwrap();
+
+        let reader = database.main_read_txn().unwrap();
+        let parsed = parse_query(&reader, &index, "Hello world").unwrap();
+
+        assert_eq!(parsed.tokens.len(), 2);
+        assert_eq!(parsed.tokens[0].word, "hello");
+        assert_eq!(parsed.tokens[0].original, "Hello");
+        assert!(parsed.tokens[0].modified);
+        assert_eq!(parsed.tokens[1].word, "world");
+        assert!(!parsed.tokens[1].modified);
+        assert_eq!(parsed.tokens[1].word_index, 1);
+        assert_eq!(parsed.tokens[1].char_index, 6);
+        assert_eq!(parsed.tokens[1].kind, TokenKind::Word);
+    }
+
+    #[test]
+    fn stop_words_and_stems_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = Database::open_or_create(dir.path(), DatabaseOptions::default()).unwrap();
+        let index = database.create_index("test").unwrap();
+
+        let mut writer = database.main_write_txn().unwrap();
+        let stop_words = fst::Set::from_iter(vec!["is", "the"]).unwrap();
+        index.main.put_stop_words_fst(&mut writer, &stop_words).unwrap();
+        index.main.put_stemming_language(&mut writer, "english").unwrap();
+        writer.commit().unwrap();
+
+        let reader = database.main_read_txn().unwrap();
+        let analysis = analyze_query(&reader, &index, "The dog is running").unwrap();
+
+        assert_eq!(analysis.original_query, "The dog is running");
+        assert_eq!(analysis.processed_query, "dog running");
+        assert_eq!(analysis.stopped_words, vec!["the", "is"]);
+        assert_eq!(analysis.stemmed_tokens, vec![StemmedToken { original: "running".into(), stemmed: "run".into() }]);
+    }
+}
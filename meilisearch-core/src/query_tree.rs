@@ -5,15 +5,19 @@ use std::ops::Range;
 use std::time::Instant;
 use std::{cmp, fmt, iter::once};
 
-use fst::{IntoStreamer, Streamer};
+use fst::{Automaton, IntoStreamer, Streamer};
 use itertools::{EitherOrBoth, merge_join_by};
 use meilisearch_tokenizer::split_query_string;
 use sdset::{Set, SetBuf, SetOperation};
 use log::debug;
+use rust_stemmers::Stemmer;
 
 use crate::database::MainT;
 use crate::{store, DocumentId, DocIndex, MResult, FstSetCow};
-use crate::automaton::{normalize_str, build_dfa, build_prefix_dfa, build_exact_dfa};
+use crate::automaton::{normalize_str, normalize_synonym, build_dfa, build_prefix_dfa, build_exact_dfa};
+use crate::automaton::{build_dfa_with_typos, build_prefix_dfa_with_typos, typos_for_similarity};
+use crate::phonetic::phonetic_word;
+use crate::stemming::{stem, stemmed_word};
 use crate::QueryWordsMapper;
 
 #[derive(Clone, PartialEq, Eq, Hash)]
@@ -52,6 +56,18 @@ impl Operation {
         Operation::Query(Query { id, prefix, exact: true, kind: QueryKind::NonTolerant(s.to_string()) })
     }
 
+    fn phonetic(id: QueryId, s: &str) -> Operation {
+        Operation::Query(Query { id, prefix: false, exact: false, kind: QueryKind::Phonetic(s.to_string()) })
+    }
+
+    fn stemmed(id: QueryId, s: &str) -> Operation {
+        Operation::Query(Query { id, prefix: false, exact: false, kind: QueryKind::Stemmed(s.to_string()) })
+    }
+
+    fn wildcard(id: QueryId, prefix: &str) -> Operation {
+        Operation::Query(Query { id, prefix: true, exact: true, kind: QueryKind::Wildcard(prefix.to_string()) })
+    }
+
     fn phrase2(id: QueryId, prefix: bool, (left, right): (&str, &str)) -> Operation {
         let kind = QueryKind::Phrase(vec![left.to_owned(), right.to_owned()]);
         Operation::Query(Query { id, prefix, exact: true, kind })
@@ -86,6 +102,12 @@ pub enum QueryKind {
     Tolerant(String),
     NonTolerant(String),
     Phrase(Vec<String>),
+    /// Matches the words that sound like this one.
+    Phonetic(String),
+    /// Matches the words that have this stem.
+    Stemmed(String),
+    /// Matches the words starting with this prefix, without typos.
+    Wildcard(String),
 }
 
 impl fmt::Debug for Query {
@@ -102,6 +124,15 @@ impl fmt::Debug for Query {
             QueryKind::Phrase(words) => {
                 f.debug_struct(&(prefix + "Phrase")).field("id", &id).field("words", &words).finish()
             },
+            QueryKind::Phonetic(word) => {
+                f.debug_struct(&(prefix + "Phonetic")).field("id", &id).field("word", &word).finish()
+            },
+            QueryKind::Stemmed(word) => {
+                f.debug_struct(&(prefix + "Stemmed")).field("id", &id).field("word", &word).finish()
+            },
+            QueryKind::Wildcard(word) => {
+                f.debug_struct(&(prefix + "Wildcard")).field("id", &id).field("word", &word).finish()
+            },
         }
     }
 }
@@ -116,8 +147,26 @@ pub struct Context<'a> {
     pub words_set: FstSetCow<'a>,
     pub stop_words: FstSetCow<'a>,
     pub synonyms: store::Synonyms,
+    /// The alternatives of the one-way and equivalent synonyms, by normalized words.
+    pub synonyms_expansions: HashMap<String, Vec<String>>,
     pub postings_lists: store::PostingsLists,
     pub prefix_postings_lists: store::PrefixPostingsListsCache,
+    pub phonetic_matching: bool,
+    /// When defined, the typos allowed for a word depend on its length, see `typos_for_similarity`.
+    pub min_similarity: Option<f32>,
+    /// The stemmers of the languages of the index, the words of the query are searched by all their stems.
+    pub stemmers: Vec<Stemmer>,
+    /// The maximum number of words a wildcard (e.g. `shoe*`) is expanded to.
+    pub max_wildcard_expansions: usize,
+}
+
+/// The number of words a wildcard is expanded to when not specified.
+pub const DEFAULT_MAX_WILDCARD_EXPANSIONS: usize = 1000;
+
+/// Returns the prefix of a word ending with a `*`, leading wildcards are not supported.
+fn wildcard_prefix(word: &str) -> Option<&str> {
+    let prefix = word.trim_end_matches('*');
+    if prefix.len() < word.len() && !prefix.is_empty() { Some(prefix) } else { None }
 }
 
 fn split_best_frequency<'a>(reader: &heed::RoTxn<MainT>, ctx: &Context, word: &'a str) -> MResult<Option<(&'a str, &'a str)>> {
@@ -145,8 +194,10 @@ fn split_best_frequency<'a>(reader: &heed::RoTxn<MainT>, ctx: &Context, word: &'
     Ok(best.map(|(_, l, r)| (l, r)))
 }
 
+/// Synonyms are only expanded here, at query time, they are never written in the postings lists.
+/// This is why a synonyms update is taken into account without reindexing the documents.
 fn fetch_synonyms(reader: &heed::RoTxn<MainT>, ctx: &Context, words: &[&str]) -> MResult<Vec<Vec<String>>> {
-    let words = normalize_str(&words.join(" "));
+    let words = normalize_synonym(&words.join(" "));
     let set = ctx.synonyms.synonyms_fst(reader, words.as_bytes())?;
 
     let mut strings = Vec::new();
@@ -158,9 +209,42 @@ fn fetch_synonyms(reader: &heed::RoTxn<MainT>, ctx: &Context, words: &[&str]) ->
         }
     }
 
+    for input in ctx.synonyms_expansions.get(&words).into_iter().flatten() {
+        let alts: Vec<_> = input.split_ascii_whitespace().map(ToOwned::to_owned).collect();
+        if !strings.contains(&alts) {
+            strings.push(alts);
+        }
+    }
+
     Ok(strings)
 }
 
+/// Returns the alternatives the words are searched with, a one-way synonym only expands
+/// the words it is defined for while any word of an equivalent group expands to the others.
+pub fn synonyms_expansions(reader: &heed::RoTxn<MainT>, main: store::Main) -> MResult<HashMap<String, Vec<String>>> {
+    let mut expansions: HashMap<String, Vec<String>> = HashMap::new();
+
+    for (words, alternatives) in main.one_way_synonyms(reader)?.unwrap_or_default() {
+        let entry = expansions.entry(normalize_synonym(&words)).or_default();
+        entry.extend(alternatives.iter().map(|alt| normalize_str(alt)));
+    }
+
+    for group in main.equivalent_synonyms(reader)?.unwrap_or_default() {
+        let group: Vec<_> = group.iter().map(|words| normalize_synonym(words)).collect();
+        for words in &group {
+            let entry = expansions.entry(words.clone()).or_default();
+            entry.extend(group.iter().filter(|alt| *alt != words).cloned());
+        }
+    }
+
+    for alternatives in expansions.values_mut() {
+        alternatives.sort_unstable();
+        alternatives.dedup();
+    }
+
+    Ok(expansions)
+}
+
 fn create_operation<I, F>(iter: I, f: F) -> Operation
 where I: IntoIterator<Item=Operation>,
       F: Fn(Vec<Operation>) -> Operation,
@@ -180,8 +264,9 @@ pub fn create_query_tree(
     query: &str,
 ) -> MResult<(Operation, HashMap<QueryId, Range<usize>>)>
 {
-    let words = split_query_string(query).map(str::to_lowercase);
-    let words = words.filter(|w| !ctx.stop_words.contains(w));
+    // the leading wildcards are ignored, they would require to scan all the words
+    let words = split_query_string(query).map(|w| w.trim_start_matches('*').to_lowercase());
+    let words = words.filter(|w| !w.trim_end_matches('*').is_empty() && !ctx.stop_words.contains(w));
     let words: Vec<_> = words.enumerate().collect();
 
     let mut mapper = QueryWordsMapper::new(words.iter().map(|(_, w)| w));
@@ -197,6 +282,11 @@ pub fn create_query_tree(
 
         for ngram in 1..=MAX_NGRAM {
             if let Some(group) = words.get(..ngram) {
+                // a wildcard can only match a single word
+                if ngram > 1 && group.iter().any(|(_, w)| wildcard_prefix(w).is_some()) {
+                    continue;
+                }
+
                 let mut group_ops = Vec::new();
 
                 let tail = &words[ngram..];
@@ -204,6 +294,10 @@ pub fn create_query_tree(
 
                 let mut group_alts = Vec::new();
                 match group {
+                    [(id, word)] if wildcard_prefix(word).is_some() => {
+                        let prefix = wildcard_prefix(word).unwrap();
+                        group_alts.push(Operation::wildcard(*id, prefix));
+                    },
                     [(id, word)] => {
                         let mut idgen = ((id + 1) * 100)..;
                         let range = (*id)..id+1;
@@ -216,6 +310,26 @@ pub fn create_query_tree(
                                 Operation::phrase2(id, is_last, ws)
                             });
 
+                        let phonetic = if ctx.phonetic_matching {
+                            phonetic_word(word).map(|_| {
+                                let id = idgen.next().unwrap();
+                                mapper.declare(range.clone(), id, &[word]);
+                                Operation::phonetic(id, word)
+                            })
+                        } else {
+                            None
+                        };
+
+                        let mut stems: Vec<_> = ctx.stemmers.iter().filter_map(|stemmer| stem(stemmer, word)).collect();
+                        stems.sort_unstable();
+                        stems.dedup();
+
+                        let stemmed: Vec<_> = stems.iter().map(|stem| {
+                            let id = idgen.next().unwrap();
+                            mapper.declare(range.clone(), id, &[word]);
+                            Operation::stemmed(id, stem)
+                        }).collect();
+
                         let synonyms = fetch_synonyms(reader, ctx, &[word])?
                             .into_iter()
                             .map(|alts| {
@@ -236,7 +350,7 @@ pub fn create_query_tree(
                         let original = Operation::tolerant(*id, is_last, word);
 
                         group_alts.push(original);
-                        group_alts.extend(synonyms.chain(phrase));
+                        group_alts.extend(synonyms.chain(phrase).chain(phonetic).chain(stemmed));
                     },
                     words => {
                         let id = words[0].0;
@@ -301,6 +415,8 @@ pub type Cache<'o, 'txn> = HashMap<&'o Operation, Cow<'txn, Set<DocumentId>>>;
 pub struct QueryResult<'o, 'txn> {
     pub docids: Cow<'txn, Set<DocumentId>>,
     pub queries: Postings<'o, 'txn>,
+    /// The wildcard prefixes that matched more words than `max_wildcard_expansions`.
+    pub truncated_wildcards: Vec<String>,
 }
 
 pub fn traverse_query_tree<'o, 'txn>(
@@ -314,6 +430,7 @@ pub fn traverse_query_tree<'o, 'txn>(
         ctx: &Context,
         cache: &mut Cache<'o, 'txn>,
         postings: &mut Postings<'o, 'txn>,
+        truncated: &mut Vec<String>,
         depth: usize,
         operations: &'o [Operation],
     ) -> MResult<Cow<'txn, Set<DocumentId>>>
@@ -326,9 +443,9 @@ pub fn traverse_query_tree<'o, 'txn>(
         for op in operations {
             if cache.get(op).is_none() {
                 let docids = match op {
-                    Operation::And(ops) => execute_and(reader, ctx, cache, postings, depth + 1, &ops)?,
-                    Operation::Or(ops) => execute_or(reader, ctx, cache, postings, depth + 1, &ops)?,
-                    Operation::Query(query) => execute_query(reader, ctx, postings, depth + 1, &query)?,
+                    Operation::And(ops) => execute_and(reader, ctx, cache, postings, truncated, depth + 1, &ops)?,
+                    Operation::Or(ops) => execute_or(reader, ctx, cache, postings, truncated, depth + 1, &ops)?,
+                    Operation::Query(query) => execute_query(reader, ctx, postings, truncated, depth + 1, &query)?,
                 };
                 cache.insert(op, docids);
             }
@@ -353,6 +470,7 @@ pub fn traverse_query_tree<'o, 'txn>(
         ctx: &Context,
         cache: &mut Cache<'o, 'txn>,
         postings: &mut Postings<'o, 'txn>,
+        truncated: &mut Vec<String>,
         depth: usize,
         operations: &'o [Operation],
     ) -> MResult<Cow<'txn, Set<DocumentId>>>
@@ -365,9 +483,9 @@ pub fn traverse_query_tree<'o, 'txn>(
         for op in operations {
             if cache.get(op).is_none() {
                 let docids = match op {
-                    Operation::And(ops) => execute_and(reader, ctx, cache, postings, depth + 1, &ops)?,
-                    Operation::Or(ops) => execute_or(reader, ctx, cache, postings, depth + 1, &ops)?,
-                    Operation::Query(query) => execute_query(reader, ctx, postings, depth + 1, &query)?,
+                    Operation::And(ops) => execute_and(reader, ctx, cache, postings, truncated, depth + 1, &ops)?,
+                    Operation::Or(ops) => execute_or(reader, ctx, cache, postings, truncated, depth + 1, &ops)?,
+                    Operation::Query(query) => execute_query(reader, ctx, postings, truncated, depth + 1, &query)?,
                 };
                 cache.insert(op, docids);
             }
@@ -391,6 +509,7 @@ pub fn traverse_query_tree<'o, 'txn>(
         reader: &'txn heed::RoTxn<MainT>,
         ctx: &Context,
         postings: &mut Postings<'o, 'txn>,
+        truncated: &mut Vec<String>,
         depth: usize,
         query: &'o Query,
     ) -> MResult<Cow<'txn, Set<DocumentId>>>
@@ -430,7 +549,13 @@ pub fn traverse_query_tree<'o, 'txn>(
                     Cow::Owned(docids)
 
                 } else {
-                    let dfa = if *prefix { build_prefix_dfa(word) } else { build_dfa(word) };
+                    let dfa = match ctx.min_similarity {
+                        Some(min_similarity) => {
+                            let typos = typos_for_similarity(min_similarity, word);
+                            if *prefix { build_prefix_dfa_with_typos(word, typos) } else { build_dfa_with_typos(word, typos) }
+                        },
+                        None => if *prefix { build_prefix_dfa(word) } else { build_dfa(word) },
+                    };
 
                     let byte = word.as_bytes()[0];
                     let mut stream = if byte == u8::max_value() {
@@ -541,6 +666,68 @@ pub fn traverse_query_tree<'o, 'txn>(
                     Cow::default()
                 }
             },
+            QueryKind::Phonetic(word) => {
+                match phonetic_word(word) {
+                    Some(phonetic) => {
+                        let result = ctx.postings_lists.postings_list(reader, phonetic.as_bytes())?.unwrap_or_default();
+                        // A phonetic match is considered as a word with one typo.
+                        let key = PostingsKey { query, input: phonetic.into_bytes(), distance: 1, is_exact: false };
+                        postings.insert(key, result.matches);
+                        result.docids
+                    },
+                    None => Cow::default(),
+                }
+            },
+            QueryKind::Stemmed(stem) => {
+                let stemmed = stemmed_word(stem);
+                let result = ctx.postings_lists.postings_list(reader, stemmed.as_bytes())?.unwrap_or_default();
+                // A stemmed match is considered as a word with one typo,
+                // this way the exact matches are ranked first.
+                let key = PostingsKey { query, input: stemmed.into_bytes(), distance: 1, is_exact: false };
+                postings.insert(key, result.matches);
+                result.docids
+            },
+            QueryKind::Wildcard(prefix) => {
+                let automaton = fst::automaton::Str::new(prefix).starts_with();
+                let mut stream = ctx.words_set.search(automaton).into_stream();
+
+                let before = Instant::now();
+                let mut expansions = 0;
+                let mut results = Vec::new();
+                while let Some(input) = stream.next() {
+                    if expansions == ctx.max_wildcard_expansions {
+                        if !truncated.contains(prefix) {
+                            truncated.push(prefix.clone());
+                        }
+                        break;
+                    }
+                    expansions += 1;
+
+                    if let Some(result) = ctx.postings_lists.postings_list(reader, input)? {
+                        let is_exact = input.len() == prefix.len();
+                        results.push(result.docids);
+                        let key = PostingsKey { query, input: input.to_owned(), distance: 0, is_exact };
+                        postings.insert(key, result.matches);
+                    }
+                }
+                debug!("{:3$}docids retrieval ({:?}) took {:.02?}", "", results.len(), before.elapsed(), depth * 2);
+
+                let before = Instant::now();
+                let docids = if results.len() > 10 {
+                    let cap = results.iter().map(|dis| dis.len()).sum();
+                    let mut docids = Vec::with_capacity(cap);
+                    for dis in results {
+                        docids.extend_from_slice(&dis);
+                    }
+                    SetBuf::from_dirty(docids)
+                } else {
+                    let sets = results.iter().map(AsRef::as_ref).collect();
+                    sdset::multi::Union::new(sets).into_set_buf()
+                };
+                debug!("{:2$}docids construction took {:.02?}", "", before.elapsed(), depth * 2);
+
+                Cow::Owned(docids)
+            },
         };
 
         debug!("{:4$}{:?} fetched {:?} documents in {:.02?}", "", query, docids.len(), before.elapsed(), depth * 2);
@@ -549,12 +736,13 @@ pub fn traverse_query_tree<'o, 'txn>(
 
     let mut cache = Cache::new();
     let mut postings = Postings::new();
+    let mut truncated = Vec::new();
 
     let docids = match tree {
-        Operation::And(ops) => execute_and(reader, ctx, &mut cache, &mut postings, 0, &ops)?,
-        Operation::Or(ops) => execute_or(reader, ctx, &mut cache, &mut postings, 0, &ops)?,
-        Operation::Query(query) => execute_query(reader, ctx, &mut postings, 0, &query)?,
+        Operation::And(ops) => execute_and(reader, ctx, &mut cache, &mut postings, &mut truncated, 0, &ops)?,
+        Operation::Or(ops) => execute_or(reader, ctx, &mut cache, &mut postings, &mut truncated, 0, &ops)?,
+        Operation::Query(query) => execute_query(reader, ctx, &mut postings, &mut truncated, 0, &query)?,
     };
 
-    Ok(QueryResult { docids, queries: postings })
+    Ok(QueryResult { docids, queries: postings, truncated_wildcards: truncated })
 }
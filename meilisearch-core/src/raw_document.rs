@@ -13,6 +13,12 @@ pub struct RawDocument<'a, 'tag> {
     /// Does this document contains a field
     /// with one word that is exactly matching
     pub contains_one_word_field: bool,
+    /// Does this document contains a field with all
+    /// the query words, in order and without typos
+    pub contains_exact_match: bool,
+    /// The indexes of the criteria that ranked this document apart from
+    /// some of the documents it was tied with, only recorded when asked.
+    pub decisive_criteria: Vec<usize>,
 }
 
 impl<'a, 'tag> RawDocument<'a, 'tag> {
@@ -46,6 +52,8 @@ impl<'a, 'tag> RawDocument<'a, 'tag> {
             processed_matches: Vec::new(),
             processed_distances: Vec::new(),
             contains_one_word_field: false,
+            contains_exact_match: false,
+            decisive_criteria: Vec::new(),
         }
     }
 }
@@ -1,14 +1,18 @@
 use std::borrow::Cow;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::convert::TryFrom;
 
 use deunicode::deunicode_with_tofu;
 use meilisearch_schema::IndexedPos;
 use meilisearch_tokenizer::{is_cjk, SeqTokenizer, Token, Tokenizer};
+use rust_stemmers::Stemmer;
 use sdset::SetBuf;
 
 use crate::{DocIndex, DocumentId};
 use crate::FstSetCow;
+use crate::decompound::decompound;
+use crate::phonetic::{is_phonetic_word, phonetic_word};
+use crate::stemming::{is_stemmed_word, language_name, stem, stemmed_word, stemmer};
 
 const WORD_LENGTH_LIMIT: usize = 80;
 
@@ -17,6 +21,10 @@ type Word = Vec<u8>; // TODO make it be a SmallVec
 pub struct RawIndexer<A> {
     word_limit: usize, // the maximum number of indexed words
     stop_words: fst::Set<A>,
+    stemmer: Option<Stemmer>,
+    languages_stemmers: HashMap<&'static str, Stemmer>,
+    docs_languages: HashMap<DocumentId, &'static str>,
+    decompound_dictionary: Option<fst::Set<Vec<u8>>>,
     words_doc_indexes: BTreeMap<Word, Vec<DocIndex>>,
     docs_words: HashMap<DocumentId, Vec<Word>>,
 }
@@ -24,6 +32,8 @@ pub struct RawIndexer<A> {
 pub struct Indexed<'a> {
     pub words_doc_indexes: BTreeMap<Word, SetBuf<DocIndex>>,
     pub docs_words: HashMap<DocumentId, FstSetCow<'a>>,
+    /// The languages the documents were stemmed in, when it is not the one of the index.
+    pub languages: BTreeSet<String>,
 }
 
 impl<A> RawIndexer<A> {
@@ -35,10 +45,43 @@ impl<A> RawIndexer<A> {
         RawIndexer {
             word_limit: limit,
             stop_words,
+            stemmer: None,
+            languages_stemmers: HashMap::new(),
+            docs_languages: HashMap::new(),
+            decompound_dictionary: None,
             words_doc_indexes: BTreeMap::new(),
             docs_words: HashMap::new(),
         }
     }
+
+    /// The stem of every word is also indexed, at the position of the word.
+    pub fn set_stemmer(&mut self, stemmer: Option<Stemmer>) {
+        self.stemmer = stemmer;
+    }
+
+    /// The words of the document are stemmed in this language instead of the one of the index,
+    /// given by its name or by its ISO 639-1 code. The unsupported languages are ignored.
+    pub fn set_document_language(&mut self, id: DocumentId, language: &str) {
+        let language = match language_name(language) {
+            Some(language) => language,
+            None => return,
+        };
+
+        if !self.languages_stemmers.contains_key(language) {
+            match stemmer(language) {
+                Some(stemmer) => self.languages_stemmers.insert(language, stemmer),
+                None => return,
+            };
+        }
+
+        self.docs_languages.insert(id, language);
+    }
+
+    /// The compound words are also indexed as the words they are made of, at the position
+    /// of the compound word. These parts must be words of the dictionary or of the indexed texts.
+    pub fn set_decompound_dictionary(&mut self, dictionary: Option<fst::Set<Vec<u8>>>) {
+        self.decompound_dictionary = dictionary;
+    }
 }
 
 impl<A: AsRef<[u8]>> RawIndexer<A> {
@@ -52,6 +95,7 @@ impl<A: AsRef<[u8]>> RawIndexer<A> {
                 indexed_pos,
                 self.word_limit,
                 &self.stop_words,
+                document_stemmer(id, self.stemmer.as_ref(), &self.languages_stemmers, &self.docs_languages),
                 &mut self.words_doc_indexes,
                 &mut self.docs_words,
             );
@@ -66,6 +110,37 @@ impl<A: AsRef<[u8]>> RawIndexer<A> {
         number_of_words
     }
 
+    /// Indexes the phonetic codes of the words of the text at the positions of these words,
+    /// the words themselves must be indexed by `index_text`.
+    pub fn index_text_phonetic(&mut self, id: DocumentId, indexed_pos: IndexedPos, text: &str) {
+        for token in Tokenizer::new(text) {
+            if token.word_index >= self.word_limit {
+                break;
+            }
+
+            let lower = token.word.to_lowercase();
+            if self.stop_words.contains(&lower) {
+                continue;
+            }
+
+            let docindex = match token_to_docindex(id, indexed_pos, Token { word: &lower, ..token }) {
+                Some(docindex) => docindex,
+                None => break,
+            };
+
+            if let Some(phonetic) = phonetic_word(&lower) {
+                let word = Vec::from(phonetic);
+                if word.len() <= WORD_LENGTH_LIMIT {
+                    self.words_doc_indexes
+                        .entry(word.clone())
+                        .or_insert_with(Vec::new)
+                        .push(docindex);
+                    self.docs_words.entry(id).or_insert_with(Vec::new).push(word);
+                }
+            }
+        }
+    }
+
     pub fn index_text_seq<'s, I>(&mut self, id: DocumentId, indexed_pos: IndexedPos, iter: I)
     where
         I: IntoIterator<Item = &'s str>,
@@ -78,6 +153,7 @@ impl<A: AsRef<[u8]>> RawIndexer<A> {
                 indexed_pos,
                 self.word_limit,
                 &self.stop_words,
+                document_stemmer(id, self.stemmer.as_ref(), &self.languages_stemmers, &self.docs_languages),
                 &mut self.words_doc_indexes,
                 &mut self.docs_words,
             );
@@ -88,7 +164,11 @@ impl<A: AsRef<[u8]>> RawIndexer<A> {
         }
     }
 
-    pub fn build(self) -> Indexed<'static> {
+    pub fn build(mut self) -> Indexed<'static> {
+        if let Some(dictionary) = self.decompound_dictionary.take() {
+            self.index_compounds_parts(&dictionary);
+        }
+
         let words_doc_indexes = self
             .words_doc_indexes
             .into_iter()
@@ -106,19 +186,67 @@ impl<A: AsRef<[u8]>> RawIndexer<A> {
             })
             .collect();
 
+        let languages = self.languages_stemmers.keys().map(|language| language.to_string()).collect();
+
         Indexed {
             words_doc_indexes,
             docs_words,
+            languages,
         }
     }
 }
 
+impl<A> RawIndexer<A> {
+    fn index_compounds_parts(&mut self, dictionary: &fst::Set<Vec<u8>>) {
+        let words_doc_indexes = &self.words_doc_indexes;
+        let is_known = |part: &str| words_doc_indexes.contains_key(part.as_bytes()) || dictionary.contains(part);
+
+        let mut parts_doc_indexes = Vec::new();
+        for (word, indexes) in words_doc_indexes {
+            if is_phonetic_word(word) || is_stemmed_word(word) {
+                continue;
+            }
+
+            let word = match std::str::from_utf8(word) {
+                Ok(word) => word,
+                Err(_) => continue,
+            };
+
+            if let Some(parts) = decompound(word, &is_known) {
+                for part in parts {
+                    parts_doc_indexes.push((Vec::from(part), indexes.clone()));
+                }
+            }
+        }
+
+        for (part, indexes) in parts_doc_indexes {
+            for docindex in &indexes {
+                self.docs_words.entry(docindex.document_id).or_insert_with(Vec::new).push(part.clone());
+            }
+            self.words_doc_indexes.entry(part).or_insert_with(Vec::new).extend(indexes);
+        }
+    }
+}
+
+fn document_stemmer<'a>(
+    id: DocumentId,
+    stemmer: Option<&'a Stemmer>,
+    languages_stemmers: &'a HashMap<&'static str, Stemmer>,
+    docs_languages: &HashMap<DocumentId, &'static str>,
+) -> Option<&'a Stemmer> {
+    match docs_languages.get(&id) {
+        Some(language) => languages_stemmers.get(language),
+        None => stemmer,
+    }
+}
+
 fn index_token<A>(
     token: Token,
     id: DocumentId,
     indexed_pos: IndexedPos,
     word_limit: usize,
     stop_words: &fst::Set<A>,
+    stemmer: Option<&Stemmer>,
     words_doc_indexes: &mut BTreeMap<Word, Vec<DocIndex>>,
     docs_words: &mut HashMap<DocumentId, Vec<Word>>,
 ) -> bool
@@ -159,6 +287,17 @@ where A: AsRef<[u8]>,
                             }
                         }
                     }
+
+                    if let Some(stem) = stemmer.and_then(|stemmer| stem(stemmer, &lower)) {
+                        let word = Vec::from(stemmed_word(&stem));
+                        if word.len() <= WORD_LENGTH_LIMIT {
+                            words_doc_indexes
+                                .entry(word.clone())
+                                .or_insert_with(Vec::new)
+                                .push(docindex);
+                            docs_words.entry(id).or_insert_with(Vec::new).push(word);
+                        }
+                    }
                 }
             }
             None => return false,
@@ -277,4 +416,69 @@ mod tests {
             .get(&"🇯🇵".to_owned().into_bytes())
             .is_some());
     }
+
+    #[test]
+    fn phonetic_codes_share_positions() {
+        let mut indexer = RawIndexer::new(fst::Set::default());
+
+        let docid = DocumentId(0);
+        let indexed_pos = IndexedPos(0);
+        let text = "John Smith";
+        indexer.index_text(docid, indexed_pos, text);
+        indexer.index_text_phonetic(docid, indexed_pos, text);
+
+        let Indexed {
+            words_doc_indexes, ..
+        } = indexer.build();
+
+        let word = words_doc_indexes.get(&b"smith"[..]).unwrap();
+        let code = phonetic_word("smyth").unwrap().into_bytes();
+        let phonetic = words_doc_indexes.get(&code).unwrap();
+        assert_eq!(word.as_slice(), phonetic.as_slice());
+    }
+
+    #[test]
+    fn compound_words_parts() {
+        let dictionary = fst::Set::from_iter(vec!["training"]).unwrap();
+
+        let mut indexer = RawIndexer::new(fst::Set::default());
+        indexer.set_decompound_dictionary(Some(dictionary));
+
+        let indexed_pos = IndexedPos(0);
+        indexer.index_text(DocumentId(0), indexed_pos, "Fußball");
+        indexer.index_text(DocumentId(1), indexed_pos, "Fußballtraining");
+
+        let Indexed {
+            words_doc_indexes, ..
+        } = indexer.build();
+
+        let compound = words_doc_indexes.get("fußballtraining".as_bytes()).unwrap();
+        let training = words_doc_indexes.get(&b"training"[..]).unwrap();
+        assert_eq!(compound.as_slice(), training.as_slice());
+
+        let football = words_doc_indexes.get("fußball".as_bytes()).unwrap();
+        assert_eq!(football.len(), 2);
+    }
+
+    #[test]
+    fn documents_stemmed_in_their_language() {
+        let mut indexer = RawIndexer::new(fst::Set::default());
+        indexer.set_stemmer(crate::stemming::stemmer("english"));
+        indexer.set_document_language(DocumentId(1), "de");
+
+        let indexed_pos = IndexedPos(0);
+        indexer.index_text(DocumentId(0), indexed_pos, "running");
+        indexer.index_text(DocumentId(1), indexed_pos, "laufen");
+
+        let Indexed {
+            words_doc_indexes, languages, ..
+        } = indexer.build();
+
+        let english = words_doc_indexes.get(stemmed_word("run").as_bytes()).unwrap();
+        assert_eq!(english[0].document_id, DocumentId(0));
+        let german = words_doc_indexes.get(stemmed_word("lauf").as_bytes()).unwrap();
+        assert_eq!(german[0].document_id, DocumentId(1));
+        assert!(words_doc_indexes.get(stemmed_word("laufen").as_bytes()).is_none());
+        assert_eq!(languages.into_iter().collect::<Vec<_>>(), vec!["german"]);
+    }
 }
@@ -5,12 +5,17 @@ use std::iter::IntoIterator;
 use serde::{Deserialize, Deserializer, Serialize};
 use once_cell::sync::Lazy;
 
+use crate::criterion::{Expression, ExpressionError};
+
 use self::RankingRule::*;
 
 pub const DEFAULT_RANKING_RULES: [RankingRule; 6] = [Typo, Words, Proximity, Attribute, WordsPosition, Exactness];
 
+/// The maximum distance, in words, between two query words that the proximity rule distinguishes.
+pub const DEFAULT_PROXIMITY_WINDOW: usize = 8;
+
 static RANKING_RULE_REGEX: Lazy<regex::Regex> = Lazy::new(|| {
-    let regex = regex::Regex::new(r"(asc|desc)\(([a-zA-Z0-9-_]*)\)").unwrap();
+    let regex = regex::Regex::new(r"^(asc|desc)\((.+)\)$").unwrap();
     regex
 });
 
@@ -33,6 +38,50 @@ pub struct Settings {
     pub accept_new_fields: Option<Option<bool>>,
     #[serde(default, deserialize_with = "deserialize_some")]
     pub attributes_for_faceting: Option<Option<Vec<String>>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub max_fields_count: Option<Option<usize>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub max_index_size: Option<Option<usize>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub phonetic_matching: Option<Option<bool>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub phonetic_attributes: Option<Option<Vec<String>>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub stemming_language: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub decompound_tokens: Option<Option<bool>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub proximity_precision: Option<Option<ProximityPrecision>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub proximity_window: Option<Option<usize>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub pinned_hits: Option<Option<Vec<PinnedHit>>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub demoted_documents: Option<Option<Vec<DemotedDocument>>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub image_hash_field: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub language_detection_field: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub one_way_synonyms: Option<Option<BTreeMap<String, Vec<String>>>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub equivalent_synonyms: Option<Option<Vec<Vec<String>>>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub hierarchical_facets: Option<Option<BTreeMap<String, String>>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub transformer: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub cross_field_boost: Option<Option<bool>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub document_versions: Option<Option<usize>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub referenced_documents: Option<Option<Vec<ReferencedDocument>>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub embedder: Option<Option<Embedder>>,
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub embedding_strategy: Option<Option<EmbeddingStrategy>>,
 }
 
 // Any value that is present is considered Some value, including null.
@@ -63,10 +112,169 @@ impl Settings {
             synonyms: settings.synonyms.into(),
             accept_new_fields: settings.accept_new_fields.into(),
             attributes_for_faceting: settings.attributes_for_faceting.into(),
+            max_fields_count: settings.max_fields_count.into(),
+            max_index_size: settings.max_index_size.into(),
+            phonetic_matching: settings.phonetic_matching.into(),
+            phonetic_attributes: settings.phonetic_attributes.into(),
+            stemming_language: settings.stemming_language.into(),
+            decompound_tokens: settings.decompound_tokens.into(),
+            proximity_precision: settings.proximity_precision.into(),
+            proximity_window: settings.proximity_window.into(),
+            pinned_hits: settings.pinned_hits.into(),
+            demoted_documents: settings.demoted_documents.into(),
+            image_hash_field: settings.image_hash_field.into(),
+            language_detection_field: settings.language_detection_field.into(),
+            one_way_synonyms: settings.one_way_synonyms.into(),
+            equivalent_synonyms: settings.equivalent_synonyms.into(),
+            hierarchical_facets: settings.hierarchical_facets.into(),
+            transformer: settings.transformer.into(),
+            webhook_url: settings.webhook_url.into(),
+            cross_field_boost: settings.cross_field_boost.into(),
+            document_versions: settings.document_versions.into(),
+            referenced_documents: settings.referenced_documents.into(),
+            embedder: settings.embedder.into(),
+            embedding_strategy: settings.embedding_strategy.into(),
         })
     }
 }
 
+/// How the proximity ranking rule measures the distance between the query words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProximityPrecision {
+    /// The number of words between the query words, up to the proximity window.
+    ByWord,
+    /// Only whether the query words are in the same attribute.
+    ByAttribute,
+}
+
+impl Default for ProximityPrecision {
+    fn default() -> ProximityPrecision {
+        ProximityPrecision::ByWord
+    }
+}
+
+/// A document returned at a given position of the results, whatever its ranking is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PinnedHit {
+    /// The primary key value of the document.
+    pub id: String,
+    /// The position of the document, starting at 1, or `None` to append it after the results.
+    #[serde(default)]
+    pub position: Option<usize>,
+    /// The pattern of the queries the document is pinned for, every query when `None`.
+    #[serde(default)]
+    pub query: Option<String>,
+}
+
+impl PinnedHit {
+    /// Whether the document is pinned for this query, see `query_matches`.
+    pub fn matches(&self, query: &str) -> bool {
+        query_matches(self.query.as_deref(), query)
+    }
+}
+
+/// A document moved down the results, whatever its ranking is.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DemotedDocument {
+    /// The primary key value of the document.
+    pub id: String,
+    /// Between 0 and 1, the position of the document is divided by it, 0 removes the document from the results.
+    pub penalty_factor: f64,
+    /// The pattern of the queries the document is demoted for, every query when `None`.
+    #[serde(default)]
+    pub query: Option<String>,
+}
+
+impl DemotedDocument {
+    /// Whether the document is demoted for this query, see `query_matches`.
+    pub fn matches(&self, query: &str) -> bool {
+        query_matches(self.query.as_deref(), query)
+    }
+}
+
+/// The fields of the documents of another index copied into the documents referencing them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ReferencedDocument {
+    /// The field of the documents holding the foreign key, or an array of foreign keys.
+    pub field: String,
+    /// The uid of the index of the referenced documents.
+    pub foreign_index: String,
+    /// The primary key of the referenced documents.
+    pub foreign_key: String,
+    /// The paths the fields are copied to, e.g. `author.name` copies the `name`
+    /// field of the referenced document into the `author` object.
+    pub fields_to_inject: Vec<String>,
+}
+
+/// The field the embeddings of the documents are stored in when the embedder does not set one.
+pub const DEFAULT_DOCUMENT_EMBEDDING_FIELD: &str = "_vectors.default";
+
+/// The model generating the embeddings of the documents when they are indexed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Embedder {
+    pub source: EmbedderSource,
+    pub model: String,
+    /// The number of dimensions of the embeddings.
+    pub dimensions: usize,
+    /// The path of the field the embeddings are stored in, e.g. `_vectors.default`.
+    #[serde(default = "default_document_embedding_field")]
+    pub document_embedding_field: String,
+    /// The key the API of the source is called with.
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+fn default_document_embedding_field() -> String {
+    DEFAULT_DOCUMENT_EMBEDDING_FIELD.to_string()
+}
+
+/// The API generating the embeddings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EmbedderSource {
+    #[serde(rename = "openai")]
+    OpenAi,
+}
+
+/// When the embeddings of the documents are generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EmbeddingStrategy {
+    /// When the documents are indexed.
+    Eager,
+    /// When the documents are first returned by a search.
+    Lazy,
+}
+
+impl Default for EmbeddingStrategy {
+    fn default() -> EmbeddingStrategy {
+        EmbeddingStrategy::Eager
+    }
+}
+
+/// Whether the query matches the pattern, every query matches no pattern. The pattern is
+/// compared to the query regardless of the case and of the spaces, a pattern ending with `*`
+/// matches the queries starting with the rest of the pattern.
+fn query_matches(pattern: Option<&str>, query: &str) -> bool {
+    let normalize = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+
+    let pattern = match pattern {
+        Some(pattern) => pattern.trim(),
+        None => return true,
+    };
+
+    let query = normalize(query);
+    if pattern.ends_with('*') {
+        query.starts_with(&normalize(&pattern[..pattern.len() - 1]))
+    } else {
+        query == normalize(pattern)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum UpdateState<T> {
     Update(T),
@@ -85,11 +293,17 @@ impl <T> From<Option<Option<T>>> for UpdateState<T> {
 }
 
 #[derive(Debug, Clone)]
-pub struct RankingRuleConversionError;
+pub enum RankingRuleConversionError {
+    InvalidRule,
+    InvalidExpression(ExpressionError),
+}
 
 impl std::fmt::Display for RankingRuleConversionError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "impossible to convert into RankingRule")
+        match self {
+            RankingRuleConversionError::InvalidRule => write!(f, "impossible to convert into RankingRule"),
+            RankingRuleConversionError::InvalidExpression(e) => write!(f, "invalid ranking rule expression; {}", e),
+        }
     }
 }
 
@@ -103,6 +317,7 @@ pub enum RankingRule {
     Exactness,
     Asc(String),
     Desc(String),
+    ExactMatch,
 }
 
 impl std::fmt::Display for RankingRule {
@@ -114,6 +329,7 @@ impl std::fmt::Display for RankingRule {
             RankingRule::Attribute => f.write_str("attribute"),
             RankingRule::WordsPosition => f.write_str("wordsPosition"),
             RankingRule::Exactness => f.write_str("exactness"),
+            RankingRule::ExactMatch => f.write_str("exactMatch"),
             RankingRule::Asc(field) => write!(f, "asc({})", field),
             RankingRule::Desc(field) => write!(f, "desc({})", field),
         }
@@ -131,12 +347,15 @@ impl FromStr for RankingRule {
             "attribute" => RankingRule::Attribute,
             "wordsPosition" => RankingRule::WordsPosition,
             "exactness" => RankingRule::Exactness,
+            "exactMatch" => RankingRule::ExactMatch,
             _ => {
-                let captures = RANKING_RULE_REGEX.captures(s).ok_or(RankingRuleConversionError)?;
-                match (captures.get(1).map(|m| m.as_str()), captures.get(2)) {
-                    (Some("asc"), Some(field)) => RankingRule::Asc(field.as_str().to_string()),
-                    (Some("desc"), Some(field)) => RankingRule::Desc(field.as_str().to_string()),
-                    _ => return Err(RankingRuleConversionError)
+                let captures = RANKING_RULE_REGEX.captures(s).ok_or(RankingRuleConversionError::InvalidRule)?;
+                let expression = captures.get(2).map(|m| m.as_str().trim()).unwrap_or_default();
+                Expression::from_str(expression).map_err(RankingRuleConversionError::InvalidExpression)?;
+                match captures.get(1).map(|m| m.as_str()) {
+                    Some("asc") => RankingRule::Asc(expression.to_string()),
+                    Some("desc") => RankingRule::Desc(expression.to_string()),
+                    _ => return Err(RankingRuleConversionError::InvalidRule)
                 }
             }
         };
@@ -145,10 +364,14 @@ impl FromStr for RankingRule {
 }
 
 impl RankingRule {
-    pub fn field(&self) -> Option<&str> {
+    /// Returns the attributes the custom ranking rule sorts by, the ones of its expression.
+    /// The expression was checked when the rule was parsed, an invalid one has no attributes.
+    pub fn fields(&self) -> Vec<String> {
         match self {
-            RankingRule::Asc(field) | RankingRule::Desc(field) => Some(field),
-            _ => None,
+            RankingRule::Asc(rule) | RankingRule::Desc(rule) => Expression::from_str(rule)
+                .map(|expression| expression.fields().into_iter().map(str::to_string).collect())
+                .unwrap_or_default(),
+            _ => Vec::new(),
         }
     }
 
@@ -170,6 +393,40 @@ pub struct SettingsUpdate {
     pub synonyms: UpdateState<BTreeMap<String, Vec<String>>>,
     pub accept_new_fields: UpdateState<bool>,
     pub attributes_for_faceting: UpdateState<Vec<String>>,
+    pub max_fields_count: UpdateState<usize>,
+    pub max_index_size: UpdateState<usize>,
+    pub phonetic_matching: UpdateState<bool>,
+    pub phonetic_attributes: UpdateState<Vec<String>>,
+    pub stemming_language: UpdateState<String>,
+    pub decompound_tokens: UpdateState<bool>,
+    pub proximity_precision: UpdateState<ProximityPrecision>,
+    pub proximity_window: UpdateState<usize>,
+    pub pinned_hits: UpdateState<Vec<PinnedHit>>,
+    pub demoted_documents: UpdateState<Vec<DemotedDocument>>,
+    pub image_hash_field: UpdateState<String>,
+    pub language_detection_field: UpdateState<String>,
+    pub one_way_synonyms: UpdateState<BTreeMap<String, Vec<String>>>,
+    pub equivalent_synonyms: UpdateState<Vec<Vec<String>>>,
+    pub hierarchical_facets: UpdateState<BTreeMap<String, String>>,
+    pub transformer: UpdateState<String>,
+    pub webhook_url: UpdateState<String>,
+    pub cross_field_boost: UpdateState<bool>,
+    pub document_versions: UpdateState<usize>,
+    pub referenced_documents: UpdateState<Vec<ReferencedDocument>>,
+    pub embedder: UpdateState<Embedder>,
+    pub embedding_strategy: UpdateState<EmbeddingStrategy>,
+}
+
+impl SettingsUpdate {
+    /// Returns the settings without the API key of the embedder, for it not
+    /// to be returned with the status of the update nor sent to the webhook.
+    pub fn without_secrets(&self) -> SettingsUpdate {
+        let mut settings = self.clone();
+        if let UpdateState::Update(embedder) = &mut settings.embedder {
+            embedder.api_key = None;
+        }
+        settings
+    }
 }
 
 impl Default for SettingsUpdate {
@@ -184,6 +441,28 @@ impl Default for SettingsUpdate {
             synonyms: UpdateState::Nothing,
             accept_new_fields: UpdateState::Nothing,
             attributes_for_faceting: UpdateState::Nothing,
+            max_fields_count: UpdateState::Nothing,
+            max_index_size: UpdateState::Nothing,
+            phonetic_matching: UpdateState::Nothing,
+            phonetic_attributes: UpdateState::Nothing,
+            stemming_language: UpdateState::Nothing,
+            decompound_tokens: UpdateState::Nothing,
+            proximity_precision: UpdateState::Nothing,
+            proximity_window: UpdateState::Nothing,
+            pinned_hits: UpdateState::Nothing,
+            demoted_documents: UpdateState::Nothing,
+            image_hash_field: UpdateState::Nothing,
+            language_detection_field: UpdateState::Nothing,
+            one_way_synonyms: UpdateState::Nothing,
+            equivalent_synonyms: UpdateState::Nothing,
+            hierarchical_facets: UpdateState::Nothing,
+            transformer: UpdateState::Nothing,
+            webhook_url: UpdateState::Nothing,
+            cross_field_boost: UpdateState::Nothing,
+            document_versions: UpdateState::Nothing,
+            referenced_documents: UpdateState::Nothing,
+            embedder: UpdateState::Nothing,
+            embedding_strategy: UpdateState::Nothing,
         }
     }
 }
@@ -0,0 +1,114 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeSet, HashMap};
+
+use fst::Streamer;
+
+use crate::database::MainT;
+use crate::phonetic::is_phonetic_word;
+use crate::stemming::is_stemmed_word;
+use crate::{store, DocumentId, MResult};
+
+/// Returns the set of words of the document, as they are stored in the inverted index.
+pub fn document_words(
+    reader: &heed::RoTxn<MainT>,
+    index: &store::Index,
+    document_id: DocumentId,
+) -> MResult<BTreeSet<String>> {
+    let words = index.docs_words.doc_words(reader, document_id)?;
+
+    let mut set = BTreeSet::new();
+    let mut stream = words.stream();
+    while let Some(word) = stream.next() {
+        if is_phonetic_word(word) || is_stemmed_word(word) {
+            continue;
+        }
+        if let Ok(word) = std::str::from_utf8(word) {
+            set.insert(word.to_owned());
+        }
+    }
+
+    Ok(set)
+}
+
+/// Returns, at most, `limit` words of the document sorted by the number of times
+/// they appear in it, the most frequent first.
+pub fn most_frequent_words(
+    reader: &heed::RoTxn<MainT>,
+    index: &store::Index,
+    document_id: DocumentId,
+    words: &BTreeSet<String>,
+    limit: usize,
+) -> MResult<Vec<String>> {
+    let mut frequencies = Vec::with_capacity(words.len());
+
+    for word in words {
+        if let Some(postings) = index.postings_lists.postings_list(reader, word.as_bytes())? {
+            // The matches are sorted by document id first, we only count the ones of this document.
+            let matches = postings.matches.as_slice();
+            let start = matches
+                .binary_search_by(|m| if m.document_id < document_id { Ordering::Less } else { Ordering::Greater })
+                .unwrap_or_else(|x| x);
+            let end = matches
+                .binary_search_by(|m| if m.document_id <= document_id { Ordering::Less } else { Ordering::Greater })
+                .unwrap_or_else(|x| x);
+            frequencies.push((end - start, word));
+        }
+    }
+
+    frequencies.sort_by_key(|(count, word)| (Reverse(*count), *word));
+
+    Ok(frequencies.into_iter().take(limit).map(|(_, word)| word.clone()).collect())
+}
+
+/// Returns, at most, `limit` of the documents containing at least one of the words,
+/// the ones containing the most words first, and the number of documents containing one.
+pub fn documents_sharing_words(
+    reader: &heed::RoTxn<MainT>,
+    index: &store::Index,
+    words: &[String],
+    limit: usize,
+) -> MResult<(Vec<DocumentId>, usize)> {
+    let mut shared_words: HashMap<DocumentId, usize> = HashMap::new();
+    for word in words {
+        if let Some(postings) = index.postings_lists.postings_list(reader, word.as_bytes())? {
+            for document_id in postings.docids.iter() {
+                *shared_words.entry(*document_id).or_default() += 1;
+            }
+        }
+    }
+
+    let count = shared_words.len();
+    let mut documents: Vec<_> = shared_words.into_iter().collect();
+    documents.sort_unstable_by_key(|(document_id, shared)| (Reverse(*shared), *document_id));
+
+    Ok((documents.into_iter().take(limit).map(|(document_id, _)| document_id).collect(), count))
+}
+
+/// The size of the intersection of both sets divided by the size of their union.
+pub fn jaccard_similarity(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let intersection = a.intersection(b).count();
+    let union = a.len() + b.len() - intersection;
+
+    if union == 0 {
+        0.0
+    } else {
+        intersection as f64 / union as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(words: &[&str]) -> BTreeSet<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn jaccard() {
+        assert_eq!(jaccard_similarity(&set(&["a", "b"]), &set(&["a", "b"])), 1.0);
+        assert_eq!(jaccard_similarity(&set(&["a", "b"]), &set(&["b", "c"])), 1.0 / 3.0);
+        assert_eq!(jaccard_similarity(&set(&["a"]), &set(&["b"])), 0.0);
+        assert_eq!(jaccard_similarity(&set(&[]), &set(&[])), 0.0);
+    }
+}
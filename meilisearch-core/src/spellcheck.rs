@@ -0,0 +1,109 @@
+use std::cmp::Reverse;
+
+use fst::{IntoStreamer, Streamer};
+use levenshtein_automata::Distance;
+use meilisearch_tokenizer::split_query_string;
+use serde::Serialize;
+
+use crate::automaton::build_spellcheck_dfa;
+use crate::database::MainT;
+use crate::phonetic::is_phonetic_word;
+use crate::stemming::is_stemmed_word;
+use crate::{store, MResult};
+
+/// The maximum number of suggestions returned for a single word.
+const MAX_SUGGESTIONS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WordSuggestions {
+    pub original: String,
+    /// The words of the index that are the closest to the original one,
+    /// the fewer typos and the more documents containing it the better.
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Spellcheck {
+    /// The query where every unknown word is replaced by its best suggestion.
+    pub corrected: String,
+    /// The suggestions for the words that are not part of the index vocabulary.
+    pub words: Vec<WordSuggestions>,
+}
+
+/// Corrects the query words using the words of the indexed documents as the dictionary.
+pub fn spellcheck(
+    reader: &heed::RoTxn<MainT>,
+    index: &store::Index,
+    query: &str,
+) -> MResult<Spellcheck> {
+    let words_set = index.main.words_fst(reader)?;
+    let stop_words = index.main.stop_words_fst(reader)?;
+
+    let mut corrected = Vec::new();
+    let mut words = Vec::new();
+
+    for word in split_query_string(query).map(str::to_lowercase) {
+        if words_set.contains(&word) || stop_words.contains(&word) {
+            corrected.push(word);
+            continue;
+        }
+
+        let dfa = build_spellcheck_dfa(&word);
+        let mut stream = words_set.search(&dfa).into_stream();
+
+        let mut candidates = Vec::new();
+        while let Some(input) = stream.next() {
+            if is_phonetic_word(input) || is_stemmed_word(input) {
+                continue;
+            }
+
+            let distance = match dfa.eval(input) {
+                Distance::Exact(distance) => distance,
+                Distance::AtLeast(_) => continue,
+            };
+
+            if let Ok(candidate) = std::str::from_utf8(input) {
+                let frequency = index.postings_lists
+                    .postings_list(reader, input)?
+                    .map_or(0, |postings| postings.docids.len());
+                candidates.push((distance, Reverse(frequency), candidate.to_owned()));
+            }
+        }
+
+        candidates.sort_unstable();
+        let suggestions: Vec<_> = candidates.into_iter().take(MAX_SUGGESTIONS).map(|(_, _, w)| w).collect();
+
+        corrected.push(suggestions.first().cloned().unwrap_or_else(|| word.clone()));
+        words.push(WordSuggestions { original: word, suggestions });
+    }
+
+    Ok(Spellcheck { corrected: corrected.join(" "), words })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::database::{Database, DatabaseOptions};
+
+    #[test]
+    fn unknown_words_are_corrected() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = Database::open_or_create(dir.path(), DatabaseOptions::default()).unwrap();
+        let index = database.create_index("test").unwrap();
+
+        let mut writer = database.main_write_txn().unwrap();
+        let words = fst::Set::from_iter(vec!["hello", "misspell", "world"]).unwrap();
+        index.main.put_words_fst(&mut writer, &words).unwrap();
+        writer.commit().unwrap();
+
+        let reader = database.main_read_txn().unwrap();
+        let result = spellcheck(&reader, &index, "Hello misipel").unwrap();
+
+        assert_eq!(result.corrected, "hello misspell");
+        assert_eq!(result.words, vec![WordSuggestions {
+            original: "misipel".to_string(),
+            suggestions: vec!["misspell".to_string()],
+        }]);
+    }
+}
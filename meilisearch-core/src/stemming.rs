@@ -0,0 +1,179 @@
+use std::collections::BTreeSet;
+
+use rust_stemmers::{Algorithm, Stemmer};
+use whatlang::{Detector, Lang};
+
+use crate::database::MainT;
+use crate::{store, MResult};
+
+/// Stems are stored in the same postings lists as the words, this prefix ensures that
+/// they can never be confused with a word of the documents or of a query.
+const STEM_PREFIX: char = '\u{2}';
+
+/// Returns the English name of the language given by its name or by its
+/// ISO 639-1 code (e.g. `de`), `None` if the language is not supported.
+pub fn language_name(language: &str) -> Option<&'static str> {
+    let name = match language.trim().to_lowercase().as_str() {
+        "arabic" | "ar" => "arabic",
+        "danish" | "da" => "danish",
+        "dutch" | "nl" => "dutch",
+        "english" | "en" => "english",
+        "french" | "fr" => "french",
+        "german" | "de" => "german",
+        "greek" | "el" => "greek",
+        "hungarian" | "hu" => "hungarian",
+        "italian" | "it" => "italian",
+        "norwegian" | "no" | "nb" => "norwegian",
+        "portuguese" | "pt" => "portuguese",
+        "romanian" | "ro" => "romanian",
+        "russian" | "ru" => "russian",
+        "spanish" | "es" => "spanish",
+        "swedish" | "sv" => "swedish",
+        "tamil" | "ta" => "tamil",
+        "turkish" | "tr" => "turkish",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+/// Returns the stemmer of the language, given by its name or by its ISO 639-1 code,
+/// `None` if the language is not supported.
+pub fn stemmer(language: &str) -> Option<Stemmer> {
+    let algorithm = match language_name(language)? {
+        "arabic" => Algorithm::Arabic,
+        "danish" => Algorithm::Danish,
+        "dutch" => Algorithm::Dutch,
+        "english" => Algorithm::English,
+        "french" => Algorithm::French,
+        "german" => Algorithm::German,
+        "greek" => Algorithm::Greek,
+        "hungarian" => Algorithm::Hungarian,
+        "italian" => Algorithm::Italian,
+        "norwegian" => Algorithm::Norwegian,
+        "portuguese" => Algorithm::Portuguese,
+        "romanian" => Algorithm::Romanian,
+        "russian" => Algorithm::Russian,
+        "spanish" => Algorithm::Spanish,
+        "swedish" => Algorithm::Swedish,
+        "tamil" => Algorithm::Tamil,
+        "turkish" => Algorithm::Turkish,
+        _ => return None,
+    };
+
+    Some(Stemmer::create(algorithm))
+}
+
+/// Returns the language of the language detection, given by its English name.
+fn detected_language(language: &str) -> Option<Lang> {
+    let lang = match language {
+        "arabic" => Lang::Ara,
+        "danish" => Lang::Dan,
+        "dutch" => Lang::Nld,
+        "english" => Lang::Eng,
+        "french" => Lang::Fra,
+        "german" => Lang::Deu,
+        "greek" => Lang::Ell,
+        "hungarian" => Lang::Hun,
+        "italian" => Lang::Ita,
+        "norwegian" => Lang::Nob,
+        "portuguese" => Lang::Por,
+        "romanian" => Lang::Ron,
+        "russian" => Lang::Rus,
+        "spanish" => Lang::Spa,
+        "swedish" => Lang::Swe,
+        "tamil" => Lang::Tam,
+        "turkish" => Lang::Tur,
+        _ => return None,
+    };
+
+    Some(lang)
+}
+
+/// Returns the one of the languages, given by their English name, the query is written in,
+/// `None` if it cannot be reliably detected.
+pub fn query_language<'a>(query: &str, languages: &'a BTreeSet<String>) -> Option<&'a str> {
+    let langs: Vec<_> = languages.iter().filter_map(|language| detected_language(language)).collect();
+    if langs.len() < 2 {
+        return None;
+    }
+
+    let info = Detector::with_allowlist(langs).detect(query).filter(|info| info.is_reliable())?;
+    languages.iter().map(String::as_str).find(|language| detected_language(language) == Some(info.lang()))
+}
+
+/// Returns the stemmers of the stemming language of the index and of the languages
+/// of the documents. When the query is detected to be written in one of them, only
+/// its stemmer is returned, otherwise the query words are searched by their stem in each of them.
+pub fn query_stemmers(reader: &heed::RoTxn<MainT>, main: store::Main, query: &str) -> MResult<Vec<Stemmer>> {
+    let mut languages = BTreeSet::new();
+    if let Some(language) = main.stemming_language(reader)?.as_deref().and_then(language_name) {
+        languages.insert(language.to_string());
+    }
+    languages.extend(main.documents_languages(reader)?.unwrap_or_default());
+
+    if let Some(language) = query_language(query, &languages) {
+        return Ok(stemmer(language).into_iter().collect());
+    }
+
+    Ok(languages.iter().filter_map(|language| stemmer(language)).collect())
+}
+
+/// Returns whether this indexed word is a stem and not a word of the documents.
+pub fn is_stemmed_word(word: &[u8]) -> bool {
+    word.starts_with(&[STEM_PREFIX as u8])
+}
+
+/// Returns the stem of the word, `None` if it is empty.
+pub fn stem(stemmer: &Stemmer, word: &str) -> Option<String> {
+    let stem = stemmer.stem(word);
+    if stem.is_empty() { None } else { Some(stem.into_owned()) }
+}
+
+/// Returns the stem in the form it is indexed.
+pub fn stemmed_word(stem: &str) -> String {
+    let mut stemmed = String::with_capacity(stem.len() + 1);
+    stemmed.push(STEM_PREFIX);
+    stemmed.push_str(stem);
+    stemmed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_stems() {
+        let english = stemmer("English").unwrap();
+        assert_eq!(stem(&english, "run").as_deref(), Some("run"));
+        assert_eq!(stem(&english, "running").as_deref(), Some("run"));
+        assert_eq!(stem(&english, "runs").as_deref(), Some("run"));
+        assert!(stemmer("klingon").is_none());
+    }
+
+    #[test]
+    fn language_codes() {
+        assert_eq!(language_name("de"), Some("german"));
+        assert_eq!(language_name("German"), Some("german"));
+        assert_eq!(language_name("nb"), Some("norwegian"));
+        assert_eq!(language_name("xx"), None);
+
+        let german = stemmer("de").unwrap();
+        assert_eq!(stem(&german, "laufen").as_deref(), Some("lauf"));
+    }
+
+    #[test]
+    fn query_languages() {
+        let languages: BTreeSet<_> = ["english", "french", "german"].iter().map(ToString::to_string).collect();
+        assert_eq!(query_language("der schnelle braune Fuchs springt über den faulen Hund", &languages), Some("german"));
+        assert_eq!(query_language("le renard brun saute par-dessus le chien paresseux", &languages), Some("french"));
+        assert_eq!(query_language("the lord of the rings", &languages), Some("english"));
+
+        // the short queries are often too ambiguous to be reliably detected
+        assert_eq!(query_language("red leather boots", &languages), None);
+
+        // there is nothing to choose from with a single language
+        let english: BTreeSet<_> = ["english"].iter().map(ToString::to_string).collect();
+        assert_eq!(query_language("der schnelle braune Fuchs", &english), None);
+    }
+}
@@ -0,0 +1,85 @@
+use std::convert::TryInto;
+
+use chrono::{DateTime, Utc};
+use heed::types::{ByteSlice, SerdeJson};
+use heed::Result as ZResult;
+use serde::{Deserialize, Serialize};
+
+use crate::database::UpdateT;
+
+/// The length of the query ids, the UUIDs given in the search responses.
+pub const QUERY_ID_LEN: usize = 16;
+
+/// A click on a document of the results of a search.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClickEvent {
+    pub document_id: String,
+    pub position: usize,
+    pub clicked_at: DateTime<Utc>,
+}
+
+/// The clicks are keyed by query id followed by the document id, they are kept
+/// in the updates environment to never wait for an update being processed.
+#[derive(Copy, Clone)]
+pub struct Clicks {
+    pub(crate) clicks: heed::Database<ByteSlice, SerdeJson<ClickEvent>>,
+}
+
+fn click_key(query_id: &[u8; QUERY_ID_LEN], document_id: &str) -> Vec<u8> {
+    let mut key = Vec::with_capacity(QUERY_ID_LEN + document_id.len());
+    key.extend_from_slice(query_id);
+    key.extend_from_slice(document_id.as_bytes());
+    key
+}
+
+impl Clicks {
+    pub fn put_click(
+        self,
+        writer: &mut heed::RwTxn<UpdateT>,
+        query_id: &[u8; QUERY_ID_LEN],
+        click: &ClickEvent,
+    ) -> ZResult<()> {
+        let key = click_key(query_id, &click.document_id);
+        self.clicks.put(writer, &key, click)
+    }
+
+    pub fn iter<'txn>(
+        self,
+        reader: &'txn heed::RoTxn<UpdateT>,
+    ) -> ZResult<impl Iterator<Item = ZResult<([u8; QUERY_ID_LEN], ClickEvent)>> + 'txn> {
+        let iter = self.clicks.iter(reader)?;
+        Ok(iter.filter_map(|result| match result {
+            Ok((key, click)) => {
+                let query_id = key.get(..QUERY_ID_LEN)?.try_into().ok()?;
+                Some(Ok((query_id, click)))
+            }
+            Err(e) => Some(Err(e)),
+        }))
+    }
+
+    /// Removes the clicks that happened before the given date, returns the number of clicks removed.
+    pub fn remove_clicked_before(
+        self,
+        writer: &mut heed::RwTxn<UpdateT>,
+        date: DateTime<Utc>,
+    ) -> ZResult<usize> {
+        let mut expired = Vec::new();
+        for result in self.clicks.iter(writer)? {
+            let (key, click) = result?;
+            if click.clicked_at < date {
+                expired.push(key.to_vec());
+            }
+        }
+
+        for key in &expired {
+            self.clicks.delete(writer, key)?;
+        }
+
+        Ok(expired.len())
+    }
+
+    pub fn clear(self, writer: &mut heed::RwTxn<UpdateT>) -> ZResult<()> {
+        self.clicks.clear(writer)
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use heed::types::{ByteSlice, SerdeJson};
+use heed::Result as ZResult;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::database::MainT;
+
+/// A previous version of a document, archived when the document was replaced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentVersion {
+    pub version: u64,
+    pub archived_at: DateTime<Utc>,
+    pub document: IndexMap<String, Value>,
+}
+
+/// The versions are keyed by the external document id followed by a zero byte and
+/// the big endian version number, the versions of a document are therefore sorted.
+#[derive(Copy, Clone)]
+pub struct DocumentVersions {
+    pub(crate) document_versions: heed::Database<ByteSlice, SerdeJson<DocumentVersion>>,
+}
+
+fn versions_prefix(document_id: &str) -> Vec<u8> {
+    let mut prefix = Vec::with_capacity(document_id.len() + 1);
+    prefix.extend_from_slice(document_id.as_bytes());
+    prefix.push(0);
+    prefix
+}
+
+fn version_key(document_id: &str, version: u64) -> Vec<u8> {
+    let mut key = versions_prefix(document_id);
+    key.extend_from_slice(&version.to_be_bytes());
+    key
+}
+
+impl DocumentVersions {
+    /// Archives the document as its newest version and only keeps the `max_versions` newest ones,
+    /// returns the number of the new version.
+    pub fn archive(
+        self,
+        writer: &mut heed::RwTxn<MainT>,
+        document_id: &str,
+        document: IndexMap<String, Value>,
+        max_versions: usize,
+    ) -> ZResult<u64> {
+        let versions = self.versions(writer, document_id)?;
+        let version = versions.last().map_or(1, |last| last.version + 1);
+
+        let archived = DocumentVersion { version, archived_at: Utc::now(), document };
+        self.document_versions.put(writer, &version_key(document_id, version), &archived)?;
+
+        let kept = max_versions.saturating_sub(1);
+        for old in &versions[..versions.len().saturating_sub(kept)] {
+            self.document_versions.delete(writer, &version_key(document_id, old.version))?;
+        }
+
+        Ok(version)
+    }
+
+    /// Returns the archived versions of the document, from the oldest to the newest.
+    pub fn versions(self, reader: &heed::RoTxn<MainT>, document_id: &str) -> ZResult<Vec<DocumentVersion>> {
+        self.document_versions
+            .prefix_iter(reader, &versions_prefix(document_id))?
+            .map(|result| result.map(|(_, version)| version))
+            .collect()
+    }
+
+    pub fn version(
+        self,
+        reader: &heed::RoTxn<MainT>,
+        document_id: &str,
+        version: u64,
+    ) -> ZResult<Option<DocumentVersion>> {
+        self.document_versions.get(reader, &version_key(document_id, version))
+    }
+
+    pub fn clear(self, writer: &mut heed::RwTxn<MainT>) -> ZResult<()> {
+        self.document_versions.clear(writer)
+    }
+}
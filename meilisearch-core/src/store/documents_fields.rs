@@ -1,3 +1,5 @@
+use std::mem;
+
 use heed::types::{ByteSlice, OwnedType};
 use crate::database::MainT;
 use heed::Result as ZResult;
@@ -6,6 +8,11 @@ use meilisearch_schema::FieldId;
 use super::DocumentFieldStoredKey;
 use crate::DocumentId;
 
+/// The number of bytes a stored field takes, its key included.
+pub(crate) fn field_size(value: &[u8]) -> u64 {
+    (mem::size_of::<DocumentFieldStoredKey>() + value.len()) as u64
+}
+
 #[derive(Copy, Clone)]
 pub struct DocumentsFields {
     pub(crate) documents_fields: heed::Database<OwnedType<DocumentFieldStoredKey>, ByteSlice>,
@@ -37,6 +44,33 @@ impl DocumentsFields {
         self.documents_fields.clear(writer)
     }
 
+    /// Returns the number of bytes of the stored fields, keys included.
+    pub fn size(self, reader: &heed::RoTxn<MainT>) -> ZResult<u64> {
+        let mut size = 0;
+        for result in self.documents_fields.iter(reader)? {
+            let (_, bytes) = result?;
+            size += field_size(bytes);
+        }
+        Ok(size)
+    }
+
+    /// Returns the number of bytes of the stored fields of the document, keys included.
+    pub fn document_size(self, reader: &heed::RoTxn<MainT>, document_id: DocumentId) -> ZResult<u64> {
+        let mut size = 0;
+        for result in self.document_fields(reader, document_id)? {
+            let (_, bytes) = result?;
+            size += field_size(bytes);
+        }
+        Ok(size)
+    }
+
+    pub fn iter<'txn>(
+        self,
+        reader: &'txn heed::RoTxn<MainT>,
+    ) -> ZResult<heed::RoIter<'txn, OwnedType<DocumentFieldStoredKey>, ByteSlice>> {
+        self.documents_fields.iter(reader)
+    }
+
     pub fn document_attribute<'txn>(
         self,
         reader: &'txn heed::RoTxn<MainT>,
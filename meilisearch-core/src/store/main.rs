@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use chrono::{DateTime, Utc};
 use heed::types::{ByteSlice, OwnedType, SerdeBincode, Str};
@@ -9,25 +9,51 @@ use sdset::Set;
 
 use crate::database::MainT;
 use crate::{RankedMap, MResult};
-use crate::settings::RankingRule;
+use crate::settings::{DemotedDocument, Embedder, EmbeddingStrategy, PinnedHit, ProximityPrecision, RankingRule, ReferencedDocument};
 use crate::{FstSetCow, FstMapCow};
 use super::{CowSet, DocumentsIds};
 
 const ATTRIBUTES_FOR_FACETING_KEY: &str = "attributes-for-faceting";
 const CREATED_AT_KEY: &str = "created-at";
+const CROSS_FIELD_BOOST_KEY: &str = "cross-field-boost";
 const CUSTOMS_KEY: &str = "customs";
+const DECOMPOUND_TOKENS_KEY: &str = "decompound-tokens";
+const DEMOTED_DOCUMENTS_KEY: &str = "demoted-documents";
 const DISTINCT_ATTRIBUTE_KEY: &str = "distinct-attribute";
+const DOCUMENT_VERSIONS_KEY: &str = "document-versions";
+const DOCUMENTS_LANGUAGES_KEY: &str = "documents-languages";
+const DOCUMENTS_SIZE_KEY: &str = "documents-size";
+const EMBEDDER_KEY: &str = "embedder";
+const EMBEDDING_STRATEGY_KEY: &str = "embedding-strategy";
+const EQUIVALENT_SYNONYMS_KEY: &str = "equivalent-synonyms";
 const EXTERNAL_DOCIDS_KEY: &str = "external-docids";
 const FIELDS_DISTRIBUTION_KEY: &str = "fields-distribution";
+const HIERARCHICAL_FACETS_KEY: &str = "hierarchical-facets";
+const IMAGE_HASH_FIELD_KEY: &str = "image-hash-field";
 const INTERNAL_DOCIDS_KEY: &str = "internal-docids";
+const LANGUAGE_DETECTION_FIELD_KEY: &str = "language-detection-field";
+const LAST_APPLIED_UPDATE_ID_KEY: &str = "last-applied-update-id";
+const LAST_SUCCESSFUL_INDEXING_AT_KEY: &str = "last-successful-indexing-at";
+const MAX_FIELDS_COUNT_KEY: &str = "max-fields-count";
+const MAX_INDEX_SIZE_KEY: &str = "max-index-size";
 const NAME_KEY: &str = "name";
 const NUMBER_OF_DOCUMENTS_KEY: &str = "number-of-documents";
+const ONE_WAY_SYNONYMS_KEY: &str = "one-way-synonyms";
+const PHONETIC_ATTRIBUTES_KEY: &str = "phonetic-attributes";
+const PHONETIC_MATCHING_KEY: &str = "phonetic-matching";
+const PINNED_HITS_KEY: &str = "pinned-hits";
+const PROXIMITY_PRECISION_KEY: &str = "proximity-precision";
+const PROXIMITY_WINDOW_KEY: &str = "proximity-window";
 const RANKED_MAP_KEY: &str = "ranked-map";
 const RANKING_RULES_KEY: &str = "ranking-rules";
+const REFERENCED_DOCUMENTS_KEY: &str = "referenced-documents";
 const SCHEMA_KEY: &str = "schema";
+const STEMMING_LANGUAGE_KEY: &str = "stemming-language";
 const STOP_WORDS_KEY: &str = "stop-words";
 const SYNONYMS_KEY: &str = "synonyms";
+const TRANSFORMER_KEY: &str = "transformer";
 const UPDATED_AT_KEY: &str = "updated-at";
+const WEBHOOK_URL_KEY: &str = "webhook-url";
 const WORDS_KEY: &str = "words";
 
 pub type FreqsMap = HashMap<String, usize>;
@@ -71,6 +97,23 @@ impl Main {
         Ok(self.main.get::<_, Str, SerdeDatetime>(reader, UPDATED_AT_KEY)?)
     }
 
+    pub fn put_last_successful_indexing_at(self, writer: &mut heed::RwTxn<MainT>) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeDatetime>(writer, LAST_SUCCESSFUL_INDEXING_AT_KEY, &Utc::now())?)
+    }
+
+    pub fn last_successful_indexing_at(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<DateTime<Utc>>> {
+        Ok(self.main.get::<_, Str, SerdeDatetime>(reader, LAST_SUCCESSFUL_INDEXING_AT_KEY)?)
+    }
+
+    pub fn put_last_applied_update_id(self, writer: &mut heed::RwTxn<MainT>, update_id: u64) -> MResult<()> {
+        Ok(self.main.put::<_, Str, OwnedType<u64>>(writer, LAST_APPLIED_UPDATE_ID_KEY, &update_id)?)
+    }
+
+    /// Returns the id of the last update that was committed to the main store.
+    pub fn last_applied_update_id(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<u64>> {
+        Ok(self.main.get::<_, Str, OwnedType<u64>>(reader, LAST_APPLIED_UPDATE_ID_KEY)?)
+    }
+
     pub fn put_internal_docids(self, writer: &mut heed::RwTxn<MainT>, ids: &sdset::Set<DocumentId>) -> MResult<()> {
         Ok(self.main.put::<_, Str, DocumentsIds>(writer, INTERNAL_DOCIDS_KEY, ids)?)
     }
@@ -197,6 +240,11 @@ impl Main {
         }
     }
 
+    /// Returns whether synonyms are defined for this word.
+    pub fn contains_synonym(self, reader: &heed::RoTxn<MainT>, word: &str) -> MResult<bool> {
+        Ok(self.synonyms_fst(reader)?.contains(word))
+    }
+
     pub fn synonyms(self, reader: &heed::RoTxn<MainT>) -> MResult<Vec<String>> {
         let synonyms = self
             .synonyms_fst(&reader)?
@@ -205,6 +253,42 @@ impl Main {
         Ok(synonyms)
     }
 
+    pub fn one_way_synonyms(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<BTreeMap<String, Vec<String>>>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<BTreeMap<String, Vec<String>>>>(reader, ONE_WAY_SYNONYMS_KEY)?)
+    }
+
+    pub fn put_one_way_synonyms(self, writer: &mut heed::RwTxn<MainT>, synonyms: &BTreeMap<String, Vec<String>>) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<BTreeMap<String, Vec<String>>>>(writer, ONE_WAY_SYNONYMS_KEY, synonyms)?)
+    }
+
+    pub fn delete_one_way_synonyms(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, ONE_WAY_SYNONYMS_KEY)?)
+    }
+
+    pub fn equivalent_synonyms(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<Vec<Vec<String>>>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<Vec<Vec<String>>>>(reader, EQUIVALENT_SYNONYMS_KEY)?)
+    }
+
+    pub fn put_equivalent_synonyms(self, writer: &mut heed::RwTxn<MainT>, synonyms: &[Vec<String>]) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<Vec<Vec<String>>>>(writer, EQUIVALENT_SYNONYMS_KEY, &synonyms.to_vec())?)
+    }
+
+    pub fn delete_equivalent_synonyms(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, EQUIVALENT_SYNONYMS_KEY)?)
+    }
+
+    pub fn hierarchical_facets(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<BTreeMap<String, String>>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<BTreeMap<String, String>>>(reader, HIERARCHICAL_FACETS_KEY)?)
+    }
+
+    pub fn put_hierarchical_facets(self, writer: &mut heed::RwTxn<MainT>, levels: &BTreeMap<String, String>) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<BTreeMap<String, String>>>(writer, HIERARCHICAL_FACETS_KEY, levels)?)
+    }
+
+    pub fn delete_hierarchical_facets(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, HIERARCHICAL_FACETS_KEY)?)
+    }
+
     pub fn put_stop_words_fst<A: AsRef<[u8]>>(self, writer: &mut heed::RwTxn<MainT>, fst: &fst::Set<A>) -> MResult<()> {
         let bytes = fst.as_fst().as_bytes();
         Ok(self.main.put::<_, Str, ByteSlice>(writer, STOP_WORDS_KEY, bytes)?)
@@ -244,6 +328,16 @@ impl Main {
         }
     }
 
+    pub fn put_documents_size(self, writer: &mut heed::RwTxn<MainT>, value: u64) -> MResult<()> {
+        Ok(self.main.put::<_, Str, OwnedType<u64>>(writer, DOCUMENTS_SIZE_KEY, &value)?)
+    }
+
+    /// Returns the number of bytes of the stored documents, it is not known for the indexes
+    /// whose documents were added before it was counted.
+    pub fn documents_size(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<u64>> {
+        Ok(self.main.get::<_, Str, OwnedType<u64>>(reader, DOCUMENTS_SIZE_KEY)?)
+    }
+
     pub fn put_fields_distribution(
         self,
         writer: &mut heed::RwTxn<MainT>,
@@ -301,6 +395,248 @@ impl Main {
         Ok(self.main.delete::<_, Str>(writer, DISTINCT_ATTRIBUTE_KEY)?)
     }
 
+    pub fn max_fields_count(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<usize>> {
+        let count = self.main.get::<_, Str, OwnedType<u64>>(reader, MAX_FIELDS_COUNT_KEY)?;
+        Ok(count.map(|count| count as usize))
+    }
+
+    pub fn put_max_fields_count(self, writer: &mut heed::RwTxn<MainT>, value: usize) -> MResult<()> {
+        Ok(self.main.put::<_, Str, OwnedType<u64>>(writer, MAX_FIELDS_COUNT_KEY, &(value as u64))?)
+    }
+
+    pub fn delete_max_fields_count(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, MAX_FIELDS_COUNT_KEY)?)
+    }
+
+    pub fn max_index_size(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<usize>> {
+        let size = self.main.get::<_, Str, OwnedType<u64>>(reader, MAX_INDEX_SIZE_KEY)?;
+        Ok(size.map(|size| size as usize))
+    }
+
+    pub fn put_max_index_size(self, writer: &mut heed::RwTxn<MainT>, value: usize) -> MResult<()> {
+        Ok(self.main.put::<_, Str, OwnedType<u64>>(writer, MAX_INDEX_SIZE_KEY, &(value as u64))?)
+    }
+
+    pub fn delete_max_index_size(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, MAX_INDEX_SIZE_KEY)?)
+    }
+
+    /// Returns the number of previous versions kept for each document.
+    pub fn document_versions(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<usize>> {
+        let versions = self.main.get::<_, Str, OwnedType<u64>>(reader, DOCUMENT_VERSIONS_KEY)?;
+        Ok(versions.map(|versions| versions as usize))
+    }
+
+    pub fn put_document_versions(self, writer: &mut heed::RwTxn<MainT>, value: usize) -> MResult<()> {
+        Ok(self.main.put::<_, Str, OwnedType<u64>>(writer, DOCUMENT_VERSIONS_KEY, &(value as u64))?)
+    }
+
+    pub fn delete_document_versions(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, DOCUMENT_VERSIONS_KEY)?)
+    }
+
+    pub fn phonetic_matching(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<bool>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<bool>>(reader, PHONETIC_MATCHING_KEY)?)
+    }
+
+    pub fn put_phonetic_matching(self, writer: &mut heed::RwTxn<MainT>, value: bool) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<bool>>(writer, PHONETIC_MATCHING_KEY, &value)?)
+    }
+
+    pub fn delete_phonetic_matching(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, PHONETIC_MATCHING_KEY)?)
+    }
+
+    pub fn phonetic_attributes(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<Vec<String>>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<Vec<String>>>(reader, PHONETIC_ATTRIBUTES_KEY)?)
+    }
+
+    pub fn put_phonetic_attributes(self, writer: &mut heed::RwTxn<MainT>, attributes: &[String]) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<Vec<String>>>(writer, PHONETIC_ATTRIBUTES_KEY, &attributes.to_vec())?)
+    }
+
+    pub fn delete_phonetic_attributes(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, PHONETIC_ATTRIBUTES_KEY)?)
+    }
+
+    pub fn stemming_language(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<String>> {
+        Ok(self.main.get::<_, Str, Str>(reader, STEMMING_LANGUAGE_KEY)?.map(str::to_owned))
+    }
+
+    pub fn put_stemming_language(self, writer: &mut heed::RwTxn<MainT>, language: &str) -> MResult<()> {
+        Ok(self.main.put::<_, Str, Str>(writer, STEMMING_LANGUAGE_KEY, language)?)
+    }
+
+    pub fn delete_stemming_language(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, STEMMING_LANGUAGE_KEY)?)
+    }
+
+    pub fn decompound_tokens(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<bool>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<bool>>(reader, DECOMPOUND_TOKENS_KEY)?)
+    }
+
+    pub fn put_decompound_tokens(self, writer: &mut heed::RwTxn<MainT>, value: bool) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<bool>>(writer, DECOMPOUND_TOKENS_KEY, &value)?)
+    }
+
+    pub fn delete_decompound_tokens(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, DECOMPOUND_TOKENS_KEY)?)
+    }
+
+    pub fn proximity_precision(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<ProximityPrecision>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<ProximityPrecision>>(reader, PROXIMITY_PRECISION_KEY)?)
+    }
+
+    pub fn put_proximity_precision(self, writer: &mut heed::RwTxn<MainT>, value: ProximityPrecision) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<ProximityPrecision>>(writer, PROXIMITY_PRECISION_KEY, &value)?)
+    }
+
+    pub fn delete_proximity_precision(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, PROXIMITY_PRECISION_KEY)?)
+    }
+
+    pub fn proximity_window(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<usize>> {
+        let window = self.main.get::<_, Str, OwnedType<u64>>(reader, PROXIMITY_WINDOW_KEY)?;
+        Ok(window.map(|window| window as usize))
+    }
+
+    pub fn put_proximity_window(self, writer: &mut heed::RwTxn<MainT>, value: usize) -> MResult<()> {
+        Ok(self.main.put::<_, Str, OwnedType<u64>>(writer, PROXIMITY_WINDOW_KEY, &(value as u64))?)
+    }
+
+    pub fn delete_proximity_window(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, PROXIMITY_WINDOW_KEY)?)
+    }
+
+    pub fn pinned_hits(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<Vec<PinnedHit>>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<Vec<PinnedHit>>>(reader, PINNED_HITS_KEY)?)
+    }
+
+    pub fn put_pinned_hits(self, writer: &mut heed::RwTxn<MainT>, value: &[PinnedHit]) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<Vec<PinnedHit>>>(writer, PINNED_HITS_KEY, &value.to_vec())?)
+    }
+
+    pub fn delete_pinned_hits(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, PINNED_HITS_KEY)?)
+    }
+
+    pub fn referenced_documents(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<Vec<ReferencedDocument>>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<Vec<ReferencedDocument>>>(reader, REFERENCED_DOCUMENTS_KEY)?)
+    }
+
+    pub fn put_referenced_documents(self, writer: &mut heed::RwTxn<MainT>, value: &[ReferencedDocument]) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<Vec<ReferencedDocument>>>(writer, REFERENCED_DOCUMENTS_KEY, &value.to_vec())?)
+    }
+
+    pub fn delete_referenced_documents(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, REFERENCED_DOCUMENTS_KEY)?)
+    }
+
+    pub fn embedder(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<Embedder>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<Embedder>>(reader, EMBEDDER_KEY)?)
+    }
+
+    pub fn put_embedder(self, writer: &mut heed::RwTxn<MainT>, value: &Embedder) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<Embedder>>(writer, EMBEDDER_KEY, value)?)
+    }
+
+    pub fn delete_embedder(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, EMBEDDER_KEY)?)
+    }
+
+    pub fn embedding_strategy(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<EmbeddingStrategy>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<EmbeddingStrategy>>(reader, EMBEDDING_STRATEGY_KEY)?)
+    }
+
+    pub fn put_embedding_strategy(self, writer: &mut heed::RwTxn<MainT>, value: EmbeddingStrategy) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<EmbeddingStrategy>>(writer, EMBEDDING_STRATEGY_KEY, &value)?)
+    }
+
+    pub fn delete_embedding_strategy(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, EMBEDDING_STRATEGY_KEY)?)
+    }
+
+    pub fn demoted_documents(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<Vec<DemotedDocument>>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<Vec<DemotedDocument>>>(reader, DEMOTED_DOCUMENTS_KEY)?)
+    }
+
+    pub fn put_demoted_documents(self, writer: &mut heed::RwTxn<MainT>, value: &[DemotedDocument]) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<Vec<DemotedDocument>>>(writer, DEMOTED_DOCUMENTS_KEY, &value.to_vec())?)
+    }
+
+    pub fn delete_demoted_documents(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, DEMOTED_DOCUMENTS_KEY)?)
+    }
+
+    pub fn image_hash_field(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<String>> {
+        Ok(self.main.get::<_, Str, Str>(reader, IMAGE_HASH_FIELD_KEY)?.map(str::to_owned))
+    }
+
+    pub fn put_image_hash_field(self, writer: &mut heed::RwTxn<MainT>, field: &str) -> MResult<()> {
+        Ok(self.main.put::<_, Str, Str>(writer, IMAGE_HASH_FIELD_KEY, field)?)
+    }
+
+    pub fn delete_image_hash_field(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, IMAGE_HASH_FIELD_KEY)?)
+    }
+
+    pub fn transformer(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<String>> {
+        Ok(self.main.get::<_, Str, Str>(reader, TRANSFORMER_KEY)?.map(str::to_owned))
+    }
+
+    pub fn put_transformer(self, writer: &mut heed::RwTxn<MainT>, transformer: &str) -> MResult<()> {
+        Ok(self.main.put::<_, Str, Str>(writer, TRANSFORMER_KEY, transformer)?)
+    }
+
+    pub fn delete_transformer(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, TRANSFORMER_KEY)?)
+    }
+
+    pub fn webhook_url(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<String>> {
+        Ok(self.main.get::<_, Str, Str>(reader, WEBHOOK_URL_KEY)?.map(str::to_owned))
+    }
+
+    pub fn put_webhook_url(self, writer: &mut heed::RwTxn<MainT>, url: &str) -> MResult<()> {
+        Ok(self.main.put::<_, Str, Str>(writer, WEBHOOK_URL_KEY, url)?)
+    }
+
+    pub fn delete_webhook_url(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, WEBHOOK_URL_KEY)?)
+    }
+
+    pub fn cross_field_boost(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<bool>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<bool>>(reader, CROSS_FIELD_BOOST_KEY)?)
+    }
+
+    pub fn put_cross_field_boost(self, writer: &mut heed::RwTxn<MainT>, value: bool) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<bool>>(writer, CROSS_FIELD_BOOST_KEY, &value)?)
+    }
+
+    pub fn delete_cross_field_boost(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, CROSS_FIELD_BOOST_KEY)?)
+    }
+
+    pub fn language_detection_field(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<String>> {
+        Ok(self.main.get::<_, Str, Str>(reader, LANGUAGE_DETECTION_FIELD_KEY)?.map(str::to_owned))
+    }
+
+    pub fn put_language_detection_field(self, writer: &mut heed::RwTxn<MainT>, field: &str) -> MResult<()> {
+        Ok(self.main.put::<_, Str, Str>(writer, LANGUAGE_DETECTION_FIELD_KEY, field)?)
+    }
+
+    pub fn delete_language_detection_field(self, writer: &mut heed::RwTxn<MainT>) -> MResult<bool> {
+        Ok(self.main.delete::<_, Str>(writer, LANGUAGE_DETECTION_FIELD_KEY)?)
+    }
+
+    /// The languages of the documents, read from their language detection field when they were indexed.
+    pub fn documents_languages(self, reader: &heed::RoTxn<MainT>) -> MResult<Option<BTreeSet<String>>> {
+        Ok(self.main.get::<_, Str, SerdeBincode<BTreeSet<String>>>(reader, DOCUMENTS_LANGUAGES_KEY)?)
+    }
+
+    pub fn put_documents_languages(self, writer: &mut heed::RwTxn<MainT>, languages: &BTreeSet<String>) -> MResult<()> {
+        Ok(self.main.put::<_, Str, SerdeBincode<BTreeSet<String>>>(writer, DOCUMENTS_LANGUAGES_KEY, languages)?)
+    }
+
     pub fn put_customs(self, writer: &mut heed::RwTxn<MainT>, customs: &[u8]) -> MResult<()> {
         Ok(self.main.put::<_, Str, ByteSlice>(writer, CUSTOMS_KEY, customs)?)
     }
@@ -1,5 +1,7 @@
+mod clicks;
 mod cow_set;
 mod docs_words;
+mod document_versions;
 mod documents_ids;
 mod documents_fields;
 mod documents_fields_counts;
@@ -12,9 +14,12 @@ mod synonyms;
 mod updates;
 mod updates_results;
 
+pub use self::clicks::{ClickEvent, Clicks, QUERY_ID_LEN};
 pub use self::cow_set::CowSet;
 pub use self::docs_words::DocsWords;
+pub use self::document_versions::{DocumentVersion, DocumentVersions};
 pub use self::documents_fields::{DocumentFieldsIter, DocumentsFields};
+pub(crate) use self::documents_fields::field_size;
 pub use self::documents_fields_counts::{DocumentFieldsCountsIter, DocumentsFieldsCounts, DocumentsIdsIter};
 pub use self::documents_ids::{DocumentsIds, DiscoverIds};
 pub use self::facets::Facets;
@@ -29,12 +34,15 @@ pub use self::updates_results::UpdatesResults;
 use std::borrow::Cow;
 use std::collections::HashSet;
 use std::convert::TryInto;
+use std::sync::Arc;
 use std::{mem, ptr};
 
 use heed::{BytesEncode, BytesDecode};
+use indexmap::IndexMap;
 use meilisearch_schema::{IndexedPos, FieldId};
 use sdset::{Set, SetBuf};
 use serde::de::{self, Deserialize};
+use serde_json::Value;
 use zerocopy::{AsBytes, FromBytes};
 
 use crate::criterion::Criteria;
@@ -177,6 +185,10 @@ fn documents_fields_counts_name(name: &str) -> String {
     format!("store-{}-documents-fields-counts", name)
 }
 
+fn document_versions_name(name: &str) -> String {
+    format!("store-{}-document-versions", name)
+}
+
 fn synonyms_name(name: &str) -> String {
     format!("store-{}-synonyms", name)
 }
@@ -205,12 +217,17 @@ fn facets_name(name: &str) -> String {
     format!("store-{}-facets", name)
 }
 
+fn clicks_name(name: &str) -> String {
+    format!("store-{}-clicks", name)
+}
+
 #[derive(Clone)]
 pub struct Index {
     pub main: Main,
     pub postings_lists: PostingsLists,
     pub documents_fields: DocumentsFields,
     pub documents_fields_counts: DocumentsFieldsCounts,
+    pub document_versions: DocumentVersions,
     pub facets: Facets,
     pub synonyms: Synonyms,
     pub docs_words: DocsWords,
@@ -219,7 +236,9 @@ pub struct Index {
 
     pub updates: Updates,
     pub updates_results: UpdatesResults,
+    pub clicks: Clicks,
     pub(crate) updates_notifier: UpdateEventsEmitter,
+    pub(crate) update_progress: Arc<update::UpdateProgress>,
 }
 
 impl Index {
@@ -278,6 +297,22 @@ impl Index {
         }
     }
 
+    /// Returns the number of bytes of the stored documents, it is counted when the documents
+    /// are added and deleted, the stored fields are only read when it is not known yet.
+    pub fn size(&self, reader: &heed::RoTxn<MainT>) -> MResult<u64> {
+        match self.main.documents_size(reader)? {
+            Some(size) => Ok(size),
+            None => Ok(self.documents_fields.size(reader)?),
+        }
+    }
+
+    pub(crate) fn update_size<F>(&self, writer: &mut heed::RwTxn<MainT>, f: F) -> MResult<()>
+    where F: FnOnce(u64) -> u64,
+    {
+        let size = self.size(writer)?;
+        self.main.put_documents_size(writer, f(size))
+    }
+
     pub fn customs_update(&self, writer: &mut heed::RwTxn<UpdateT>, customs: Vec<u8>) -> MResult<u64> {
         let _ = self.updates_notifier.send(UpdateEvent::NewUpdate);
         Ok(update::push_customs_update(writer, self.updates, self.updates_results, customs)?)
@@ -288,6 +323,11 @@ impl Index {
         Ok(update::push_settings_update(writer, self.updates, self.updates_results, update)?)
     }
 
+    pub fn synonyms_edit(&self, writer: &mut heed::RwTxn<UpdateT>, edit: update::SynonymsEdit) -> MResult<u64> {
+        let _ = self.updates_notifier.send(UpdateEvent::NewUpdate);
+        Ok(update::push_synonyms_edit(writer, self.updates, self.updates_results, edit)?)
+    }
+
     pub fn documents_addition<D>(&self) -> update::DocumentsAddition<D> {
         update::DocumentsAddition::new(
             self.updates,
@@ -304,6 +344,26 @@ impl Index {
         )
     }
 
+    pub fn documents_embeddings(&self, writer: &mut heed::RwTxn<UpdateT>, documents: Vec<IndexMap<String, Value>>) -> MResult<u64> {
+        let _ = self.updates_notifier.send(UpdateEvent::NewUpdate);
+        update::push_documents_embeddings(writer, self.updates, self.updates_results, documents)
+    }
+
+    pub fn documents_import(
+        &self,
+        writer: &mut heed::RwTxn<UpdateT>,
+        import: update::DocumentsImport,
+        error_strategy: update::DocumentErrorStrategy,
+    ) -> MResult<u64> {
+        let _ = self.updates_notifier.send(UpdateEvent::NewUpdate);
+        update::push_documents_import(writer, self.updates, self.updates_results, import, error_strategy)
+    }
+
+    pub fn document_patch(&self, writer: &mut heed::RwTxn<UpdateT>, patch: update::DocumentPatch) -> MResult<u64> {
+        let _ = self.updates_notifier.send(UpdateEvent::NewUpdate);
+        update::push_document_patch(writer, self.updates, self.updates_results, patch)
+    }
+
     pub fn documents_deletion(&self) -> update::DocumentsDeletion {
         update::DocumentsDeletion::new(
             self.updates,
@@ -329,7 +389,50 @@ impl Index {
         reader: &heed::RoTxn<UpdateT>,
         update_id: u64,
     ) -> MResult<Option<update::UpdateStatus>> {
-        update::update_status(reader, self.updates, self.updates_results, update_id)
+        let status = update::update_status(reader, self.updates, self.updates_results, update_id)?;
+        Ok(status.map(|status| self.with_progress(status)))
+    }
+
+    pub fn updates_status_from<F>(
+        &self,
+        reader: &heed::RoTxn<UpdateT>,
+        from: u64,
+        limit: usize,
+        filter: F,
+    ) -> MResult<update::UpdatesStatusPage>
+    where F: Fn(&update::UpdateStatus) -> bool,
+    {
+        let mut page = update::updates_status_from(reader, self.updates, self.updates_results, from, limit, filter)?;
+        page.updates = page.updates.into_iter().map(|status| self.with_progress(status)).collect();
+        Ok(page)
+    }
+
+    /// Returns the number of updates of the index, the update ids being contiguous from zero.
+    pub fn updates_count(&self, reader: &heed::RoTxn<UpdateT>) -> MResult<u64> {
+        let last_enqueued = self.updates.last_update(reader)?.map(|(id, _)| id);
+        let last_processed = self.updates_results.last_update(reader)?.map(|(id, _)| id);
+        Ok(last_enqueued.max(last_processed).map_or(0, |id| id + 1))
+    }
+
+    /// Returns the number of updates waiting to be processed, the processed ones
+    /// being removed from the front of the contiguous enqueued ones.
+    pub fn pending_updates_count(&self, reader: &heed::RoTxn<UpdateT>) -> MResult<u64> {
+        let first = self.updates.first_update(reader)?.map(|(id, _)| id);
+        let last = self.updates.last_update(reader)?.map(|(id, _)| id);
+        match (first, last) {
+            (Some(first), Some(last)) => Ok(last - first + 1),
+            _ => Ok(0),
+        }
+    }
+
+    fn with_progress(&self, status: update::UpdateStatus) -> update::UpdateStatus {
+        match status {
+            update::UpdateStatus::Enqueued { mut content } => {
+                content.details = self.update_progress.details(content.update_id);
+                update::UpdateStatus::Enqueued { content }
+            }
+            status => status,
+        }
     }
 
     pub fn all_updates_status(&self, reader: &heed::RoTxn<UpdateT>) -> MResult<Vec<update::UpdateStatus>> {
@@ -383,6 +486,7 @@ pub fn create(
     let postings_lists_name = postings_lists_name(name);
     let documents_fields_name = documents_fields_name(name);
     let documents_fields_counts_name = documents_fields_counts_name(name);
+    let document_versions_name = document_versions_name(name);
     let synonyms_name = synonyms_name(name);
     let docs_words_name = docs_words_name(name);
     let prefix_documents_cache_name = prefix_documents_cache_name(name);
@@ -390,12 +494,14 @@ pub fn create(
     let updates_name = updates_name(name);
     let updates_results_name = updates_results_name(name);
     let facets_name = facets_name(name);
+    let clicks_name = clicks_name(name);
 
     // open all the stores
     let main = env.create_poly_database(Some(&main_name))?;
     let postings_lists = env.create_database(Some(&postings_lists_name))?;
     let documents_fields = env.create_database(Some(&documents_fields_name))?;
     let documents_fields_counts = env.create_database(Some(&documents_fields_counts_name))?;
+    let document_versions = env.create_database(Some(&document_versions_name))?;
     let facets = env.create_database(Some(&facets_name))?;
     let synonyms = env.create_database(Some(&synonyms_name))?;
     let docs_words = env.create_database(Some(&docs_words_name))?;
@@ -403,12 +509,14 @@ pub fn create(
     let prefix_postings_lists_cache = env.create_database(Some(&prefix_postings_lists_cache_name))?;
     let updates = update_env.create_database(Some(&updates_name))?;
     let updates_results = update_env.create_database(Some(&updates_results_name))?;
+    let clicks = update_env.create_database(Some(&clicks_name))?;
 
     Ok(Index {
         main: Main { main },
         postings_lists: PostingsLists { postings_lists },
         documents_fields: DocumentsFields { documents_fields },
         documents_fields_counts: DocumentsFieldsCounts { documents_fields_counts },
+        document_versions: DocumentVersions { document_versions },
         synonyms: Synonyms { synonyms },
         docs_words: DocsWords { docs_words },
         prefix_postings_lists_cache: PrefixPostingsListsCache { prefix_postings_lists_cache },
@@ -417,7 +525,9 @@ pub fn create(
 
         updates: Updates { updates },
         updates_results: UpdatesResults { updates_results },
+        clicks: Clicks { clicks },
         updates_notifier,
+        update_progress: Arc::default(),
     })
 }
 
@@ -432,6 +542,7 @@ pub fn open(
     let postings_lists_name = postings_lists_name(name);
     let documents_fields_name = documents_fields_name(name);
     let documents_fields_counts_name = documents_fields_counts_name(name);
+    let document_versions_name = document_versions_name(name);
     let synonyms_name = synonyms_name(name);
     let docs_words_name = docs_words_name(name);
     let prefix_documents_cache_name = prefix_documents_cache_name(name);
@@ -439,6 +550,7 @@ pub fn open(
     let prefix_postings_lists_cache_name = prefix_postings_lists_cache_name(name);
     let updates_name = updates_name(name);
     let updates_results_name = updates_results_name(name);
+    let clicks_name = clicks_name(name);
 
     // open all the stores
     let main = match env.open_poly_database(Some(&main_name))? {
@@ -485,12 +597,16 @@ pub fn open(
         Some(updates_results) => updates_results,
         None => return Ok(None),
     };
+    // the clicks and document versions stores are created for the indexes that existed before them
+    let clicks = update_env.create_database(Some(&clicks_name))?;
+    let document_versions = env.create_database(Some(&document_versions_name))?;
 
     Ok(Some(Index {
         main: Main { main },
         postings_lists: PostingsLists { postings_lists },
         documents_fields: DocumentsFields { documents_fields },
         documents_fields_counts: DocumentsFieldsCounts { documents_fields_counts },
+        document_versions: DocumentVersions { document_versions },
         synonyms: Synonyms { synonyms },
         docs_words: DocsWords { docs_words },
         prefix_documents_cache: PrefixDocumentsCache { prefix_documents_cache },
@@ -498,7 +614,9 @@ pub fn open(
         prefix_postings_lists_cache: PrefixPostingsListsCache { prefix_postings_lists_cache },
         updates: Updates { updates },
         updates_results: UpdatesResults { updates_results },
+        clicks: Clicks { clicks },
         updates_notifier,
+        update_progress: Arc::default(),
     }))
 }
 
@@ -512,11 +630,13 @@ pub fn clear(
     index.postings_lists.clear(writer)?;
     index.documents_fields.clear(writer)?;
     index.documents_fields_counts.clear(writer)?;
+    index.document_versions.clear(writer)?;
     index.synonyms.clear(writer)?;
     index.docs_words.clear(writer)?;
     index.prefix_documents_cache.clear(writer)?;
     index.prefix_postings_lists_cache.clear(writer)?;
     index.updates.clear(update_writer)?;
     index.updates_results.clear(update_writer)?;
+    index.clicks.clear(update_writer)?;
     Ok(())
 }
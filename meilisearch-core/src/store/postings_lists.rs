@@ -1,4 +1,6 @@
 use std::borrow::Cow;
+use std::convert::TryInto;
+use std::mem;
 
 use heed::Result as ZResult;
 use heed::types::ByteSlice;
@@ -37,6 +39,28 @@ impl PostingsLists {
         self.postings_lists.clear(writer)
     }
 
+    pub fn iter<'txn>(
+        self,
+        reader: &'txn heed::RoTxn<MainT>,
+    ) -> ZResult<heed::RoIter<'txn, ByteSlice, PostingsCodec>> {
+        self.postings_lists.iter(reader)
+    }
+
+    /// Returns the words along with the number of documents of their postings list, only
+    /// the start of each list is read, the rest of it is not loaded from the disk.
+    pub fn documents_counts<'txn>(
+        self,
+        reader: &'txn heed::RoTxn<MainT>,
+    ) -> ZResult<impl Iterator<Item = ZResult<(&'txn [u8], usize)>> + 'txn> {
+        let iter = self.postings_lists.as_polymorph().iter::<_, ByteSlice, ByteSlice>(reader)?;
+        Ok(iter.map(|result| {
+            result.map(|(word, bytes)| {
+                let len = bytes.get(..mem::size_of::<u64>()).and_then(|bytes| bytes.try_into().ok());
+                (word, len.map_or(0, u64::from_be_bytes) as usize)
+            })
+        }))
+    }
+
     pub fn postings_list<'txn>(
         self,
         reader: &'txn heed::RoTxn<MainT>,
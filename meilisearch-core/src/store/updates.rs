@@ -32,6 +32,15 @@ impl Updates {
         self.updates.get(reader, &update_id)
     }
 
+    pub fn updates_from<'txn>(
+        self,
+        reader: &'txn heed::RoTxn<UpdateT>,
+        from: u64,
+    ) -> ZResult<impl Iterator<Item = ZResult<(u64, Update)>> + 'txn> {
+        let iter = self.updates.range(reader, &(BEU64::new(from)..))?;
+        Ok(iter.map(|result| result.map(|(key, data)| (key.get(), data))))
+    }
+
     pub fn put_update(
         self,
         writer: &mut heed::RwTxn<UpdateT>,
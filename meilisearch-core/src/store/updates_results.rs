@@ -20,6 +20,15 @@ impl UpdatesResults {
         }
     }
 
+    pub fn updates_results_from<'txn>(
+        self,
+        reader: &'txn heed::RoTxn<UpdateT>,
+        from: u64,
+    ) -> ZResult<impl Iterator<Item = ZResult<(u64, ProcessedUpdateResult)>> + 'txn> {
+        let iter = self.updates_results.range(reader, &(BEU64::new(from)..))?;
+        Ok(iter.map(|result| result.map(|(key, data)| (key.get(), data))))
+    }
+
     pub fn put_update_result(
         self,
         writer: &mut heed::RwTxn<UpdateT>,
@@ -0,0 +1,189 @@
+use std::fmt;
+use std::str::FromStr;
+
+use indexmap::IndexMap;
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde_json::Value;
+
+/// The maximum number of operations a transformer can run on a document.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// The maximum depth of the expressions and of the function calls of a transformer.
+const MAX_DEPTH: usize = 32;
+
+/// The maximum size of the strings, arrays and maps a transformer can build.
+const MAX_SIZE: usize = 1024 * 1024;
+
+/// A [rhai](https://rhai.rs) script applied to the documents before they are stored,
+/// the document is the `doc` map, e.g.
+/// `doc.title = doc.title.to_lower(); doc.site = domain(doc.url); doc.remove("notes");`.
+///
+/// Besides the functions of rhai, `domain(url)` returns the host of a URL. The scripts cannot
+/// print nor evaluate other scripts, and the operations, depth and sizes they can reach are bounded.
+pub struct Transformer {
+    engine: Engine,
+    ast: AST,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransformerError(String);
+
+impl fmt::Display for TransformerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TransformerError {}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_expr_depths(MAX_DEPTH, MAX_DEPTH)
+        .set_max_call_levels(MAX_DEPTH)
+        .set_max_string_size(MAX_SIZE)
+        .set_max_array_size(MAX_SIZE)
+        .set_max_map_size(MAX_SIZE)
+        .set_fail_on_invalid_map_property(true)
+        .on_print(|_| ())
+        .on_debug(|_, _, _| ())
+        .disable_symbol("eval");
+
+    engine.register_fn("domain", |url: &str| -> Result<String, Box<rhai::EvalAltResult>> {
+        domain(url).map(ToString::to_string).ok_or_else(|| format!("`{}` is not a URL", url).into())
+    });
+
+    engine
+}
+
+impl Transformer {
+    /// Applies the script to the document, which is left untouched when it fails.
+    pub fn apply(&self, document: &mut IndexMap<String, Value>) -> Result<(), String> {
+        let doc = rhai::serde::to_dynamic(&*document).map_err(|e| e.to_string())?;
+        let mut scope = Scope::new();
+        scope.push("doc", doc);
+
+        self.engine.run_ast_with_scope(&mut scope, &self.ast).map_err(|e| e.to_string())?;
+
+        let doc = scope.get_value::<Dynamic>("doc").unwrap_or_default();
+        let mut transformed: IndexMap<String, Value> = match rhai::serde::from_dynamic(&doc) {
+            Ok(transformed) => transformed,
+            Err(_) => return Err(format!("`doc` must be a map, found a {}", doc.type_name())),
+        };
+
+        // the maps of rhai are sorted, the attributes that were there keep their order
+        let mut ordered: IndexMap<_, _> = document
+            .keys()
+            .filter_map(|name| transformed.shift_remove(name).map(|value| (name.clone(), value)))
+            .collect();
+        ordered.extend(transformed);
+
+        *document = ordered;
+        Ok(())
+    }
+}
+
+/// Returns the host of the URL, without the `www.` subdomain.
+fn domain(url: &str) -> Option<&str> {
+    let url = url.trim();
+    let rest = &url[url.find("://")? + 3..];
+    let host = rest.split(|c| c == '/' || c == '?' || c == '#').next()?;
+    let host = host.rsplit('@').next()?.split(':').next()?;
+    let host = if host.starts_with("www.") { &host[4..] } else { host };
+
+    if host.is_empty() { None } else { Some(host) }
+}
+
+impl FromStr for Transformer {
+    type Err = TransformerError;
+
+    fn from_str(s: &str) -> Result<Transformer, TransformerError> {
+        let engine = engine();
+        let ast = engine.compile(s).map_err(|e| TransformerError(e.to_string()))?;
+        Ok(Transformer { engine, ast })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn document(value: Value) -> IndexMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn apply_script() {
+        let transformer: Transformer = r#"
+            doc.title = doc.title.to_lower();
+            doc.site = domain(doc.url);
+            doc.label = doc.brand.to_upper() + " #" + doc.id;
+            doc.remove("notes");
+        "#.parse().unwrap();
+
+        let mut doc = document(json!({
+            "id": 12,
+            "title": "Red Shoes",
+            "url": "https://www.shop.example.com/shoes?color=red",
+            "brand": "acme",
+            "notes": "internal",
+        }));
+        transformer.apply(&mut doc).unwrap();
+
+        let expected = document(json!({
+            "id": 12,
+            "title": "red shoes",
+            "url": "https://www.shop.example.com/shoes?color=red",
+            "brand": "acme",
+            "label": "ACME #12",
+            "site": "shop.example.com",
+        }));
+        assert_eq!(doc, expected);
+        assert!(doc.keys().eq(expected.keys()));
+    }
+
+    #[test]
+    fn failed_scripts_keep_the_document() {
+        let transformer: Transformer = "doc.title = doc.title.to_lower(); doc.site = domain(doc.url);".parse().unwrap();
+
+        let original = document(json!({ "title": "Red Shoes", "url": 42 }));
+        let mut doc = original.clone();
+        assert!(transformer.apply(&mut doc).is_err());
+        assert_eq!(doc, original);
+
+        // the missing attributes are errors
+        let mut doc = document(json!({ "title": "Red Shoes" }));
+        assert!(transformer.apply(&mut doc).is_err());
+
+        let transformer: Transformer = "doc = 12;".parse().unwrap();
+        let mut doc = original.clone();
+        assert!(transformer.apply(&mut doc).is_err());
+        assert_eq!(doc, original);
+    }
+
+    #[test]
+    fn scripts_are_bounded() {
+        let transformer: Transformer = "loop { doc.count = 1; }".parse().unwrap();
+        let mut doc = document(json!({ "title": "Red Shoes" }));
+        assert!(transformer.apply(&mut doc).is_err());
+
+        let transformer: Transformer = "fn f(x) { f(x) } doc.title = f(1);".parse().unwrap();
+        assert!(transformer.apply(&mut doc).is_err());
+
+        let nested = format!("doc.title = {}1{};", "(".repeat(100), ")".repeat(100));
+        assert!(nested.parse::<Transformer>().is_err());
+
+        assert!(r#"eval("doc.title = 1")"#.parse::<Transformer>().is_err());
+    }
+
+    #[test]
+    fn invalid_transformers() {
+        assert!("doc.title =".parse::<Transformer>().is_err());
+        assert!("doc.title = doc.title.to_lower(".parse::<Transformer>().is_err());
+        assert!("doc.title = \"unclosed".parse::<Transformer>().is_err());
+        assert!("".parse::<Transformer>().is_ok());
+    }
+}
@@ -0,0 +1,111 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::database::{MainT, UpdateT};
+use crate::update::{apply_documents_addition, next_update_id, AdditionResult, DocumentErrorStrategy, Update};
+use crate::{store, Error, MResult};
+
+/// What a patch does with its null values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictsOnNull {
+    /// The fields set to null by the patch are removed, as RFC 7396 specifies.
+    Delete,
+    /// The fields set to null by the patch are kept with a null value.
+    Preserve,
+}
+
+/// A JSON Merge Patch (RFC 7396) of a document, applied to the document as it is when
+/// the update is processed, the updates enqueued before it are not overwritten.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentPatch {
+    pub document_id: String,
+    pub patch: IndexMap<String, Value>,
+    pub conflicts_on_null: ConflictsOnNull,
+}
+
+pub fn push_document_patch(
+    writer: &mut heed::RwTxn<UpdateT>,
+    updates_store: store::Updates,
+    updates_results_store: store::UpdatesResults,
+    patch: DocumentPatch,
+) -> MResult<u64> {
+    let last_update_id = next_update_id(writer, updates_store, updates_results_store)?;
+
+    let update = Update::document_patch(patch);
+    updates_store.put_update(writer, last_update_id, &update)?;
+
+    Ok(last_update_id)
+}
+
+pub fn apply_document_patch<'a, 'b>(
+    writer: &'a mut heed::RwTxn<'b, MainT>,
+    index: &store::Index,
+    patch: DocumentPatch,
+) -> MResult<AdditionResult> {
+    let DocumentPatch { document_id, patch, conflicts_on_null } = patch;
+
+    let internal_id = index.main.external_to_internal_docid(writer, &document_id)?;
+    let document: Option<IndexMap<String, Value>> = match internal_id {
+        Some(internal_id) => index.document(writer, None, internal_id)?,
+        None => None,
+    };
+    let document = document.ok_or(Error::DocumentNotFound(document_id))?;
+
+    let mut document = Value::Object(document.into_iter().collect());
+    merge_patch(&mut document, Value::Object(patch.into_iter().collect()), conflicts_on_null);
+
+    let document = match document {
+        Value::Object(document) => document.into_iter().collect(),
+        _ => unreachable!("an object patched by an object is an object"),
+    };
+
+    apply_documents_addition(writer, index, vec![document], DocumentErrorStrategy::Abort)
+}
+
+/// Applies the JSON Merge Patch to the value, as described in RFC 7396.
+fn merge_patch(target: &mut Value, patch: Value, conflicts_on_null: ConflictsOnNull) {
+    let patch = match patch {
+        Value::Object(patch) => patch,
+        patch => {
+            *target = patch;
+            return;
+        }
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            match value {
+                Value::Null if conflicts_on_null == ConflictsOnNull::Delete => {
+                    target.remove(&key);
+                }
+                Value::Null => {
+                    target.insert(key, Value::Null);
+                }
+                value => merge_patch(target.entry(key).or_insert(Value::Null), value, conflicts_on_null),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn null_values_are_deleted_or_preserved() {
+        let mut target = json!({ "title": "Carol", "year": 2015, "director": { "name": "Todd Haynes", "born": 1961 } });
+        merge_patch(&mut target, json!({ "year": null, "director": { "born": null } }), ConflictsOnNull::Delete);
+        assert_eq!(target, json!({ "title": "Carol", "director": { "name": "Todd Haynes" } }));
+
+        merge_patch(&mut target, json!({ "title": null, "director": "Todd Haynes" }), ConflictsOnNull::Preserve);
+        assert_eq!(target, json!({ "title": null, "director": "Todd Haynes" }));
+    }
+}
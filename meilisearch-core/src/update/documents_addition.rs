@@ -1,12 +1,13 @@
 use std::borrow::Cow;
-use std::collections::{HashMap, BTreeMap};
+use std::collections::{HashMap, HashSet, BTreeMap, BTreeSet};
 
 use fst::{set::OpBuilder, SetBuilder};
 use indexmap::IndexMap;
+use log::warn;
 use meilisearch_schema::{Schema, FieldId};
 use meilisearch_types::DocumentId;
 use sdset::{duo::Union, SetOperation};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::database::{MainT, UpdateT};
@@ -14,17 +15,48 @@ use crate::database::{UpdateEvent, UpdateEventsEmitter};
 use crate::facets;
 use crate::raw_indexer::RawIndexer;
 use crate::serde::Deserializer;
+use crate::stemming::stemmer;
 use crate::store::{self, DocumentsFields, DocumentsFieldsCounts, DiscoverIds};
-use crate::update::helpers::{index_value, value_to_number, extract_document_id};
+use crate::transformer::Transformer;
+use crate::update::helpers::{index_value, index_phonetic_value, value_to_number, value_to_string, extract_document_id};
+use crate::update::progress::PROGRESS_STEP;
 use crate::update::{apply_documents_deletion, compute_short_prefixes, next_update_id, Update};
 use crate::{Error, MResult, RankedMap};
 
+/// What an addition does with the documents that cannot be added,
+/// e.g. the ones with an invalid primary key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DocumentErrorStrategy {
+    /// The addition fails at the first invalid document.
+    Abort,
+    /// The invalid documents are left out and reported, the other ones are added.
+    Skip,
+    /// All the documents are checked and the addition fails with all the errors.
+    Fail,
+}
+
+impl Default for DocumentErrorStrategy {
+    fn default() -> DocumentErrorStrategy {
+        DocumentErrorStrategy::Abort
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedDocument {
+    /// The primary key value of the document, if it has one.
+    pub id: Value,
+    pub error: String,
+}
+
 pub struct DocumentsAddition<D> {
     updates_store: store::Updates,
     updates_results_store: store::UpdatesResults,
     updates_notifier: UpdateEventsEmitter,
     documents: Vec<D>,
     is_partial: bool,
+    error_strategy: DocumentErrorStrategy,
+    detected_primary_key: Option<String>,
 }
 
 impl<D> DocumentsAddition<D> {
@@ -39,6 +71,8 @@ impl<D> DocumentsAddition<D> {
             updates_notifier,
             documents: Vec::new(),
             is_partial: false,
+            error_strategy: DocumentErrorStrategy::default(),
+            detected_primary_key: None,
         }
     }
 
@@ -53,6 +87,8 @@ impl<D> DocumentsAddition<D> {
             updates_notifier,
             documents: Vec::new(),
             is_partial: true,
+            error_strategy: DocumentErrorStrategy::default(),
+            detected_primary_key: None,
         }
     }
 
@@ -60,6 +96,16 @@ impl<D> DocumentsAddition<D> {
         self.documents.push(document);
     }
 
+    pub fn set_error_strategy(&mut self, error_strategy: DocumentErrorStrategy) {
+        self.error_strategy = error_strategy;
+    }
+
+    /// Gives the primary key detected in the documents to the index when the update is
+    /// processed, if the index still has none, it is not saved before.
+    pub fn set_detected_primary_key(&mut self, primary_key: String) {
+        self.detected_primary_key = Some(primary_key);
+    }
+
     pub fn finalize(self, writer: &mut heed::RwTxn<UpdateT>) -> MResult<u64>
     where
         D: serde::Serialize,
@@ -71,6 +117,8 @@ impl<D> DocumentsAddition<D> {
             self.updates_results_store,
             self.documents,
             self.is_partial,
+            self.error_strategy,
+            self.detected_primary_key,
         )?;
         Ok(update_id)
     }
@@ -88,6 +136,8 @@ pub fn push_documents_addition<D: serde::Serialize>(
     updates_results_store: store::UpdatesResults,
     addition: Vec<D>,
     is_partial: bool,
+    error_strategy: DocumentErrorStrategy,
+    detected_primary_key: Option<String>,
 ) -> MResult<u64> {
     let mut values = Vec::with_capacity(addition.len());
     for add in addition {
@@ -98,17 +148,34 @@ pub fn push_documents_addition<D: serde::Serialize>(
 
     let last_update_id = next_update_id(writer, updates_store, updates_results_store)?;
 
-    let update = if is_partial {
-        Update::documents_partial(values)
+    let mut update = if is_partial {
+        Update::documents_partial(values, error_strategy)
     } else {
-        Update::documents_addition(values)
+        Update::documents_addition(values, error_strategy)
     };
+    update.detected_primary_key = detected_primary_key;
 
     updates_store.put_update(writer, last_update_id, &update)?;
 
     Ok(last_update_id)
 }
 
+/// Enqueues the embeddings generated for the documents, they are applied like a partial
+/// addition, without archiving the previous versions of the documents.
+pub fn push_documents_embeddings(
+    writer: &mut heed::RwTxn<UpdateT>,
+    updates_store: store::Updates,
+    updates_results_store: store::UpdatesResults,
+    documents: Vec<IndexMap<String, Value>>,
+) -> MResult<u64> {
+    let last_update_id = next_update_id(writer, updates_store, updates_results_store)?;
+
+    let update = Update::documents_embeddings(documents);
+    updates_store.put_update(writer, last_update_id, &update)?;
+
+    Ok(last_update_id)
+}
+
 fn index_document<A>(
     writer: &mut heed::RwTxn<MainT>,
     documents_fields: DocumentsFields,
@@ -119,7 +186,8 @@ fn index_document<A>(
     field_id: FieldId,
     document_id: DocumentId,
     value: &Value,
-) -> MResult<()>
+    phonetic: bool,
+) -> MResult<u64>
 where A: AsRef<[u8]>,
 {
     let serialized = serde_json::to_vec(value)?;
@@ -135,6 +203,10 @@ where A: AsRef<[u8]>,
                 number_of_words as u16,
             )?;
         }
+
+        if phonetic {
+            index_phonetic_value(indexer, document_id, *indexed_pos, value);
+        }
     }
 
     if schema.is_ranked(field_id) {
@@ -142,15 +214,120 @@ where A: AsRef<[u8]>,
         ranked_map.insert(document_id, field_id, number);
     }
 
-    Ok(())
+    Ok(store::field_size(&serialized))
+}
+
+/// Returns the attributes that must be phonetically indexed,
+/// none of them are when the phonetic matching is disabled.
+fn phonetic_attributes(reader: &heed::RoTxn<MainT>, index: &store::Index) -> MResult<HashSet<String>> {
+    if !index.main.phonetic_matching(reader)?.unwrap_or(false) {
+        return Ok(HashSet::new());
+    }
+
+    let attributes = index.main.phonetic_attributes(reader)?.unwrap_or_default();
+    Ok(attributes.into_iter().collect())
+}
+
+/// Returns the dictionary used to split the compound words, the words of the index,
+/// or `None` when the tokens must not be decompounded.
+fn decompound_dictionary(reader: &heed::RoTxn<MainT>, index: &store::Index) -> MResult<Option<fst::Set<Vec<u8>>>> {
+    if !index.main.decompound_tokens(reader)?.unwrap_or(false) {
+        return Ok(None);
+    }
+
+    let words = index.main.words_fst(reader)?.map_data(Cow::into_owned)?;
+    Ok(Some(words))
+}
+
+/// Runs the documents through the same validation and tokenization steps as an
+/// addition, without writing anything, and returns the errors along with the
+/// position of the document that raised them.
+pub fn check_documents_addition(
+    reader: &heed::RoTxn<MainT>,
+    index: &store::Index,
+    mut schema: Schema,
+    documents: &[IndexMap<String, Value>],
+) -> MResult<Vec<(usize, Error)>>
+{
+    let primary_key = schema.primary_key().ok_or(Error::MissingPrimaryKey)?.to_owned();
+
+    let external_docids = index.main.external_docids(reader)?;
+    let internal_docids = index.main.internal_docids(reader)?;
+    let mut available_ids = DiscoverIds::new(&internal_docids);
+
+    let attributes_for_facetting = index.main.attributes_for_faceting(reader)?;
+    let max_fields_count = index.main.max_fields_count(reader)?;
+    let stop_words = index.main.stop_words_fst(reader)?.map_data(Cow::into_owned)?;
+    let mut indexer = RawIndexer::new(stop_words);
+
+    let mut errors = Vec::new();
+
+    for (position, document) in documents.iter().enumerate() {
+        let document_id = match extract_document_id(&primary_key, document, &external_docids, &mut available_ids) {
+            Ok((document_id, _)) => document_id,
+            Err(e) => {
+                errors.push((position, e.into()));
+                continue;
+            }
+        };
+
+        for (attribute, value) in document {
+            if let Some(max_fields_count) = max_fields_count {
+                if !schema.contains(attribute) && schema.fields_count() >= max_fields_count {
+                    continue;
+                }
+            }
+
+            let field_id = match schema.insert_and_index(attribute) {
+                Ok(field_id) => field_id,
+                Err(e) => {
+                    errors.push((position, e.into()));
+                    break;
+                }
+            };
+
+            if let Some(indexed_pos) = schema.is_indexed(field_id) {
+                index_value(&mut indexer, document_id, *indexed_pos, value);
+            }
+        }
+
+        if let Some(attributes_for_facetting) = &attributes_for_facetting {
+            let mut documents = HashMap::new();
+            documents.insert(document_id, document.clone());
+            if let Err(e) = facets::facet_map_from_docs(&schema, &documents, attributes_for_facetting.as_ref()) {
+                errors.push((position, e));
+            }
+        }
+    }
+
+    Ok(errors)
+}
+
+/// The maximum number of warnings of a documents addition, the other ones are counted.
+const MAX_WARNINGS: usize = 100;
+
+/// The maximum number of skipped documents listed by a documents addition, the other ones are counted.
+const MAX_SKIPPED_DOCUMENTS: usize = 100;
+
+/// What a documents addition changed, besides the documents themselves.
+pub struct AdditionResult {
+    /// The warnings about the documents stored as they were, e.g. when the transformer failed.
+    pub warnings: Vec<String>,
+    pub skipped_documents: Vec<SkippedDocument>,
+    /// The number of skipped documents, including the ones that are not listed.
+    pub skipped_documents_count: usize,
+    /// The ids of the documents added or replaced.
+    pub documents_ids: Vec<String>,
 }
 
 pub fn apply_addition<'a, 'b>(
     writer: &'a mut heed::RwTxn<'b, MainT>,
     index: &store::Index,
     new_documents: Vec<IndexMap<String, Value>>,
-    partial: bool
-) -> MResult<()>
+    partial: bool,
+    error_strategy: DocumentErrorStrategy,
+    archive_versions: bool,
+) -> MResult<AdditionResult>
 {
     let mut schema = match index.main.schema(writer)? {
         Some(schema) => schema,
@@ -164,22 +341,56 @@ pub fn apply_addition<'a, 'b>(
 
     let primary_key = schema.primary_key().ok_or(Error::MissingPrimaryKey)?;
 
+    // the documents a transformer fails on are stored as they are, with a warning
+    let mut warnings = Vec::new();
+    let mut omitted_warnings = 0;
+    let transformer = match index.main.transformer(writer)? {
+        Some(transformer) => match transformer.parse::<Transformer>() {
+            Ok(transformer) => Some(transformer),
+            Err(e) => {
+                warnings.push(format!("the transformer is invalid, {}; the documents are not transformed", e));
+                None
+            }
+        },
+        None => None,
+    };
+
+    // the facets of each document are checked when the invalid documents must not abort the addition
+    let attributes_for_facetting = match error_strategy {
+        DocumentErrorStrategy::Abort => None,
+        _ => index.main.attributes_for_faceting(writer)?,
+    };
+
     // 1. store documents ids for future deletion
     let mut documents_additions = HashMap::new();
     let mut new_external_docids = BTreeMap::new();
     let mut new_internal_docids = Vec::with_capacity(new_documents.len());
+    let mut invalid_documents = Vec::new();
+    let mut invalid_documents_count = 0;
+    let mut skip_document = |document: SkippedDocument| {
+        invalid_documents_count += 1;
+        if invalid_documents.len() < MAX_SKIPPED_DOCUMENTS {
+            invalid_documents.push(document);
+        }
+    };
 
     for mut document in new_documents {
-        let (internal_docid, external_docid) =
-            extract_document_id(
-                &primary_key,
-                &document,
-                &external_docids,
-                &mut available_ids,
-            )?;
-
-        new_external_docids.insert(external_docid, internal_docid.0);
-        new_internal_docids.push(internal_docid);
+        let ids = extract_document_id(
+            &primary_key,
+            &document,
+            &external_docids,
+            &mut available_ids,
+        );
+
+        let (internal_docid, external_docid) = match ids {
+            Ok(ids) => ids,
+            Err(e) if error_strategy == DocumentErrorStrategy::Abort => return Err(e.into()),
+            Err(e) => {
+                let id = document.get(primary_key).cloned().unwrap_or(Value::Null);
+                skip_document(SkippedDocument { id, error: Error::from(e).to_string() });
+                continue;
+            }
+        };
 
         if partial {
             let mut deserializer = Deserializer {
@@ -197,9 +408,73 @@ pub fn apply_addition<'a, 'b>(
                 }
             }
         }
+
+        if let Some(transformer) = &transformer {
+            let mut transformed = document.clone();
+            let warning = match transformer.apply(&mut transformed) {
+                Ok(()) if transformed.get(primary_key) == document.get(primary_key) => {
+                    document = transformed;
+                    None
+                }
+                Ok(()) => Some(format!(
+                    "document `{}`: the transformer cannot change the primary key, the document is stored as it is",
+                    external_docid,
+                )),
+                Err(e) => Some(format!(
+                    "document `{}`: the transformer failed, {}; the document is stored as it is",
+                    external_docid, e,
+                )),
+            };
+
+            match warning {
+                Some(_) if warnings.len() >= MAX_WARNINGS => omitted_warnings += 1,
+                Some(warning) => warnings.push(warning),
+                None => (),
+            }
+        }
+
+        if let Some(attributes_for_facetting) = &attributes_for_facetting {
+            let mut documents = HashMap::new();
+            documents.insert(internal_docid, document);
+            if let Err(e) = facets::facet_map_from_docs(&schema, &documents, attributes_for_facetting.as_ref()) {
+                skip_document(SkippedDocument { id: external_docid.into(), error: e.to_string() });
+                continue;
+            }
+            document = documents.remove(&internal_docid).unwrap();
+        }
+
+        new_external_docids.insert(external_docid, internal_docid.0);
+        new_internal_docids.push(internal_docid);
         documents_additions.insert(internal_docid, document);
     }
 
+    if error_strategy == DocumentErrorStrategy::Fail && !invalid_documents.is_empty() {
+        let errors = invalid_documents
+            .into_iter()
+            .map(|document| format!("document {}: {}", document.id, document.error))
+            .collect();
+        return Err(Error::InvalidDocuments(invalid_documents_count, errors));
+    }
+
+    // the replaced documents are archived before being removed
+    let document_versions = index.main.document_versions(writer)?.unwrap_or(0);
+    if archive_versions && document_versions > 0 {
+        for (external_docid, internal_docid) in &new_external_docids {
+            let mut deserializer = Deserializer {
+                document_id: DocumentId(*internal_docid),
+                reader: writer,
+                documents_fields: index.documents_fields,
+                schema: &schema,
+                fields: None,
+            };
+
+            let old_document = Option::<IndexMap<String, Value>>::deserialize(&mut deserializer)?;
+            if let Some(old_document) = old_document {
+                index.document_versions.archive(writer, external_docid, old_document, document_versions)?;
+            }
+        }
+    }
+
     // 2. remove the documents postings lists
     let number_of_inserted_documents = documents_additions.len();
     let documents_ids = new_external_docids.iter().map(|(id, _)| id.clone()).collect();
@@ -219,13 +494,31 @@ pub fn apply_addition<'a, 'b>(
     }
 
     let mut indexer = RawIndexer::new(stop_words);
+    indexer.set_stemmer(index.main.stemming_language(writer)?.and_then(|l| stemmer(&l)));
+    indexer.set_decompound_dictionary(decompound_dictionary(writer, index)?);
+    let max_fields_count = index.main.max_fields_count(writer)?;
+    let phonetic_attributes = phonetic_attributes(writer, index)?;
+    let language_field = index.main.language_detection_field(writer)?;
 
     // For each document in this update
+    let mut documents_size = 0;
     for (document_id, document) in documents_additions {
+        if let Some(Value::String(language)) = language_field.as_ref().and_then(|field| document.get(field)) {
+            indexer.set_document_language(document_id, language);
+        }
+
         // For each key-value pair in the document.
         for (attribute, value) in document {
+            // Unknown fields are skipped once the schema reached the maximum number of fields.
+            if let Some(max_fields_count) = max_fields_count {
+                if !schema.contains(&attribute) && schema.fields_count() >= max_fields_count {
+                    warn!("field {:?} ignored; the maximum number of fields ({}) is reached", attribute, max_fields_count);
+                    continue;
+                }
+            }
+
             let field_id = schema.insert_and_index(&attribute)?;
-            index_document(
+            documents_size += index_document(
                 writer,
                 index.documents_fields,
                 index.documents_fields_counts,
@@ -235,10 +528,21 @@ pub fn apply_addition<'a, 'b>(
                 field_id,
                 document_id,
                 &value,
+                phonetic_attributes.contains(&attribute),
             )?;
         }
     }
 
+    index.update_size(writer, |size| size + documents_size)?;
+
+    // the documents are refused when they make the index exceed its maximum size,
+    // the update fails and nothing it wrote is kept
+    if let Some(max_index_size) = index.main.max_index_size(writer)? {
+        if index.size(writer)? > max_index_size as u64 {
+            return Err(Error::IndexSizeLimitExceeded(max_index_size));
+        }
+    }
+
     write_documents_addition_index(
         writer,
         index,
@@ -249,28 +553,62 @@ pub fn apply_addition<'a, 'b>(
 
     index.main.put_schema(writer, &schema)?;
 
+    let documents_ids = new_external_docids.keys().cloned().collect();
     let new_external_docids = fst::Map::from_iter(new_external_docids.iter().map(|(ext, id)| (ext, *id as u64)))?;
     let new_internal_docids = sdset::SetBuf::from_dirty(new_internal_docids);
     index.main.merge_external_docids(writer, &new_external_docids)?;
     index.main.merge_internal_docids(writer, &new_internal_docids)?;
 
-    Ok(())
+    if omitted_warnings > 0 {
+        warnings.push(format!("the transformer failed on {} other documents, they are stored as they are", omitted_warnings));
+    }
+
+    Ok(AdditionResult {
+        warnings,
+        skipped_documents: invalid_documents,
+        skipped_documents_count: invalid_documents_count,
+        documents_ids,
+    })
 }
 
 pub fn apply_documents_partial_addition<'a, 'b>(
     writer: &'a mut heed::RwTxn<'b, MainT>,
     index: &store::Index,
     new_documents: Vec<IndexMap<String, Value>>,
-) -> MResult<()> {
-    apply_addition(writer, index, new_documents, true)
+    error_strategy: DocumentErrorStrategy,
+) -> MResult<AdditionResult> {
+    apply_addition(writer, index, new_documents, true, error_strategy, true)
+}
+
+/// Adds the embeddings to their documents, the other fields of the documents are kept.
+/// The embeddings of the documents deleted since they were generated are ignored.
+pub fn apply_documents_embeddings<'a, 'b>(
+    writer: &'a mut heed::RwTxn<'b, MainT>,
+    index: &store::Index,
+    new_documents: Vec<IndexMap<String, Value>>,
+) -> MResult<AdditionResult> {
+    let schema = index.main.schema(writer)?.ok_or(Error::SchemaMissing)?;
+    let primary_key = schema.primary_key().ok_or(Error::MissingPrimaryKey)?;
+    let external_docids = index.main.external_docids(writer)?;
+
+    let new_documents = new_documents
+        .into_iter()
+        .filter(|document| {
+            let id = document.get(primary_key).map(value_to_string);
+            id.map_or(false, |id| external_docids.get(id).is_some())
+        })
+        .collect();
+
+    apply_addition(writer, index, new_documents, true, DocumentErrorStrategy::Skip, false)
 }
 
 pub fn apply_documents_addition<'a, 'b>(
     writer: &'a mut heed::RwTxn<'b, MainT>,
     index: &store::Index,
     new_documents: Vec<IndexMap<String, Value>>,
-) -> MResult<()> {
-    apply_addition(writer, index, new_documents, false)
+    error_strategy: DocumentErrorStrategy,
+) -> MResult<AdditionResult> {
+    apply_addition(writer, index, new_documents, false, error_strategy, true)
 }
 
 pub fn reindex_all_documents(writer: &mut heed::RwTxn<MainT>, index: &store::Index) -> MResult<()> {
@@ -295,6 +633,7 @@ pub fn reindex_all_documents(writer: &mut heed::RwTxn<MainT>, index: &store::Ind
     index.facets.clear(writer)?;
     index.postings_lists.clear(writer)?;
     index.docs_words.clear(writer)?;
+    index.main.put_documents_languages(writer, &BTreeSet::new())?;
 
     let stop_words = index.main
         .stop_words_fst(writer)?
@@ -303,22 +642,36 @@ pub fn reindex_all_documents(writer: &mut heed::RwTxn<MainT>, index: &store::Ind
 
     let number_of_inserted_documents = documents_ids_to_reindex.len();
     let mut indexer = RawIndexer::new(stop_words);
+    indexer.set_stemmer(index.main.stemming_language(writer)?.and_then(|l| stemmer(&l)));
+    indexer.set_decompound_dictionary(decompound_dictionary(writer, index)?);
     let mut ram_store = HashMap::new();
+    let phonetic_attributes = phonetic_attributes(writer, index)?;
+    let language_field_id = index.main.language_detection_field(writer)?.and_then(|field| schema.id(&field));
 
     if let Some(ref attributes_for_facetting) = index.main.attributes_for_faceting(writer)? {
         let facet_map = facets::facet_map_from_docids(writer, &index, &documents_ids_to_reindex, &attributes_for_facetting)?;
         index.facets.add(writer, facet_map)?;
     }
     // ^-- https://github.com/meilisearch/MeiliSearch/pull/631#issuecomment-626624470 --v
-    for document_id in documents_ids_to_reindex {
+    index.update_progress.report(0, number_of_inserted_documents);
+    for (i, document_id) in documents_ids_to_reindex.into_iter().enumerate() {
+        if i > 0 && i % PROGRESS_STEP == 0 {
+            index.update_progress.report(i, number_of_inserted_documents);
+        }
+
         for result in index.documents_fields.document_fields(writer, document_id)? {
             let (field_id, bytes) = result?;
             let value: Value = serde_json::from_slice(bytes)?;
             ram_store.insert((document_id, field_id), value);
         }
 
+        if let Some(Value::String(language)) = language_field_id.and_then(|field_id| ram_store.get(&(document_id, field_id))) {
+            indexer.set_document_language(document_id, language);
+        }
+
         // For each key-value pair in the document.
         for ((document_id, field_id), value) in ram_store.drain() {
+            let phonetic = schema.name(field_id).map_or(false, |name| phonetic_attributes.contains(name));
             index_document(
                 writer,
                 index.documents_fields,
@@ -329,10 +682,13 @@ pub fn reindex_all_documents(writer: &mut heed::RwTxn<MainT>, index: &store::Ind
                 field_id,
                 document_id,
                 &value,
+                phonetic,
             )?;
         }
     }
 
+    index.update_progress.report(number_of_inserted_documents, number_of_inserted_documents);
+
     // 4. write the new index in the main store
     write_documents_addition_index(
         writer,
@@ -374,6 +730,12 @@ where A: AsRef<[u8]>,
         index.docs_words.put_doc_words(writer, id, &words)?;
     }
 
+    if !indexed.languages.is_empty() {
+        let mut languages = index.main.documents_languages(writer)?.unwrap_or_default();
+        languages.extend(indexed.languages);
+        index.main.put_documents_languages(writer, &languages)?;
+    }
+
     let delta_words = delta_words_builder.into_set();
 
     let words_fst = index.main.words_fst(writer)?;
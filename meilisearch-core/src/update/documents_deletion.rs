@@ -128,6 +128,7 @@ pub fn apply_documents_deletion(
     }
 
     let mut deleted_documents = HashSet::new();
+    let mut deleted_size = 0;
     let mut removed_words = BTreeSet::new();
     for (word, document_ids) in words_document_ids {
         let document_ids = SetBuf::from_dirty(document_ids);
@@ -146,12 +147,16 @@ pub fn apply_documents_deletion(
 
         for id in document_ids {
             index.documents_fields_counts.del_all_document_fields_counts(writer, id)?;
+            let document_size = index.documents_fields.document_size(writer, id)?;
             if index.documents_fields.del_all_document_fields(writer, id)? != 0 {
                 deleted_documents.insert(id);
+                deleted_size += document_size;
             }
         }
     }
 
+    index.update_size(writer, |size| size.saturating_sub(deleted_size))?;
+
     let deleted_documents_len = deleted_documents.len() as u64;
     for id in deleted_documents {
         index.docs_words.del_doc_words(writer, id)?;
@@ -0,0 +1,74 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::database::{BoxDocumentsImporter, UpdateT};
+use crate::update::{next_update_id, DocumentErrorStrategy, Update, UpdateData, UpdateProgress};
+use crate::{store, MResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportFormat {
+    /// One JSON document per line.
+    Ndjson,
+    /// A JSON array of documents.
+    Json,
+}
+
+/// The documents downloaded by an importer, with the primary key it detected in them
+/// when the index does not have one yet.
+pub type ImportedDocuments = (Vec<IndexMap<String, Value>>, Option<String>);
+
+/// A file of documents to add or replace, downloaded from its URL by the importer of the
+/// database when the update is the next one to be processed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentsImport {
+    pub url: String,
+    pub format: ImportFormat,
+    pub primary_key: Option<String>,
+    /// The downloaded documents, or why they could not be, they are never stored.
+    #[serde(skip)]
+    documents: Option<Result<ImportedDocuments, String>>,
+}
+
+impl DocumentsImport {
+    pub fn new(url: String, format: ImportFormat, primary_key: Option<String>) -> DocumentsImport {
+        DocumentsImport { url, format, primary_key, documents: None }
+    }
+
+    pub(crate) fn take_documents(&mut self) -> Result<ImportedDocuments, String> {
+        self.documents.take().unwrap_or_else(|| Err("there is no importer to download the documents".to_string()))
+    }
+}
+
+pub fn push_documents_import(
+    writer: &mut heed::RwTxn<UpdateT>,
+    updates_store: store::Updates,
+    updates_results_store: store::UpdatesResults,
+    import: DocumentsImport,
+    error_strategy: DocumentErrorStrategy,
+) -> MResult<u64> {
+    let last_update_id = next_update_id(writer, updates_store, updates_results_store)?;
+
+    let update = Update::documents_import(import, error_strategy);
+    updates_store.put_update(writer, last_update_id, &update)?;
+
+    Ok(last_update_id)
+}
+
+/// Downloads the documents of the update with the importer when it is an import, no transaction
+/// must be open meanwhile for the other writes not to wait for the download.
+pub(crate) fn download_documents(
+    index_uid: &str,
+    update_id: u64,
+    update: &mut Update,
+    importer: Option<&BoxDocumentsImporter>,
+    progress: &UpdateProgress,
+) {
+    if let (UpdateData::DocumentsImport(import), Some(importer)) = (&mut update.data, importer) {
+        progress.start(update_id);
+        let documents = (importer)(index_uid, import, progress);
+        import.documents = Some(documents);
+    }
+}
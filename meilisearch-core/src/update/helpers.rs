@@ -45,6 +45,25 @@ where A: AsRef<[u8]>,
     }
 }
 
+/// Indexes the phonetic codes of the textual parts of the value.
+pub fn index_phonetic_value<A>(
+    indexer: &mut RawIndexer<A>,
+    document_id: DocumentId,
+    indexed_pos: IndexedPos,
+    value: &Value,
+)
+where A: AsRef<[u8]>,
+{
+    match value {
+        Value::Null | Value::Bool(_) | Value::Number(_) => (),
+        Value::String(string) => indexer.index_text_phonetic(document_id, indexed_pos, &string),
+        Value::Array(_) | Value::Object(_) => {
+            let text = value_to_string(value);
+            indexer.index_text_phonetic(document_id, indexed_pos, &text)
+        },
+    }
+}
+
 /// Transforms the JSON Value type into a String.
 pub fn value_to_string(value: &Value) -> String {
     fn internal_value_to_string(string: &mut String, value: &Value) {
@@ -1,16 +1,27 @@
 mod clear_all;
 mod customs_update;
+mod document_patch;
 mod documents_addition;
 mod documents_deletion;
+mod documents_import;
+mod progress;
 mod settings_update;
+mod synonyms_edit;
 mod helpers;
 
 pub use self::clear_all::{apply_clear_all, push_clear_all};
 pub use self::customs_update::{apply_customs_update, push_customs_update};
-pub use self::documents_addition::{apply_documents_addition, apply_documents_partial_addition, DocumentsAddition};
+pub use self::document_patch::{apply_document_patch, push_document_patch, ConflictsOnNull, DocumentPatch};
+pub use self::documents_addition::{apply_documents_addition, apply_documents_partial_addition, check_documents_addition, AdditionResult};
+pub use self::documents_addition::{apply_documents_embeddings, push_documents_embeddings};
+pub use self::documents_addition::{DocumentErrorStrategy, DocumentsAddition, SkippedDocument};
 pub use self::documents_deletion::{apply_documents_deletion, DocumentsDeletion};
+pub use self::documents_import::{push_documents_import, DocumentsImport, ImportFormat, ImportedDocuments};
+pub(crate) use self::documents_import::download_documents;
 pub use self::helpers::{index_value, value_to_string, value_to_number, discover_document_id, extract_document_id};
+pub use self::progress::{UpdateDetails, UpdateProgress};
 pub use self::settings_update::{apply_settings_update, push_settings_update};
+pub use self::synonyms_edit::{apply_synonyms_edit, push_synonyms_edit, SynonymsEdit};
 
 use std::cmp;
 use std::time::Instant;
@@ -24,7 +35,9 @@ use sdset::Set;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::{store, MResult};
+use meilisearch_error::ErrorCode;
+
+use crate::{store, Error, MResult};
 use crate::database::{MainT, UpdateT};
 use crate::settings::SettingsUpdate;
 
@@ -32,6 +45,11 @@ use crate::settings::SettingsUpdate;
 pub struct Update {
     data: UpdateData,
     enqueued_at: DateTime<Utc>,
+    #[serde(default)]
+    document_error_strategy: DocumentErrorStrategy,
+    /// The primary key detected in the documents, given to the index when the update is processed if it still has none.
+    #[serde(default)]
+    detected_primary_key: Option<String>,
 }
 
 impl Update {
@@ -39,6 +57,8 @@ impl Update {
         Update {
             data: UpdateData::ClearAll,
             enqueued_at: Utc::now(),
+            document_error_strategy: DocumentErrorStrategy::default(),
+            detected_primary_key: None,
         }
     }
 
@@ -46,20 +66,53 @@ impl Update {
         Update {
             data: UpdateData::Customs(data),
             enqueued_at: Utc::now(),
+            document_error_strategy: DocumentErrorStrategy::default(),
+            detected_primary_key: None,
         }
     }
 
-    fn documents_addition(documents: Vec<IndexMap<String, Value>>) -> Update {
+    fn documents_addition(documents: Vec<IndexMap<String, Value>>, document_error_strategy: DocumentErrorStrategy) -> Update {
         Update {
             data: UpdateData::DocumentsAddition(documents),
             enqueued_at: Utc::now(),
+            document_error_strategy,
+            detected_primary_key: None,
         }
     }
 
-    fn documents_partial(documents: Vec<IndexMap<String, Value>>) -> Update {
+    fn documents_partial(documents: Vec<IndexMap<String, Value>>, document_error_strategy: DocumentErrorStrategy) -> Update {
         Update {
             data: UpdateData::DocumentsPartial(documents),
             enqueued_at: Utc::now(),
+            document_error_strategy,
+            detected_primary_key: None,
+        }
+    }
+
+    fn documents_embeddings(documents: Vec<IndexMap<String, Value>>) -> Update {
+        Update {
+            data: UpdateData::DocumentsEmbeddings(documents),
+            enqueued_at: Utc::now(),
+            document_error_strategy: DocumentErrorStrategy::default(),
+            detected_primary_key: None,
+        }
+    }
+
+    fn document_patch(patch: DocumentPatch) -> Update {
+        Update {
+            data: UpdateData::DocumentPatch(patch),
+            enqueued_at: Utc::now(),
+            document_error_strategy: DocumentErrorStrategy::default(),
+            detected_primary_key: None,
+        }
+    }
+
+    fn documents_import(import: DocumentsImport, document_error_strategy: DocumentErrorStrategy) -> Update {
+        Update {
+            data: UpdateData::DocumentsImport(import),
+            enqueued_at: Utc::now(),
+            document_error_strategy,
+            detected_primary_key: None,
         }
     }
 
@@ -67,6 +120,8 @@ impl Update {
         Update {
             data: UpdateData::DocumentsDeletion(data),
             enqueued_at: Utc::now(),
+            document_error_strategy: DocumentErrorStrategy::default(),
+            detected_primary_key: None,
         }
     }
 
@@ -74,6 +129,17 @@ impl Update {
         Update {
             data: UpdateData::Settings(data),
             enqueued_at: Utc::now(),
+            document_error_strategy: DocumentErrorStrategy::default(),
+            detected_primary_key: None,
+        }
+    }
+
+    fn synonyms_edit(edit: SynonymsEdit) -> Update {
+        Update {
+            data: UpdateData::SynonymsEdit(edit),
+            enqueued_at: Utc::now(),
+            document_error_strategy: DocumentErrorStrategy::default(),
+            detected_primary_key: None,
         }
     }
 }
@@ -84,8 +150,13 @@ pub enum UpdateData {
     Customs(Vec<u8>),
     DocumentsAddition(Vec<IndexMap<String, Value>>),
     DocumentsPartial(Vec<IndexMap<String, Value>>),
+    /// The embeddings generated for the documents, each one with its primary key.
+    DocumentsEmbeddings(Vec<IndexMap<String, Value>>),
+    DocumentsImport(DocumentsImport),
+    DocumentPatch(DocumentPatch),
     DocumentsDeletion(Vec<String>),
-    Settings(SettingsUpdate)
+    Settings(SettingsUpdate),
+    SynonymsEdit(SynonymsEdit),
 }
 
 impl UpdateData {
@@ -99,11 +170,19 @@ impl UpdateData {
             UpdateData::DocumentsPartial(addition) => UpdateType::DocumentsPartial {
                 number: addition.len(),
             },
+            UpdateData::DocumentsEmbeddings(addition) => UpdateType::DocumentsEmbeddings {
+                number: addition.len(),
+            },
+            UpdateData::DocumentsImport(_) => UpdateType::DocumentsImport { number: None },
+            UpdateData::DocumentPatch(_) => UpdateType::DocumentPatch,
             UpdateData::DocumentsDeletion(deletion) => UpdateType::DocumentsDeletion {
                 number: deletion.len(),
             },
             UpdateData::Settings(update) => UpdateType::Settings {
-                settings: update.clone(),
+                settings: update.without_secrets(),
+            },
+            UpdateData::SynonymsEdit(edit) => UpdateType::SynonymsEdit {
+                edit: edit.clone(),
             },
         }
     }
@@ -116,8 +195,31 @@ pub enum UpdateType {
     Customs,
     DocumentsAddition { number: usize },
     DocumentsPartial { number: usize },
+    DocumentsEmbeddings { number: usize },
+    /// The number of documents of the file is only known once it is downloaded.
+    DocumentsImport { number: Option<usize> },
+    DocumentPatch,
     DocumentsDeletion { number: usize },
     Settings { settings: SettingsUpdate },
+    SynonymsEdit { edit: SynonymsEdit },
+}
+
+impl UpdateType {
+    /// Returns the name of the type, as it is serialized.
+    pub fn name(&self) -> &'static str {
+        match self {
+            UpdateType::ClearAll => "ClearAll",
+            UpdateType::Customs => "Customs",
+            UpdateType::DocumentsAddition { .. } => "DocumentsAddition",
+            UpdateType::DocumentsPartial { .. } => "DocumentsPartial",
+            UpdateType::DocumentsEmbeddings { .. } => "DocumentsEmbeddings",
+            UpdateType::DocumentsImport { .. } => "DocumentsImport",
+            UpdateType::DocumentPatch => "DocumentPatch",
+            UpdateType::DocumentsDeletion { .. } => "DocumentsDeletion",
+            UpdateType::Settings { .. } => "Settings",
+            UpdateType::SynonymsEdit { .. } => "SynonymsEdit",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -128,6 +230,23 @@ pub struct ProcessedUpdateResult {
     pub update_type: UpdateType,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    /// The values that were changed to be applied, e.g. the synonyms merged once lowercased.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// The documents left out of the addition, with the `skip` document error strategy.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_documents: Vec<SkippedDocument>,
+    /// The number of documents left out, only the first ones are listed in `skipped_documents`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped_documents_count: Option<usize>,
+    /// The primary key detected in the documents and given to the index by the update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_primary_key: Option<String>,
+    /// The ids of the documents added, replaced or deleted, unknown when all of them could have changed.
+    #[serde(skip)]
+    pub changed_documents: Option<Vec<String>>,
     pub duration: f64, // in seconds
     pub enqueued_at: DateTime<Utc>,
     pub processed_at: DateTime<Utc>,
@@ -140,6 +259,12 @@ pub struct EnqueuedUpdateResult {
     #[serde(rename = "type")]
     pub update_type: UpdateType,
     pub enqueued_at: DateTime<Utc>,
+    /// The primary key detected in the documents, given to the index by the update if it still has none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_primary_key: Option<String>,
+    /// The progress of the reindexing, while the update is being processed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<UpdateDetails>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -159,6 +284,89 @@ pub enum UpdateStatus {
     },
 }
 
+/// A page of the updates of an index, ordered by id.
+#[derive(Debug, Clone)]
+pub struct UpdatesStatusPage {
+    pub updates: Vec<UpdateStatus>,
+    /// The id of the first update of the next page, if there is one.
+    pub next: Option<u64>,
+}
+
+/// Returns the status of at most `limit` updates accepted by the filter, starting from the
+/// `from` update id, by reading the processed and then the enqueued updates from this id onward.
+pub fn updates_status_from<F>(
+    update_reader: &heed::RoTxn<UpdateT>,
+    updates_store: store::Updates,
+    updates_results_store: store::UpdatesResults,
+    from: u64,
+    limit: usize,
+    filter: F,
+) -> MResult<UpdatesStatusPage>
+where F: Fn(&UpdateStatus) -> bool,
+{
+    let mut updates = Vec::with_capacity(limit);
+    let mut next_enqueued_id = from;
+
+    for result in updates_results_store.updates_results_from(update_reader, from)? {
+        let (update_id, result) = result?;
+        next_enqueued_id = update_id + 1;
+
+        let status = if result.error.is_some() {
+            UpdateStatus::Failed { content: result }
+        } else {
+            UpdateStatus::Processed { content: result }
+        };
+
+        if filter(&status) {
+            if updates.len() == limit {
+                return Ok(UpdatesStatusPage { updates, next: Some(update_id) });
+            }
+            updates.push(status);
+        }
+    }
+
+    for result in updates_store.updates_from(update_reader, next_enqueued_id)? {
+        let (update_id, update) = result?;
+
+        let status = UpdateStatus::Enqueued {
+            content: EnqueuedUpdateResult {
+                update_id,
+                update_type: update.data.update_type(),
+                enqueued_at: update.enqueued_at,
+                detected_primary_key: update.detected_primary_key,
+                details: None,
+            },
+        };
+
+        if filter(&status) {
+            if updates.len() == limit {
+                return Ok(UpdatesStatusPage { updates, next: Some(update_id) });
+            }
+            updates.push(status);
+        }
+    }
+
+    Ok(UpdatesStatusPage { updates, next: None })
+}
+
+impl UpdateStatus {
+    /// Returns the name of the status, as it is serialized.
+    pub fn name(&self) -> &'static str {
+        match self {
+            UpdateStatus::Enqueued { .. } => "enqueued",
+            UpdateStatus::Failed { .. } => "failed",
+            UpdateStatus::Processed { .. } => "processed",
+        }
+    }
+
+    pub fn update_type(&self) -> &UpdateType {
+        match self {
+            UpdateStatus::Enqueued { content } => &content.update_type,
+            UpdateStatus::Failed { content } | UpdateStatus::Processed { content } => &content.update_type,
+        }
+    }
+}
+
 pub fn update_status(
     update_reader: &heed::RoTxn<UpdateT>,
     updates_store: store::Updates,
@@ -179,6 +387,8 @@ pub fn update_status(
                     update_id,
                     update_type: update.data.update_type(),
                     enqueued_at: update.enqueued_at,
+                    detected_primary_key: update.detected_primary_key,
+                    details: None,
                 },
             })),
             None => Ok(None),
@@ -210,9 +420,15 @@ pub fn update_task<'a, 'b>(
     update: Update,
 ) -> MResult<ProcessedUpdateResult> {
     debug!("Processing update number {}", update_id);
+    index.update_progress.start(update_id);
 
-    let Update { enqueued_at, data } = update;
+    let Update { enqueued_at, data, document_error_strategy, detected_primary_key } = update;
 
+    let mut warnings = Vec::new();
+    let mut skipped_documents = Vec::new();
+    let mut skipped_documents_count = None;
+    let mut changed_documents = None;
+    let mut primary_key_set = None;
     let (update_type, result, duration) = match data {
         UpdateData::ClearAll => {
             let start = Instant::now();
@@ -237,7 +453,14 @@ pub fn update_task<'a, 'b>(
                 number: documents.len(),
             };
 
-            let result = apply_documents_addition(writer, index, documents);
+            let result = apply_detected_primary_key(writer, index, detected_primary_key, &mut primary_key_set)
+                .and_then(|()| apply_documents_addition(writer, index, documents, document_error_strategy))
+                .map(|addition| {
+                    warnings = addition.warnings;
+                    skipped_documents = addition.skipped_documents;
+                    skipped_documents_count = Some(addition.skipped_documents_count).filter(|count| *count > 0);
+                    changed_documents = Some(addition.documents_ids);
+                });
 
             (update_type, result, start.elapsed())
         }
@@ -248,7 +471,59 @@ pub fn update_task<'a, 'b>(
                 number: documents.len(),
             };
 
-            let result = apply_documents_partial_addition(writer, index, documents);
+            let result = apply_detected_primary_key(writer, index, detected_primary_key, &mut primary_key_set)
+                .and_then(|()| apply_documents_partial_addition(writer, index, documents, document_error_strategy))
+                .map(|addition| {
+                    warnings = addition.warnings;
+                    skipped_documents = addition.skipped_documents;
+                    skipped_documents_count = Some(addition.skipped_documents_count).filter(|count| *count > 0);
+                    changed_documents = Some(addition.documents_ids);
+                });
+
+            (update_type, result, start.elapsed())
+        }
+        UpdateData::DocumentsEmbeddings(documents) => {
+            let start = Instant::now();
+
+            let update_type = UpdateType::DocumentsEmbeddings {
+                number: documents.len(),
+            };
+
+            let result = apply_documents_embeddings(writer, index, documents)
+                .map(|addition| changed_documents = Some(addition.documents_ids));
+
+            (update_type, result, start.elapsed())
+        }
+        UpdateData::DocumentsImport(mut import) => {
+            let start = Instant::now();
+
+            let (update_type, result) = match import.take_documents() {
+                Ok((documents, detected_primary_key)) => {
+                    let update_type = UpdateType::DocumentsImport { number: Some(documents.len()) };
+                    let result = apply_detected_primary_key(writer, index, detected_primary_key, &mut primary_key_set)
+                        .and_then(|()| apply_documents_addition(writer, index, documents, document_error_strategy))
+                        .map(|addition| {
+                            warnings = addition.warnings;
+                            skipped_documents = addition.skipped_documents;
+                            skipped_documents_count = Some(addition.skipped_documents_count).filter(|count| *count > 0);
+                            changed_documents = Some(addition.documents_ids);
+                        });
+                    (update_type, result)
+                }
+                Err(e) => (UpdateType::DocumentsImport { number: None }, Err(Error::ImportFailed(e))),
+            };
+
+            (update_type, result, start.elapsed())
+        }
+        UpdateData::DocumentPatch(patch) => {
+            let start = Instant::now();
+
+            let update_type = UpdateType::DocumentPatch;
+            let result = apply_document_patch(writer, index, patch)
+                .map(|addition| {
+                    warnings = addition.warnings;
+                    changed_documents = Some(addition.documents_ids);
+                });
 
             (update_type, result, start.elapsed())
         }
@@ -259,6 +534,7 @@ pub fn update_task<'a, 'b>(
                 number: documents.len(),
             };
 
+            changed_documents = Some(documents.clone());
             let result = apply_documents_deletion(writer, index, documents);
 
             (update_type, result, start.elapsed())
@@ -267,14 +543,26 @@ pub fn update_task<'a, 'b>(
             let start = Instant::now();
 
             let update_type = UpdateType::Settings {
-                settings: settings.clone(),
+                settings: settings.without_secrets(),
             };
 
             let result = apply_settings_update(
                 writer,
                 index,
                 settings,
-            );
+            ).map(|settings_warnings| warnings = settings_warnings);
+
+            (update_type, result, start.elapsed())
+        }
+        UpdateData::SynonymsEdit(edit) => {
+            let start = Instant::now();
+
+            let update_type = UpdateType::SynonymsEdit {
+                edit: edit.clone(),
+            };
+
+            let result = apply_synonyms_edit(writer, index, edit)
+                .map(|edit_warnings| warnings = edit_warnings);
 
             (update_type, result, start.elapsed())
         }
@@ -285,10 +573,23 @@ pub fn update_task<'a, 'b>(
         update_id, update_type, result
     );
 
+    index.update_progress.finish();
+
+    // written in the same transaction as the update for it not to be applied twice
+    if result.is_ok() {
+        index.main.put_last_applied_update_id(writer, update_id)?;
+    }
+
     let status = ProcessedUpdateResult {
         update_id,
         update_type,
-        error: result.map_err(|e| e.to_string()).err(),
+        error: result.as_ref().map_err(|e| e.to_string()).err(),
+        error_code: result.as_ref().map_err(|e| e.error_name()).err(),
+        warnings,
+        skipped_documents,
+        skipped_documents_count,
+        detected_primary_key: primary_key_set.filter(|_| result.is_ok()),
+        changed_documents,
         duration: duration.as_secs_f64(),
         enqueued_at,
         processed_at: Utc::now(),
@@ -297,6 +598,48 @@ pub fn update_task<'a, 'b>(
     Ok(status)
 }
 
+/// Gives the primary key detected in the documents to the index when it still has none,
+/// it is reported in the result of the update once it is set.
+fn apply_detected_primary_key(
+    writer: &mut heed::RwTxn<MainT>,
+    index: &store::Index,
+    primary_key: Option<String>,
+    primary_key_set: &mut Option<String>,
+) -> MResult<()> {
+    let primary_key = match primary_key {
+        Some(primary_key) => primary_key,
+        None => return Ok(()),
+    };
+
+    let mut schema = index.main.schema(writer)?.ok_or(Error::SchemaMissing)?;
+    if schema.primary_key().is_none() {
+        schema.set_primary_key(&primary_key)?;
+        index.main.put_schema(writer, &schema)?;
+        *primary_key_set = Some(primary_key);
+    }
+
+    Ok(())
+}
+
+/// Returns the result of an update that was already applied to the main store
+/// but whose result was not written, the process having stopped in between.
+pub fn applied_update_result(update_id: u64, update: Update) -> ProcessedUpdateResult {
+    ProcessedUpdateResult {
+        update_id,
+        update_type: update.data.update_type(),
+        error: None,
+        error_code: None,
+        warnings: Vec::new(),
+        skipped_documents: Vec::new(),
+        skipped_documents_count: None,
+        detected_primary_key: None,
+        changed_documents: None,
+        duration: 0.0,
+        enqueued_at: update.enqueued_at,
+        processed_at: Utc::now(),
+    }
+}
+
 fn compute_short_prefixes<A>(
     writer: &mut heed::RwTxn<MainT>,
     words_fst: &fst::Set<A>,
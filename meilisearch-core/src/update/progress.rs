@@ -0,0 +1,96 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+/// The number of documents reindexed between two reports of the progress.
+pub const PROGRESS_STEP: usize = 1000;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDetails {
+    pub processed_documents: usize,
+    pub total_documents: usize,
+    /// The number of bytes of the file of an import downloaded so far.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downloaded_bytes: Option<u64>,
+    /// The size of the file of an import, when its server gives it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+}
+
+/// The progress of the update being processed, shared by all the clones of an index
+/// for the status of the update to be known while it is being processed.
+#[derive(Debug, Default)]
+pub struct UpdateProgress {
+    current: Mutex<Option<(u64, Option<UpdateDetails>)>>,
+}
+
+impl UpdateProgress {
+    /// Starts reporting the progress of the update, the progress of the download of an import
+    /// is kept once its documents are being indexed.
+    pub(crate) fn start(&self, update_id: u64) {
+        let mut current = self.current.lock().unwrap();
+        match *current {
+            Some((id, _)) if id == update_id => (),
+            _ => *current = Some((update_id, None)),
+        }
+    }
+
+    pub(crate) fn report(&self, processed_documents: usize, total_documents: usize) {
+        if let Some((_, details)) = self.current.lock().unwrap().as_mut() {
+            let details = details.get_or_insert_with(UpdateDetails::default);
+            details.processed_documents = processed_documents;
+            details.total_documents = total_documents;
+        }
+    }
+
+    /// Reports the progress of the download of the file of the import being processed.
+    pub fn report_download(&self, downloaded_bytes: u64, total_bytes: Option<u64>) {
+        if let Some((_, details)) = self.current.lock().unwrap().as_mut() {
+            let details = details.get_or_insert_with(UpdateDetails::default);
+            details.downloaded_bytes = Some(downloaded_bytes);
+            details.total_bytes = total_bytes;
+        }
+    }
+
+    pub(crate) fn finish(&self) {
+        *self.current.lock().unwrap() = None;
+    }
+
+    /// Returns the details of the update if it is the one being processed.
+    pub fn details(&self, update_id: u64) -> Option<UpdateDetails> {
+        match *self.current.lock().unwrap() {
+            Some((id, details)) if id == update_id => details,
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn details_of_the_processed_update() {
+        let progress = UpdateProgress::default();
+        progress.report(10, 100);
+        assert_eq!(progress.details(0), None);
+
+        progress.start(3);
+        assert_eq!(progress.details(3), None);
+
+        progress.report(1000, 2500);
+        let details = UpdateDetails { processed_documents: 1000, total_documents: 2500, ..UpdateDetails::default() };
+        assert_eq!(progress.details(3), Some(details));
+        assert_eq!(progress.details(2), None);
+
+        // the download of an import is still reported once its documents are indexed
+        progress.report_download(512, Some(1024));
+        progress.start(3);
+        let details = UpdateDetails { downloaded_bytes: Some(512), total_bytes: Some(1024), ..details };
+        assert_eq!(progress.details(3), Some(details));
+
+        progress.finish();
+        assert_eq!(progress.details(3), None);
+    }
+}
@@ -9,6 +9,7 @@ use crate::database::{MainT, UpdateT};
 use crate::settings::{UpdateState, SettingsUpdate, RankingRule};
 use crate::update::documents_addition::reindex_all_documents;
 use crate::update::{next_update_id, Update};
+use crate::automaton::normalize_synonym;
 use crate::{store, MResult, Error};
 
 pub fn push_settings_update(
@@ -25,12 +26,14 @@ pub fn push_settings_update(
     Ok(last_update_id)
 }
 
+/// Applies the settings and returns the warnings about the values that were changed to be stored.
 pub fn apply_settings_update(
     writer: &mut heed::RwTxn<MainT>,
     index: &store::Index,
     settings: SettingsUpdate,
-) -> MResult<()> {
+) -> MResult<Vec<String>> {
     let mut must_reindex = false;
+    let mut warnings = Vec::new();
 
     let mut schema = match index.main.schema(writer)? {
         Some(schema) => schema,
@@ -44,7 +47,7 @@ pub fn apply_settings_update(
 
     match settings.ranking_rules {
         UpdateState::Update(v) => {
-            let ranked_field: Vec<&str> = v.iter().filter_map(RankingRule::field).collect();
+            let ranked_field: Vec<String> = v.iter().flat_map(RankingRule::fields).collect();
             schema.update_ranked(&ranked_field)?;
             for name in ranked_field {
                 if schema.accept_new_fields() {
@@ -63,6 +66,29 @@ pub fn apply_settings_update(
         UpdateState::Nothing => (),
     }
 
+    // the image hashes are read from the ranked map, the field stays ranked whatever the ranking rules are
+    match settings.image_hash_field {
+        UpdateState::Update(v) => {
+            schema.set_ranked(&v)?;
+            index.main.put_image_hash_field(writer, &v)?;
+            must_reindex = true;
+        },
+        UpdateState::Clear => {
+            if let Some(field) = index.main.image_hash_field(writer)? {
+                let ranking_rules = index.main.ranking_rules(writer)?.unwrap_or_default();
+                if !ranking_rules.iter().flat_map(RankingRule::fields).any(|name| name == field) {
+                    schema.remove_ranked(&field);
+                }
+                index.main.delete_image_hash_field(writer)?;
+            }
+        },
+        UpdateState::Nothing => {
+            if let Some(field) = index.main.image_hash_field(writer)? {
+                schema.set_ranked(&field)?;
+            }
+        },
+    }
+
     match settings.distinct_attribute {
         UpdateState::Update(v) => {
             index.main.put_distinct_attribute(writer, &v)?;
@@ -73,6 +99,128 @@ pub fn apply_settings_update(
         UpdateState::Nothing => (),
     }
 
+    match settings.max_fields_count {
+        UpdateState::Update(v) => {
+            index.main.put_max_fields_count(writer, v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_max_fields_count(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.max_index_size {
+        UpdateState::Update(v) => {
+            index.main.put_max_index_size(writer, v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_max_index_size(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.phonetic_matching {
+        UpdateState::Update(v) => {
+            index.main.put_phonetic_matching(writer, v)?;
+            must_reindex = true;
+        },
+        UpdateState::Clear => {
+            index.main.delete_phonetic_matching(writer)?;
+            must_reindex = true;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.phonetic_attributes {
+        UpdateState::Update(v) => {
+            index.main.put_phonetic_attributes(writer, &v)?;
+            must_reindex = true;
+        },
+        UpdateState::Clear => {
+            index.main.delete_phonetic_attributes(writer)?;
+            must_reindex = true;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.stemming_language {
+        UpdateState::Update(v) => {
+            index.main.put_stemming_language(writer, &v)?;
+            must_reindex = true;
+        },
+        UpdateState::Clear => {
+            index.main.delete_stemming_language(writer)?;
+            must_reindex = true;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.language_detection_field {
+        UpdateState::Update(v) => {
+            index.main.put_language_detection_field(writer, &v)?;
+            must_reindex = true;
+        },
+        UpdateState::Clear => {
+            index.main.delete_language_detection_field(writer)?;
+            must_reindex = true;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.decompound_tokens {
+        UpdateState::Update(v) => {
+            index.main.put_decompound_tokens(writer, v)?;
+            must_reindex = true;
+        },
+        UpdateState::Clear => {
+            index.main.delete_decompound_tokens(writer)?;
+            must_reindex = true;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    // the proximity settings are only used when ranking, the documents are not reindexed
+    match settings.proximity_precision {
+        UpdateState::Update(v) => {
+            index.main.put_proximity_precision(writer, v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_proximity_precision(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.proximity_window {
+        UpdateState::Update(v) => {
+            index.main.put_proximity_window(writer, v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_proximity_window(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    // the pinned and demoted documents are placed after the ranking, the documents are not reindexed
+    match settings.pinned_hits {
+        UpdateState::Update(v) => {
+            index.main.put_pinned_hits(writer, &v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_pinned_hits(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.demoted_documents {
+        UpdateState::Update(v) => {
+            index.main.put_demoted_documents(writer, &v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_demoted_documents(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
     match settings.accept_new_fields {
         UpdateState::Update(v) => {
             schema.set_accept_new_fields(v);
@@ -114,6 +262,18 @@ pub fn apply_settings_update(
         UpdateState::Nothing => (),
     }
 
+    // the embeddings are stored in the documents but they are neither searched nor displayed
+    let embedder = match &settings.embedder {
+        UpdateState::Update(embedder) => Some(embedder.clone()),
+        UpdateState::Clear => None,
+        UpdateState::Nothing => index.main.embedder(writer)?,
+    };
+    if let Some(embedder) = embedder {
+        if hide_embedding_field(&mut schema, &embedder.document_embedding_field)? {
+            must_reindex = true;
+        }
+    }
+
     index.main.put_schema(writer, &schema)?;
 
     match settings.stop_words {
@@ -131,16 +291,191 @@ pub fn apply_settings_update(
     }
 
     match settings.synonyms {
-        UpdateState::Update(synonyms) => apply_synonyms_update(writer, index, synonyms)?,
+        UpdateState::Update(synonyms) => {
+            let synonyms = normalize_synonyms(synonyms, "synonyms", &mut warnings);
+            apply_synonyms_update(writer, index, synonyms)?;
+        },
         UpdateState::Clear => apply_synonyms_update(writer, index, BTreeMap::new())?,
         UpdateState::Nothing => (),
     }
 
+    match settings.one_way_synonyms {
+        UpdateState::Update(v) => {
+            let v = normalize_synonyms(v, "oneWaySynonyms", &mut warnings);
+            index.main.put_one_way_synonyms(writer, &v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_one_way_synonyms(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.equivalent_synonyms {
+        UpdateState::Update(v) => {
+            let v = normalize_equivalent_synonyms(v, &mut warnings);
+            index.main.put_equivalent_synonyms(writer, &v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_equivalent_synonyms(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.hierarchical_facets {
+        UpdateState::Update(v) => {
+            index.main.put_hierarchical_facets(writer, &v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_hierarchical_facets(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    // only the documents added afterwards are transformed
+    match settings.transformer {
+        UpdateState::Update(v) => {
+            index.main.put_transformer(writer, &v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_transformer(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.webhook_url {
+        UpdateState::Update(v) => {
+            index.main.put_webhook_url(writer, &v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_webhook_url(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.cross_field_boost {
+        UpdateState::Update(v) => {
+            index.main.put_cross_field_boost(writer, v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_cross_field_boost(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.document_versions {
+        UpdateState::Update(v) => {
+            index.main.put_document_versions(writer, v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_document_versions(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.referenced_documents {
+        UpdateState::Update(v) => {
+            index.main.put_referenced_documents(writer, &v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_referenced_documents(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.embedder {
+        UpdateState::Update(v) => {
+            index.main.put_embedder(writer, &v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_embedder(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
+    match settings.embedding_strategy {
+        UpdateState::Update(v) => {
+            index.main.put_embedding_strategy(writer, v)?;
+        },
+        UpdateState::Clear => {
+            index.main.delete_embedding_strategy(writer)?;
+        },
+        UpdateState::Nothing => (),
+    }
+
     if must_reindex {
         reindex_all_documents(writer, index)?;
     }
 
-    Ok(())
+    Ok(warnings)
+}
+
+/// Removes the field holding the embeddings from the searchable and displayed attributes,
+/// it is added to the schema for the documents not to make it searchable. Returns whether
+/// it was searchable, the documents must then be reindexed.
+fn hide_embedding_field(schema: &mut Schema, path: &str) -> MResult<bool> {
+    let root = path.split('.').next().unwrap_or(path);
+    let field_id = schema.insert(root)?;
+    let was_indexed = schema.is_indexed(field_id).is_some();
+    schema.remove_indexed(root);
+    schema.remove_displayed(root);
+    Ok(was_indexed)
+}
+
+/// Normalizes the words like the query words and folds the case of their synonyms, then removes the
+/// duplicated synonyms. The synonyms of the words that are the same once normalized are merged and a warning is emitted.
+pub fn normalize_synonyms(
+    synonyms: BTreeMap<String, Vec<String>>,
+    setting: &str,
+    warnings: &mut Vec<String>,
+) -> BTreeMap<String, Vec<String>> {
+    let mut normalized: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut originals: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for (word, alternatives) in synonyms {
+        let key = normalize_synonym(&word);
+        let entry = normalized.entry(key.clone()).or_default();
+        for alternative in alternatives {
+            let alternative = caseless::default_case_fold_str(&alternative);
+            if !entry.contains(&alternative) {
+                entry.push(alternative);
+            }
+        }
+        originals.entry(key).or_default().push(word);
+    }
+
+    for (key, words) in originals {
+        if words.len() > 1 {
+            let words: Vec<_> = words.iter().map(|word| format!("`{}`", word)).collect();
+            warnings.push(format!("{}: the synonyms of {} were merged under `{}`", setting, words.join(", "), key));
+        }
+    }
+
+    normalized
+}
+
+/// Folds the case of the words of the groups and removes the duplicated words, the groups
+/// left with a single word do not define any synonym, they are dropped and a warning is emitted.
+pub fn normalize_equivalent_synonyms(groups: Vec<Vec<String>>, warnings: &mut Vec<String>) -> Vec<Vec<String>> {
+    let mut normalized = Vec::with_capacity(groups.len());
+
+    for group in groups {
+        let mut words: Vec<String> = Vec::with_capacity(group.len());
+        for word in &group {
+            let word = caseless::default_case_fold_str(word);
+            if !words.iter().any(|other| normalize_synonym(other) == normalize_synonym(&word)) {
+                words.push(word);
+            }
+        }
+
+        if words.len() > 1 {
+            normalized.push(words);
+        } else {
+            let group: Vec<_> = group.iter().map(|word| format!("`{}`", word)).collect();
+            warnings.push(format!("equivalentSynonyms: the group of {} was dropped, its words are the same", group.join(", ")));
+        }
+    }
+
+    normalized
 }
 
 fn apply_attributes_for_faceting_update(
@@ -316,3 +651,54 @@ pub fn apply_synonyms_update(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synonyms_are_lowercased_and_merged() {
+        let mut synonyms = BTreeMap::new();
+        synonyms.insert("TV".to_string(), vec!["television".to_string(), "Television".to_string(), "TELEVISION".to_string()]);
+        synonyms.insert("tv".to_string(), vec!["telly".to_string()]);
+        synonyms.insert("NYC".to_string(), vec!["New York".to_string()]);
+
+        let mut warnings = Vec::new();
+        let normalized = normalize_synonyms(synonyms, "synonyms", &mut warnings);
+
+        let mut expected = BTreeMap::new();
+        expected.insert("nyc".to_string(), vec!["new york".to_string()]);
+        expected.insert("tv".to_string(), vec!["television".to_string(), "telly".to_string()]);
+        assert_eq!(normalized, expected);
+        assert_eq!(warnings, vec!["synonyms: the synonyms of `TV`, `tv` were merged under `tv`".to_string()]);
+    }
+
+    #[test]
+    fn synonyms_case_is_folded() {
+        let mut synonyms = BTreeMap::new();
+        synonyms.insert("Straße".to_string(), vec!["Road".to_string()]);
+        synonyms.insert("STRASSE".to_string(), vec!["ROAD".to_string(), "Weg".to_string()]);
+
+        let mut warnings = Vec::new();
+        let normalized = normalize_synonyms(synonyms, "synonyms", &mut warnings);
+
+        let mut expected = BTreeMap::new();
+        expected.insert("strasse".to_string(), vec!["road".to_string(), "weg".to_string()]);
+        assert_eq!(normalized, expected);
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn equivalent_synonyms_are_normalized() {
+        let groups = vec![
+            vec!["TV".to_string(), "Television".to_string(), "tv".to_string()],
+            vec!["Straße".to_string(), "STRASSE".to_string()],
+        ];
+
+        let mut warnings = Vec::new();
+        let normalized = normalize_equivalent_synonyms(groups, &mut warnings);
+
+        assert_eq!(normalized, vec![vec!["tv".to_string(), "television".to_string()]]);
+        assert_eq!(warnings, vec!["equivalentSynonyms: the group of `Straße`, `STRASSE` was dropped, its words are the same".to_string()]);
+    }
+}
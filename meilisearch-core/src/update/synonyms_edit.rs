@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use fst::{set::OpBuilder, SetBuilder};
+use heed::Result as ZResult;
+use sdset::SetBuf;
+use serde::{Deserialize, Serialize};
+
+use crate::database::{MainT, UpdateT};
+use crate::update::settings_update::normalize_synonyms;
+use crate::update::{next_update_id, Update};
+use crate::{store, MResult};
+
+/// A change to the synonyms of some words only, it is applied on the synonyms
+/// the index has when the update is processed, not when it is enqueued.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SynonymsEdit {
+    /// Adds the synonyms to the ones the words already have.
+    Merge(BTreeMap<String, Vec<String>>),
+    /// Removes the word and its synonyms.
+    DeleteWord(String),
+}
+
+pub fn push_synonyms_edit(
+    writer: &mut heed::RwTxn<UpdateT>,
+    updates_store: store::Updates,
+    updates_results_store: store::UpdatesResults,
+    edit: SynonymsEdit,
+) -> ZResult<u64> {
+    let last_update_id = next_update_id(writer, updates_store, updates_results_store)?;
+
+    let update = Update::synonyms_edit(edit);
+    updates_store.put_update(writer, last_update_id, &update)?;
+
+    Ok(last_update_id)
+}
+
+/// Applies the edit and returns the warnings about the synonyms that were merged or skipped.
+pub fn apply_synonyms_edit(
+    writer: &mut heed::RwTxn<MainT>,
+    index: &store::Index,
+    edit: SynonymsEdit,
+) -> MResult<Vec<String>> {
+    let mut warnings = Vec::new();
+
+    let synonyms_set = match edit {
+        SynonymsEdit::Merge(synonyms) => {
+            // the synonyms that are the same once normalized are duplicates too
+            let received: usize = synonyms.values().map(Vec::len).sum();
+            let synonyms = normalize_synonyms(synonyms, "synonyms", &mut warnings);
+
+            let mut imported = 0;
+            let mut words_builder = SetBuilder::memory();
+
+            for (word, alternatives) in synonyms {
+                let mut merged = index.synonyms.synonyms(writer, word.as_bytes())?;
+                for alternative in alternatives {
+                    if !merged.contains(&alternative) {
+                        merged.push(alternative);
+                        imported += 1;
+                    }
+                }
+
+                let alternatives = {
+                    let alternatives = SetBuf::from_dirty(merged);
+                    let mut alternatives_builder = SetBuilder::memory();
+                    alternatives_builder.extend_iter(alternatives)?;
+                    alternatives_builder.into_set()
+                };
+
+                index.synonyms.put_synonyms(writer, word.as_bytes(), &alternatives)?;
+                words_builder.insert(&word)?;
+            }
+
+            warnings.push(format!("synonyms: {} synonyms imported, {} duplicates skipped", imported, received - imported));
+
+            let delta_words = words_builder.into_set();
+            let synonyms_fst = index.main.synonyms_fst(writer)?;
+            let op = OpBuilder::new()
+                .add(&synonyms_fst)
+                .add(&delta_words)
+                .r#union();
+
+            let mut synonyms_builder = SetBuilder::memory();
+            synonyms_builder.extend_stream(op)?;
+            synonyms_builder.into_set()
+        }
+        SynonymsEdit::DeleteWord(word) => {
+            index.synonyms.del_synonyms(writer, word.as_bytes())?;
+
+            let mut words_builder = SetBuilder::memory();
+            words_builder.insert(&word)?;
+            let delta_words = words_builder.into_set();
+
+            let synonyms_fst = index.main.synonyms_fst(writer)?;
+            let op = OpBuilder::new()
+                .add(&synonyms_fst)
+                .add(&delta_words)
+                .difference();
+
+            let mut synonyms_builder = SetBuilder::memory();
+            synonyms_builder.extend_stream(op)?;
+            synonyms_builder.into_set()
+        }
+    };
+
+    index.main.put_synonyms_fst(writer, &synonyms_set)?;
+
+    Ok(warnings)
+}
@@ -0,0 +1,84 @@
+use std::ptr;
+
+use zerocopy::AsBytes;
+
+use crate::database::MainT;
+use crate::{store, MResult};
+
+/// The size of a memory page, reading one byte of each is enough for the OS to load it.
+const PAGE_SIZE: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarmStrategy {
+    /// Reads every postings list and every stored document field.
+    Full,
+    /// Reads only the postings lists of the terms that appear in the most documents.
+    FrequentTerms(usize),
+}
+
+/// Sequentially reads the index for its pages to be loaded in the page cache
+/// of the OS, returns the number of bytes that were read.
+pub fn warm(reader: &heed::RoTxn<MainT>, index: &store::Index, strategy: WarmStrategy) -> MResult<u64> {
+    let mut bytes = 0;
+
+    match strategy {
+        WarmStrategy::Full => {
+            for result in index.postings_lists.iter(reader)? {
+                let (_, postings) = result?;
+                bytes += touch_pages(postings.docids.as_bytes());
+                bytes += touch_pages(postings.matches.as_bytes());
+            }
+
+            for result in index.documents_fields.iter(reader)? {
+                let (_, value) = result?;
+                bytes += touch_pages(value);
+            }
+        }
+        WarmStrategy::FrequentTerms(count) => {
+            // only the number of documents written at the start of the postings lists
+            // is read, the lists are not decoded as the unaligned ones would be copied
+            let mut terms = Vec::new();
+            for result in index.postings_lists.documents_counts(reader)? {
+                let (word, count) = result?;
+                terms.push((count, word.to_vec()));
+            }
+
+            terms.sort_unstable_by(|(a, _), (b, _)| b.cmp(a));
+
+            for (_, word) in terms.iter().take(count) {
+                if let Some(postings) = index.postings_lists.postings_list(reader, word)? {
+                    bytes += touch_pages(postings.docids.as_bytes());
+                    bytes += touch_pages(postings.matches.as_bytes());
+                }
+            }
+        }
+    }
+
+    Ok(bytes)
+}
+
+fn touch_pages(bytes: &[u8]) -> u64 {
+    for byte in bytes.iter().step_by(PAGE_SIZE) {
+        // a volatile read is never optimized out even if its value is not used
+        unsafe { ptr::read_volatile(byte) };
+    }
+    bytes.len() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::database::{Database, DatabaseOptions};
+
+    #[test]
+    fn warm_an_empty_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let database = Database::open_or_create(dir.path(), DatabaseOptions::default()).unwrap();
+        let index = database.create_index("test").unwrap();
+
+        let reader = database.main_read_txn().unwrap();
+        assert_eq!(warm(&reader, &index, WarmStrategy::Full).unwrap(), 0);
+        assert_eq!(warm(&reader, &index, WarmStrategy::FrequentTerms(10)).unwrap(), 0);
+    }
+}
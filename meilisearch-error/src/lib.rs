@@ -56,16 +56,29 @@ pub enum Code {
     InvalidState,
     MissingPrimaryKey,
     PrimaryKeyAlreadyPresent,
+    SchemaMissing,
+    WordIndexMissing,
 
     MaxFieldsLimitExceeded,
+    IndexSizeLimitExceeded,
     MissingDocumentId,
+    InvalidDocumentId,
+    InvalidDocument,
+    InvalidDocumentFacet,
+    ImportFailed,
 
     Facet,
-    Filter,
+    FacetAttributeNotFound,
+    FacetAttributeNotSet,
+    FacetsNotSet,
+    FilterInvalidSyntax,
+    SortAttributeNotSortable,
 
     BadParameter,
     BadRequest,
     DocumentNotFound,
+    IdempotencyKeyInUse,
+    IdempotencyKeyReused,
     Internal,
     InvalidToken,
     Maintenance,
@@ -73,8 +86,11 @@ pub enum Code {
     MissingHeader,
     NotFound,
     PayloadTooLarge,
+    ReadOnlyMode,
+    RequestTimeout,
     RetrieveDocument,
     SearchDocuments,
+    TooManyRequests,
     UnsupportedMediaType,
 }
 
@@ -93,19 +109,32 @@ impl Code {
 
             // invalid state error
             InvalidState => ErrCode::internal("invalid_state", StatusCode::INTERNAL_SERVER_ERROR),
-            MissingPrimaryKey => ErrCode::internal("missing_primary_key", StatusCode::INTERNAL_SERVER_ERROR),
+            MissingPrimaryKey => ErrCode::invalid("missing_primary_key", StatusCode::BAD_REQUEST),
             PrimaryKeyAlreadyPresent => ErrCode::internal("primary_key_already_present", StatusCode::INTERNAL_SERVER_ERROR),
+            SchemaMissing => ErrCode::internal("schema_missing", StatusCode::INTERNAL_SERVER_ERROR),
+            WordIndexMissing => ErrCode::internal("word_index_missing", StatusCode::INTERNAL_SERVER_ERROR),
 
             // invalid document
             MaxFieldsLimitExceeded => ErrCode::invalid("max_field_limit_exceeded", StatusCode::BAD_REQUEST),
+            IndexSizeLimitExceeded => ErrCode::invalid("index_size_limit_exceeded", StatusCode::BAD_REQUEST),
             MissingDocumentId => ErrCode::invalid("missing_document_id", StatusCode::BAD_REQUEST),
+            InvalidDocumentId => ErrCode::invalid("invalid_document_id", StatusCode::BAD_REQUEST),
+            InvalidDocument => ErrCode::invalid("invalid_document", StatusCode::BAD_REQUEST),
+            InvalidDocumentFacet => ErrCode::invalid("invalid_document_facet", StatusCode::BAD_REQUEST),
+            ImportFailed => ErrCode::invalid("import_failed", StatusCode::BAD_REQUEST),
 
             Facet => ErrCode::invalid("invalid_facet", StatusCode::BAD_REQUEST),
-            Filter => ErrCode::invalid("invalid_filter", StatusCode::BAD_REQUEST),
+            FacetAttributeNotFound => ErrCode::invalid("facet_attribute_not_found", StatusCode::BAD_REQUEST),
+            FacetAttributeNotSet => ErrCode::invalid("facet_attribute_not_set", StatusCode::BAD_REQUEST),
+            FacetsNotSet => ErrCode::invalid("facets_not_set", StatusCode::BAD_REQUEST),
+            FilterInvalidSyntax => ErrCode::invalid("invalid_filter", StatusCode::BAD_REQUEST),
+            SortAttributeNotSortable => ErrCode::invalid("sort_attribute_not_sortable", StatusCode::BAD_REQUEST),
 
             BadParameter => ErrCode::invalid("bad_parameter", StatusCode::BAD_REQUEST),
             BadRequest => ErrCode::invalid("bad_request", StatusCode::BAD_REQUEST),
-            DocumentNotFound => ErrCode::internal("document_not_found", StatusCode::NOT_FOUND),
+            DocumentNotFound => ErrCode::invalid("document_not_found", StatusCode::NOT_FOUND),
+            IdempotencyKeyInUse => ErrCode::invalid("idempotency_key_in_use", StatusCode::CONFLICT),
+            IdempotencyKeyReused => ErrCode::invalid("idempotency_key_reused", StatusCode::CONFLICT),
             Internal => ErrCode::internal("internal", StatusCode::INTERNAL_SERVER_ERROR),
             InvalidToken => ErrCode::authentication("invalid_token", StatusCode::UNAUTHORIZED),
             Maintenance =>  ErrCode::internal("maintenance", StatusCode::SERVICE_UNAVAILABLE),
@@ -113,8 +142,11 @@ impl Code {
             MissingHeader => ErrCode::authentication("missing_header", StatusCode::UNAUTHORIZED),
             NotFound => ErrCode::invalid("not_found", StatusCode::NOT_FOUND),
             PayloadTooLarge => ErrCode::invalid("payload_too_large", StatusCode::PAYLOAD_TOO_LARGE),
+            ReadOnlyMode => ErrCode::invalid("read_only_mode", StatusCode::METHOD_NOT_ALLOWED),
+            RequestTimeout => ErrCode::invalid("request_timeout", StatusCode::REQUEST_TIMEOUT),
             RetrieveDocument => ErrCode::internal("retrieve_document", StatusCode::BAD_REQUEST),
             SearchDocuments => ErrCode::internal("search_error", StatusCode::BAD_REQUEST),
+            TooManyRequests => ErrCode::invalid("too_many_requests", StatusCode::TOO_MANY_REQUESTS),
             UnsupportedMediaType => ErrCode::invalid("unsupported_media_type", StatusCode::UNSUPPORTED_MEDIA_TYPE),
         }
     }
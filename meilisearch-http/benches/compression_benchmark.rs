@@ -0,0 +1,107 @@
+use std::fs::File;
+
+use actix_http::Request;
+use actix_rt::SystemRunner;
+use actix_service::Service;
+use actix_web::body::MessageBody;
+use actix_web::dev::ServiceResponse;
+use actix_web::http::header;
+use actix_web::{middleware, test, web, App, HttpResponse};
+use bytes::Bytes;
+use meilisearch_http::helpers::CompressResponse;
+use serde_json::Value;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// The number of documents of the response, the movies dataset is repeated with new ids.
+const DOCUMENTS_COUNT: usize = 1_000_000;
+
+/// The threshold of the server by default, 1Mb.
+const THRESHOLD: usize = 1024 * 1024;
+
+fn documents_response() -> Bytes {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../datasets/movies/movies.json");
+    let movies_file = File::open(path).expect("find movies");
+    let movies: Vec<Value> = serde_json::from_reader(movies_file).unwrap();
+
+    let documents: Vec<_> = movies
+        .iter()
+        .cycle()
+        .take(DOCUMENTS_COUNT)
+        .enumerate()
+        .map(|(id, movie)| {
+            let mut document = movie.clone();
+            document["id"] = Value::from(id.to_string());
+            document
+        })
+        .collect();
+
+    Bytes::from(serde_json::to_vec(&documents).unwrap())
+}
+
+/// Gets the documents with the encoding, the app is given back as the future run by the system must own it.
+fn get_documents<S, B>(system: &mut SystemRunner, mut app: S, encoding: &'static str) -> (S, Bytes)
+where
+    S: Service<Request = Request, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    let req = test::TestRequest::get()
+        .uri("/documents")
+        .header(header::ACCEPT_ENCODING, encoding)
+        .to_request();
+
+    system.block_on(async move {
+        let res = test::call_service(&mut app, req).await;
+        let body = test::read_body(res).await;
+        (app, body)
+    })
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let body = documents_response();
+
+    let mut system = actix_rt::System::new("compression_benchmark");
+    let route_body = body.clone();
+    let mut app = Some(system.block_on(test::init_service(
+        App::new()
+            .route("/documents", web::get().to(move || {
+                let body = route_body.clone();
+                async move { HttpResponse::Ok().content_type("application/json").body(body) }
+            }))
+            .wrap(CompressResponse::new(THRESHOLD))
+            .wrap(middleware::Compress::default()),
+    )));
+
+    let encodings = ["identity", "gzip", "br"];
+
+    // the bandwidth reduction, the time spent compressing is measured below
+    for encoding in &encodings {
+        let (service, response) = get_documents(&mut system, app.take().unwrap(), encoding);
+        let size = response.len();
+        app = Some(service);
+        println!(
+            "{} documents with {}: {} bytes, {:.1}% of the {} bytes of the response",
+            DOCUMENTS_COUNT,
+            encoding,
+            size,
+            size as f64 * 100.0 / body.len() as f64,
+            body.len(),
+        );
+    }
+
+    let mut group = c.benchmark_group("compression");
+    group.sample_size(10);
+    for encoding in &encodings {
+        group.bench_with_input(BenchmarkId::new("documents", encoding), encoding, |b, encoding| {
+            b.iter(|| {
+                let (service, response) = get_documents(&mut system, app.take().unwrap(), encoding);
+                app = Some(service);
+                response
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
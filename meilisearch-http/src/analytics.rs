@@ -13,6 +13,29 @@ use crate::Opt;
 
 const AMPLITUDE_API_KEY: &str = "f7fba398780e06d8fe6666a9be7e3d47";
 
+/// The endpoint the analytics events are sent to.
+pub const AMPLITUDE_ENDPOINT: &str = "https://api.amplitude.com/httpapi";
+
+/// The number of seconds between two analytics events.
+pub const EVENTS_INTERVAL_SECS: u64 = 3600; // one hour
+
+/// Every field sent with an analytics event along with what it contains,
+/// neither the queries, the documents nor the settings are ever sent.
+pub const COLLECTED_DATA: [(&str, &str); 12] = [
+    ("user_id", "a hash of the username, the hostname and the platform of the machine"),
+    ("device_id", "the platform of the machine, e.g. linux"),
+    ("event_type", "always runtime_tick"),
+    ("time", "the timestamp at which the event is sent"),
+    ("app_version", "the version of MeiliSearch"),
+    ("user_properties.env", "the environment the server runs in, development or production"),
+    ("user_properties.start_since_days", "the number of days since the server started"),
+    ("user_properties.user_email", "the value of MEILI_USER_EMAIL, only when it is set"),
+    ("user_properties.server_provider", "the value of MEILI_SERVER_PROVIDER, only when it is set"),
+    ("event_properties.database_size", "the size in bytes of the database directory"),
+    ("event_properties.last_update_timestamp", "the timestamp of the last processed update"),
+    ("event_properties.number_of_documents", "the number of documents of every index, without their name"),
+];
+
 #[derive(Debug, Serialize)]
 struct EventProperties {
     database_size: u64,
@@ -80,7 +103,7 @@ pub fn analytics_sender(data: Data, opt: Opt) {
     let hostname = whoami::hostname();
     let platform = whoami::platform();
 
-    let uid = username + &hostname + &platform.to_string();
+    let uid = username + hostname.as_str() + platform.to_string().as_str();
 
     let mut hasher = SipHasher::new();
     uid.hash(&mut hasher);
@@ -126,12 +149,12 @@ pub fn analytics_sender(data: Data, opt: Opt) {
         };
 
         let body = qs::to_string(&request).unwrap();
-        let response = ureq::post("https://api.amplitude.com/httpapi").send_string(&body);
+        let response = ureq::post(AMPLITUDE_ENDPOINT).send_string(&body);
         if !response.ok() {
             let body = response.into_string().unwrap();
             error!("Unsuccessful call to Amplitude: {}", body);
         }
 
-        thread::sleep(Duration::from_secs(3600)) // one hour
+        thread::sleep(Duration::from_secs(EVENTS_INTERVAL_SECS))
     }
 }
@@ -1,13 +1,33 @@
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use meilisearch_core::{Database, DatabaseOptions};
 use sha2::Digest;
 use sysinfo::Pid;
 
+use crate::helpers::imports::ImportKeys;
+use crate::helpers::webhook::Webhooks;
+use crate::helpers::{
+    self, BackgroundWorker, DocumentsBatches, Experiments, IdempotencyKeys, PendingEmbeddings, QueryAnalytics,
+    TokenSetsCache,
+};
 use crate::index_update_callback;
 use crate::option::Opt;
 
+/// The number of webhook calls that can wait for the one being sent, and for their retry.
+const MAX_PENDING_WEBHOOKS: usize = 256;
+
+/// The number of references refreshes that can wait for the one being done.
+const MAX_PENDING_REFRESHES: usize = 64;
+
+/// The number of embedding jobs that can wait for the one being generated.
+const MAX_PENDING_EMBEDDINGS: usize = 64;
+
+/// The number of indexes that can wait for the one being warmed.
+const MAX_PENDING_WARMS: usize = 8;
+
 #[derive(Clone)]
 pub struct Data {
     inner: Arc<DataInner>,
@@ -28,6 +48,33 @@ pub struct DataInner {
     pub api_keys: ApiKeys,
     pub server_pid: Pid,
     pub http_payload_size_limit: usize,
+    pub compress_response_threshold: usize,
+    pub read_only: bool,
+    pub log_queries: bool,
+    pub log_query_strings: bool,
+    pub analytics_enabled: bool,
+    pub token_sets: TokenSetsCache,
+    pub documents_batches: DocumentsBatches,
+    pub webhooks: Webhooks,
+    /// Refreshes the documents referencing the updated documents one index after the other.
+    pub references_worker: BackgroundWorker,
+    /// Generates the embeddings of the documents one job after the other.
+    pub embeddings_worker: BackgroundWorker,
+    pub pending_embeddings: PendingEmbeddings,
+    /// Warms the indexes one after the other.
+    pub warm_worker: BackgroundWorker,
+    /// The indexes being warmed or waiting to be, an index is not warmed twice at the same time.
+    pub warming_indexes: Arc<Mutex<HashSet<String>>>,
+    pub allow_private_import_urls: bool,
+    pub allow_private_webhook_urls: bool,
+    pub import_keys: ImportKeys,
+    pub idempotency_keys: IdempotencyKeys,
+    pub query_analytics: QueryAnalytics,
+    pub max_wildcard_expansions: usize,
+    pub enable_http2_push: bool,
+    /// When the expired clicks of each index were last removed.
+    pub clicks_pruned_at: Arc<Mutex<HashMap<String, Instant>>>,
+    pub experiments: Experiments,
 }
 
 #[derive(Clone)]
@@ -60,11 +107,22 @@ impl Data {
         let server_pid = sysinfo::get_current_pid().unwrap();
 
         let db_opt = DatabaseOptions {
-            main_map_size: opt.main_map_size,
-            update_map_size: opt.update_map_size,
+            main_map_size: opt.db_map_size.unwrap_or(opt.main_map_size),
+            update_map_size: opt.db_map_size.unwrap_or(opt.update_map_size),
         };
 
         let http_payload_size_limit = opt.http_payload_size_limit;
+        let compress_response_threshold = opt.compress_response_threshold;
+        let read_only = opt.read_only;
+        let log_queries = opt.log_queries;
+        let log_query_strings = opt.log_query_strings;
+        let analytics_enabled = opt.analytics_enabled();
+        let query_analytics = QueryAnalytics::new(opt.query_analytics_size);
+        let max_wildcard_expansions = opt.max_wildcard_expansions;
+        let enable_http2_push = opt.enable_http2_push;
+        let allow_private_import_urls = opt.allow_private_import_urls;
+        let allow_private_webhook_urls = opt.allow_private_webhook_urls;
+        let import_keys = opt.import_keys();
 
         let db = Arc::new(Database::open_or_create(opt.db_path, db_opt).unwrap());
 
@@ -82,6 +140,28 @@ impl Data {
             api_keys,
             server_pid,
             http_payload_size_limit,
+            compress_response_threshold,
+            read_only,
+            log_queries,
+            log_query_strings,
+            analytics_enabled,
+            token_sets: TokenSetsCache::default(),
+            documents_batches: DocumentsBatches::default(),
+            webhooks: Webhooks::new(MAX_PENDING_WEBHOOKS, allow_private_webhook_urls),
+            references_worker: BackgroundWorker::new("references", MAX_PENDING_REFRESHES),
+            embeddings_worker: BackgroundWorker::new("embeddings", MAX_PENDING_EMBEDDINGS),
+            pending_embeddings: PendingEmbeddings::default(),
+            warm_worker: BackgroundWorker::new("warm", MAX_PENDING_WARMS),
+            warming_indexes: Arc::default(),
+            allow_private_import_urls,
+            allow_private_webhook_urls,
+            import_keys,
+            idempotency_keys: IdempotencyKeys::default(),
+            query_analytics,
+            max_wildcard_expansions,
+            enable_http2_push,
+            clicks_pruned_at: Arc::default(),
+            experiments: Experiments::default(),
         };
 
         let data = Data {
@@ -93,6 +173,11 @@ impl Data {
             index_update_callback(&index_uid, &callback_context, status);
         }));
 
+        let importer_context = data.clone();
+        db.set_documents_importer(Box::new(move |index_uid, import, progress| {
+            helpers::imports::import_documents(&importer_context, index_uid, import, progress)
+        }));
+
         data
     }
 }
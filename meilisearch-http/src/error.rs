@@ -5,13 +5,14 @@ use actix_http::ResponseBuilder;
 use actix_web as aweb;
 use actix_web::error::JsonPayloadError;
 use actix_web::http::StatusCode;
-use serde_json::json;
+use serde::Serialize;
+use utoipa::ToSchema;
 
 use meilisearch_error::{ErrorCode, Code};
 
 #[derive(Debug)]
 pub struct ResponseError {
-    inner: Box<dyn ErrorCode>,
+    inner: Box<dyn ErrorCode + Send>,
 }
 
 impl error::Error for ResponseError {}
@@ -40,6 +41,8 @@ pub enum Error {
     BadRequest(String),
     CreateIndex(String),
     DocumentNotFound(String),
+    IdempotencyKeyInUse(String),
+    IdempotencyKeyReused(String),
     IndexNotFound(String),
     Internal(String),
     InvalidIndexUid,
@@ -51,7 +54,11 @@ pub enum Error {
     OpenIndex(String),
     RetrieveDocument(u32, String),
     SearchDocuments(String),
+    SortAttributeNotSortable(String),
     PayloadTooLarge,
+    ReadOnlyMode,
+    RequestTimeout,
+    TooManyRequests(String),
     UnsupportedMediaType,
 }
 
@@ -65,6 +72,8 @@ impl ErrorCode for Error {
             BadRequest(_) => Code::BadRequest,
             CreateIndex(_) => Code::CreateIndex,
             DocumentNotFound(_) => Code::DocumentNotFound,
+            IdempotencyKeyInUse(_) => Code::IdempotencyKeyInUse,
+            IdempotencyKeyReused(_) => Code::IdempotencyKeyReused,
             IndexNotFound(_) => Code::IndexNotFound,
             Internal(_) => Code::Internal,
             InvalidIndexUid => Code::InvalidIndexUid,
@@ -76,7 +85,11 @@ impl ErrorCode for Error {
             OpenIndex(_) => Code::OpenIndex,
             RetrieveDocument(_, _) => Code::RetrieveDocument,
             SearchDocuments(_) => Code::SearchDocuments,
+            SortAttributeNotSortable(_) => Code::SortAttributeNotSortable,
             PayloadTooLarge => Code::PayloadTooLarge,
+            ReadOnlyMode => Code::ReadOnlyMode,
+            RequestTimeout => Code::RequestTimeout,
+            TooManyRequests(_) => Code::TooManyRequests,
             UnsupportedMediaType => Code::UnsupportedMediaType,
         }
     }
@@ -94,7 +107,13 @@ impl error::Error for FacetCountError {}
 
 impl ErrorCode for FacetCountError {
     fn error_code(&self) -> Code {
-        Code::BadRequest
+        use FacetCountError::*;
+
+        match self {
+            AttributeNotSet(_) => Code::FacetAttributeNotSet,
+            SyntaxError(_) | UnexpectedToken { .. } => Code::Facet,
+            NoFacetSet => Code::FacetsNotSet,
+        }
     }
 }
 
@@ -153,6 +172,14 @@ impl Error {
         Error::DocumentNotFound(err.to_string())
     }
 
+    pub fn idempotency_key_in_use(key: impl fmt::Display) -> Error {
+        Error::IdempotencyKeyInUse(key.to_string())
+    }
+
+    pub fn idempotency_key_reused(key: impl fmt::Display) -> Error {
+        Error::IdempotencyKeyReused(key.to_string())
+    }
+
     pub fn missing_header(err: impl fmt::Display) -> Error {
         Error::MissingHeader(err.to_string())
     }
@@ -184,6 +211,22 @@ impl Error {
     pub fn search_documents(err: impl fmt::Display) -> Error {
         Error::SearchDocuments(err.to_string())
     }
+
+    pub fn sort_attribute_not_sortable(attribute: impl fmt::Display) -> Error {
+        Error::SortAttributeNotSortable(attribute.to_string())
+    }
+
+    pub fn read_only_mode() -> Error {
+        Error::ReadOnlyMode
+    }
+
+    pub fn request_timeout() -> Error {
+        Error::RequestTimeout
+    }
+
+    pub fn too_many_requests(err: impl fmt::Display) -> Error {
+        Error::TooManyRequests(err.to_string())
+    }
 }
 
 impl fmt::Display for Error {
@@ -192,10 +235,12 @@ impl fmt::Display for Error {
             Self::BadParameter(param, err) => write!(f, "Url parameter {} error: {}", param, err),
             Self::BadRequest(err) => f.write_str(err),
             Self::CreateIndex(err) => write!(f, "Impossible to create index; {}", err),
-            Self::DocumentNotFound(document_id) => write!(f, "Document with id {} not found", document_id),
+            Self::DocumentNotFound(document_id) => write!(f, "Document {} not found", document_id),
+            Self::IdempotencyKeyInUse(key) => write!(f, "The request with the idempotency key {} is still being processed", key),
+            Self::IdempotencyKeyReused(key) => write!(f, "The idempotency key {} was already used for a different request", key),
             Self::IndexNotFound(index_uid) => write!(f, "Index {} not found", index_uid),
             Self::Internal(err) => f.write_str(err),
-            Self::InvalidIndexUid => f.write_str("Index must have a valid uid; Index uid can be of type integer or string only composed of alphanumeric characters, hyphens (-) and underscores (_)."),
+            Self::InvalidIndexUid => f.write_str("Index must have a valid uid; Index uid must be 1 to 128 characters long, only composed of alphanumeric characters, hyphens (-) and underscores (_), and cannot start with a digit."),
             Self::InvalidToken(err) => write!(f, "Invalid API key: {}", err),
             Self::Maintenance => f.write_str("Server is in maintenance, please try again later"),
             Self::MissingAuthorizationHeader => f.write_str("You must have an authorization token"),
@@ -204,20 +249,34 @@ impl fmt::Display for Error {
             Self::OpenIndex(err) => write!(f, "Impossible to open index; {}", err),
             Self::RetrieveDocument(id, err) => write!(f, "impossible to retrieve the document with id: {}; {}", id, err),
             Self::SearchDocuments(err) => write!(f, "impossible to search documents; {}", err),
+            Self::SortAttributeNotSortable(attribute) => write!(f, "{} is not an attribute of an asc or desc ranking rule", attribute),
             Self::PayloadTooLarge => f.write_str("Payload to large"),
+            Self::ReadOnlyMode => f.write_str("The server is in read-only mode, write requests are refused"),
+            Self::RequestTimeout => f.write_str("The request took too long to complete"),
+            Self::TooManyRequests(err) => write!(f, "Too many requests; {}", err),
             Self::UnsupportedMediaType => f.write_str("Unsupported media type"),
         }
     }
 }
 
+/// The body of every error returned by the HTTP API.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorResponseBody {
+    pub message: String,
+    pub error_code: String,
+    pub error_type: String,
+    pub error_link: String,
+}
+
 impl aweb::error::ResponseError for ResponseError {
     fn error_response(&self) -> aweb::HttpResponse {
-        ResponseBuilder::new(self.status_code()).json(json!({
-            "message": self.to_string(),
-            "errorCode": self.error_name(),
-            "errorType": self.error_type(),
-            "errorLink": self.error_url(),
-        }))
+        ResponseBuilder::new(self.status_code()).json(ErrorResponseBody {
+            message: self.to_string(),
+            error_code: self.error_name(),
+            error_type: self.error_type(),
+            error_link: self.error_url(),
+        })
     }
 
     fn status_code(&self) -> StatusCode {
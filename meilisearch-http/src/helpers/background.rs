@@ -0,0 +1,64 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::thread;
+
+use log::error;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Runs the jobs one after the other on a single thread. At most `capacity` jobs
+/// wait to be run, the ones pushed while the queue is full are refused.
+#[derive(Clone)]
+pub struct BackgroundWorker {
+    sender: SyncSender<Job>,
+}
+
+impl BackgroundWorker {
+    pub fn new(name: &str, capacity: usize) -> BackgroundWorker {
+        let (sender, receiver) = sync_channel::<Job>(capacity);
+
+        let thread_name = name.to_string();
+        thread::Builder::new()
+            .name(thread_name.clone())
+            .spawn(move || {
+                for job in receiver {
+                    // a job that panics does not stop the ones after it
+                    if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+                        error!("A job of the {} worker panicked", thread_name);
+                    }
+                }
+            })
+            .expect("Impossible to spawn a background worker");
+
+        BackgroundWorker { sender }
+    }
+
+    /// Enqueues the job, returns `false` when the queue is full and the job is dropped.
+    pub fn push<F: FnOnce() + Send + 'static>(&self, job: F) -> bool {
+        self.sender.try_send(Box::new(job)).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn jobs_are_refused_once_the_queue_is_full() {
+        let worker = BackgroundWorker::new("test", 1);
+
+        // the first job blocks the worker until the end of the test
+        let (unblock, blocked) = channel::<()>();
+        let (started, wait_started) = channel();
+        assert!(worker.push(move || {
+            started.send(()).unwrap();
+            blocked.recv().unwrap();
+        }));
+        wait_started.recv().unwrap();
+
+        assert!(worker.push(|| ()));
+        assert!(!worker.push(|| ()));
+        unblock.send(()).unwrap();
+    }
+}
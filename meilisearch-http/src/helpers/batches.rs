@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use indexmap::IndexMap;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde_json::Value;
+
+use crate::error::Error;
+
+/// The time after which a batch that was not committed is rolled back.
+const BATCH_TTL: Duration = Duration::from_secs(3600);
+
+/// The maximum number of documents of a batch.
+const MAX_BATCH_DOCUMENTS: usize = 1_000_000;
+
+/// The maximum size of the documents of a batch, in bytes of JSON.
+const MAX_BATCH_SIZE: usize = 512 * 1024 * 1024;
+
+type Document = IndexMap<String, Value>;
+
+struct Batch {
+    index_uid: String,
+    documents: Vec<Document>,
+    /// The size of the documents, in bytes of JSON.
+    size: usize,
+    /// Whether the batch is being committed, documents cannot be appended to it meanwhile.
+    committing: bool,
+    started_at: Instant,
+}
+
+/// Keeps the documents sent in several requests until they are
+/// committed together, as a single documents addition.
+#[derive(Clone, Default)]
+pub struct DocumentsBatches {
+    inner: Arc<Mutex<HashMap<String, Batch>>>,
+}
+
+impl DocumentsBatches {
+    /// Starts a new batch of documents for the index and returns its id.
+    pub fn start(&self, index_uid: &str) -> String {
+        let batch_id: String = rand::thread_rng().sample_iter(&Alphanumeric).take(16).collect();
+
+        let mut inner = self.inner.lock().unwrap();
+        remove_expired(&mut inner);
+        inner.insert(batch_id.clone(), Batch {
+            index_uid: index_uid.to_string(),
+            documents: Vec::new(),
+            size: 0,
+            committing: false,
+            started_at: Instant::now(),
+        });
+
+        batch_id
+    }
+
+    /// Appends the documents to the batch and returns the number of documents it contains,
+    /// or `None` if there is no such batch for this index. The documents are refused when
+    /// the batch would be too large or while it is being committed.
+    pub fn append(&self, index_uid: &str, batch_id: &str, documents: Vec<Document>) -> Result<Option<usize>, Error> {
+        let size: usize = documents.iter().map(|document| serde_json::to_vec(document).map_or(0, |json| json.len())).sum();
+
+        let mut inner = self.inner.lock().unwrap();
+        remove_expired(&mut inner);
+        let batch = match inner.get_mut(batch_id).filter(|batch| batch.index_uid == index_uid) {
+            Some(batch) => batch,
+            None => return Ok(None),
+        };
+
+        if batch.committing {
+            return Err(Error::bad_request(format!("the batch {} is being committed", batch_id)));
+        }
+        if batch.documents.len() + documents.len() > MAX_BATCH_DOCUMENTS {
+            let message = format!("a batch cannot contain more than {} documents", MAX_BATCH_DOCUMENTS);
+            return Err(Error::bad_request(message));
+        }
+        if batch.size + size > MAX_BATCH_SIZE {
+            let message = format!("the documents of a batch cannot weigh more than {} bytes", MAX_BATCH_SIZE);
+            return Err(Error::bad_request(message));
+        }
+
+        batch.documents.extend(documents);
+        batch.size += size;
+        Ok(Some(batch.documents.len()))
+    }
+
+    /// Starts committing the batch and returns a copy of its documents, or `None` if there is no such
+    /// batch for this index. The batch is kept until its commit is ended, it is removed once enqueued.
+    pub fn begin_commit(&self, index_uid: &str, batch_id: &str) -> Result<Option<Vec<Document>>, Error> {
+        let mut inner = self.inner.lock().unwrap();
+        remove_expired(&mut inner);
+        match inner.get_mut(batch_id).filter(|batch| batch.index_uid == index_uid) {
+            Some(batch) if batch.committing => Err(Error::bad_request(format!("the batch {} is being committed", batch_id))),
+            Some(batch) => {
+                batch.committing = true;
+                Ok(Some(batch.documents.clone()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Ends the commit of the batch, it is removed when its documents were enqueued
+    /// and can be committed again otherwise.
+    pub fn end_commit(&self, batch_id: &str, enqueued: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        if enqueued {
+            inner.remove(batch_id);
+        } else if let Some(batch) = inner.get_mut(batch_id) {
+            batch.committing = false;
+        }
+    }
+
+    /// Removes the batch and returns its documents, or `None` if there is no such batch for this index.
+    pub fn remove(&self, index_uid: &str, batch_id: &str) -> Option<Vec<Document>> {
+        let mut inner = self.inner.lock().unwrap();
+        remove_expired(&mut inner);
+        match inner.get(batch_id) {
+            Some(batch) if batch.index_uid == index_uid => inner.remove(batch_id).map(|batch| batch.documents),
+            _ => None,
+        }
+    }
+}
+
+fn remove_expired(batches: &mut HashMap<String, Batch>) {
+    batches.retain(|_, batch| batch.started_at.elapsed() < BATCH_TTL);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn document(id: u64) -> Document {
+        let mut document = Document::new();
+        document.insert("id".to_string(), json!(id));
+        document
+    }
+
+    #[test]
+    fn batches_are_scoped_to_their_index() {
+        let batches = DocumentsBatches::default();
+        let batch_id = batches.start("movies");
+
+        assert_eq!(batches.append("movies", &batch_id, vec![document(1)]).unwrap(), Some(1));
+        assert_eq!(batches.append("movies", &batch_id, vec![document(2), document(3)]).unwrap(), Some(3));
+        assert_eq!(batches.append("books", &batch_id, vec![document(4)]).unwrap(), None);
+        assert!(batches.remove("books", &batch_id).is_none());
+
+        assert_eq!(batches.remove("movies", &batch_id).map(|documents| documents.len()), Some(3));
+        assert!(batches.remove("movies", &batch_id).is_none());
+    }
+
+    #[test]
+    fn batches_are_kept_until_their_commit_succeeds() {
+        let batches = DocumentsBatches::default();
+        let batch_id = batches.start("movies");
+        batches.append("movies", &batch_id, vec![document(1)]).unwrap();
+
+        assert_eq!(batches.begin_commit("movies", &batch_id).unwrap().map(|documents| documents.len()), Some(1));
+        assert!(batches.append("movies", &batch_id, vec![document(2)]).is_err());
+        assert!(batches.begin_commit("movies", &batch_id).is_err());
+
+        // a failed commit can be retried
+        batches.end_commit(&batch_id, false);
+        assert!(batches.begin_commit("movies", &batch_id).unwrap().is_some());
+
+        batches.end_commit(&batch_id, true);
+        assert!(batches.begin_commit("movies", &batch_id).unwrap().is_none());
+    }
+}
@@ -0,0 +1,84 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use actix_service::{Service, Transform};
+use actix_web::dev::{BodyEncoding, BodySize, MessageBody, ServiceRequest, ServiceResponse};
+use actix_web::http::ContentEncoding;
+use futures::future::{ok, Future, Ready};
+
+/// Leaves uncompressed the response bodies that are smaller than the threshold. The bigger
+/// ones, and the streamed ones whose size is unknown, are compressed by the `Compress`
+/// middleware wrapping the app, with the encoding the client prefers.
+#[derive(Clone, Copy)]
+pub struct CompressResponse {
+    threshold: usize,
+}
+
+impl CompressResponse {
+    pub fn new(threshold: usize) -> CompressResponse {
+        CompressResponse { threshold }
+    }
+}
+
+impl<S: 'static, B> Transform<S> for CompressResponse
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type InitError = ();
+    type Transform = CompressResponseMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(CompressResponseMiddleware {
+            threshold: self.threshold,
+            service,
+        })
+    }
+}
+
+pub struct CompressResponseMiddleware<S> {
+    threshold: usize,
+    service: S,
+}
+
+impl<S, B> Service for CompressResponseMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let threshold = self.threshold as u64;
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+
+            let size = match res.response().body().size() {
+                BodySize::Sized(size) => Some(size as u64),
+                BodySize::Sized64(size) => Some(size),
+                _ => None,
+            };
+
+            if size.map_or(false, |size| size < threshold) && res.response().get_encoding().is_none() {
+                res.response_mut().encoding(ContentEncoding::Identity);
+            }
+
+            Ok(res)
+        })
+    }
+}
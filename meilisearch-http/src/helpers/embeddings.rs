@@ -0,0 +1,322 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use log::error;
+use meilisearch_core::settings::{Embedder, EmbedderSource, EmbeddingStrategy};
+use meilisearch_core::update::value_to_string;
+use meilisearch_core::{DocumentId, Index, MainReader};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::error::{Error, ResponseError};
+use crate::routes::document::Document;
+use crate::Data;
+
+/// The number of documents embedded by each call to the API of the embedder.
+const EMBEDDING_BATCH_SIZE: usize = 64;
+
+/// The time after which a call to the API of the embedder is abandoned, in milliseconds.
+const EMBEDDING_TIMEOUT_MS: u64 = 30_000;
+
+const OPENAI_EMBEDDINGS_URL: &str = "https://api.openai.com/v1/embeddings";
+
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// The documents whose embedding is being generated, by index, they are not queued again
+/// when other searches find them in the meantime.
+#[derive(Clone, Default)]
+pub struct PendingEmbeddings {
+    inner: Arc<Mutex<HashSet<(String, DocumentId)>>>,
+}
+
+impl PendingEmbeddings {
+    /// Marks the documents as pending, returns the ones that were not already.
+    fn insert(&self, index_uid: &str, documents_ids: &[DocumentId]) -> Vec<DocumentId> {
+        let mut inner = self.inner.lock().unwrap();
+        documents_ids
+            .iter()
+            .filter(|id| inner.insert((index_uid.to_string(), **id)))
+            .copied()
+            .collect()
+    }
+
+    fn remove(&self, index_uid: &str, documents_ids: &[DocumentId]) {
+        let mut inner = self.inner.lock().unwrap();
+        for id in documents_ids {
+            inner.remove(&(index_uid.to_string(), *id));
+        }
+    }
+}
+
+/// The documents whose missing embeddings are generated.
+enum EmbeddedDocuments {
+    /// All the documents of the index, once its embedder changed.
+    All,
+    /// The documents added or replaced by an update, by their ids.
+    Updated(Vec<String>),
+    /// The hits of the searches, with the lazy embedding strategy.
+    Hits(Vec<DocumentId>),
+}
+
+/// Generates on the embeddings worker the embeddings of the documents of the index that have
+/// none, with the embedder of the index settings, the embeddings are then added to the documents.
+/// Only the documents with these ids are embedded, all of them when there are none. Nothing is
+/// generated when the embedding strategy is lazy.
+pub fn embed_documents(data: &Data, index_uid: &str, documents_ids: Option<Vec<String>>) {
+    let documents = documents_ids.map_or(EmbeddedDocuments::All, EmbeddedDocuments::Updated);
+    let job_data = data.clone();
+    let job_index_uid = index_uid.to_string();
+    let pushed = data.embeddings_worker.push(move || {
+        if let Err(e) = embed_missing_documents(&job_data, &job_index_uid, documents) {
+            error!("Impossible to generate the embeddings of the documents of {}; {}", job_index_uid, e);
+        }
+    });
+
+    if !pushed {
+        error!("Too many embeddings are pending, the documents of {} are not embedded", index_uid);
+    }
+}
+
+/// Generates on the embeddings worker the embeddings of these documents, the hits of a search
+/// when the embedding strategy is lazy. The documents that already have an embedding, or whose
+/// embedding is pending, are skipped.
+///
+/// No search uses the embeddings yet, so the lazy strategy cannot wait for the first search that
+/// would need them: the hits of every search are embedded instead, as they are the documents
+/// such a search would most likely return. A document is only embedded once.
+pub fn embed_documents_lazily(
+    data: &Data,
+    reader: &MainReader,
+    index: &Index,
+    index_uid: &str,
+    embedder: &Embedder,
+    documents_ids: &[DocumentId],
+) -> Result<(), ResponseError> {
+    let schema = index
+        .main
+        .schema(reader)?
+        .ok_or(Error::internal("Impossible to retrieve the schema"))?;
+
+    let mut parts = embedder.document_embedding_field.splitn(2, '.');
+    let root = parts.next().unwrap_or_default();
+    let path = parts.next();
+
+    let mut missing = Vec::new();
+    for &document_id in documents_ids {
+        let value = match schema.id(root) {
+            Some(field_id) => index.document_attribute::<Value>(reader, document_id, field_id)?,
+            None => None,
+        };
+        let value = match (value, path) {
+            (Some(value), Some(path)) => path.split('.').try_fold(value, |value, part| value.get(part).cloned()),
+            (value, _) => value,
+        };
+        if value.map_or(true, |value| value.is_null()) {
+            missing.push(document_id);
+        }
+    }
+
+    let documents_ids = data.pending_embeddings.insert(index_uid, &missing);
+    if documents_ids.is_empty() {
+        return Ok(());
+    }
+
+    let job_data = data.clone();
+    let job_index_uid = index_uid.to_string();
+    let job_documents_ids = documents_ids.clone();
+    let pushed = data.embeddings_worker.push(move || {
+        let result = embed_missing_documents(&job_data, &job_index_uid, EmbeddedDocuments::Hits(job_documents_ids.clone()));
+        job_data.pending_embeddings.remove(&job_index_uid, &job_documents_ids);
+        if let Err(e) = result {
+            error!("Impossible to generate the embeddings of the documents of {}; {}", job_index_uid, e);
+        }
+    });
+
+    // the documents are embedded by a later search
+    if !pushed {
+        data.pending_embeddings.remove(index_uid, &documents_ids);
+    }
+
+    Ok(())
+}
+
+fn embed_missing_documents(
+    data: &Data,
+    index_uid: &str,
+    documents: EmbeddedDocuments,
+) -> Result<(), ResponseError> {
+    let index = match data.db.open_index(index_uid) {
+        Some(index) => index,
+        None => return Ok(()),
+    };
+
+    let reader = data.db.main_read_txn()?;
+    let embedder = match index.main.embedder(&reader)? {
+        Some(embedder) => embedder,
+        None => return Ok(()),
+    };
+
+    let strategy = index.main.embedding_strategy(&reader)?.unwrap_or_default();
+    let documents_ids: Vec<_> = match (documents, strategy) {
+        (EmbeddedDocuments::Hits(documents_ids), _) => documents_ids,
+        (_, EmbeddingStrategy::Lazy) => return Ok(()),
+        (EmbeddedDocuments::All, EmbeddingStrategy::Eager) => {
+            index.documents_fields_counts.documents_ids(&reader)?.collect::<Result<_, _>>()?
+        }
+        (EmbeddedDocuments::Updated(ids), EmbeddingStrategy::Eager) => {
+            let mut documents_ids = Vec::with_capacity(ids.len());
+            for id in ids {
+                documents_ids.extend(index.main.external_to_internal_docid(&reader, &id)?);
+            }
+            documents_ids
+        }
+    };
+
+    let schema = index
+        .main
+        .schema(&reader)?
+        .ok_or(Error::internal("Impossible to retrieve the schema"))?;
+    let primary_key = schema.primary_key().ok_or(Error::internal("Impossible to retrieve the primary key"))?;
+
+    // the embeddings are not part of the text they are generated from
+    let embedding_root = embedder.document_embedding_field.split('.').next().unwrap_or_default();
+    let searchable_attributes: Vec<String> = schema
+        .indexed_name()
+        .into_iter()
+        .filter(|name| *name != embedding_root)
+        .map(String::from)
+        .collect();
+
+    // only the primary key and the field of the embedding are written back, for the
+    // changes made to the other fields while the embeddings are generated to be kept
+    let mut documents = Vec::new();
+    let mut texts = Vec::new();
+    for document_id in documents_ids {
+        let document: Option<Document> = index.document(&reader, None, document_id)?;
+        if let Some(mut document) = document {
+            let text = document_text(&document, &searchable_attributes);
+            if embedding(&document, &embedder.document_embedding_field).is_none() && !text.is_empty() {
+                document.retain(|name, _| name == primary_key || name == embedding_root);
+                documents.push(document);
+                texts.push(text);
+            }
+        }
+    }
+
+    // the API is not called while the transaction is open
+    drop(reader);
+
+    if documents.is_empty() {
+        return Ok(());
+    }
+
+    let mut embeddings = Vec::with_capacity(texts.len());
+    for texts in texts.chunks(EMBEDDING_BATCH_SIZE) {
+        embeddings.extend(generate_embeddings(&embedder, texts)?);
+    }
+
+    for (document, embedding) in documents.iter_mut().zip(embeddings) {
+        set_embedding(document, &embedder.document_embedding_field, json!(embedding));
+    }
+
+    data.db.update_write(|w| index.documents_embeddings(w, documents))?;
+
+    Ok(())
+}
+
+/// The text the embedding of the document is generated from, the values of its searchable attributes.
+fn document_text(document: &Document, searchable_attributes: &[String]) -> String {
+    searchable_attributes
+        .iter()
+        .filter_map(|name| document.get(name))
+        .map(value_to_string)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the embedding of the document at this path, e.g. `_vectors.default`.
+fn embedding<'a>(document: &'a Document, path: &str) -> Option<&'a Value> {
+    let mut parts = path.split('.');
+    let mut value = document.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_object()?.get(part)?;
+    }
+    Some(value).filter(|value| !value.is_null())
+}
+
+/// Stores the embedding in the document at this path, creating the missing objects.
+fn set_embedding(document: &mut Document, path: &str, embedding: Value) {
+    let mut parts = path.split('.');
+    let root = match parts.next() {
+        Some(root) => root,
+        None => return,
+    };
+
+    let mut value = document.entry(root.to_string()).or_insert(Value::Null);
+    for part in parts {
+        if !value.is_object() {
+            *value = Value::Object(Map::new());
+        }
+        value = match value {
+            Value::Object(object) => object.entry(part.to_string()).or_insert(Value::Null),
+            _ => return,
+        };
+    }
+
+    *value = embedding;
+}
+
+/// Calls the API of the embedder, returns an embedding for each text, in the same order.
+fn generate_embeddings(embedder: &Embedder, texts: &[String]) -> Result<Vec<Vec<f32>>, Error> {
+    let url = match embedder.source {
+        EmbedderSource::OpenAi => OPENAI_EMBEDDINGS_URL,
+    };
+
+    let body = json!({
+        "model": embedder.model,
+        "input": texts,
+        "dimensions": embedder.dimensions,
+    });
+
+    let mut request = ureq::post(url);
+    request.set("Content-Type", "application/json");
+    request.timeout_connect(EMBEDDING_TIMEOUT_MS);
+    request.timeout_read(EMBEDDING_TIMEOUT_MS);
+    if let Some(api_key) = &embedder.api_key {
+        request.set("Authorization", &format!("Bearer {}", api_key));
+    }
+
+    let response = request.send_string(&body.to_string());
+    if let Some(e) = response.synthetic_error() {
+        return Err(Error::internal(format!("the embedder could not be called; {}", e)));
+    }
+    if !response.ok() {
+        return Err(Error::internal(format!("the embedder responded with {}", response.status())));
+    }
+
+    let response = response
+        .into_string()
+        .map_err(|e| Error::internal(format!("the embedder response could not be read; {}", e)))?;
+    let mut response: OpenAiResponse = serde_json::from_str(&response)
+        .map_err(|e| Error::internal(format!("the embedder response is invalid; {}", e)))?;
+
+    if response.data.len() != texts.len() {
+        return Err(Error::internal("the embedder did not return an embedding for each document"));
+    }
+    if response.data.iter().any(|data| data.embedding.len() != embedder.dimensions) {
+        return Err(Error::internal("the embedder returned embeddings of unexpected dimensions"));
+    }
+
+    response.data.sort_by_key(|data| data.index);
+    Ok(response.data.into_iter().map(|data| data.embedding).collect())
+}
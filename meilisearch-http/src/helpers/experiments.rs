@@ -0,0 +1,249 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use meilisearch_core::settings::RankingRule;
+use serde::Serialize;
+use siphasher::sip::SipHasher;
+
+/// The number of the last searches of an experiment remembered to attribute the clicks to their group.
+const MAX_REMEMBERED_QUERIES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExperimentGroup {
+    Control,
+    Treatment,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct GroupMetrics {
+    pub searches: usize,
+    pub zero_results: usize,
+    pub clicks: usize,
+    /// The sum of the positions of the clicked hits.
+    pub clicked_positions: usize,
+}
+
+impl GroupMetrics {
+    pub fn zero_result_rate(&self) -> Option<f64> {
+        if self.searches == 0 { None } else { Some(self.zero_results as f64 / self.searches as f64) }
+    }
+
+    /// The average position of the clicked hits, starting at 0.
+    pub fn average_rank(&self) -> Option<f64> {
+        if self.clicks == 0 { None } else { Some(self.clicked_positions as f64 / self.clicks as f64) }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Experiment {
+    pub name: String,
+    pub control: Vec<RankingRule>,
+    pub treatment: Vec<RankingRule>,
+    /// The percentage of the searches ranked with the treatment ranking rules.
+    pub traffic_split: u8,
+    pub active: bool,
+    pub control_metrics: GroupMetrics,
+    pub treatment_metrics: GroupMetrics,
+    /// The query ids of the remembered searches, the oldest first.
+    queries: VecDeque<String>,
+    groups: HashMap<String, ExperimentGroup>,
+}
+
+impl Experiment {
+    /// Returns the group the query falls into, the same query always falls into the same group.
+    fn group(&self, query: &str) -> ExperimentGroup {
+        let mut hasher = SipHasher::new();
+        query.trim().to_lowercase().hash(&mut hasher);
+        if hasher.finish() % 100 < u64::from(self.traffic_split) {
+            ExperimentGroup::Treatment
+        } else {
+            ExperimentGroup::Control
+        }
+    }
+
+    fn metrics_mut(&mut self, group: ExperimentGroup) -> &mut GroupMetrics {
+        match group {
+            ExperimentGroup::Control => &mut self.control_metrics,
+            ExperimentGroup::Treatment => &mut self.treatment_metrics,
+        }
+    }
+}
+
+/// The ranking rules experiments of the indexes, each index has at most one active experiment.
+/// The experiments and their metrics are kept in memory only.
+#[derive(Clone, Default)]
+pub struct Experiments {
+    inner: Arc<Mutex<HashMap<String, Vec<Experiment>>>>,
+}
+
+impl Experiments {
+    /// Creates the experiment and makes it the active one of the index,
+    /// returns `false` if there already is an experiment with this name.
+    pub fn create(
+        &self,
+        index_uid: &str,
+        name: &str,
+        control: Vec<RankingRule>,
+        treatment: Vec<RankingRule>,
+        traffic_split: u8,
+    ) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let experiments = inner.entry(index_uid.to_string()).or_insert_with(Vec::new);
+        if experiments.iter().any(|e| e.name == name) {
+            return false;
+        }
+
+        experiments.iter_mut().for_each(|e| e.active = false);
+        experiments.push(Experiment {
+            name: name.to_string(),
+            control,
+            treatment,
+            traffic_split,
+            active: true,
+            control_metrics: GroupMetrics::default(),
+            treatment_metrics: GroupMetrics::default(),
+            queries: VecDeque::new(),
+            groups: HashMap::new(),
+        });
+
+        true
+    }
+
+    /// Removes the experiment, returns `false` if there is no experiment with this name.
+    /// The searches of the index are ranked with its ranking rules again if it was the active one.
+    pub fn delete(&self, index_uid: &str, name: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let experiments = match inner.get_mut(index_uid) {
+            Some(experiments) => experiments,
+            None => return false,
+        };
+
+        let len = experiments.len();
+        experiments.retain(|e| e.name != name);
+        experiments.len() != len
+    }
+
+    pub fn get(&self, index_uid: &str, name: &str) -> Option<Experiment> {
+        let inner = self.inner.lock().unwrap();
+        inner.get(index_uid)?.iter().find(|e| e.name == name).cloned()
+    }
+
+    /// Returns the group the query falls into and the ranking rules to use,
+    /// or `None` if the index has no active experiment.
+    pub fn assign(&self, index_uid: &str, query: &str) -> Option<(ExperimentGroup, Vec<RankingRule>)> {
+        let inner = self.inner.lock().unwrap();
+        let experiment = inner.get(index_uid)?.iter().find(|e| e.active)?;
+        let group = experiment.group(query);
+        let rules = match group {
+            ExperimentGroup::Control => experiment.control.clone(),
+            ExperimentGroup::Treatment => experiment.treatment.clone(),
+        };
+        Some((group, rules))
+    }
+
+    pub fn record_search(&self, index_uid: &str, group: ExperimentGroup, query_id: &str, zero_results: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        let experiment = match inner.get_mut(index_uid).and_then(|es| es.iter_mut().find(|e| e.active)) {
+            Some(experiment) => experiment,
+            None => return,
+        };
+
+        let metrics = experiment.metrics_mut(group);
+        metrics.searches += 1;
+        metrics.zero_results += zero_results as usize;
+
+        if experiment.groups.insert(query_id.to_string(), group).is_none() {
+            if experiment.queries.len() >= MAX_REMEMBERED_QUERIES {
+                if let Some(oldest) = experiment.queries.pop_front() {
+                    experiment.groups.remove(&oldest);
+                }
+            }
+            experiment.queries.push_back(query_id.to_string());
+        }
+    }
+
+    /// Counts the click in the group of the experiment the search was part of, if any.
+    pub fn record_click(&self, index_uid: &str, query_id: &str, position: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        let experiments = match inner.get_mut(index_uid) {
+            Some(experiments) => experiments,
+            None => return,
+        };
+
+        for experiment in experiments {
+            if let Some(&group) = experiment.groups.get(query_id) {
+                let metrics = experiment.metrics_mut(group);
+                metrics.clicks += 1;
+                metrics.clicked_positions += position;
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_are_split_by_the_traffic_percentage() {
+        let experiments = Experiments::default();
+        assert!(experiments.create("movies", "all", vec![RankingRule::Typo], vec![RankingRule::Words], 100));
+        assert!(!experiments.create("movies", "all", Vec::new(), Vec::new(), 0));
+
+        let (group, rules) = experiments.assign("movies", "carol").unwrap();
+        assert_eq!(group, ExperimentGroup::Treatment);
+        assert_eq!(rules.len(), 1);
+        assert!(experiments.assign("other", "carol").is_none());
+
+        assert!(experiments.create("movies", "none", Vec::new(), Vec::new(), 0));
+        assert_eq!(experiments.assign("movies", "carol").unwrap().0, ExperimentGroup::Control);
+        assert!(!experiments.get("movies", "all").unwrap().active);
+    }
+
+    #[test]
+    fn clicks_are_attributed_to_the_group_of_the_search() {
+        let experiments = Experiments::default();
+        experiments.create("movies", "split", Vec::new(), Vec::new(), 100);
+
+        experiments.record_search("movies", ExperimentGroup::Treatment, "query-1", false);
+        experiments.record_search("movies", ExperimentGroup::Treatment, "query-2", true);
+        experiments.record_click("movies", "query-1", 3);
+        experiments.record_click("movies", "query-1", 1);
+        experiments.record_click("movies", "unknown", 5);
+
+        let experiment = experiments.get("movies", "split").unwrap();
+        assert_eq!(experiment.control_metrics, GroupMetrics::default());
+        assert_eq!(experiment.treatment_metrics.zero_result_rate(), Some(0.5));
+        assert_eq!(experiment.treatment_metrics.average_rank(), Some(2.0));
+    }
+
+    #[test]
+    fn only_the_last_searches_are_remembered() {
+        let experiments = Experiments::default();
+        experiments.create("movies", "split", Vec::new(), Vec::new(), 100);
+
+        for i in 0..=MAX_REMEMBERED_QUERIES {
+            experiments.record_search("movies", ExperimentGroup::Treatment, &format!("query-{}", i), false);
+        }
+        experiments.record_click("movies", "query-0", 1);
+        experiments.record_click("movies", &format!("query-{}", MAX_REMEMBERED_QUERIES), 1);
+
+        let experiment = experiments.get("movies", "split").unwrap();
+        assert_eq!(experiment.treatment_metrics.clicks, 1);
+        assert_eq!(experiment.groups.len(), MAX_REMEMBERED_QUERIES);
+    }
+
+    #[test]
+    fn deleted_experiments_are_not_assigned() {
+        let experiments = Experiments::default();
+        experiments.create("movies", "split", Vec::new(), Vec::new(), 100);
+
+        assert!(experiments.delete("movies", "split"));
+        assert!(!experiments.delete("movies", "split"));
+        assert!(experiments.get("movies", "split").is_none());
+        assert!(experiments.assign("movies", "carol").is_none());
+    }
+}
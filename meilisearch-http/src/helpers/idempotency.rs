@@ -0,0 +1,186 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+use crate::routes::IndexUpdateResponse;
+
+/// How long the response to a request is returned again for the requests with the same idempotency key.
+const IDEMPOTENCY_KEY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The maximum number of idempotency keys kept, the oldest ones are forgotten first.
+const MAX_IDEMPOTENCY_KEYS: usize = 10_000;
+
+/// An idempotency key is only shared by the requests sent with the same API key.
+type ScopedKey = (Option<String>, String);
+
+struct IdempotentRequest {
+    /// The hash of the index, the parameters and the body of the request.
+    request_hash: u64,
+    /// `None` while the first request with the key is being processed.
+    response: Option<IndexUpdateResponse>,
+    received_at: Instant,
+}
+
+#[derive(Default)]
+struct Inner {
+    requests: HashMap<ScopedKey, IdempotentRequest>,
+    /// The keys in the order they were received, to forget the expired ones first.
+    order: VecDeque<(Instant, ScopedKey)>,
+}
+
+impl Inner {
+    fn remove_expired(&mut self, ttl: Duration, max_keys: usize) {
+        while let Some((received_at, _)) = self.order.front() {
+            if received_at.elapsed() < ttl && self.requests.len() < max_keys {
+                break;
+            }
+            let (received_at, key) = self.order.pop_front().unwrap();
+            // the key may have been released and reserved again since
+            if self.requests.get(&key).map_or(false, |request| request.received_at == received_at) {
+                self.requests.remove(&key);
+            }
+        }
+    }
+}
+
+/// Keeps the response to the documents updates sent with an `Idempotency-Key` header,
+/// so that sending the same request again does not enqueue another update.
+/// The keys are forgotten when the server stops.
+#[derive(Clone)]
+pub struct IdempotencyKeys {
+    inner: Arc<Mutex<Inner>>,
+    ttl: Duration,
+    max_keys: usize,
+}
+
+impl Default for IdempotencyKeys {
+    fn default() -> IdempotencyKeys {
+        IdempotencyKeys::new(IDEMPOTENCY_KEY_TTL, MAX_IDEMPOTENCY_KEYS)
+    }
+}
+
+/// The outcome of the reservation of an idempotency key.
+pub enum Reservation {
+    /// The response to the previous request with the key.
+    Done(IndexUpdateResponse),
+    /// The key is reserved for this request, it is released if the guard
+    /// is dropped before the response is given.
+    Reserved(ReservedKey),
+}
+
+pub struct ReservedKey {
+    keys: IdempotencyKeys,
+    key: Option<ScopedKey>,
+    received_at: Instant,
+}
+
+impl ReservedKey {
+    /// Keeps the response, it is returned to the next requests with the key.
+    pub fn complete(mut self, response: IndexUpdateResponse) {
+        if let Some(key) = self.key.take() {
+            let mut inner = self.keys.inner.lock().unwrap();
+            if let Some(request) = inner.requests.get_mut(&key).filter(|r| r.received_at == self.received_at) {
+                request.response = Some(response);
+            }
+        }
+    }
+}
+
+impl Drop for ReservedKey {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let mut inner = self.keys.inner.lock().unwrap();
+            if inner.requests.get(&key).map_or(false, |request| request.received_at == self.received_at) {
+                inner.requests.remove(&key);
+            }
+        }
+    }
+}
+
+impl IdempotencyKeys {
+    pub fn new(ttl: Duration, max_keys: usize) -> IdempotencyKeys {
+        IdempotencyKeys { inner: Arc::default(), ttl, max_keys }
+    }
+
+    /// Returns the response to the previous request with this key, or reserves the key for this
+    /// request. The key cannot be used again for a different request, nor while the request it
+    /// is reserved for is being processed.
+    pub fn reserve(&self, api_key: Option<&str>, key: &str, request_hash: u64) -> Result<Reservation, Error> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.remove_expired(self.ttl, self.max_keys);
+
+        let scoped_key = (api_key.map(String::from), key.to_string());
+        match inner.requests.get(&scoped_key) {
+            Some(request) if request.request_hash != request_hash => Err(Error::idempotency_key_reused(key)),
+            Some(IdempotentRequest { response: Some(response), .. }) => Ok(Reservation::Done(response.clone())),
+            Some(_) => Err(Error::idempotency_key_in_use(key)),
+            None => {
+                let received_at = Instant::now();
+                let request = IdempotentRequest { request_hash, response: None, received_at };
+                inner.requests.insert(scoped_key.clone(), request);
+                inner.order.push_back((received_at, scoped_key.clone()));
+                Ok(Reservation::Reserved(ReservedKey { keys: self.clone(), key: Some(scoped_key), received_at }))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update_id(reservation: Result<Reservation, Error>) -> Option<u64> {
+        match reservation.unwrap() {
+            Reservation::Done(response) => Some(response.update_id),
+            Reservation::Reserved(_) => None,
+        }
+    }
+
+    #[test]
+    fn responses_are_kept_for_the_same_request() {
+        let keys = IdempotencyKeys::default();
+        let reserved = match keys.reserve(None, "key", 1).unwrap() {
+            Reservation::Reserved(reserved) => reserved,
+            Reservation::Done(_) => panic!("the key is not used yet"),
+        };
+
+        // the key is reserved until the response is given
+        assert!(keys.reserve(None, "key", 1).is_err());
+        reserved.complete(IndexUpdateResponse::with_id(7));
+
+        assert_eq!(update_id(keys.reserve(None, "key", 1)), Some(7));
+        assert!(keys.reserve(None, "key", 2).is_err());
+        assert_eq!(update_id(keys.reserve(None, "other", 2)), None);
+
+        // the keys of the other API keys are not shared
+        assert_eq!(update_id(keys.reserve(Some("private"), "key", 2)), None);
+
+        // the expired keys can be used again
+        let keys = IdempotencyKeys::new(Duration::from_secs(0), MAX_IDEMPOTENCY_KEYS);
+        if let Reservation::Reserved(reserved) = keys.reserve(None, "key", 1).unwrap() {
+            reserved.complete(IndexUpdateResponse::with_id(7));
+        }
+        assert_eq!(update_id(keys.reserve(None, "key", 2)), None);
+    }
+
+    #[test]
+    fn the_keys_are_released_when_the_request_fails() {
+        let keys = IdempotencyKeys::default();
+        drop(keys.reserve(None, "key", 1).unwrap());
+        assert_eq!(update_id(keys.reserve(None, "key", 2)), None);
+    }
+
+    #[test]
+    fn the_oldest_keys_are_forgotten_first() {
+        let keys = IdempotencyKeys::new(IDEMPOTENCY_KEY_TTL, 2);
+        for (key, update_id) in &[("a", 1), ("b", 2), ("c", 3)] {
+            if let Reservation::Reserved(reserved) = keys.reserve(None, key, 1).unwrap() {
+                reserved.complete(IndexUpdateResponse::with_id(*update_id));
+            }
+        }
+
+        assert_eq!(update_id(keys.reserve(None, "c", 1)), Some(3));
+        assert_eq!(update_id(keys.reserve(None, "a", 1)), None);
+    }
+}
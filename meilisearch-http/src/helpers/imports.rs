@@ -0,0 +1,345 @@
+use std::io::{self, BufRead, BufReader, Read};
+
+use chrono::Utc;
+use meilisearch_core::update::{DocumentsImport, ImportFormat, ImportedDocuments, UpdateProgress};
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, ResponseError};
+use crate::helpers::public_hosts;
+use crate::helpers::references::inject_referenced_documents;
+use crate::helpers::webhook::hmac_sha256;
+use crate::routes::document::{set_primary_key, Document};
+use crate::Data;
+
+/// The size of the chunks the progress of a download is reported after.
+const DOWNLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The time after which connecting to the server of the file, or waiting for its data, is abandoned, in milliseconds.
+const DOWNLOAD_TIMEOUT_MS: u64 = 30_000;
+
+/// The region of the S3 buckets when none is configured.
+const DEFAULT_S3_REGION: &str = "us-east-1";
+
+/// The HMAC key the objects of a cloud storage are downloaded with.
+#[derive(Debug, Clone)]
+pub struct StorageKey {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+/// The keys the objects of the cloud storages are downloaded with, the objects
+/// of a storage without a key are downloaded without authentication.
+#[derive(Debug, Clone, Default)]
+pub struct ImportKeys {
+    pub s3: Option<StorageKey>,
+    pub s3_region: Option<String>,
+    pub gcs: Option<StorageKey>,
+}
+
+/// Where the file of an import is downloaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportSource {
+    /// An `http(s)://` URL, the Azure Blob Storage URLs are such URLs.
+    Http(String),
+    S3 { bucket: String, key: String },
+    Gcs { bucket: String, key: String },
+}
+
+impl ImportSource {
+    pub fn parse(url: &str) -> Result<ImportSource, String> {
+        let object = |prefix: &str| {
+            let path = &url[prefix.len()..];
+            match path.find('/') {
+                Some(slash) if slash > 0 && slash + 1 < path.len() => {
+                    Ok((path[..slash].to_string(), path[slash + 1..].to_string()))
+                }
+                _ => Err("the URL must contain a bucket and an object key".to_string()),
+            }
+        };
+
+        if url.starts_with("https://") || url.starts_with("http://") {
+            Ok(ImportSource::Http(url.to_string()))
+        } else if url.starts_with("s3://") {
+            let (bucket, key) = object("s3://")?;
+            Ok(ImportSource::S3 { bucket, key })
+        } else if url.starts_with("gs://") {
+            let (bucket, key) = object("gs://")?;
+            Ok(ImportSource::Gcs { bucket, key })
+        } else {
+            Err("the scheme must be http, https, s3 or gs".to_string())
+        }
+    }
+}
+
+/// The names the cloud storages give to the parts of the AWS Signature Version 4.
+struct SigningScheme {
+    algorithm: &'static str,
+    key_prefix: &'static str,
+    header_prefix: &'static str,
+    service: &'static str,
+    request: &'static str,
+}
+
+const S3_SIGNING: SigningScheme = SigningScheme {
+    algorithm: "AWS4-HMAC-SHA256",
+    key_prefix: "AWS4",
+    header_prefix: "x-amz",
+    service: "s3",
+    request: "aws4_request",
+};
+
+const GCS_SIGNING: SigningScheme = SigningScheme {
+    algorithm: "GOOG4-HMAC-SHA256",
+    key_prefix: "GOOG4",
+    header_prefix: "x-goog",
+    service: "storage",
+    request: "goog4_request",
+};
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Percent-encodes the object key as the canonical URIs of the signatures require it, the slashes are kept.
+fn encode_key(key: &str) -> String {
+    let mut encoded = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn signing_key(scheme: &SigningScheme, secret_access_key: &str, date: &str, region: &str) -> Vec<u8> {
+    let mut key = format!("{}{}", scheme.key_prefix, secret_access_key).into_bytes();
+    for part in &[date, region, scheme.service, scheme.request] {
+        key = hmac_sha256(&key, part.as_bytes());
+    }
+    key
+}
+
+/// Signs the request getting the object at this path of the host with the key,
+/// as the AWS Signature Version 4 describes it.
+fn sign(request: &mut ureq::Request, scheme: &SigningScheme, key: &StorageKey, host: &str, path: &str, region: &str) {
+    let datetime = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let date = &datetime[..8];
+    let date_header = format!("{}-date", scheme.header_prefix);
+    let content_header = format!("{}-content-sha256", scheme.header_prefix);
+
+    let signed_headers = format!("host;{};{}", content_header, date_header);
+    let canonical_request = format!(
+        "GET\n{}\n\nhost:{}\n{}:UNSIGNED-PAYLOAD\n{}:{}\n\n{}\nUNSIGNED-PAYLOAD",
+        path, host, content_header, date_header, datetime, signed_headers,
+    );
+
+    let scope = format!("{}/{}/{}/{}", date, region, scheme.service, scheme.request);
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        scheme.algorithm, datetime, scope, hex(&Sha256::digest(canonical_request.as_bytes())),
+    );
+    let signature = hmac_sha256(&signing_key(scheme, &key.secret_access_key, date, region), string_to_sign.as_bytes());
+
+    request.set(&content_header, "UNSIGNED-PAYLOAD");
+    request.set(&date_header, &datetime);
+    request.set("Authorization", &format!(
+        "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+        scheme.algorithm, key.access_key_id, scope, signed_headers, hex(&signature),
+    ));
+}
+
+/// Returns the request getting the file, signed when the storage of the object has a key.
+/// The requests of the objects are not redirected, the signatures are only valid for their host.
+fn download_request(data: &Data, source: &ImportSource, s3_region: &str) -> ureq::Request {
+    let agent = public_hosts::agent(data.allow_private_import_urls);
+
+    let mut request = match source {
+        ImportSource::Http(url) => agent.get(url),
+        ImportSource::S3 { bucket, key } => {
+            let host = format!("{}.s3.{}.amazonaws.com", bucket, s3_region);
+            let path = format!("/{}", encode_key(key));
+            let mut request = agent.get(&format!("https://{}{}", host, path));
+            request.redirects(0);
+            if let Some(storage_key) = &data.import_keys.s3 {
+                sign(&mut request, &S3_SIGNING, storage_key, &host, &path, s3_region);
+            }
+            request
+        }
+        ImportSource::Gcs { bucket, key } => {
+            let host = "storage.googleapis.com";
+            let path = format!("/{}/{}", bucket, encode_key(key));
+            let mut request = agent.get(&format!("https://{}{}", host, path));
+            request.redirects(0);
+            if let Some(storage_key) = &data.import_keys.gcs {
+                sign(&mut request, &GCS_SIGNING, storage_key, host, &path, "auto");
+            }
+            request
+        }
+    };
+
+    request.timeout_connect(DOWNLOAD_TIMEOUT_MS);
+    request.timeout_read(DOWNLOAD_TIMEOUT_MS);
+    request
+}
+
+/// Reports the progress of the download, which is stopped once it exceeds the payload size limit.
+struct DownloadReader<'a, R> {
+    inner: R,
+    progress: &'a UpdateProgress,
+    payload_size_limit: usize,
+    downloaded_bytes: u64,
+    total_bytes: Option<u64>,
+}
+
+impl<R: Read> Read for DownloadReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+
+        self.downloaded_bytes += read as u64;
+        if self.downloaded_bytes > self.payload_size_limit as u64 {
+            let message = format!("the file is larger than the payload size limit of {} bytes", self.payload_size_limit);
+            return Err(io::Error::new(io::ErrorKind::Other, message));
+        }
+
+        if read > 0 {
+            self.progress.report_download(self.downloaded_bytes, self.total_bytes);
+        }
+        Ok(read)
+    }
+}
+
+/// Parses the documents as the file is downloaded, it cannot be larger than the payload size limit of the requests.
+fn download_documents(
+    data: &Data,
+    source: &ImportSource,
+    format: ImportFormat,
+    progress: &UpdateProgress,
+) -> Result<Vec<Document>, String> {
+    let s3_region = data.import_keys.s3_region.as_deref().unwrap_or(DEFAULT_S3_REGION);
+    let mut response = download_request(data, source, s3_region).call();
+
+    // the buckets of the other regions refuse the request and answer with their region
+    if let ImportSource::S3 { .. } = source {
+        let bucket_region = response.header("x-amz-bucket-region").map(ToString::to_string);
+        match bucket_region {
+            Some(bucket_region) if !response.ok() && bucket_region != s3_region => {
+                response = download_request(data, source, &bucket_region).call();
+            }
+            _ => (),
+        }
+    }
+
+    if let Some(error) = response.synthetic_error() {
+        return Err(format!("the file could not be downloaded; {}", error));
+    }
+    if !response.ok() {
+        return Err(format!("the file could not be downloaded; the server responded with {}", response.status()));
+    }
+
+    let total_bytes = response.header("Content-Length").and_then(|length| length.parse().ok());
+    progress.report_download(0, total_bytes);
+    let reader = DownloadReader {
+        inner: response.into_reader(),
+        progress,
+        payload_size_limit: data.http_payload_size_limit,
+        downloaded_bytes: 0,
+        total_bytes,
+    };
+
+    parse_documents(BufReader::with_capacity(DOWNLOAD_CHUNK_SIZE, reader), format)
+}
+
+fn parse_documents<R: BufRead>(reader: R, format: ImportFormat) -> Result<Vec<Document>, String> {
+    match format {
+        ImportFormat::Json => serde_json::from_reader(reader).map_err(|e| {
+            if e.is_io() {
+                format!("the file could not be downloaded; {}", e)
+            } else {
+                format!("invalid JSON; {}", e)
+            }
+        }),
+        ImportFormat::Ndjson => {
+            let mut documents = Vec::new();
+            for (i, line) in reader.lines().enumerate() {
+                let line = line.map_err(|e| match e.kind() {
+                    io::ErrorKind::InvalidData => format!("invalid NDJSON; {}", e),
+                    _ => format!("the file could not be downloaded; {}", e),
+                })?;
+                if !line.trim().is_empty() {
+                    let document = serde_json::from_str(&line).map_err(|e| format!("invalid NDJSON at line {}; {}", i + 1, e))?;
+                    documents.push(document);
+                }
+            }
+            Ok(documents)
+        }
+    }
+}
+
+/// Sets the primary key and injects the referenced documents like `POST /indexes/{index_uid}/documents` does,
+/// returns the primary key detected in the documents, the update gives it to the index.
+fn prepare_documents(
+    data: &Data,
+    index_uid: &str,
+    primary_key: Option<&str>,
+    documents: &mut [Document],
+) -> Result<Option<String>, ResponseError> {
+    let index = data
+        .db
+        .open_index(index_uid)
+        .ok_or(Error::index_not_found(index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+    let mut schema = index
+        .main
+        .schema(&reader)?
+        .ok_or(Error::internal("Impossible to retrieve the schema"))?;
+
+    let mut detected_primary_key = set_primary_key(&mut schema, index_uid, primary_key, documents)?;
+    if detected_primary_key.is_some() && primary_key.is_some() {
+        data.db.main_write(|w| index.main.put_schema(w, &schema))?;
+        detected_primary_key = None;
+    }
+
+    inject_referenced_documents(&data.db, &reader, &index, documents)?;
+
+    Ok(detected_primary_key)
+}
+
+/// Downloads the documents of the import of the index, when its update is processed,
+/// the progress of the download is reported in the details of the update.
+pub fn import_documents(
+    data: &Data,
+    index_uid: &str,
+    import: &DocumentsImport,
+    progress: &UpdateProgress,
+) -> Result<ImportedDocuments, String> {
+    let source = ImportSource::parse(&import.url)?;
+    let mut documents = download_documents(data, &source, import.format, progress)?;
+    let detected_primary_key = prepare_documents(data, index_uid, import.primary_key.as_deref(), &mut documents)
+        .map_err(|e| e.to_string())?;
+    Ok((documents, detected_primary_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloud_storage_urls() {
+        let source = ImportSource::parse("s3://movies/2020/movies.ndjson").unwrap();
+        assert_eq!(source, ImportSource::S3 { bucket: "movies".to_string(), key: "2020/movies.ndjson".to_string() });
+        assert!(ImportSource::parse("gs://movies/").is_err());
+        assert!(ImportSource::parse("ftp://example.com/movies.ndjson").is_err());
+
+        assert_eq!(encode_key("2020/les misérables.ndjson"), "2020/les%20mis%C3%A9rables.ndjson");
+    }
+
+    #[test]
+    fn signing_key_matches_the_aws_example() {
+        // https://docs.aws.amazon.com/general/latest/gr/signature-v4-examples.html
+        let scheme = SigningScheme { service: "iam", ..S3_SIGNING };
+        let key = signing_key(&scheme, "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1");
+        assert_eq!(hex(&key), "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+    }
+}
@@ -1,23 +1,37 @@
-use std::cmp::Ordering;
+use std::cmp::{self, Ordering};
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
+use std::iter;
+use std::ops::Range;
 use std::time::Instant;
+use std::vec;
 
 use indexmap::IndexMap;
 use log::error;
 use meilisearch_core::{Filter, MainReader};
-use meilisearch_core::facets::FacetFilter;
+use meilisearch_core::facets::{FacetFilter, FacetNode};
+use meilisearch_core::query_parser::{matched_words, QueryAnalysis};
 use meilisearch_core::criterion::*;
-use meilisearch_core::settings::RankingRule;
-use meilisearch_core::{Highlight, Index, RankedMap};
+use meilisearch_core::settings::{RankingRule, DEFAULT_PROXIMITY_WINDOW, DEFAULT_RANKING_RULES};
+use meilisearch_core::{Document, DocumentId, Highlight, Index, RankedMap};
 use meilisearch_schema::{FieldId, Schema};
 use meilisearch_tokenizer::is_cjk;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use siphasher::sip::SipHasher;
 use slice_group_by::GroupBy;
+use utoipa::ToSchema;
 
 use crate::error::{Error, ResponseError};
+use crate::helpers::experiments::ExperimentGroup;
+use crate::helpers::query_id::new_query_id;
+
+/// The maximum number of characters of the explanation of the ranking of a hit.
+const MAX_EXPLANATION_LENGTH: usize = 512;
+
+/// The tags the matches of the highlighted attributes are wrapped in.
+const HTML_HIGHLIGHT_TAGS: (&str, &str) = ("<em>", "</em>");
+const MARKDOWN_HIGHLIGHT_TAGS: (&str, &str) = ("**", "**");
 
 pub trait IndexSearchExt {
     fn new_search(&self, query: String) -> SearchBuilder;
@@ -35,12 +49,26 @@ impl IndexSearchExt for Index {
             attributes_to_highlight: None,
             filters: None,
             matches: false,
+            matched_words: false,
             facet_filters: None,
             facets: None,
+            facet_tree: None,
+            candidates: None,
+            min_similarity: None,
+            max_wildcard_expansions: None,
+            ranking_rules: None,
+            image_hash: None,
+            count_only: false,
+            explain_ranking: false,
+            adaptive_crop: false,
+            highlight_tags: HTML_HIGHLIGHT_TAGS,
+            crop_side: CropSide::Both,
+            all_documents: false,
         }
     }
 }
 
+#[derive(Clone)]
 pub struct SearchBuilder<'a> {
     index: &'a Index,
     query: String,
@@ -51,8 +79,21 @@ pub struct SearchBuilder<'a> {
     attributes_to_highlight: Option<HashSet<String>>,
     filters: Option<String>,
     matches: bool,
+    matched_words: bool,
     facet_filters: Option<FacetFilter>,
-    facets: Option<Vec<(FieldId, String)>>
+    facets: Option<Vec<(FieldId, String)>>,
+    facet_tree: Option<Vec<(FieldId, String)>>,
+    candidates: Option<Vec<DocumentId>>,
+    min_similarity: Option<f32>,
+    max_wildcard_expansions: Option<usize>,
+    ranking_rules: Option<Vec<RankingRule>>,
+    image_hash: Option<u64>,
+    count_only: bool,
+    explain_ranking: bool,
+    adaptive_crop: bool,
+    highlight_tags: (&'static str, &'static str),
+    crop_side: CropSide,
+    all_documents: bool,
 }
 
 impl<'a> SearchBuilder<'a> {
@@ -71,6 +112,23 @@ impl<'a> SearchBuilder<'a> {
         self
     }
 
+    /// The values no longer than their crop length are left as they are.
+    pub fn adaptive_crop(&mut self) -> &SearchBuilder {
+        self.adaptive_crop = true;
+        self
+    }
+
+    pub fn crop_side(&mut self, value: CropSide) -> &SearchBuilder {
+        self.crop_side = value;
+        self
+    }
+
+    /// The matches are wrapped in `**` instead of `<em>` tags.
+    pub fn markdown_highlight(&mut self) -> &SearchBuilder {
+        self.highlight_tags = MARKDOWN_HIGHLIGHT_TAGS;
+        self
+    }
+
     pub fn attributes_to_retrieve(&mut self, value: HashSet<String>) -> &SearchBuilder {
         self.attributes_to_retrieve = Some(value);
         self
@@ -102,12 +160,81 @@ impl<'a> SearchBuilder<'a> {
         self
     }
 
+    pub fn get_matched_words(&mut self) -> &SearchBuilder {
+        self.matched_words = true;
+        self
+    }
+
     pub fn add_facets(&mut self, facets: Vec<(FieldId, String)>) -> &SearchBuilder {
         self.facets = Some(facets);
         self
     }
 
+    /// Counts the values of a hierarchical facet level by level, the tree is returned
+    /// in the facets distribution under the name of the attribute of the first level.
+    pub fn add_facet_tree(&mut self, levels: Vec<(FieldId, String)>) -> &SearchBuilder {
+        self.facet_tree = Some(levels);
+        self
+    }
+
+    /// Only the given documents are ranked.
+    pub fn candidates(&mut self, documents_ids: Vec<DocumentId>) -> &SearchBuilder {
+        self.candidates = Some(documents_ids);
+        self
+    }
+
+    pub fn min_similarity(&mut self, value: f32) -> &SearchBuilder {
+        self.min_similarity = Some(value);
+        self
+    }
+
+    pub fn max_wildcard_expansions(&mut self, value: usize) -> &SearchBuilder {
+        self.max_wildcard_expansions = Some(value);
+        self
+    }
+
+    /// Ranks the documents with these rules instead of the ones of the index settings.
+    pub fn ranking_rules(&mut self, value: Vec<RankingRule>) -> &SearchBuilder {
+        self.ranking_rules = Some(value);
+        self
+    }
+
+    /// Ranks the documents by the similarity of their image with the one of this perceptual hash first.
+    pub fn image_hash(&mut self, value: u64) -> &SearchBuilder {
+        self.image_hash = Some(value);
+        self
+    }
+
+    /// Only counts the documents matching the query, no hit is retrieved nor formatted.
+    pub fn count_only(&mut self) -> &SearchBuilder {
+        self.count_only = true;
+        self
+    }
+
+    /// Explains with the criteria that ranked it why each hit is at its position.
+    pub fn explain_ranking(&mut self) -> &SearchBuilder {
+        self.explain_ranking = true;
+        self
+    }
+
+    /// Returns all the documents in the order they were added instead of the ones matching the query.
+    pub fn all_documents(&mut self) -> &SearchBuilder {
+        self.all_documents = true;
+        self
+    }
+
     pub fn search(self, reader: &MainReader) -> Result<SearchResult, ResponseError> {
+        let (mut result, mut hits) = self.search_lazily(reader)?;
+        while let Some(hit) = hits.next_hit(reader) {
+            result.hits.push(hit?);
+        }
+
+        Ok(result)
+    }
+
+    /// Ranks the documents of the search without retrieving them, the results have no hits
+    /// but the ids of their documents, the hits are retrieved one by one by the `PendingHits`.
+    pub fn search_lazily(mut self, reader: &MainReader) -> Result<(SearchResult, PendingHits<'a>), ResponseError> {
         let schema = self
             .index
             .main
@@ -122,12 +249,34 @@ impl<'a> SearchBuilder<'a> {
             None => self.index.query_builder(),
         };
 
-        if let Some(filter_expression) = &self.filters {
-            let filter = Filter::parse(filter_expression, &schema)?;
-            let index = &self.index;
+        let filter = match &self.filters {
+            Some(filter_expression) => Some(Filter::parse(filter_expression, &schema)?),
+            None => None,
+        };
+
+        let pinned_hits = self.pinned_hits(reader, filter.as_ref())?;
+        let mut excluded_ids: HashSet<DocumentId> = pinned_hits.iter().map(|(id, _)| *id).collect();
+
+        let mut penalty_factors = HashMap::new();
+        for (id, penalty_factor) in self.demoted_documents(reader)? {
+            if excluded_ids.contains(&id) {
+                continue;
+            }
+            if penalty_factor == 0.0 {
+                excluded_ids.insert(id);
+            } else {
+                penalty_factors.insert(id, penalty_factor);
+            }
+        }
+
+        // the pinned documents are placed once the others are ranked
+        // and the documents demoted with a penalty factor of 0 are removed
+        query_builder.set_excluded_documents(excluded_ids.into_iter().collect());
+
+        if let Some(filter) = filter {
+            let index = self.index;
             query_builder.with_filter(move |id| {
                 let reader = &reader;
-                let filter = &filter;
                 match filter.test(reader, index, id) {
                     Ok(res) => res,
                     Err(e) => {
@@ -140,7 +289,7 @@ impl<'a> SearchBuilder<'a> {
 
         if let Some(field) = self.index.main.distinct_attribute(reader)? {
             if let Some(field_id) = schema.id(&field) {
-                let index = &self.index;
+                let index = self.index;
                 query_builder.with_distinct(1, move |id| {
                     match index.document_attribute_bytes(reader, id, field_id) {
                         Ok(Some(bytes)) => {
@@ -154,12 +303,58 @@ impl<'a> SearchBuilder<'a> {
             }
         }
 
-        query_builder.set_facet_filter(self.facet_filters);
-        query_builder.set_facets(self.facets);
+        // the distances of the hits are computed before the builder gives its fields to the query
+        let image_distance = self.image_distance(reader, &ranked_map, &schema)?;
+
+        query_builder.set_facet_filter(self.facet_filters.take());
+        query_builder.set_facets(self.facets.take());
+        let facet_tree_root = self.facet_tree.as_ref().and_then(|levels| levels.first()).map(|(_, name)| name.clone());
+        query_builder.set_facet_tree(self.facet_tree.take());
+        query_builder.set_candidates(self.candidates.take());
+        query_builder.set_min_similarity(self.min_similarity);
+        if let Some(max_wildcard_expansions) = self.max_wildcard_expansions {
+            query_builder.set_max_wildcard_expansions(max_wildcard_expansions);
+        }
+        query_builder.set_explain_ranking(self.explain_ranking);
+        query_builder.set_all_documents(self.all_documents);
+
+        // the positions of the pinned and demoted documents shift the ranked ones, that must all
+        // be known, the demoted documents make as many other ones move up to the range
+        let range = self.offset..(self.offset + self.limit);
+        let moved_documents = !self.count_only && (!pinned_hits.is_empty() || !penalty_factors.is_empty());
+        let ranked_range = if self.count_only {
+            0..0
+        } else if moved_documents {
+            0..(range.end + penalty_factors.len())
+        } else {
+            range.clone()
+        };
 
         let start = Instant::now();
-        let result = query_builder.query(reader, &self.query, self.offset..(self.offset + self.limit));
-        let search_result = result.map_err(Error::search_documents)?;
+        let result = query_builder.query(reader, &self.query, ranked_range.clone());
+        let mut search_result = result.map_err(Error::search_documents)?;
+        if moved_documents {
+            // less documents than asked for means that they are all there
+            let complete = search_result.documents.len() < ranked_range.end;
+            let documents = demote_documents(search_result.documents, complete, |document| penalty_factors.get(&document.id).cloned());
+
+            search_result.nb_hits += pinned_hits.len();
+            let pinned_documents = pinned_hits
+                .into_iter()
+                .map(|(id, position)| {
+                    let document = Document {
+                        id,
+                        highlights: Vec::new(),
+                        phonetic_match: false,
+                        ranking_explanation: vec!["pinned".to_string()],
+                    };
+                    (document, position)
+                })
+                .collect();
+            search_result.documents = pin_documents(documents, pinned_documents, complete, range);
+        } else if self.count_only {
+            search_result.nb_hits += pinned_hits.len();
+        }
         let time_ms = start.elapsed().as_millis() as usize;
 
         let mut all_attributes: HashSet<&str> = HashSet::new();
@@ -189,70 +384,131 @@ impl<'a> SearchBuilder<'a> {
             },
         }
 
-        let mut hits = Vec::with_capacity(self.limit);
-        for doc in search_result.documents {
-            let mut document: IndexMap<String, Value> = self
-                .index
-                .document(reader, Some(&all_attributes), doc.id)
-                .map_err(|e| Error::retrieve_document(doc.id.0, e))?
-                .ok_or(Error::internal(
-                    "Impossible to retrieve the document; Corrupted data",
-                ))?;
-
-            let mut formatted = document.iter()
-                .filter(|(key, _)| all_formatted.contains(key.as_str()))
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect();
-
-            let mut matches = doc.highlights.clone();
-
-            // Crops fields if needed
-            if let Some(fields) = &self.attributes_to_crop {
-                crop_document(&mut formatted, &mut matches, &schema, fields);
-            }
-
-            // Transform to readable matches
-            if let Some(attributes_to_highlight) = &self.attributes_to_highlight {
-                let matches = calculate_matches(
-                    &matches,
-                    self.attributes_to_highlight.clone(),
-                    &schema,
-                );
-                formatted = calculate_highlights(&formatted, &matches, attributes_to_highlight);
-            }
-
-            let matches_info = if self.matches {
-                Some(calculate_matches(&matches, self.attributes_to_retrieve.clone(), &schema))
-            } else {
-                None
-            };
-
-            if let Some(attributes_to_retrieve) = &self.attributes_to_retrieve {
-                document.retain(|key, _| attributes_to_retrieve.contains(&key.to_string()))
-            }
-
-            let hit = SearchHit {
-                document,
-                formatted,
-                matches_info,
-            };
+        // The attributes that are not displayed must never be returned,
+        // whatever the attributes to retrieve, to highlight or to crop are.
+        let displayed_attributes = schema.displayed_name();
+        all_attributes.retain(|attribute| displayed_attributes.contains(attribute));
+        all_formatted.retain(|attribute| displayed_attributes.contains(attribute));
+
+        let all_attributes = all_attributes.into_iter().map(str::to_string).collect();
+        let all_formatted = all_formatted.into_iter().map(str::to_string).collect();
+
+        let documents_ids = search_result.documents.iter().map(|doc| doc.id).collect();
+        let documents: Vec<_> = search_result
+            .documents
+            .into_iter()
+            .map(|doc| {
+                let distance = image_distance.as_ref().and_then(|image_distance| image_distance.distance(doc.id));
+                (doc, distance)
+            })
+            .collect();
+
+        let warnings = search_result
+            .truncated_wildcards
+            .iter()
+            .map(|prefix| format!("{}* matches too many words, only the first ones are searched", prefix))
+            .collect();
 
-            hits.push(hit);
+        let mut facets_distribution: Option<HashMap<_, _>> = search_result.facets.map(|facets| {
+            facets.into_iter().map(|(name, values)| (name, FacetDistribution::Values(values.into_iter().collect()))).collect()
+        });
+        if let (Some(root), Some(tree)) = (facet_tree_root, search_result.facet_tree) {
+            facets_distribution.get_or_insert_with(HashMap::new).insert(root, FacetDistribution::Tree(tree));
         }
 
         let results = SearchResult {
-            hits,
+            hits: Vec::new(),
             offset: self.offset,
             limit: self.limit,
             nb_hits: search_result.nb_hits,
             exhaustive_nb_hits: search_result.exhaustive_nb_hit,
             processing_time_ms: time_ms,
             query: self.query.to_string(),
-            facets_distribution: search_result.facets,
+            query_id: new_query_id(),
+            experiment_group: None,
+            query_analysis: None,
+            facets_distribution,
             exhaustive_facets_count: search_result.exhaustive_facets_count,
+            warnings,
+            fallback_strategy: None,
+            aggregations: None,
+            documents_ids,
+        };
+
+        let hits = PendingHits {
+            search: self,
+            schema,
+            all_attributes,
+            all_formatted,
+            documents: documents.into_iter().enumerate(),
         };
 
-        Ok(results)
+        Ok((results, hits))
+    }
+
+    /// Returns the documents pinned for the query along with their positions, the documents
+    /// that are not in the index or that do not match the filters nor the facet filters are ignored.
+    fn pinned_hits(&self, reader: &MainReader, filter: Option<&Filter>) -> Result<Vec<(DocumentId, Option<usize>)>, ResponseError> {
+        let pinned_hits = self.index.main.pinned_hits(reader)?.unwrap_or_default();
+
+        let mut documents: Vec<(DocumentId, Option<usize>)> = Vec::new();
+        for hit in pinned_hits.iter().filter(|hit| hit.matches(&self.query)) {
+            if let Some(id) = self.index.main.external_to_internal_docid(reader, &hit.id)? {
+                if let Some(filter) = filter {
+                    if !filter.test(reader, &self.index, id)? {
+                        continue;
+                    }
+                }
+                if let Some(facet_filters) = &self.facet_filters {
+                    if !facet_filters.matches(reader, &self.index, id)? {
+                        continue;
+                    }
+                }
+                if documents.iter().all(|(pinned_id, _)| *pinned_id != id) {
+                    documents.push((id, hit.position));
+                }
+            }
+        }
+
+        Ok(documents)
+    }
+
+    /// Returns the documents demoted for the query along with their penalty factors,
+    /// the documents that are not in the index are ignored.
+    fn demoted_documents(&self, reader: &MainReader) -> Result<Vec<(DocumentId, f64)>, ResponseError> {
+        let demoted_documents = self.index.main.demoted_documents(reader)?.unwrap_or_default();
+
+        let mut documents = Vec::new();
+        for document in demoted_documents.iter().filter(|document| document.matches(&self.query)) {
+            if let Some(id) = self.index.main.external_to_internal_docid(reader, &document.id)? {
+                documents.push((id, document.penalty_factor));
+            }
+        }
+
+        Ok(documents)
+    }
+
+    /// Returns the criterion measuring the distance between the image of the documents and
+    /// the one of the query, when the query has an image hash.
+    fn image_distance<'r>(
+        &self,
+        reader: &MainReader,
+        ranked_map: &'r RankedMap,
+        schema: &Schema,
+    ) -> Result<Option<ImageDistance<'r>>, ResponseError> {
+        let hash = match self.image_hash {
+            Some(hash) => hash,
+            None => return Ok(None),
+        };
+
+        let field_id = self
+            .index
+            .main
+            .image_hash_field(reader)?
+            .and_then(|field| schema.id(&field))
+            .ok_or(Error::bad_parameter("imageHash", "the index has no imageHashField"))?;
+
+        Ok(Some(ImageDistance::new(ranked_map, field_id, hash)))
     }
 
     pub fn get_criteria(
@@ -261,18 +517,45 @@ impl<'a> SearchBuilder<'a> {
         ranked_map: &'a RankedMap,
         schema: &Schema,
     ) -> Result<Option<Criteria<'a>>, ResponseError> {
-        let ranking_rules = self.index.main.ranking_rules(reader)?;
+        let image_distance = self.image_distance(reader, ranked_map, schema)?;
+        let cross_field_boost = self.index.main.cross_field_boost(reader)?.unwrap_or(false);
+
+        let ranking_rules = match &self.ranking_rules {
+            Some(ranking_rules) => Some(ranking_rules.clone()),
+            None => self.index.main.ranking_rules(reader)?,
+        };
+
+        let ranking_rules = match ranking_rules {
+            None if image_distance.is_some() || cross_field_boost => Some(DEFAULT_RANKING_RULES.to_vec()),
+            ranking_rules => ranking_rules,
+        };
 
         if let Some(ranking_rules) = ranking_rules {
-            let mut builder = CriteriaBuilder::with_capacity(7 + ranking_rules.len());
+            let proximity_window = self.index.main.proximity_window(reader)?.unwrap_or(DEFAULT_PROXIMITY_WINDOW);
+            let proximity_precision = self.index.main.proximity_precision(reader)?.unwrap_or_default();
+
+            let mut builder = CriteriaBuilder::with_capacity(8 + ranking_rules.len());
+            // the visually closest documents come first, the ranking rules break the ties
+            if let Some(image_distance) = image_distance {
+                builder.push(image_distance);
+            }
+            // the documents matching the words in more fields come first, after the documents
+            // matching more words
+            let mut cross_field = if cross_field_boost { Some(CrossField) } else { None };
             for rule in ranking_rules {
                 match rule {
                     RankingRule::Typo => builder.push(Typo),
-                    RankingRule::Words => builder.push(Words),
-                    RankingRule::Proximity => builder.push(Proximity),
+                    RankingRule::Words => {
+                        builder.push(Words);
+                        if let Some(cross_field) = cross_field.take() {
+                            builder.push(cross_field);
+                        }
+                    }
+                    RankingRule::Proximity => builder.push(Proximity::new(proximity_window, proximity_precision)),
                     RankingRule::Attribute => builder.push(Attribute),
                     RankingRule::WordsPosition => builder.push(WordsPosition),
                     RankingRule::Exactness => builder.push(Exactness),
+                    RankingRule::ExactMatch => builder.push(ExactMatch),
                     RankingRule::Asc(field) => {
                         match SortByAttr::lower_is_better(&ranked_map, &schema, &field) {
                             Ok(rule) => builder.push(rule),
@@ -287,7 +570,10 @@ impl<'a> SearchBuilder<'a> {
                     }
                 }
             }
-            builder.push(DocumentId);
+            if let Some(cross_field) = cross_field {
+                builder.push(cross_field);
+            }
+            builder.push(meilisearch_core::criterion::DocumentId);
             return Ok(Some(builder.build()));
         }
 
@@ -295,6 +581,113 @@ impl<'a> SearchBuilder<'a> {
     }
 }
 
+/// The hits of a search that are still to be retrieved, in the order of the results.
+pub struct PendingHits<'a> {
+    search: SearchBuilder<'a>,
+    schema: Schema,
+    all_attributes: HashSet<String>,
+    all_formatted: HashSet<String>,
+    documents: iter::Enumerate<vec::IntoIter<(Document, Option<u32>)>>,
+}
+
+impl<'a> PendingHits<'a> {
+    /// Retrieves and formats the next hit, returns `None` once they are all retrieved.
+    pub fn next_hit(&mut self, reader: &MainReader) -> Option<Result<SearchHit, ResponseError>> {
+        let (i, (doc, image_distance)) = self.documents.next()?;
+        Some(self.hit(reader, i, doc, image_distance))
+    }
+
+    fn hit(&self, reader: &MainReader, i: usize, doc: Document, image_distance: Option<u32>) -> Result<SearchHit, ResponseError> {
+        let search = &self.search;
+        let schema = &self.schema;
+        let all_attributes: HashSet<&str> = self.all_attributes.iter().map(String::as_str).collect();
+
+        let mut document: IndexMap<String, Value> = search
+            .index
+            .document(reader, Some(&all_attributes), doc.id)
+            .map_err(|e| Error::retrieve_document(doc.id.0, e))?
+            .ok_or(Error::internal(
+                "Impossible to retrieve the document; Corrupted data",
+            ))?;
+
+        let mut formatted = document.iter()
+            .filter(|(key, _)| self.all_formatted.contains(key.as_str()))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        let mut matches = doc.highlights.clone();
+
+        // Crops fields if needed
+        if let Some(fields) = &search.attributes_to_crop {
+            crop_document(&mut formatted, &mut matches, schema, fields, search.adaptive_crop, search.crop_side);
+        }
+
+        // Transform to readable matches
+        if let Some(attributes_to_highlight) = &search.attributes_to_highlight {
+            let matches = calculate_matches(
+                &matches,
+                search.attributes_to_highlight.clone(),
+                schema,
+            );
+            formatted = calculate_highlights(&formatted, &matches, attributes_to_highlight, search.highlight_tags);
+        }
+
+        let matches_info = if search.matches {
+            Some(calculate_matches(&matches, search.attributes_to_retrieve.clone(), schema))
+        } else {
+            None
+        };
+
+        let matched_words = if search.matched_words {
+            Some(matched_words(reader, search.index, doc.id, &search.query, search.min_similarity)?)
+        } else {
+            None
+        };
+
+        if let Some(attributes_to_retrieve) = &search.attributes_to_retrieve {
+            document.retain(|key, _| attributes_to_retrieve.contains(&key.to_string()))
+        }
+
+        Ok(SearchHit {
+            document,
+            formatted,
+            matches_info,
+            matched_words,
+            phonetic_match: doc.phonetic_match,
+            image_distance,
+            explanation: if search.explain_ranking {
+                Some(ranking_explanation(search.offset + i + 1, &doc.ranking_explanation))
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// What is searched instead when the query found no hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FallbackStrategy {
+    /// Nothing, the results stay empty.
+    None,
+    /// The query again with as many typos as possible allowed, whatever the `minSimilarity`.
+    TypoRelax,
+    /// All the documents, in the order they were added.
+    All,
+}
+
+/// The side of the first match the context of a cropped value is taken on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CropSide {
+    /// The text before the match.
+    Left,
+    /// The text after the match.
+    Right,
+    /// The text around the match.
+    Both,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct MatchPosition {
     pub start: usize,
@@ -322,11 +715,42 @@ pub struct SearchHit {
     pub formatted: IndexMap<String, Value>,
     #[serde(rename = "_matchesInfo", skip_serializing_if = "Option::is_none")]
     pub matches_info: Option<MatchesInfos>,
+    /// The words of the query found in the document.
+    #[serde(rename = "_matchedWords", skip_serializing_if = "Option::is_none")]
+    pub matched_words: Option<Vec<String>>,
+    #[serde(rename = "_phoneticMatch", default, skip_serializing_if = "is_false")]
+    pub phonetic_match: bool,
+    /// The number of bits that differ between the hash of the document image and the query one.
+    #[serde(rename = "_imageDistance", default, skip_serializing_if = "Option::is_none")]
+    pub image_distance: Option<u32>,
+    /// Why the hit is ranked at its position, see `showHitExplanation`.
+    #[serde(rename = "_explanation", default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Describes the criteria that ranked the hit at this position, in the order they were applied,
+/// truncated to `MAX_EXPLANATION_LENGTH` characters.
+fn ranking_explanation(position: usize, criteria: &[String]) -> String {
+    let explanation = if criteria.is_empty() {
+        format!("Ranked #{} by: no criterion, tied with the hits around it", position)
+    } else {
+        format!("Ranked #{} by: {}", position, criteria.join(", then "))
+    };
+
+    match explanation.char_indices().nth(MAX_EXPLANATION_LENGTH) {
+        Some((end, _)) => explanation[..end].to_string(),
+        None => explanation,
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchResult {
+    #[schema(value_type = Vec<Object>)]
     pub hits: Vec<SearchHit>,
     pub offset: usize,
     pub limit: usize,
@@ -334,14 +758,115 @@ pub struct SearchResult {
     pub exhaustive_nb_hits: bool,
     pub processing_time_ms: usize,
     pub query: String,
+    /// Identifies this search when reporting the clicks on its hits.
+    pub query_id: String,
+    /// The group of the active ranking rules experiment this search fell into.
+    #[serde(rename = "_experimentGroup", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub experiment_group: Option<ExperimentGroup>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub facets_distribution: Option<HashMap<String, HashMap<String, usize>>>,
+    #[schema(value_type = Option<Object>)]
+    pub query_analysis: Option<QueryAnalysis>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub facets_distribution: Option<HashMap<String, FacetDistribution>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exhaustive_facets_count: Option<bool>,
+    /// Why the results may be incomplete, e.g. a wildcard matching too many words.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// The fallback that found the hits, the query itself found none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub fallback_strategy: Option<FallbackStrategy>,
+    /// The hits of each aggregation, by name, in the order they were asked for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub aggregations: Option<IndexMap<String, AggregationResult>>,
+    /// The internal ids of the hits, in the same order.
+    #[serde(skip)]
+    pub documents_ids: Vec<DocumentId>,
+}
+
+/// The first hits of the results matching the filter of an aggregation, ranked by its sort.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregationResult {
+    pub hits: Vec<SearchHit>,
+    /// Like the one of the search, counts the documents matching the query before the filter is applied.
+    pub nb_hits: usize,
+}
+
+/// The values of a facet and the number of hits having them, in the order they are serialized in.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum FacetDistribution {
+    Values(IndexMap<String, usize>),
+    /// The values of a hierarchical facet along with the ones of its next levels.
+    Tree(IndexMap<String, FacetNode>),
+}
+
+/// Moves down the ranked documents that have a penalty factor, the position of such a
+/// document is divided by its factor. The documents must be the first ones of the results,
+/// `complete` tells whether they are all there.
+fn demote_documents<T, F>(documents: Vec<T>, complete: bool, penalty_factor: F) -> Vec<T>
+where F: Fn(&T) -> Option<f64>,
+{
+    let mut kept = Vec::with_capacity(documents.len());
+    let mut demoted = Vec::new();
+    for (index, document) in documents.into_iter().enumerate() {
+        match penalty_factor(&document) {
+            Some(factor) => {
+                let position = ((index + 1) as f64 / factor).ceil() as usize;
+                demoted.push((document, position));
+            }
+            None => kept.push(document),
+        }
+    }
+
+    demoted.sort_by_key(|(_, position)| *position);
+    for (document, position) in demoted {
+        let index = position - 1;
+        if index <= kept.len() {
+            kept.insert(index, document);
+        } else if complete {
+            kept.push(document);
+        }
+    }
+
+    kept
+}
+
+/// Inserts the pinned documents at their positions, starting at 1, among the ranked documents,
+/// that must be the first ones of the results, and returns the documents of the range.
+/// A document pinned at a position already taken goes right after it, the documents without
+/// a position are appended once all the ranked documents are known, `complete` tells whether they are.
+fn pin_documents<T>(mut documents: Vec<T>, pinned: Vec<(T, Option<usize>)>, complete: bool, range: Range<usize>) -> Vec<T> {
+    let (mut positioned, appended): (Vec<_>, Vec<_>) = pinned.into_iter().partition(|(_, position)| position.is_some());
+    positioned.sort_by_key(|(_, position)| *position);
+
+    let mut min_index = 0;
+    for (document, position) in positioned {
+        let index = cmp::max(position.unwrap_or_default().saturating_sub(1), min_index);
+        min_index = index + 1;
+        if index <= documents.len() {
+            documents.insert(index, document);
+        } else if complete {
+            documents.push(document);
+        }
+    }
+
+    if complete {
+        documents.extend(appended.into_iter().map(|(document, _)| document));
+    }
+
+    let Range { start, end } = range;
+    documents.into_iter().skip(start).take(end - start).collect()
 }
 
-/// returns the start index and the length on the crop.
-fn aligned_crop(text: &str, match_index: usize, context: usize) -> (usize, usize) {
+/// returns the start index and the length on the crop, the crop keeps `context` characters
+/// on both sides of the match or twice as many on a single side.
+fn aligned_crop(text: &str, match_index: usize, context: usize, side: CropSide) -> (usize, usize) {
     let is_word_component = |c: &char| c.is_alphanumeric() && !is_cjk(*c);
 
     let word_end_index = |mut index| {
@@ -355,7 +880,12 @@ fn aligned_crop(text: &str, match_index: usize, context: usize) -> (usize, usize
         // count need to be at least 1 for cjk queries to return something
         return (match_index, 1 + text.chars().skip(match_index).take_while(is_word_component).count());
     }
-    let start = match match_index.saturating_sub(context) {
+    let (before, after) = match side {
+        CropSide::Left => (2 * context, 0),
+        CropSide::Right => (0, 2 * context),
+        CropSide::Both => (context, context),
+    };
+    let start = match match_index.saturating_sub(before) {
         0 => 0,
         n => {
             let word_end_index = word_end_index(n);
@@ -363,7 +893,8 @@ fn aligned_crop(text: &str, match_index: usize, context: usize) -> (usize, usize
             word_end_index + text.chars().skip(word_end_index).take_while(char::is_ascii_whitespace).count()
         }
     };
-    let end = word_end_index(match_index + context);
+    // the word of the match is always kept
+    let end = word_end_index(match_index + cmp::max(after, 1));
 
     (start, end - start)
 }
@@ -372,11 +903,12 @@ fn crop_text(
     text: &str,
     matches: impl IntoIterator<Item = Highlight>,
     context: usize,
+    side: CropSide,
 ) -> (String, Vec<Highlight>) {
     let mut matches = matches.into_iter().peekable();
 
     let char_index = matches.peek().map(|m| m.char_index as usize).unwrap_or(0);
-    let (start, count) = aligned_crop(text, char_index, context);
+    let (start, count) = aligned_crop(text, char_index, context, side);
 
     // TODO do something about double allocation
     let text = text
@@ -404,6 +936,8 @@ fn crop_document(
     matches: &mut Vec<Highlight>,
     schema: &Schema,
     fields: &HashMap<String, usize>,
+    adaptive: bool,
+    side: CropSide,
 ) {
     matches.sort_unstable_by_key(|m| (m.char_index, m.char_length));
 
@@ -419,8 +953,13 @@ fn crop_document(
             .cloned();
 
         if let Some(Value::String(ref mut original_text)) = document.get_mut(field) {
+            // an adaptive crop keeps the values no longer than the crop length as they are
+            if adaptive && original_text.chars().count() <= *length {
+                continue;
+            }
+
             let (cropped_text, cropped_matches) =
-                crop_text(original_text, selected_matches, *length);
+                crop_text(original_text, selected_matches, *length, side);
 
             *original_text = cropped_text;
 
@@ -472,6 +1011,7 @@ fn calculate_highlights(
     document: &IndexMap<String, Value>,
     matches: &MatchesInfos,
     attributes_to_highlight: &HashSet<String>,
+    (pre_tag, post_tag): (&str, &str),
 ) -> IndexMap<String, Value> {
     let mut highlight_result = document.clone();
 
@@ -492,9 +1032,9 @@ fn calculate_highlights(
                     let highlighted = value.get(m.start..(m.start + m.length));
                     if let (Some(before), Some(highlighted)) = (before, highlighted) {
                         highlighted_value.extend(before);
-                        highlighted_value.push_str("<em>");
+                        highlighted_value.push_str(pre_tag);
                         highlighted_value.extend(highlighted);
-                        highlighted_value.push_str("</em>");
+                        highlighted_value.push_str(post_tag);
                         index = m.start + m.length;
                     } else {
                         error!("value: {:?}; index: {:?}, match: {:?}", value, index, m);
@@ -517,16 +1057,16 @@ mod tests {
         let text = r#"En ce début de trentième millénaire, l'Empire n'a jamais été aussi puissant, aussi étendu à travers toute la galaxie. C'est dans sa capitale, Trantor, que l'éminent savant Hari Seldon invente la psychohistoire, une science toute nouvelle, à base de psychologie et de mathématiques, qui lui permet de prédire l'avenir... C'est-à-dire l'effondrement de l'Empire d'ici cinq siècles et au-delà, trente mille années de chaos et de ténèbres. Pour empêcher cette catastrophe et sauver la civilisation, Seldon crée la Fondation."#;
 
         // simple test
-        let (start, length) = aligned_crop(&text, 6, 2);
+        let (start, length) = aligned_crop(&text, 6, 2, CropSide::Both);
         let cropped =  text.chars().skip(start).take(length).collect::<String>().trim().to_string();
         assert_eq!("début", cropped);
 
         // first word test
-        let (start, length) = aligned_crop(&text, 0, 1);
+        let (start, length) = aligned_crop(&text, 0, 1, CropSide::Both);
         let cropped =  text.chars().skip(start).take(length).collect::<String>().trim().to_string();
         assert_eq!("En", cropped);
         // last word test
-        let (start, length) = aligned_crop(&text, 510, 2);
+        let (start, length) = aligned_crop(&text, 510, 2, CropSide::Both);
         let cropped =  text.chars().skip(start).take(length).collect::<String>().trim().to_string();
         assert_eq!("Fondation", cropped);
 
@@ -534,16 +1074,90 @@ mod tests {
         let text = "this isのス foo myタイリ test";
 
         // mixed charset
-        let (start, length) = aligned_crop(&text, 5, 3);
+        let (start, length) = aligned_crop(&text, 5, 3, CropSide::Both);
         let cropped =  text.chars().skip(start).take(length).collect::<String>().trim().to_string();
         assert_eq!("isの", cropped);
 
         // split regular word / CJK word, no space
-        let (start, length) = aligned_crop(&text, 7, 1);
+        let (start, length) = aligned_crop(&text, 7, 1, CropSide::Both);
         let cropped =  text.chars().skip(start).take(length).collect::<String>().trim().to_string();
         assert_eq!("の", cropped);
     }
 
+    #[test]
+    fn one_sided_crops() {
+        let text = "En ce début de trentième millénaire, l'Empire n'a jamais été aussi puissant.";
+
+        let (start, length) = aligned_crop(&text, 6, 5, CropSide::Left);
+        let cropped =  text.chars().skip(start).take(length).collect::<String>().trim().to_string();
+        assert_eq!("En ce début", cropped);
+
+        let (start, length) = aligned_crop(&text, 6, 5, CropSide::Right);
+        let cropped =  text.chars().skip(start).take(length).collect::<String>().trim().to_string();
+        assert_eq!("début de trentième", cropped);
+    }
+
+    #[test]
+    fn adaptive_crops() {
+        let data = r#"{
+            "title": " Fondation ",
+            "description": "En ce début de trentième millénaire, l'Empire n'a jamais été aussi puissant."
+        }"#;
+
+        let mut schema = Schema::with_primary_key("title");
+        schema.insert("description").unwrap();
+
+        let mut fields = HashMap::new();
+        fields.insert("title".to_string(), 20);
+        fields.insert("description".to_string(), 20);
+
+        // the values no longer than the crop length are kept as they are
+        let mut document: IndexMap<String, Value> = serde_json::from_str(data).unwrap();
+        super::crop_document(&mut document, &mut Vec::new(), &schema, &fields, true, CropSide::Both);
+        assert_eq!(document["title"], " Fondation ");
+        assert_eq!(document["description"], "En ce début de trentième");
+
+        let mut document: IndexMap<String, Value> = serde_json::from_str(data).unwrap();
+        super::crop_document(&mut document, &mut Vec::new(), &schema, &fields, false, CropSide::Both);
+        assert_eq!(document["title"], "Fondation");
+        assert_eq!(document["description"], "En ce début de trentième");
+    }
+
+    #[test]
+    fn pinned_documents() {
+        let pinned = vec![(10, Some(2)), (11, None), (12, Some(2)), (13, Some(100))];
+
+        // all the ranked documents are known, the documents past the end are appended
+        let documents = pin_documents(vec![1, 2, 3], pinned.clone(), true, 0..20);
+        assert_eq!(documents, vec![1, 10, 12, 2, 3, 13, 11]);
+
+        let documents = pin_documents(vec![1, 2, 3], pinned.clone(), true, 2..4);
+        assert_eq!(documents, vec![12, 2]);
+
+        // there are more ranked documents than the ones returned
+        let documents = pin_documents(vec![1, 2, 3, 4], pinned, false, 0..4);
+        assert_eq!(documents, vec![1, 10, 12, 2]);
+    }
+
+    #[test]
+    fn demoted_documents() {
+        let penalty_factor = |document: &i32| match document {
+            1 => Some(0.25),
+            3 => Some(0.5),
+            _ => None,
+        };
+
+        let documents = demote_documents(vec![1, 2, 3, 4, 5, 6], true, penalty_factor);
+        assert_eq!(documents, vec![2, 4, 5, 1, 6, 3]);
+
+        // the documents moved past the known ones are appended when they are all known
+        let documents = demote_documents(vec![1, 2, 3], true, penalty_factor);
+        assert_eq!(documents, vec![2, 1, 3]);
+
+        let documents = demote_documents(vec![1, 2, 3], false, penalty_factor);
+        assert_eq!(documents, vec![2]);
+    }
+
     #[test]
     fn calculate_matches() {
         let mut matches = Vec::new();
@@ -600,7 +1214,7 @@ mod tests {
             length: 9,
         });
         matches.insert("description".to_string(), m);
-        let result = super::calculate_highlights(&document, &matches, &attributes_to_highlight);
+        let result = super::calculate_highlights(&document, &matches, &attributes_to_highlight, HTML_HIGHLIGHT_TAGS);
 
         let mut result_expected = IndexMap::new();
         result_expected.insert(
@@ -635,7 +1249,7 @@ mod tests {
         });
         matches.insert("title".to_string(), m);
 
-        let result = super::calculate_highlights(&document, &matches, &attributes_to_highlight);
+        let result = super::calculate_highlights(&document, &matches, &attributes_to_highlight, HTML_HIGHLIGHT_TAGS);
 
         let mut result_expected = IndexMap::new();
         result_expected.insert(
@@ -645,4 +1259,21 @@ mod tests {
 
         assert_eq!(result, result_expected);
     }
+
+    #[test]
+    fn markdown_highlights() {
+        let data = r#"{
+            "title": "Fondation (Isaac ASIMOV)"
+        }"#;
+
+        let document: IndexMap<String, Value> = serde_json::from_str(data).unwrap();
+        let mut attributes_to_highlight = HashSet::new();
+        attributes_to_highlight.insert("title".to_string());
+
+        let mut matches = HashMap::new();
+        matches.insert("title".to_string(), vec![MatchPosition { start: 0, length: 9 }]);
+
+        let result = super::calculate_highlights(&document, &matches, &attributes_to_highlight, MARKDOWN_HIGHLIGHT_TAGS);
+        assert_eq!(result["title"], "**Fondation** (Isaac ASIMOV)");
+    }
 }
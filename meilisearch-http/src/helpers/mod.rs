@@ -1,6 +1,31 @@
 pub mod authentication;
+pub mod background;
+pub mod batches;
+pub mod compression;
+pub mod embeddings;
+pub mod experiments;
+pub mod idempotency;
+pub mod imports;
 pub mod meilisearch;
 pub mod normalize_path;
+pub mod public_hosts;
+pub mod query_analytics;
+pub mod query_id;
+pub mod read_only;
+pub mod references;
+pub mod request_timeout;
+pub mod token_sets;
+pub mod webhook;
 
 pub use authentication::Authentication;
+pub use background::BackgroundWorker;
+pub use batches::DocumentsBatches;
+pub use compression::CompressResponse;
+pub use embeddings::PendingEmbeddings;
+pub use experiments::Experiments;
+pub use idempotency::IdempotencyKeys;
 pub use normalize_path::NormalizePath;
+pub use query_analytics::QueryAnalytics;
+pub use read_only::ReadOnly;
+pub use request_timeout::RequestTimeout;
+pub use token_sets::TokenSetsCache;
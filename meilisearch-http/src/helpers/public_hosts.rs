@@ -0,0 +1,79 @@
+use std::io;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+
+/// Resolves the address of the host, `host:port`, and refuses the loopback, private or
+/// link-local addresses, the server must not be used to reach the services of its own network.
+pub fn public_addresses(netloc: &str) -> io::Result<Vec<SocketAddr>> {
+    let addresses: Vec<_> = netloc.to_socket_addrs()?.collect();
+
+    if addresses.iter().any(|address| !is_public_ip(address.ip())) {
+        let message = "the host must not be a loopback, private or link-local address";
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, message));
+    }
+
+    Ok(addresses)
+}
+
+/// Refuses the URLs whose host resolves to a loopback, private or link-local address, they are
+/// checked again when the request is sent, the host could be resolved to another address.
+pub fn check_public_url(url: &str) -> Result<(), String> {
+    let host = ureq::get(url).get_host().map_err(|e| e.to_string())?;
+
+    public_addresses(&format!("{}:0", host))
+        .map(drop)
+        .map_err(|e| format!("the host could not be resolved; {}", e))
+}
+
+/// Returns an agent connecting only to the public addresses, unless the private ones are allowed.
+/// The addresses are checked as the connections are made, the redirections included, a host
+/// cannot resolve to another address once checked.
+pub fn agent(allow_private: bool) -> ureq::Agent {
+    let mut agent = ureq::Agent::new();
+    if !allow_private {
+        agent.set_resolver(public_addresses);
+    }
+    agent
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            let shared = a == 100 && (b & 0xc0) == 64; // 100.64.0.0/10
+            !(ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified()
+                || ip.is_broadcast() || ip.is_multicast() || ip.is_documentation() || shared || a == 0)
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            let unique_local = (first & 0xfe00) == 0xfc00; // fc00::/7
+            let link_local = (first & 0xffc0) == 0xfe80; // fe80::/10
+            let mapped = match ip.segments() {
+                [0, 0, 0, 0, 0, 0xffff, ..] => ip.to_ipv4().map(IpAddr::V4),
+                _ => None,
+            };
+            match mapped {
+                Some(ip) => is_public_ip(ip),
+                None => !(ip.is_loopback() || ip.is_unspecified() || ip.is_multicast() || unique_local || link_local),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_addresses_are_refused() {
+        assert!(public_addresses("127.0.0.1:80").is_err());
+        assert!(public_addresses("10.1.2.3:80").is_err());
+        assert!(public_addresses("[::ffff:192.168.0.1]:80").is_err());
+        assert!(public_addresses("[fe80::1]:80").is_err());
+        assert!(public_addresses("93.184.216.34:80").is_ok());
+
+        // the connections are refused before the request is sent
+        let response = agent(false).get("http://127.0.0.1:1/").call();
+        let error = response.synthetic_error().as_ref().map(ToString::to_string).unwrap_or_default();
+        assert!(error.contains("loopback"), "{}", error);
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use meilisearch_tokenizer::Tokenizer;
+
+struct RecordedQuery {
+    tokens: String,
+    zero_results: bool,
+}
+
+/// Remembers, in memory only, the tokens of the last search queries and whether
+/// they returned no results, the oldest queries are forgotten past the capacity.
+#[derive(Clone)]
+pub struct QueryAnalytics {
+    capacity: usize,
+    queries: Arc<Mutex<VecDeque<RecordedQuery>>>,
+}
+
+impl QueryAnalytics {
+    pub fn new(capacity: usize) -> QueryAnalytics {
+        QueryAnalytics {
+            capacity,
+            queries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    pub fn record(&self, query: &str, zero_results: bool) {
+        let tokens: Vec<_> = Tokenizer::new(query).map(|token| token.word.to_lowercase()).collect();
+        if tokens.is_empty() || self.capacity == 0 {
+            return;
+        }
+
+        let mut queries = self.queries.lock().unwrap();
+        if queries.len() >= self.capacity {
+            queries.pop_front();
+        }
+        queries.push_back(RecordedQuery { tokens: tokens.join(" "), zero_results });
+    }
+
+    /// Returns the most frequent queries with their number of occurences, only
+    /// counting the ones that returned no results when `zero_results` is `true`.
+    pub fn top_queries(&self, limit: usize, zero_results: bool) -> Vec<(String, usize)> {
+        let mut counts = HashMap::new();
+        for query in self.queries.lock().unwrap().iter() {
+            if !zero_results || query.zero_results {
+                *counts.entry(query.tokens.clone()).or_insert(0) += 1;
+            }
+        }
+
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_unstable_by(|(a, ac), (b, bc)| bc.cmp(ac).then_with(|| a.cmp(b)));
+        counts.truncate(limit);
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oldest_queries_are_forgotten() {
+        let analytics = QueryAnalytics::new(3);
+        analytics.record("Hello World", false);
+        analytics.record("hello   world", true);
+        analytics.record("carol", true);
+        analytics.record("", true);
+
+        assert_eq!(analytics.top_queries(10, false), vec![("hello world".to_string(), 2), ("carol".to_string(), 1)]);
+        assert_eq!(analytics.top_queries(10, true), vec![("carol".to_string(), 1), ("hello world".to_string(), 1)]);
+
+        analytics.record("carol", false);
+        analytics.record("carol", false);
+
+        assert_eq!(analytics.top_queries(1, false), vec![("carol".to_string(), 3)]);
+        assert_eq!(analytics.top_queries(10, true), vec![("carol".to_string(), 1)]);
+    }
+}
@@ -0,0 +1,60 @@
+use meilisearch_core::store::QUERY_ID_LEN;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Generates a random version 4 UUID identifying a search, to which the clicks are reported.
+pub fn new_query_id() -> String {
+    format_query_id(rand::random())
+}
+
+/// Generates the version 4 UUID of this hash, the searches with the same hash share their query id.
+pub fn query_id_from_hash(hash: u64) -> String {
+    format_query_id(StdRng::seed_from_u64(hash).gen())
+}
+
+fn format_query_id(mut bytes: [u8; QUERY_ID_LEN]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// Parses a hyphenated UUID, as given by `new_query_id`, into its bytes.
+pub fn parse_query_id(query_id: &str) -> Option<[u8; QUERY_ID_LEN]> {
+    let groups: Vec<_> = query_id.split('-').collect();
+    if groups.iter().map(|g| g.len()).collect::<Vec<_>>() != [8, 4, 4, 4, 12] {
+        return None;
+    }
+
+    let hex: String = groups.concat();
+    let mut bytes = [0; QUERY_ID_LEN];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_ids_round_trip() {
+        let query_id = new_query_id();
+        assert_eq!(query_id.len(), 36);
+        assert_eq!(query_id.as_bytes()[14], b'4');
+
+        let bytes = parse_query_id(&query_id).unwrap();
+        let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(hex, query_id.replace('-', ""));
+
+        assert_eq!(query_id_from_hash(42), query_id_from_hash(42));
+        assert_ne!(query_id_from_hash(42), query_id_from_hash(43));
+        assert!(parse_query_id(&query_id_from_hash(42)).is_some());
+
+        assert_eq!(parse_query_id("not-a-uuid"), None);
+        assert_eq!(parse_query_id("zzzzzzzz-0000-4000-8000-000000000000"), None);
+    }
+}
@@ -0,0 +1,121 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use actix_service::{Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use futures::future::{ok, Future, Ready};
+
+use crate::error::{Error, ResponseError};
+
+/// The routes of an index, after `/indexes/{index_uid}/`, that are sent with a `POST` but never
+/// write to the database. A new route of this kind must be listed here to be served in read-only mode.
+const READ_POST_ROUTES: [&str; 9] = [
+    "search",
+    "rerank",
+    "spellcheck",
+    "analyze",
+    "warm",
+    "debug/parse-query",
+    "documents/exists",
+    "documents/transform",
+    "settings/preview",
+];
+
+/// Refuses, with a `read_only_mode` error, every request that could write
+/// to the database when the server is started in read-only mode.
+#[derive(Clone, Copy)]
+pub struct ReadOnly {
+    enabled: bool,
+}
+
+impl ReadOnly {
+    pub fn new(enabled: bool) -> ReadOnly {
+        ReadOnly { enabled }
+    }
+}
+
+impl<S: 'static, B> Transform<S> for ReadOnly
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type InitError = ();
+    type Transform = ReadOnlyMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ReadOnlyMiddleware {
+            enabled: self.enabled,
+            service,
+        })
+    }
+}
+
+pub struct ReadOnlyMiddleware<S> {
+    enabled: bool,
+    service: S,
+}
+
+impl<S, B> Service for ReadOnlyMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        if self.enabled && is_write_request(req.method(), req.path()) {
+            let error = ResponseError::from(Error::read_only_mode());
+            return Box::pin(ok(req.error_response(error)));
+        }
+
+        Box::pin(self.service.call(req))
+    }
+}
+
+fn is_write_request(method: &Method, path: &str) -> bool {
+    match *method {
+        Method::GET | Method::HEAD | Method::OPTIONS => false,
+        Method::POST => {
+            let mut parts = path.trim_matches('/').splitn(3, '/');
+            let route = match (parts.next(), parts.next(), parts.next()) {
+                (Some("indexes"), Some(_), Some(route)) => route,
+                _ => return true,
+            };
+            !READ_POST_ROUTES.contains(&route)
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_requests() {
+        assert!(!is_write_request(&Method::GET, "/indexes/movies/documents"));
+        assert!(!is_write_request(&Method::POST, "/indexes/movies/search"));
+        assert!(!is_write_request(&Method::POST, "/indexes/movies/search/"));
+        assert!(!is_write_request(&Method::POST, "/indexes/movies/debug/parse-query"));
+        assert!(!is_write_request(&Method::POST, "/indexes/movies/documents/exists"));
+        assert!(is_write_request(&Method::POST, "/indexes/movies/documents"));
+        assert!(is_write_request(&Method::POST, "/indexes/search/documents"));
+        assert!(is_write_request(&Method::POST, "/indexes"));
+        assert!(is_write_request(&Method::PUT, "/health"));
+        assert!(is_write_request(&Method::DELETE, "/indexes/movies"));
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashSet;
+
+use meilisearch_core::settings::ReferencedDocument;
+use meilisearch_core::update::value_to_string;
+use meilisearch_core::{Database, DocumentId, Index, MainReader};
+use serde_json::{Map, Value};
+
+use crate::error::ResponseError;
+use crate::routes::document::Document;
+use crate::Data;
+
+/// The documents of a foreign index, retrieved by their primary key.
+struct ForeignDocuments {
+    index: Index,
+}
+
+impl ForeignDocuments {
+    /// Returns `None` when the foreign key is not the primary key of the foreign index,
+    /// the documents could only be found by reading all of them.
+    fn new(reader: &MainReader, index: Index, foreign_key: &str) -> Result<Option<ForeignDocuments>, ResponseError> {
+        let schema = index.main.schema(reader)?;
+        if schema.as_ref().and_then(|schema| schema.primary_key()) != Some(foreign_key) {
+            return Ok(None);
+        }
+
+        Ok(Some(ForeignDocuments { index }))
+    }
+
+    fn get(&self, reader: &MainReader, id: &str) -> Result<Option<Document>, ResponseError> {
+        match self.index.main.external_to_internal_docid(reader, id)? {
+            Some(internal_id) => Ok(self.index.document(reader, None, internal_id)?),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Copies the fields of the documents referenced by the documents into them, as the
+/// `referencedDocuments` setting of the index describes. The documents that do not
+/// have the field holding the foreign key are left untouched, like all of them when
+/// the foreign key is not the primary key of the foreign index.
+pub fn inject_referenced_documents(
+    db: &Database,
+    reader: &MainReader,
+    index: &Index,
+    documents: &mut [Document],
+) -> Result<(), ResponseError> {
+    let references = match index.main.referenced_documents(reader)? {
+        Some(references) => references,
+        None => return Ok(()),
+    };
+
+    for reference in &references {
+        let foreign_index = match db.open_index(&reference.foreign_index) {
+            Some(foreign_index) => foreign_index,
+            None => continue,
+        };
+
+        let foreign_documents = match ForeignDocuments::new(reader, foreign_index, &reference.foreign_key)? {
+            Some(foreign_documents) => foreign_documents,
+            None => continue,
+        };
+
+        for document in documents.iter_mut() {
+            inject_reference(reader, reference, &foreign_documents, document)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn inject_reference(
+    reader: &MainReader,
+    reference: &ReferencedDocument,
+    foreign_documents: &ForeignDocuments,
+    document: &mut Document,
+) -> Result<(), ResponseError> {
+    let (ids, is_array) = match document.get(&reference.field) {
+        None | Some(Value::Null) => return Ok(()),
+        Some(Value::Array(values)) => (values.iter().map(value_to_string).collect::<Vec<_>>(), true),
+        Some(value) => (vec![value_to_string(value)], false),
+    };
+
+    let mut referenced = Vec::with_capacity(ids.len());
+    for id in &ids {
+        referenced.push(foreign_documents.get(reader, id)?);
+    }
+
+    for path in &reference.fields_to_inject {
+        let mut parts = path.splitn(2, '.');
+        let (target, field) = match (parts.next(), parts.next()) {
+            (Some(target), Some(field)) => (Some(target), field),
+            _ => (None, path.as_str()),
+        };
+
+        let mut values = referenced
+            .iter()
+            .map(|document| document.as_ref().and_then(|document| document.get(field)).cloned().unwrap_or(Value::Null));
+        let value = if is_array { Value::Array(values.collect()) } else { values.next().unwrap_or(Value::Null) };
+
+        match target {
+            Some(target) => {
+                let object = document.entry(target.to_string()).or_insert_with(|| Value::Object(Map::new()));
+                if !object.is_object() {
+                    *object = Value::Object(Map::new());
+                }
+                if let Value::Object(object) = object {
+                    object.insert(field.to_string(), value);
+                }
+            }
+            None => {
+                document.insert(field.to_string(), value);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Injects the referenced documents again into the documents of the indexes referencing
+/// the updated index. Only the documents referencing the changed documents are refreshed,
+/// all of them when the changed documents are unknown.
+pub fn refresh_referencing_indexes(
+    data: &Data,
+    index_uid: &str,
+    changed_documents: Option<&[String]>,
+) -> Result<(), ResponseError> {
+    let reader = data.db.main_read_txn()?;
+
+    for uid in data.db.indexes_uids() {
+        // an index cannot reference itself, it would be refreshed endlessly
+        if uid == index_uid {
+            continue;
+        }
+
+        let index = match data.db.open_index(&uid) {
+            Some(index) => index,
+            None => continue,
+        };
+
+        let references = index.main.referenced_documents(&reader)?.unwrap_or_default();
+        let fields: Vec<_> = references
+            .iter()
+            .filter(|reference| reference.foreign_index == index_uid)
+            .map(|reference| reference.field.as_str())
+            .collect();
+
+        if fields.is_empty() {
+            continue;
+        }
+
+        let documents_ids = match changed_documents {
+            Some(changed) => {
+                let changed: HashSet<_> = changed.iter().map(String::as_str).collect();
+                referencing_documents_ids(&reader, &index, &fields, &changed)?
+            }
+            None => all_documents_ids(&reader, &index)?,
+        };
+
+        refresh_referenced_documents(data, &reader, &index, documents_ids)?;
+    }
+
+    Ok(())
+}
+
+pub fn all_documents_ids(reader: &MainReader, index: &Index) -> Result<Vec<DocumentId>, ResponseError> {
+    let mut documents_ids = Vec::new();
+    for document_id in index.documents_fields_counts.documents_ids(reader)? {
+        documents_ids.push(document_id?);
+    }
+    Ok(documents_ids)
+}
+
+/// Returns the documents whose fields hold one of the ids of the changed documents,
+/// only these fields are read.
+fn referencing_documents_ids(
+    reader: &MainReader,
+    index: &Index,
+    fields: &[&str],
+    changed: &HashSet<&str>,
+) -> Result<Vec<DocumentId>, ResponseError> {
+    let attributes: HashSet<_> = fields.iter().copied().collect();
+
+    let mut documents_ids = Vec::new();
+    for document_id in index.documents_fields_counts.documents_ids(reader)? {
+        let document_id = document_id?;
+        let document: Option<Document> = index.document(reader, Some(&attributes), document_id)?;
+        let references_changed = document.map_or(false, |document| {
+            document.values().any(|value| match value {
+                Value::Array(values) => values.iter().any(|value| changed.contains(value_to_string(value).as_str())),
+                value => changed.contains(value_to_string(value).as_str()),
+            })
+        });
+
+        if references_changed {
+            documents_ids.push(document_id);
+        }
+    }
+
+    Ok(documents_ids)
+}
+
+/// Injects the referenced documents again into these documents of the index, only the
+/// injected fields of the documents whose injected fields changed are updated.
+pub fn refresh_referenced_documents(
+    data: &Data,
+    reader: &MainReader,
+    index: &Index,
+    documents_ids: Vec<DocumentId>,
+) -> Result<(), ResponseError> {
+    let references = index.main.referenced_documents(reader)?.unwrap_or_default();
+    let schema = index.main.schema(reader)?;
+    let primary_key = match schema.as_ref().and_then(|schema| schema.primary_key()) {
+        Some(primary_key) => primary_key,
+        None => return Ok(()),
+    };
+
+    let mut documents = Vec::new();
+    for document_id in documents_ids {
+        let document: Option<Document> = index.document(reader, None, document_id)?;
+        documents.extend(document);
+    }
+
+    let mut refreshed = documents.clone();
+    inject_referenced_documents(&data.db, reader, index, &mut refreshed)?;
+
+    // the other fields are not written back, for the changes made to them since
+    // the documents were read to be kept
+    let injected = injected_fields(&references);
+    let changed: Vec<_> = refreshed
+        .into_iter()
+        .zip(documents)
+        .filter(|(refreshed, document)| refreshed != document)
+        .map(|(mut refreshed, _)| {
+            refreshed.retain(|name, _| name == primary_key || injected.contains(name.as_str()));
+            refreshed
+        })
+        .collect();
+
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    let mut document_addition = index.documents_partial_addition();
+    for document in changed {
+        document_addition.update_document(document);
+    }
+
+    data.db.update_write(|w| document_addition.finalize(w))?;
+
+    Ok(())
+}
+
+/// Returns the top level fields of the documents the references inject.
+fn injected_fields(references: &[ReferencedDocument]) -> HashSet<&str> {
+    references
+        .iter()
+        .flat_map(|reference| reference.fields_to_inject.iter())
+        .filter_map(|path| path.split('.').next())
+        .collect()
+}
+
+/// Returns whether following the references of the foreign indexes, from these references
+/// of the index, leads back to the index.
+pub fn references_cycle(
+    db: &Database,
+    reader: &MainReader,
+    index_uid: &str,
+    references: &[ReferencedDocument],
+) -> Result<bool, ResponseError> {
+    let mut visited = HashSet::new();
+    let mut to_visit: Vec<String> = references.iter().map(|reference| reference.foreign_index.clone()).collect();
+
+    while let Some(uid) = to_visit.pop() {
+        if uid == index_uid {
+            return Ok(true);
+        }
+        if !visited.insert(uid.clone()) {
+            continue;
+        }
+        if let Some(index) = db.open_index(&uid) {
+            let references = index.main.referenced_documents(reader)?.unwrap_or_default();
+            to_visit.extend(references.into_iter().map(|reference| reference.foreign_index));
+        }
+    }
+
+    Ok(false)
+}
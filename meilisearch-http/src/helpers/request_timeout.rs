@@ -0,0 +1,113 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use actix_service::{Service, Transform};
+use actix_web::{dev::ServiceRequest, dev::ServiceResponse};
+use futures::future::{ok, Future, Ready};
+
+use crate::error::{Error, ResponseError};
+
+const REQUEST_TIMEOUT_HEADER: &str = "X-Meili-Request-Timeout";
+
+/// The maximum deadline, in milliseconds, that can be asked for a request.
+const MAX_REQUEST_TIMEOUT: u64 = 60_000;
+
+/// Cancels the request and returns a `request_timeout` error when it has not been
+/// handled within the number of milliseconds given in the `X-Meili-Request-Timeout` header.
+#[derive(Clone, Copy)]
+pub struct RequestTimeout;
+
+impl<S: 'static, B> Transform<S> for RequestTimeout
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type InitError = ();
+    type Transform = RequestTimeoutMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestTimeoutMiddleware { service })
+    }
+}
+
+pub struct RequestTimeoutMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service for RequestTimeoutMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let timeout = match req.headers().get(REQUEST_TIMEOUT_HEADER) {
+            Some(value) => match value.to_str().ok().and_then(|v| v.parse::<u64>().ok()) {
+                Some(millis) => Some(Duration::from_millis(millis.min(MAX_REQUEST_TIMEOUT))),
+                None => {
+                    let error = Error::bad_parameter(REQUEST_TIMEOUT_HEADER, "must be a number of milliseconds");
+                    return Box::pin(ok(req.error_response(ResponseError::from(error))));
+                }
+            },
+            None => None,
+        };
+
+        let fut = self.service.call(req);
+
+        match timeout {
+            Some(timeout) => Box::pin(async move {
+                match tokio::time::timeout(timeout, fut).await {
+                    Ok(result) => result,
+                    // the request is owned by the service, the error is answered by the server
+                    Err(_) => Err(ResponseError::from(Error::request_timeout()).into()),
+                }
+            }),
+            None => Box::pin(fut),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+
+    #[actix_rt::test]
+    async fn requests_taking_too_long_are_answered_with_a_timeout() {
+        let app = App::new().wrap(RequestTimeout).route("/", web::get().to(|| async {
+            let result = web::block(|| {
+                std::thread::sleep(Duration::from_millis(500));
+                Ok::<_, ()>(())
+            });
+            result.await.map(|_| HttpResponse::Ok().finish())
+        }));
+        let mut app = test::init_service(app).await;
+
+        let req = test::TestRequest::get().uri("/").header(REQUEST_TIMEOUT_HEADER, "10").to_request();
+        let error = app.call(req).await.err().unwrap();
+        assert_eq!(error.as_response_error().error_response().status(), StatusCode::REQUEST_TIMEOUT);
+
+        let req = test::TestRequest::get().uri("/").header(REQUEST_TIMEOUT_HEADER, "soon").to_request();
+        let res = test::call_service(&mut app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let req = test::TestRequest::get().uri("/").to_request();
+        let res = test::call_service(&mut app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
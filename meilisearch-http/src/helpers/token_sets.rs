@@ -0,0 +1,98 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+
+use meilisearch_core::DocumentId;
+
+/// The maximum number of token sets kept for a single index.
+const MAX_CACHED_TOKEN_SETS: usize = 10_000;
+
+type TokenSet = Arc<BTreeSet<String>>;
+
+/// The token sets of an index, the least recently used one is evicted first.
+#[derive(Default)]
+struct IndexTokenSets {
+    sets: HashMap<DocumentId, (TokenSet, u64)>,
+    /// The documents by the last time their token set was used.
+    recency: BTreeMap<u64, DocumentId>,
+    clock: u64,
+}
+
+impl IndexTokenSets {
+    fn get(&mut self, document_id: DocumentId) -> Option<TokenSet> {
+        let (set, used_at) = self.sets.get_mut(&document_id)?;
+        self.recency.remove(used_at);
+        self.clock += 1;
+        *used_at = self.clock;
+        self.recency.insert(self.clock, document_id);
+        Some(set.clone())
+    }
+
+    fn insert(&mut self, document_id: DocumentId, set: TokenSet) {
+        if let Some((_, used_at)) = self.sets.remove(&document_id) {
+            self.recency.remove(&used_at);
+        }
+
+        if self.sets.len() >= MAX_CACHED_TOKEN_SETS {
+            let oldest = self.recency.iter().next().map(|(used_at, id)| (*used_at, *id));
+            if let Some((used_at, id)) = oldest {
+                self.recency.remove(&used_at);
+                self.sets.remove(&id);
+            }
+        }
+
+        self.clock += 1;
+        self.sets.insert(document_id, (set, self.clock));
+        self.recency.insert(self.clock, document_id);
+    }
+}
+
+/// Keeps the token sets of the documents, by index, to avoid reading them
+/// from the inverted index on every similar documents request.
+#[derive(Clone, Default)]
+pub struct TokenSetsCache {
+    inner: Arc<Mutex<HashMap<String, IndexTokenSets>>>,
+}
+
+impl TokenSetsCache {
+    pub fn get_or_try_insert_with<F, E>(&self, index_uid: &str, document_id: DocumentId, f: F) -> Result<TokenSet, E>
+    where F: FnOnce() -> Result<BTreeSet<String>, E>,
+    {
+        if let Some(set) = self.inner.lock().unwrap().get_mut(index_uid).and_then(|sets| sets.get(document_id)) {
+            return Ok(set);
+        }
+
+        let set = Arc::new(f()?);
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.entry(index_uid.to_string()).or_default().insert(document_id, set.clone());
+
+        Ok(set)
+    }
+
+    /// Must be called each time the documents of the index are updated.
+    pub fn invalidate(&self, index_uid: &str) {
+        self.inner.lock().unwrap().remove(index_uid);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn least_recently_used_sets_are_evicted() {
+        let mut sets = IndexTokenSets::default();
+        for id in 0..MAX_CACHED_TOKEN_SETS as u32 {
+            sets.insert(DocumentId(id), Arc::default());
+        }
+
+        // the first set is used again, the second one is now the oldest
+        assert!(sets.get(DocumentId(0)).is_some());
+        sets.insert(DocumentId(MAX_CACHED_TOKEN_SETS as u32), Arc::default());
+
+        assert_eq!(sets.sets.len(), MAX_CACHED_TOKEN_SETS);
+        assert!(sets.get(DocumentId(0)).is_some());
+        assert!(sets.get(DocumentId(1)).is_none());
+        assert!(sets.get(DocumentId(2)).is_some());
+    }
+}
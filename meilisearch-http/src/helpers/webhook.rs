@@ -0,0 +1,160 @@
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{error, warn};
+use sha2::{Digest, Sha256};
+
+use super::{public_hosts, BackgroundWorker};
+
+/// The number of times a webhook call is retried after the first one fails.
+const MAX_RETRIES: u32 = 3;
+
+/// The time after which connecting to the webhook, or waiting for its response, is abandoned, in milliseconds.
+const WEBHOOK_TIMEOUT_MS: u64 = 10_000;
+
+/// The block size of SHA-256, the HMAC keys are padded to it.
+const SHA256_BLOCK_SIZE: usize = 64;
+
+/// Computes the HMAC-SHA256 of the message, as described in RFC 2104.
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut block = [0u8; SHA256_BLOCK_SIZE];
+    if key.len() > SHA256_BLOCK_SIZE {
+        let digest = Sha256::digest(key);
+        block[..digest.len()].copy_from_slice(&digest);
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.input(block.iter().map(|b| b ^ 0x36).collect::<Vec<_>>());
+    inner.input(message);
+    let inner = inner.result();
+
+    let mut outer = Sha256::new();
+    outer.input(block.iter().map(|b| b ^ 0x5c).collect::<Vec<_>>());
+    outer.input(inner);
+    outer.result().to_vec()
+}
+
+/// A call to a webhook, retried once its deadline is reached.
+struct Call {
+    url: String,
+    body: String,
+    signature: Option<String>,
+    attempt: u32,
+    deadline: Instant,
+}
+
+/// Sends the webhook calls one after the other on a worker. The calls that fail wait for their
+/// retry on another thread and are pushed back on the worker once their delay is elapsed, a
+/// webhook that does not respond does not delay the calls to the others.
+#[derive(Clone)]
+pub struct Webhooks {
+    worker: BackgroundWorker,
+    retries: SyncSender<Call>,
+    agent: ureq::Agent,
+}
+
+impl Webhooks {
+    pub fn new(capacity: usize, allow_private: bool) -> Webhooks {
+        let worker = BackgroundWorker::new("webhooks", capacity);
+        let (retries, receiver) = sync_channel::<Call>(capacity);
+        let agent = public_hosts::agent(allow_private);
+        let webhooks = Webhooks { worker, retries, agent };
+
+        let scheduler = webhooks.clone();
+        thread::Builder::new()
+            .name("webhooks retries".to_string())
+            .spawn(move || scheduler.schedule_retries(receiver))
+            .expect("Impossible to spawn the webhooks retries thread");
+
+        webhooks
+    }
+
+    /// Sends the body to the webhook, signed with the master key when there is one.
+    /// The calls that do not fit in the queue of the worker are dropped.
+    pub fn notify(&self, url: String, body: String, master_key: Option<String>) {
+        let signature = master_key.map(|key| {
+            hmac_sha256(key.as_bytes(), body.as_bytes())
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        });
+
+        self.push(Call { url, body, signature, attempt: 0, deadline: Instant::now() });
+    }
+
+    fn push(&self, call: Call) {
+        let url = call.url.clone();
+        let webhooks = self.clone();
+        if !self.worker.push(move || webhooks.send(call)) {
+            error!("Too many webhook calls are pending, the call to {} is dropped", url);
+        }
+    }
+
+    fn send(&self, mut call: Call) {
+        let mut request = self.agent.post(&call.url);
+        request.set("Content-Type", "application/json");
+        request.timeout_connect(WEBHOOK_TIMEOUT_MS);
+        request.timeout_read(WEBHOOK_TIMEOUT_MS);
+        if let Some(signature) = &call.signature {
+            request.set("X-Meili-Signature", signature);
+        }
+
+        let response = request.send_string(&call.body);
+        if response.ok() {
+            return;
+        }
+
+        match response.synthetic_error() {
+            Some(e) => warn!("Webhook call to {} failed; {}", call.url, e),
+            None => warn!("Webhook call to {} failed; the server responded with {}", call.url, response.status()),
+        }
+
+        if call.attempt == MAX_RETRIES {
+            error!("Webhook call to {} failed {} times, giving up", call.url, MAX_RETRIES + 1);
+            return;
+        }
+
+        call.deadline = Instant::now() + Duration::from_secs(1 << call.attempt);
+        call.attempt += 1;
+        if let Err(TrySendError::Full(call)) = self.retries.try_send(call) {
+            error!("Too many webhook calls are waiting for a retry, the call to {} is dropped", call.url);
+        }
+    }
+
+    /// Waits for the deadlines of the calls to retry and pushes them back on the worker.
+    fn schedule_retries(&self, receiver: Receiver<Call>) {
+        let mut waiting: Vec<Call> = Vec::new();
+        loop {
+            let received = match waiting.iter().map(|call| call.deadline).min() {
+                Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            match received {
+                Ok(call) => waiting.push(call),
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            let now = Instant::now();
+            let (due, rest): (Vec<_>, Vec<_>) = waiting.into_iter().partition(|call| call.deadline <= now);
+            waiting = rest;
+            due.into_iter().for_each(|call| self.push(call));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hmac_sha256_matches_rfc_4231() {
+        let signature = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        let hex: String = signature.iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(hex, "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+    }
+}
@@ -14,11 +14,13 @@ use actix_web::{dev, web, App};
 use chrono::Utc;
 use log::error;
 
-use meilisearch_core::ProcessedUpdateResult;
+use meilisearch_core::settings::UpdateState;
+use meilisearch_core::{ProcessedUpdateResult, UpdateStatus, UpdateType};
 
 pub use option::Opt;
 pub use self::data::Data;
 use self::error::{json_error_handler, ResponseError};
+use self::helpers::{CompressResponse, ReadOnly, RequestTimeout};
 
 pub fn create_app(
     data: &Data,
@@ -42,7 +44,13 @@ pub fn create_app(
         )
         .service(routes::load_html)
         .service(routes::load_css)
+        .configure(routes::analytics::services)
+        .configure(routes::batch::services)
+        .configure(routes::debug::services)
         .configure(routes::document::services)
+        .configure(routes::experiment::services)
+        .configure(routes::feedback::services)
+        .configure(routes::import::services)
         .configure(routes::index::services)
         .configure(routes::search::services)
         .configure(routes::setting::services)
@@ -51,13 +59,65 @@ pub fn create_app(
         .configure(routes::health::services)
         .configure(routes::stats::services)
         .configure(routes::key::services)
+        .configure(routes::openapi::services)
+        .wrap(ReadOnly::new(data.read_only))
+        .wrap(RequestTimeout)
+        .wrap(CompressResponse::new(data.compress_response_threshold))
 }
 
 pub fn index_update_callback(index_uid: &str, data: &Data, status: ProcessedUpdateResult) {
+    // the embeddings added to the documents are not notified, and they do not
+    // refresh the referencing documents nor generate other embeddings
+    let documents_embeddings = match status.update_type {
+        UpdateType::DocumentsEmbeddings { .. } => true,
+        _ => false,
+    };
+
+    if !documents_embeddings {
+        notify_webhook(index_uid, data, &status);
+    }
+
     if status.error.is_some() {
         return;
     }
 
+    data.token_sets.invalidate(index_uid);
+
+    let documents_changed = match status.update_type {
+        UpdateType::ClearAll
+        | UpdateType::DocumentsAddition { .. }
+        | UpdateType::DocumentsPartial { .. }
+        | UpdateType::DocumentsImport { .. }
+        | UpdateType::DocumentPatch
+        | UpdateType::DocumentsDeletion { .. } => true,
+        _ => false,
+    };
+
+    if documents_changed {
+        refresh_referencing_indexes(index_uid, data, status.changed_documents.clone());
+    }
+
+    let documents_added = match status.update_type {
+        UpdateType::DocumentsAddition { .. }
+        | UpdateType::DocumentsPartial { .. }
+        | UpdateType::DocumentsImport { .. }
+        | UpdateType::DocumentPatch => true,
+        _ => false,
+    };
+
+    if documents_added {
+        helpers::embeddings::embed_documents(data, index_uid, status.changed_documents.clone());
+    }
+
+    if let UpdateType::Settings { settings } = &status.update_type {
+        if let UpdateState::Update(_) = settings.referenced_documents {
+            refresh_referenced_documents(index_uid, data);
+        }
+        if let UpdateState::Update(_) = settings.embedder {
+            helpers::embeddings::embed_documents(data, index_uid, None);
+        }
+    }
+
     if let Some(index) = data.db.open_index(&index_uid) {
         let db = &data.db;
         let res = db.main_write::<_, _, ResponseError>(|mut writer| {
@@ -72,6 +132,21 @@ pub fn index_update_callback(index_uid: &str, data: &Data, status: ProcessedUpda
             if let Err(e) = index.main.put_updated_at(&mut writer) {
                 error!("Impossible to update updated_at; {}", e)
             }
+
+            let is_indexing = match status.update_type {
+                UpdateType::DocumentsAddition { .. }
+                | UpdateType::DocumentsPartial { .. }
+                | UpdateType::DocumentsImport { .. }
+                | UpdateType::DocumentPatch
+                | UpdateType::Settings { .. } => true,
+                _ => false,
+            };
+
+            if is_indexing {
+                if let Err(e) = index.main.put_last_successful_indexing_at(&mut writer) {
+                    error!("Impossible to update last_successful_indexing_at; {}", e)
+                }
+            }
             Ok(())
         });
         match res {
@@ -80,3 +155,79 @@ pub fn index_update_callback(index_uid: &str, data: &Data, status: ProcessedUpda
         }
     }
 }
+
+/// Refreshes on the references worker the documents referencing the changed documents of the index,
+/// finding them requires reading the documents of the referencing indexes.
+fn refresh_referencing_indexes(index_uid: &str, data: &Data, changed_documents: Option<Vec<String>>) {
+    let job_data = data.clone();
+    let job_index_uid = index_uid.to_string();
+    let pushed = data.references_worker.push(move || {
+        let changed_documents = changed_documents.as_deref();
+        if let Err(e) = helpers::references::refresh_referencing_indexes(&job_data, &job_index_uid, changed_documents) {
+            error!("Impossible to refresh the documents referencing {}; {}", job_index_uid, e)
+        }
+    });
+
+    if !pushed {
+        error!("Too many references refreshes are pending, the documents referencing {} are not refreshed", index_uid);
+    }
+}
+
+/// Injects on the references worker the referenced documents into the documents of the index,
+/// once its references changed.
+fn refresh_referenced_documents(index_uid: &str, data: &Data) {
+    let job_data = data.clone();
+    let job_index_uid = index_uid.to_string();
+    let pushed = data.references_worker.push(move || {
+        let index = match job_data.db.open_index(&job_index_uid) {
+            Some(index) => index,
+            None => return,
+        };
+
+        let result = job_data.db.main_read_txn().map_err(ResponseError::from).and_then(|reader| {
+            let documents_ids = helpers::references::all_documents_ids(&reader, &index)?;
+            helpers::references::refresh_referenced_documents(&job_data, &reader, &index, documents_ids)
+        });
+
+        if let Err(e) = result {
+            error!("Impossible to refresh the referenced documents of {}; {}", job_index_uid, e)
+        }
+    });
+
+    if !pushed {
+        error!("Too many references refreshes are pending, the documents of {} are not refreshed", index_uid);
+    }
+}
+
+/// Sends the processed or failed update to the webhook of the index, if it has one.
+fn notify_webhook(index_uid: &str, data: &Data, status: &ProcessedUpdateResult) {
+    let index = match data.db.open_index(index_uid) {
+        Some(index) => index,
+        None => return,
+    };
+
+    let webhook_url = data.db.main_read_txn().and_then(|reader| index.main.webhook_url(&reader));
+
+    let url = match webhook_url {
+        Ok(Some(url)) => url,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Impossible to retrieve the webhook URL; {}", e);
+            return;
+        }
+    };
+
+    let content = status.clone();
+    let update = if content.error.is_some() {
+        UpdateStatus::Failed { content }
+    } else {
+        UpdateStatus::Processed { content }
+    };
+
+    let mut body = serde_json::to_value(update).unwrap_or_default();
+    if let Some(object) = body.as_object_mut() {
+        object.insert("indexUid".to_string(), index_uid.into());
+    }
+
+    data.webhooks.notify(url, body.to_string(), data.api_keys.master.clone());
+}
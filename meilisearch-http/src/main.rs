@@ -4,11 +4,9 @@ use actix_cors::Cors;
 use actix_web::{middleware, HttpServer};
 use main_error::MainError;
 use meilisearch_http::helpers::NormalizePath;
-use meilisearch_http::{Data, Opt, create_app, index_update_callback};
+use meilisearch_http::{analytics, Data, Opt, create_app, index_update_callback};
 use structopt::StructOpt;
 
-mod analytics;
-
 #[cfg(target_os = "linux")]
 #[global_allocator]
 static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
@@ -36,7 +34,7 @@ async fn main() -> Result<(), MainError> {
             }
 
             #[cfg(all(not(debug_assertions), feature = "sentry"))]
-            if !opt.no_analytics {
+            if opt.analytics_enabled() {
                 sentry::integrations::panic::register_panic_handler();
                 sentry::integrations::env_logger::init(None, Default::default());
             }
@@ -49,7 +47,7 @@ async fn main() -> Result<(), MainError> {
 
     let data = Data::new(opt.clone());
 
-    if !opt.no_analytics {
+    if opt.analytics_enabled() {
         let analytics_data = data.clone();
         let analytics_opt = opt.clone();
         thread::spawn(move|| {
@@ -10,6 +10,8 @@ use rustls::{
 };
 use structopt::StructOpt;
 
+use crate::helpers::imports::{ImportKeys, StorageKey};
+
 const POSSIBLE_ENV: [&str; 2] = ["development", "production"];
 
 #[derive(Debug, Default, Clone, StructOpt)]
@@ -37,6 +39,11 @@ pub struct Opt {
     #[structopt(long, env = "MEILI_NO_ANALYTICS")]
     pub no_analytics: bool,
 
+    /// Whether analytics are sent to Meili, `false` disables them like `MEILI_NO_ANALYTICS` does.
+    /// The data collected is listed by the `GET /debug/analytics` route.
+    #[structopt(long, env = "MEILI_ENABLE_ANALYTICS", default_value = "true", parse(try_from_str))]
+    pub enable_analytics: bool,
+
     /// The maximum size, in bytes, of the main lmdb database directory
     #[structopt(long, env = "MEILI_MAIN_MAP_SIZE", default_value = "107374182400")] // 100GB
     pub main_map_size: usize,
@@ -45,10 +52,79 @@ pub struct Opt {
     #[structopt(long, env = "MEILI_UPDATE_MAP_SIZE", default_value = "107374182400")] // 100GB
     pub update_map_size: usize,
 
+    /// The maximum size of both the main and the update lmdb database directories, e.g. `100Gb`,
+    /// it takes precedence over the main and update map sizes when defined.
+    #[structopt(long, env = "MEILI_DB_MAP_SIZE", parse(try_from_str = parse_byte_size))]
+    pub db_map_size: Option<usize>,
+
     /// The maximum size, in bytes, of accepted JSON payloads
     #[structopt(long, env = "MEILI_HTTP_PAYLOAD_SIZE_LIMIT", default_value = "10485760")] // 10MB
     pub http_payload_size_limit: usize,
 
+    /// The minimum size of a response body to compress it, e.g. `512Kb` or `1Mb`,
+    /// the streamed bodies are always compressed as their size is unknown.
+    #[structopt(long, env = "MEILI_COMPRESS_RESPONSE_THRESHOLD", default_value = "1Mb", parse(try_from_str = parse_byte_size))]
+    pub compress_response_threshold: usize,
+
+    /// Log, after each search, the index, the length of the query, the number of hits
+    /// and the processing time as a JSON line with the `query` target.
+    #[structopt(long, env = "MEILI_LOG_QUERIES")]
+    pub log_queries: bool,
+
+    /// Also log the query strings when the queries are logged, they can contain sensitive data.
+    #[structopt(long, env = "MEILI_LOG_QUERY_STRINGS")]
+    pub log_query_strings: bool,
+
+    /// The number of search queries remembered, in memory only, to find the
+    /// most frequent ones and the ones without results.
+    #[structopt(long, env = "MEILI_QUERY_ANALYTICS_SIZE", default_value = "10000")]
+    pub query_analytics_size: usize,
+
+    /// The maximum number of indexed words a wildcard of a search query (e.g. `shoe*`) is expanded to,
+    /// a warning is returned with the search results when a wildcard matches more words.
+    #[structopt(long, env = "MEILI_MAX_WILDCARD_EXPANSIONS", default_value = "1000")]
+    pub max_wildcard_expansions: usize,
+
+    /// Refuse every request that would write to the database, only the read routes are served.
+    #[structopt(long, env = "MEILI_READ_ONLY")]
+    pub read_only: bool,
+
+    /// Allows the documents to be imported from the URLs of loopback, private and link-local addresses.
+    #[structopt(long, env = "MEILI_ALLOW_PRIVATE_IMPORT_URLS")]
+    pub allow_private_import_urls: bool,
+
+    /// Allows the webhooks to be called at the URLs of loopback, private and link-local addresses.
+    #[structopt(long, env = "MEILI_ALLOW_PRIVATE_WEBHOOK_URLS")]
+    pub allow_private_webhook_urls: bool,
+
+    /// The access key id of the AWS key the `s3://` files are imported with,
+    /// they are imported without authentication when there is none.
+    #[structopt(long, env = "MEILI_IMPORT_S3_ACCESS_KEY_ID")]
+    pub import_s3_access_key_id: Option<String>,
+
+    /// The secret access key of the AWS key the `s3://` files are imported with.
+    #[structopt(long, env = "MEILI_IMPORT_S3_SECRET_ACCESS_KEY")]
+    pub import_s3_secret_access_key: Option<String>,
+
+    /// The region of the S3 buckets the files are imported from, `us-east-1` by default,
+    /// the buckets of the other regions are found once they answer.
+    #[structopt(long, env = "MEILI_IMPORT_S3_REGION")]
+    pub import_s3_region: Option<String>,
+
+    /// The access id of the HMAC key the `gs://` files are imported with,
+    /// they are imported without authentication when there is none.
+    #[structopt(long, env = "MEILI_IMPORT_GCS_ACCESS_ID")]
+    pub import_gcs_access_id: Option<String>,
+
+    /// The secret of the HMAC key the `gs://` files are imported with.
+    #[structopt(long, env = "MEILI_IMPORT_GCS_SECRET")]
+    pub import_gcs_secret: Option<String>,
+
+    /// Adds to the search responses that are not the last page of the results
+    /// a `Link` header telling the clients to prefetch the next page.
+    #[structopt(long, env = "MEILI_ENABLE_HTTP2_PUSH")]
+    pub enable_http2_push: bool,
+
     /// Read server certificates from CERTFILE.
     /// This should contain PEM-format certificates
     /// in the right order (the first certificate should
@@ -85,6 +161,30 @@ pub struct Opt {
 }
 
 impl Opt {
+    pub fn analytics_enabled(&self) -> bool {
+        self.enable_analytics && !self.no_analytics
+    }
+
+    /// Returns the keys of the cloud storages the files are imported with, a key is only
+    /// used when both its parts are given.
+    pub fn import_keys(&self) -> ImportKeys {
+        let key = |access_key_id: &Option<String>, secret_access_key: &Option<String>| {
+            match (access_key_id, secret_access_key) {
+                (Some(access_key_id), Some(secret_access_key)) => Some(StorageKey {
+                    access_key_id: access_key_id.clone(),
+                    secret_access_key: secret_access_key.clone(),
+                }),
+                _ => None,
+            }
+        };
+
+        ImportKeys {
+            s3: key(&self.import_s3_access_key_id, &self.import_s3_secret_access_key),
+            s3_region: self.import_s3_region.clone(),
+            gcs: key(&self.import_gcs_access_id, &self.import_gcs_secret),
+        }
+    }
+
     pub fn get_ssl_config(&self) -> Result<Option<rustls::ServerConfig>, Box<dyn error::Error>> {
         if let (Some(cert_path), Some(key_path)) = (&self.ssl_cert_path, &self.ssl_key_path) {
             let client_auth = match &self.ssl_auth_path {
@@ -128,6 +228,24 @@ impl Opt {
     }
 }
 
+/// Parses a size written with an optional `b`, `Kb`, `Mb` or `Gb` unit into a number of bytes.
+fn parse_byte_size(s: &str) -> Result<usize, String> {
+    let s = s.trim();
+    let digits_end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(digits_end);
+
+    let number: usize = number.parse().map_err(|_| format!("invalid size {:?}", s))?;
+    let multiplier = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1024,
+        "mb" => 1024 * 1024,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return Err(format!("invalid size unit {:?}", unit)),
+    };
+
+    number.checked_mul(multiplier).ok_or_else(|| format!("the size {:?} is too large", s))
+}
+
 fn load_certs(filename: PathBuf) -> Result<Vec<rustls::Certificate>, Box<dyn error::Error>> {
     let certfile = fs::File::open(filename).map_err(|_| "cannot open certificate file")?;
     let mut reader = BufReader::new(certfile);
@@ -170,3 +288,30 @@ fn load_ocsp(filename: &Option<PathBuf>) -> Result<Vec<u8>, Box<dyn error::Error
 
     Ok(ret)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_byte_sizes() {
+        assert_eq!(parse_byte_size("42"), Ok(42));
+        assert_eq!(parse_byte_size("42b"), Ok(42));
+        assert_eq!(parse_byte_size("512Kb"), Ok(512 * 1024));
+        assert_eq!(parse_byte_size("1Mb"), Ok(1024 * 1024));
+        assert_eq!(parse_byte_size("2 GB"), Ok(2 * 1024 * 1024 * 1024));
+        assert!(parse_byte_size("Mb").is_err());
+        assert!(parse_byte_size("12Tb").is_err());
+        assert!(parse_byte_size("99999999999999999999Gb").is_err());
+        assert!(parse_byte_size(&format!("{}Gb", usize::MAX)).is_err());
+    }
+
+    #[test]
+    fn human_readable_db_map_size() {
+        let opt = Opt::from_iter(&["meilisearch", "--db-map-size", "200Gb"]);
+        assert_eq!(opt.db_map_size, Some(200 * 1024 * 1024 * 1024));
+
+        let opt = Opt::from_iter(&["meilisearch"]);
+        assert_eq!(opt.db_map_size, None);
+    }
+}
@@ -0,0 +1,60 @@
+use actix_web::{web, HttpResponse};
+use actix_web_macros::get;
+use serde::Serialize;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::helpers::Authentication;
+use crate::Data;
+
+/// The number of queries returned by the query analytics routes.
+const TOP_QUERIES_LIMIT: usize = 100;
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_top_queries).service(get_zero_results_queries);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_top_queries, get_zero_results_queries))]
+pub struct AnalyticsApi;
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct QueryCount {
+    /// The lowercased tokens of the query, separated by spaces.
+    query: String,
+    count: usize,
+}
+
+fn top_queries(data: &Data, zero_results: bool) -> Vec<QueryCount> {
+    data.query_analytics
+        .top_queries(TOP_QUERIES_LIMIT, zero_results)
+        .into_iter()
+        .map(|(query, count)| QueryCount { query, count })
+        .collect()
+}
+
+#[utoipa::path(
+    get,
+    path = "/analytics/queries",
+    tag = "Analytics",
+    responses(
+        (status = 200, description = "The most frequent of the last search queries", body = Vec<QueryCount>),
+    )
+)]
+#[get("/analytics/queries", wrap = "Authentication::Admin")]
+async fn get_top_queries(data: web::Data<Data>) -> HttpResponse {
+    HttpResponse::Ok().json(top_queries(&data, false))
+}
+
+#[utoipa::path(
+    get,
+    path = "/analytics/queries/zero-results",
+    tag = "Analytics",
+    responses(
+        (status = 200, description = "The most frequent of the last search queries that returned no results", body = Vec<QueryCount>),
+    )
+)]
+#[get("/analytics/queries/zero-results", wrap = "Authentication::Admin")]
+async fn get_zero_results_queries(data: web::Data<Data>) -> HttpResponse {
+    HttpResponse::Ok().json(top_queries(&data, true))
+}
@@ -0,0 +1,206 @@
+use actix_web::{web, HttpResponse};
+use actix_web_macros::post;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use meilisearch_core::update::DocumentErrorStrategy;
+use meilisearch_core::Index;
+
+use crate::error::{Error, ErrorResponseBody, ResponseError};
+use crate::helpers::references::inject_referenced_documents;
+use crate::helpers::Authentication;
+use crate::routes::document::{set_primary_key, Document};
+use crate::routes::{IndexParam, IndexUpdateResponse};
+use crate::Data;
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(start_batch)
+        .service(append_batch_documents)
+        .service(commit_batch)
+        .service(rollback_batch);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(start_batch, append_batch_documents, commit_batch, rollback_batch))]
+pub struct BatchApi;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+struct BatchParam {
+    index_uid: String,
+    batch_id: String,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct CommitBatchQuery {
+    primary_key: Option<String>,
+    /// What to do with the invalid documents: `abort` (the default), `skip` or `fail`.
+    #[param(value_type = Option<String>)]
+    document_error_strategy: Option<DocumentErrorStrategy>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct BatchResponse {
+    batch_id: String,
+    documents_count: usize,
+}
+
+fn batch_not_found(batch_id: &str) -> Error {
+    Error::not_found(format!("Batch {}", batch_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/batch/start",
+    tag = "Documents",
+    params(IndexParam),
+    responses(
+        (status = 201, description = "The batch has been started, it is rolled back if it is not committed within an hour", body = BatchResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/batch/start", wrap = "Authentication::Private")]
+async fn start_batch(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+) -> Result<HttpResponse, ResponseError> {
+    data.db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let batch_id = data.documents_batches.start(&path.index_uid);
+
+    Ok(HttpResponse::Created().json(BatchResponse { batch_id, documents_count: 0 }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/batch/{batch_id}/documents",
+    tag = "Documents",
+    params(BatchParam),
+    request_body = Vec<Object>,
+    responses(
+        (status = 200, description = "The documents have been appended to the batch", body = BatchResponse),
+        (status = 400, description = "The batch would be too large or is being committed", body = ErrorResponseBody),
+        (status = 404, description = "Batch not found", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/batch/{batch_id}/documents", wrap = "Authentication::Private")]
+async fn append_batch_documents(
+    data: web::Data<Data>,
+    path: web::Path<BatchParam>,
+    body: web::Json<Vec<Document>>,
+) -> Result<HttpResponse, ResponseError> {
+    let documents_count = data
+        .documents_batches
+        .append(&path.index_uid, &path.batch_id, body.into_inner())?
+        .ok_or_else(|| batch_not_found(&path.batch_id))?;
+
+    Ok(HttpResponse::Ok().json(BatchResponse {
+        batch_id: path.into_inner().batch_id,
+        documents_count,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/batch/{batch_id}/commit",
+    tag = "Documents",
+    params(BatchParam, CommitBatchQuery),
+    responses(
+        (status = 202, description = "All the documents of the batch will be added or replaced in a single update", body = IndexUpdateResponse),
+        (status = 400, description = "The primary key could not be inferred", body = ErrorResponseBody),
+        (status = 404, description = "Index or batch not found", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/batch/{batch_id}/commit", wrap = "Authentication::Private")]
+async fn commit_batch(
+    data: web::Data<Data>,
+    path: web::Path<BatchParam>,
+    params: web::Query<CommitBatchQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let documents = data
+        .documents_batches
+        .begin_commit(&path.index_uid, &path.batch_id)?
+        .ok_or_else(|| batch_not_found(&path.batch_id))?;
+
+    // the batch is only removed once its documents are enqueued, a failed commit can be retried
+    let result = enqueue_batch(&data, &index, &path.index_uid, &params, documents);
+    data.documents_batches.end_commit(&path.batch_id, result.is_ok());
+
+    Ok(HttpResponse::Accepted().json(result?))
+}
+
+fn enqueue_batch(
+    data: &Data,
+    index: &Index,
+    index_uid: &str,
+    params: &CommitBatchQuery,
+    mut documents: Vec<Document>,
+) -> Result<IndexUpdateResponse, ResponseError> {
+    let reader = data.db.main_read_txn()?;
+    let mut schema = index
+        .main
+        .schema(&reader)?
+        .ok_or(Error::internal("Impossible to retrieve the schema"))?;
+
+    let new_primary_key = set_primary_key(&mut schema, index_uid, params.primary_key.as_deref(), &documents)?;
+
+    inject_referenced_documents(&data.db, &reader, index, &mut documents)?;
+
+    let mut document_addition = index.documents_addition();
+    for document in documents {
+        document_addition.update_document(document);
+    }
+
+    if let Some(error_strategy) = params.document_error_strategy {
+        document_addition.set_error_strategy(error_strategy);
+    }
+
+    // the primary key given is saved once the documents are known to be enqueued,
+    // the one detected is given to the index by the update
+    let given_primary_key = match new_primary_key {
+        Some(primary_key) if params.primary_key.is_none() => {
+            document_addition.set_detected_primary_key(primary_key);
+            false
+        }
+        new_primary_key => new_primary_key.is_some(),
+    };
+
+    let update_id = data.db.update_write(|w| document_addition.finalize(w))?;
+    if given_primary_key {
+        data.db.main_write(|w| index.main.put_schema(w, &schema))?;
+    }
+
+    Ok(IndexUpdateResponse::with_id(update_id))
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/batch/{batch_id}/rollback",
+    tag = "Documents",
+    params(BatchParam),
+    responses(
+        (status = 204, description = "The batch and its documents have been discarded"),
+        (status = 404, description = "Batch not found", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/batch/{batch_id}/rollback", wrap = "Authentication::Private")]
+async fn rollback_batch(
+    data: web::Data<Data>,
+    path: web::Path<BatchParam>,
+) -> Result<HttpResponse, ResponseError> {
+    data.documents_batches
+        .remove(&path.index_uid, &path.batch_id)
+        .ok_or_else(|| batch_not_found(&path.batch_id))?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,440 @@
+use std::time::{Duration, Instant};
+
+use actix_web::{web, HttpResponse};
+use actix_web_macros::{get, post};
+use meilisearch_core::phonetic::is_phonetic_word;
+use meilisearch_core::query_parser::{parse_query, ParsedQuery};
+use meilisearch_core::stemming::is_stemmed_word;
+use meilisearch_core::update::value_to_string;
+use meilisearch_core::{DocIndex, DocumentId, Filter};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use slice_group_by::GroupBy;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use crate::analytics::{AMPLITUDE_ENDPOINT, COLLECTED_DATA, EVENTS_INTERVAL_SECS};
+use crate::error::{Error, ErrorResponseBody, ResponseError};
+use crate::helpers::Authentication;
+use crate::routes::IndexParam;
+use crate::Data;
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(parse_query_debug)
+        .service(get_schema)
+        .service(inverted_index_stats)
+        .service(get_posting_list)
+        .service(get_analytics);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(parse_query_debug, get_schema, inverted_index_stats, get_posting_list, get_analytics))]
+pub struct DebugApi;
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ParseQueryBody {
+    #[serde(default)]
+    q: String,
+    filters: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct ParseQueryResponse {
+    #[schema(value_type = Object)]
+    query: ParsedQuery,
+    #[schema(value_type = Option<Object>)]
+    filters: Option<Value>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/debug/parse-query",
+    tag = "Debug",
+    params(IndexParam),
+    request_body = ParseQueryBody,
+    responses(
+        (status = 200, description = "The parsed query and filters", body = ParseQueryResponse),
+        (status = 400, description = "The filters are invalid", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[post(
+    "/indexes/{index_uid}/debug/parse-query",
+    wrap = "Authentication::Admin"
+)]
+async fn parse_query_debug(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    body: web::Json<ParseQueryBody>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+    let schema = index
+        .main
+        .schema(&reader)?
+        .ok_or(Error::internal("Impossible to retrieve the schema"))?;
+
+    let query = parse_query(&reader, &index, &body.q)?;
+
+    let filters = match &body.filters {
+        Some(expression) => Some(Filter::parse(expression, &schema)?.to_json(&schema)),
+        None => None,
+    };
+
+    Ok(HttpResponse::Ok().json(ParseQueryResponse { query, filters }))
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct SchemaField {
+    name: String,
+    id: u16,
+    searchable: bool,
+    displayed: bool,
+    /// Whether the field is in the attributes for faceting and can be used in facet filters.
+    filterable: bool,
+    /// Whether the field is ranked and can be used in an asc or desc ranking rule.
+    sortable: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct SchemaResponse {
+    primary_key: Option<String>,
+    fields: Vec<SchemaField>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/debug/schema",
+    tag = "Debug",
+    params(IndexParam),
+    responses(
+        (status = 200, description = "The fields of the schema", body = SchemaResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[get("/indexes/{index_uid}/debug/schema", wrap = "Authentication::Admin")]
+async fn get_schema(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+    let schema = index
+        .main
+        .schema(&reader)?
+        .ok_or(Error::internal("Impossible to retrieve the schema"))?;
+    let attributes_for_faceting = index.main.attributes_for_faceting(&reader)?;
+
+    let mut fields: Vec<_> = schema
+        .names()
+        .filter_map(|name| schema.id(name).map(|id| (name, id)))
+        .map(|(name, id)| SchemaField {
+            name: name.to_string(),
+            id: id.0,
+            searchable: schema.is_indexed(id).is_some(),
+            displayed: schema.is_displayed(id),
+            filterable: attributes_for_faceting.as_ref().map_or(false, |attrs| attrs.contains(&id)),
+            sortable: schema.is_ranked(id),
+        })
+        .collect();
+    fields.sort_unstable_by_key(|field| field.id);
+
+    Ok(HttpResponse::Ok().json(SchemaResponse {
+        primary_key: schema.primary_key().map(str::to_string),
+        fields,
+    }))
+}
+
+/// The maximum time spent reading the inverted index, partial statistics are returned past it.
+const INVERTED_INDEX_STATS_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct InvertedIndexStatsQuery {
+    field: String,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct TermCount {
+    term: String,
+    count: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct InvertedIndexStatsResponse {
+    field: String,
+    unique_terms: usize,
+    total_postings: usize,
+    avg_postings_per_term: f64,
+    max_postings_per_term: Option<TermCount>,
+    /// Whether the timeout was reached before the whole inverted index was read.
+    partial: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/diagnostic/inverted-index-stats",
+    tag = "Debug",
+    params(IndexParam, InvertedIndexStatsQuery),
+    responses(
+        (status = 200, description = "The term statistics of the field", body = InvertedIndexStatsResponse),
+        (status = 400, description = "The field is not searchable", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[get(
+    "/indexes/{index_uid}/diagnostic/inverted-index-stats",
+    wrap = "Authentication::Admin"
+)]
+async fn inverted_index_stats(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    params: web::Query<InvertedIndexStatsQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+    let schema = index
+        .main
+        .schema(&reader)?
+        .ok_or(Error::internal("Impossible to retrieve the schema"))?;
+
+    let indexed_pos = schema
+        .id(&params.field)
+        .and_then(|field_id| schema.is_indexed(field_id))
+        .ok_or(Error::bad_parameter("field", format!("{} is not a searchable attribute", params.field)))?;
+
+    let start = Instant::now();
+    let mut unique_terms = 0;
+    let mut total_postings = 0;
+    let mut max_postings_per_term: Option<TermCount> = None;
+    let mut partial = false;
+
+    for result in index.postings_lists.iter(&reader).map_err(meilisearch_core::Error::from)? {
+        if start.elapsed() > INVERTED_INDEX_STATS_TIMEOUT {
+            partial = true;
+            break;
+        }
+
+        let (word, postings) = result.map_err(meilisearch_core::Error::from)?;
+        // the phonetic codes and the stems are not terms of the documents
+        if is_phonetic_word(word) || is_stemmed_word(word) {
+            continue;
+        }
+
+        let count = postings
+            .matches
+            .linear_group_by_key(|m| m.document_id)
+            .filter(|matches| matches.iter().any(|m| m.attribute == indexed_pos.0))
+            .count();
+
+        if count == 0 {
+            continue;
+        }
+
+        unique_terms += 1;
+        total_postings += count;
+        if max_postings_per_term.as_ref().map_or(true, |max| count > max.count) {
+            let term = String::from_utf8_lossy(word).into_owned();
+            max_postings_per_term = Some(TermCount { term, count });
+        }
+    }
+
+    let avg_postings_per_term = if unique_terms == 0 { 0.0 } else { total_postings as f64 / unique_terms as f64 };
+
+    Ok(HttpResponse::Ok().json(InvertedIndexStatsResponse {
+        field: params.into_inner().field,
+        unique_terms,
+        total_postings,
+        avg_postings_per_term,
+        max_postings_per_term,
+        partial,
+    }))
+}
+
+/// The number of documents of a posting list returned in a page when no limit is given.
+const DEFAULT_POSTING_LIST_LIMIT: usize = 20;
+
+/// The maximum number of documents of a posting list that can be returned in a page.
+const MAX_POSTING_LIST_LIMIT: usize = 1000;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct PostingListQuery {
+    term: String,
+    /// The maximum number of documents of the page, 20 by default and at most 1000.
+    limit: Option<usize>,
+    /// The `nextCursor` of the previous page.
+    cursor: Option<u32>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct PostingField {
+    name: String,
+    /// The positions of the term among the words of the field.
+    positions: Vec<u16>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct PostingDocument {
+    /// The primary key value of the document.
+    #[schema(value_type = Option<String>)]
+    id: Option<String>,
+    fields: Vec<PostingField>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct PostingListResponse {
+    term: String,
+    /// The number of documents containing the term.
+    document_count: usize,
+    documents: Vec<PostingDocument>,
+    /// The cursor of the next page, `None` on the last page.
+    next_cursor: Option<u32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/debug/posting-list",
+    tag = "Debug",
+    params(IndexParam, PostingListQuery),
+    responses(
+        (status = 200, description = "The documents containing the term and its positions in them", body = PostingListResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[get("/indexes/{index_uid}/debug/posting-list", wrap = "Authentication::Admin")]
+async fn get_posting_list(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    params: web::Query<PostingListQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+    let schema = index
+        .main
+        .schema(&reader)?
+        .ok_or(Error::internal("Impossible to retrieve the schema"))?;
+    let primary_key = schema.primary_key().and_then(|name| schema.id(name));
+
+    // the words are indexed lowercased
+    let term = params.term.to_lowercase();
+    let limit = params.limit.unwrap_or(DEFAULT_POSTING_LIST_LIMIT).min(MAX_POSTING_LIST_LIMIT);
+    let cursor = DocumentId(params.cursor.unwrap_or(0));
+
+    let postings = index
+        .postings_lists
+        .postings_list(&reader, term.as_bytes())
+        .map_err(meilisearch_core::Error::from)?;
+
+    let document_count = postings.as_ref().map_or(0, |postings| postings.docids.len());
+    let matches: &[DocIndex] = match &postings {
+        Some(postings) => &postings.matches,
+        None => &[],
+    };
+
+    let mut documents = Vec::new();
+    let mut next_cursor = None;
+    let documents_matches = matches.linear_group_by_key(|m| m.document_id);
+    for matches in documents_matches.filter(|matches| matches[0].document_id >= cursor) {
+        if documents.len() == limit {
+            next_cursor = Some(matches[0].document_id.0);
+            break;
+        }
+
+        let document_id = matches[0].document_id;
+        let id = match primary_key {
+            Some(primary_key) => index
+                .document_attribute::<Value>(&reader, document_id, primary_key)?
+                .map(|value| value_to_string(&value)),
+            None => None,
+        };
+
+        let fields = matches
+            .linear_group_by_key(|m| m.attribute)
+            .map(|matches| {
+                let name = schema
+                    .indexed_pos_to_field_id(matches[0].attribute)
+                    .and_then(|field_id| schema.name(field_id))
+                    .unwrap_or_default()
+                    .to_string();
+                let mut positions: Vec<_> = matches.iter().map(|m| m.word_index).collect();
+                positions.dedup();
+                PostingField { name, positions }
+            })
+            .collect();
+
+        documents.push(PostingDocument { id, fields });
+    }
+
+    Ok(HttpResponse::Ok().json(PostingListResponse {
+        term,
+        document_count,
+        documents,
+        next_cursor,
+    }))
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct CollectedField {
+    name: &'static str,
+    description: &'static str,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct AnalyticsResponse {
+    enabled: bool,
+    endpoint: &'static str,
+    interval_secs: u64,
+    collected_data: Vec<CollectedField>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/debug/analytics",
+    tag = "Debug",
+    responses(
+        (status = 200, description = "Whether analytics are sent and the data they contain", body = AnalyticsResponse),
+    )
+)]
+#[get("/debug/analytics", wrap = "Authentication::Admin")]
+async fn get_analytics(data: web::Data<Data>) -> Result<HttpResponse, ResponseError> {
+    let collected_data = COLLECTED_DATA
+        .iter()
+        .map(|&(name, description)| CollectedField { name, description })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(AnalyticsResponse {
+        enabled: data.analytics_enabled,
+        endpoint: AMPLITUDE_ENDPOINT,
+        interval_secs: EVENTS_INTERVAL_SECS,
+        collected_data,
+    }))
+}
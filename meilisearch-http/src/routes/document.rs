@@ -1,35 +1,95 @@
 use std::collections::{BTreeSet, HashSet};
+use std::hash::Hasher;
+use std::time::Instant;
 
-use actix_web::{web, HttpResponse};
-use actix_web_macros::{delete, get, post, put};
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web_macros::{delete, get, patch, post, put};
 use indexmap::IndexMap;
-use meilisearch_core::update;
-use serde::Deserialize;
+use meilisearch_core::transformer::Transformer;
+use meilisearch_core::update::{ConflictsOnNull, DocumentPatch};
+use meilisearch_core::{similar, update, DocumentId, Filter, Index, MResult, MainReader, Schema};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use siphasher::sip::SipHasher;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
 use crate::Data;
-use crate::error::{Error, ResponseError};
+use crate::error::{Error, ErrorResponseBody, ResponseError};
+use crate::helpers::idempotency::Reservation;
+use crate::helpers::references::inject_referenced_documents;
 use crate::helpers::Authentication;
 use crate::routes::{IndexParam, IndexUpdateResponse};
 
-type Document = IndexMap<String, Value>;
+pub(crate) type Document = IndexMap<String, Value>;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
 struct DocumentParam {
     index_uid: String,
     document_id: String,
 }
 
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+struct DocumentVersionParam {
+    index_uid: String,
+    document_id: String,
+    version: u64,
+}
+
 pub fn services(cfg: &mut web::ServiceConfig) {
-    cfg.service(get_document)
+    // registered first not to be taken for the document whose id is `count`
+    cfg.service(count_documents)
+        .service(get_document)
+        .service(get_similar_documents)
+        .service(replace_document)
+        .service(patch_document)
+        .service(get_document_versions)
+        .service(get_document_version)
+        .service(restore_document_version)
         .service(delete_document)
         .service(get_all_documents)
         .service(add_documents)
         .service(update_documents)
+        .service(upsert_documents)
         .service(delete_documents)
+        .service(transform_documents)
+        .service(documents_exist)
         .service(clear_all_documents);
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(
+    count_documents,
+    get_document,
+    get_similar_documents,
+    replace_document,
+    patch_document,
+    get_document_versions,
+    get_document_version,
+    restore_document_version,
+    delete_document,
+    get_all_documents,
+    add_documents,
+    update_documents,
+    upsert_documents,
+    delete_documents,
+    transform_documents,
+    documents_exist,
+    clear_all_documents,
+))]
+pub struct DocumentApi;
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/documents/{document_id}",
+    tag = "Documents",
+    params(DocumentParam),
+    responses(
+        (status = 200, description = "The document", body = Object),
+        (status = 404, description = "Index or document not found", body = ErrorResponseBody),
+    )
+)]
 #[get(
     "/indexes/{index_uid}/documents/{document_id}",
     wrap = "Authentication::Public"
@@ -56,6 +116,402 @@ async fn get_document(
     Ok(HttpResponse::Ok().json(document))
 }
 
+/// The number of the most frequent words of the document used to find the similar ones.
+const SIMILAR_QUERY_WORDS: usize = 10;
+
+/// The number of the documents sharing the most of these words that are scored.
+const SIMILAR_MAX_CANDIDATES: usize = 1000;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct SimilarQuery {
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct SimilarHit {
+    #[serde(flatten)]
+    document: Document,
+    #[serde(rename = "_jaccardScore")]
+    jaccard_score: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct SimilarResult {
+    #[schema(value_type = Vec<Object>)]
+    hits: Vec<SimilarHit>,
+    offset: usize,
+    limit: usize,
+    nb_hits: usize,
+    exhaustive_nb_hits: bool,
+    processing_time_ms: usize,
+    query: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/documents/{document_id}/similar-content",
+    tag = "Documents",
+    params(DocumentParam, SimilarQuery),
+    responses(
+        (status = 200, description = "The documents sharing the most words with this one", body = SimilarResult),
+        (status = 404, description = "Index or document not found", body = ErrorResponseBody),
+    )
+)]
+#[get(
+    "/indexes/{index_uid}/documents/{document_id}/similar-content",
+    wrap = "Authentication::Public"
+)]
+async fn get_similar_documents(
+    data: web::Data<Data>,
+    path: web::Path<DocumentParam>,
+    params: web::Query<SimilarQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let start = Instant::now();
+
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let offset = params.offset.unwrap_or(0);
+    let limit = params.limit.unwrap_or(20);
+
+    let reader = data.db.main_read_txn()?;
+    let schema = index
+        .main
+        .schema(&reader)?
+        .ok_or(Error::internal("Impossible to retrieve the schema"))?;
+
+    let internal_id = index.main
+        .external_to_internal_docid(&reader, &path.document_id)?
+        .ok_or(Error::document_not_found(&path.document_id))?;
+
+    let token_set = |document_id| {
+        data.token_sets.get_or_try_insert_with(&path.index_uid, document_id, || {
+            similar::document_words(&reader, &index, document_id)
+        })
+    };
+
+    // The most frequent words of the document form a virtual query, the documents
+    // sharing the most of them are the candidates scored by their similarity.
+    let words = token_set(internal_id)?;
+    let query_words = similar::most_frequent_words(&reader, &index, internal_id, &words, SIMILAR_QUERY_WORDS)?;
+    let query = query_words.join(" ");
+    let (candidates, nb_candidates) = similar::documents_sharing_words(&reader, &index, &query_words, SIMILAR_MAX_CANDIDATES + 1)?;
+
+    let mut scores = Vec::with_capacity(candidates.len());
+    for document_id in candidates.into_iter().filter(|id| *id != internal_id) {
+        let score = similar::jaccard_similarity(&words, &*token_set(document_id)?);
+        scores.push((document_id, score));
+    }
+    scores.sort_by(|(a, sa), (b, sb)| sb.partial_cmp(sa).unwrap_or(std::cmp::Ordering::Equal).then(a.cmp(b)));
+
+    let displayed_attributes = schema.displayed_name();
+    let mut hits = Vec::new();
+    for (document_id, jaccard_score) in scores.iter().skip(offset).take(limit) {
+        if let Some(document) = index.document(&reader, Some(&displayed_attributes), *document_id)? {
+            hits.push(SimilarHit { document, jaccard_score: *jaccard_score });
+        }
+    }
+
+    let result = SimilarResult {
+        hits,
+        offset,
+        limit,
+        nb_hits: scores.len(),
+        // the document itself is one of the candidates
+        exhaustive_nb_hits: nb_candidates <= SIMILAR_MAX_CANDIDATES + 1,
+        processing_time_ms: start.elapsed().as_millis() as usize,
+        query,
+    };
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ReplaceDocumentQuery {
+    /// Adds the document when there is none with this id, `true` by default.
+    upsert: Option<bool>,
+}
+
+#[utoipa::path(
+    put,
+    path = "/indexes/{index_uid}/documents/{document_id}",
+    tag = "Documents",
+    params(DocumentParam, ReplaceDocumentQuery),
+    request_body = Object,
+    responses(
+        (status = 202, description = "The document will be replaced", body = IndexUpdateResponse),
+        (status = 400, description = "The primary key of the document does not match the id", body = ErrorResponseBody),
+        (status = 404, description = "Index or document not found", body = ErrorResponseBody),
+    )
+)]
+#[put(
+    "/indexes/{index_uid}/documents/{document_id}",
+    wrap = "Authentication::Private"
+)]
+async fn replace_document(
+    data: web::Data<Data>,
+    path: web::Path<DocumentParam>,
+    params: web::Query<ReplaceDocumentQuery>,
+    body: web::Json<Document>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+
+    if !params.upsert.unwrap_or(true)
+        && index.main.external_to_internal_docid(&reader, &path.document_id)?.is_none()
+    {
+        return Err(Error::document_not_found(&path.document_id).into());
+    }
+
+    let mut schema = index
+        .main
+        .schema(&reader)?
+        .ok_or(Error::internal("Impossible to retrieve the schema"))?;
+
+    let mut documents = vec![body.into_inner()];
+    let new_primary_key = set_primary_key(&mut schema, &path.index_uid, None, &documents)?;
+
+    let primary_key = schema
+        .primary_key()
+        .ok_or(Error::internal("Impossible to retrieve the primary key"))?;
+    match documents[0].get(primary_key).map(update::value_to_string) {
+        Some(id) if id == path.document_id => (),
+        _ => {
+            return Err(Error::bad_request(format!(
+                "The primary key `{}` of the document must be `{}`",
+                primary_key, path.document_id,
+            )).into())
+        }
+    }
+
+    inject_referenced_documents(&data.db, &reader, &index, &mut documents)?;
+
+    let mut document_addition = index.documents_addition();
+    for document in documents {
+        document_addition.update_document(document);
+    }
+
+    if let Some(primary_key) = new_primary_key {
+        document_addition.set_detected_primary_key(primary_key);
+    }
+
+    let update_id = data.db.update_write(|w| document_addition.finalize(w))?;
+
+    Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct PatchDocumentQuery {
+    /// What to do with the null values of the patch: `delete` (the default) or `preserve`.
+    #[param(value_type = Option<String>)]
+    conflicts_on_null: Option<ConflictsOnNull>,
+}
+
+#[utoipa::path(
+    patch,
+    path = "/indexes/{index_uid}/documents/{document_id}",
+    tag = "Documents",
+    params(DocumentParam, PatchDocumentQuery),
+    request_body(content = Object, description = "A JSON Merge Patch of the document"),
+    responses(
+        (status = 202, description = "The patch will be applied to the document as it is when the update is processed", body = IndexUpdateResponse),
+        (status = 400, description = "The patched document is not valid", body = ErrorResponseBody),
+        (status = 404, description = "Index or document not found", body = ErrorResponseBody),
+    )
+)]
+#[patch(
+    "/indexes/{index_uid}/documents/{document_id}",
+    wrap = "Authentication::Private"
+)]
+async fn patch_document(
+    data: web::Data<Data>,
+    path: web::Path<DocumentParam>,
+    params: web::Query<PatchDocumentQuery>,
+    body: web::Json<Value>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+
+    if index.main.external_to_internal_docid(&reader, &path.document_id)?.is_none() {
+        return Err(Error::document_not_found(&path.document_id).into());
+    }
+
+    let schema = index
+        .main
+        .schema(&reader)?
+        .ok_or(Error::internal("Impossible to retrieve the schema"))?;
+
+    let primary_key = schema
+        .primary_key()
+        .ok_or(Error::internal("Impossible to retrieve the primary key"))?;
+
+    let patch: Document = match body.into_inner() {
+        Value::Object(patch) => patch.into_iter().collect(),
+        _ => return Err(Error::bad_request("The patch of a document must be an object").into()),
+    };
+
+    match patch.get(primary_key).map(update::value_to_string) {
+        Some(id) if id != path.document_id => {
+            return Err(Error::bad_request(format!(
+                "The primary key `{}` of the patched document must be `{}`",
+                primary_key, path.document_id,
+            )).into())
+        }
+        _ => (),
+    }
+
+    // the referenced documents of a changed reference are injected into the patch,
+    // the document itself is only patched when the update is processed
+    let mut patches = vec![patch];
+    inject_referenced_documents(&data.db, &reader, &index, &mut patches)?;
+
+    let patch = DocumentPatch {
+        document_id: path.document_id.clone(),
+        patch: patches.remove(0),
+        conflicts_on_null: params.conflicts_on_null.unwrap_or(ConflictsOnNull::Delete),
+    };
+    let update_id = data.db.update_write(|w| index.document_patch(w, patch))?;
+
+    Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/documents/{document_id}/versions",
+    tag = "Documents",
+    params(DocumentParam),
+    responses(
+        (status = 200, description = "The previous versions of the document, from the oldest to the newest", body = Vec<Object>),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[get(
+    "/indexes/{index_uid}/documents/{document_id}/versions",
+    wrap = "Authentication::Private"
+)]
+async fn get_document_versions(
+    data: web::Data<Data>,
+    path: web::Path<DocumentParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+    let versions = index
+        .document_versions
+        .versions(&reader, &path.document_id)
+        .map_err(meilisearch_core::Error::from)?;
+
+    Ok(HttpResponse::Ok().json(versions))
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/documents/{document_id}/versions/{version}",
+    tag = "Documents",
+    params(DocumentVersionParam),
+    responses(
+        (status = 200, description = "The previous version of the document", body = Object),
+        (status = 404, description = "Index or version not found", body = ErrorResponseBody),
+    )
+)]
+#[get(
+    "/indexes/{index_uid}/documents/{document_id}/versions/{version}",
+    wrap = "Authentication::Private"
+)]
+async fn get_document_version(
+    data: web::Data<Data>,
+    path: web::Path<DocumentVersionParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+    let version = index
+        .document_versions
+        .version(&reader, &path.document_id, path.version)
+        .map_err(meilisearch_core::Error::from)?
+        .ok_or_else(|| Error::not_found(format!("Version {} of the document {}", path.version, path.document_id)))?;
+
+    Ok(HttpResponse::Ok().json(version))
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/documents/{document_id}/restore/{version}",
+    tag = "Documents",
+    params(DocumentVersionParam),
+    responses(
+        (status = 202, description = "The document will be replaced by the version", body = IndexUpdateResponse),
+        (status = 404, description = "Index or version not found", body = ErrorResponseBody),
+    )
+)]
+#[post(
+    "/indexes/{index_uid}/documents/{document_id}/restore/{version}",
+    wrap = "Authentication::Private"
+)]
+async fn restore_document_version(
+    data: web::Data<Data>,
+    path: web::Path<DocumentVersionParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+    let version = index
+        .document_versions
+        .version(&reader, &path.document_id, path.version)
+        .map_err(meilisearch_core::Error::from)?
+        .ok_or_else(|| Error::not_found(format!("Version {} of the document {}", path.version, path.document_id)))?;
+
+    let mut documents = vec![version.document];
+    inject_referenced_documents(&data.db, &reader, &index, &mut documents)?;
+
+    // the current version of the document is archived in turn
+    let mut document_addition = index.documents_addition();
+    for document in documents {
+        document_addition.update_document(document);
+    }
+
+    let update_id = data.db.update_write(|w| document_addition.finalize(w))?;
+
+    Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/documents/{document_id}",
+    tag = "Documents",
+    params(DocumentParam),
+    responses(
+        (status = 202, description = "The deletion has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[delete(
     "/indexes/{index_uid}/documents/{document_id}",
     wrap = "Authentication::Private"
@@ -77,14 +533,92 @@ async fn delete_document(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct CountDocumentsQuery {
+    /// Only counts the documents matching the filter, e.g. `category = shoes`. The equalities on the
+    /// attributes for faceting are resolved by the facet index, without them every document is tested.
+    filter: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct CountDocumentsResponse {
+    count: u64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/documents/count",
+    tag = "Documents",
+    params(IndexParam, CountDocumentsQuery),
+    responses(
+        (status = 200, description = "The number of documents of the index", body = CountDocumentsResponse),
+        (status = 400, description = "The filter is invalid", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[get("/indexes/{index_uid}/documents/count", wrap = "Authentication::Public")]
+async fn count_documents(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    params: web::Query<CountDocumentsQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+
+    // the number of documents is kept up to date by the updates
+    let expression = match &params.filter {
+        Some(expression) => expression,
+        None => {
+            let count = index.main.number_of_documents(&reader)?;
+            return Ok(HttpResponse::Ok().json(CountDocumentsResponse { count }));
+        }
+    };
+
+    let schema = index
+        .main
+        .schema(&reader)?
+        .ok_or(Error::internal("Impossible to retrieve the schema"))?;
+    let filter = Filter::parse(expression, &schema)?;
+
+    let mut count = 0;
+    for document_id in filter_candidates(&reader, &index, Some(&filter))? {
+        if filter.test(&reader, &index, document_id?)? {
+            count += 1;
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(CountDocumentsResponse { count }))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct BrowseQuery {
     offset: Option<usize>,
     limit: Option<usize>,
     attributes_to_retrieve: Option<String>,
+    /// Only returns the documents matching the filter, they are not ranked. The equalities on the attributes
+    /// for faceting are resolved by the facet index, without them the documents are tested until the page is full.
+    filter: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/documents",
+    tag = "Documents",
+    params(IndexParam, BrowseQuery),
+    responses(
+        (status = 200, description = "The documents of the index", body = Vec<Object>),
+        (status = 400, description = "The filter is invalid", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get("/indexes/{index_uid}/documents", wrap = "Authentication::Public")]
 async fn get_all_documents(
     data: web::Data<Data>,
@@ -100,12 +634,38 @@ async fn get_all_documents(
     let limit = params.limit.unwrap_or(20);
 
     let reader = data.db.main_read_txn()?;
-    let documents_ids: Result<BTreeSet<_>, _> = index
-        .documents_fields_counts
-        .documents_ids(&reader)?
-        .skip(offset)
-        .take(limit)
-        .collect();
+    let schema = match params.filter {
+        Some(_) => Some(index
+            .main
+            .schema(&reader)?
+            .ok_or(Error::internal("Impossible to retrieve the schema"))?),
+        None => None,
+    };
+    let filter = match (&params.filter, &schema) {
+        (Some(expression), Some(schema)) => Some(Filter::parse(expression, schema)?),
+        _ => None,
+    };
+
+    let mut documents_ids = BTreeSet::new();
+    let mut skipped = 0;
+    for document_id in filter_candidates(&reader, &index, filter.as_ref())? {
+        if documents_ids.len() == limit {
+            break;
+        }
+
+        let document_id = document_id?;
+        if let Some(filter) = &filter {
+            if !filter.test(&reader, &index, document_id)? {
+                continue;
+            }
+        }
+
+        if skipped < offset {
+            skipped += 1;
+        } else {
+            documents_ids.insert(document_id);
+        }
+    }
 
     let attributes: Option<HashSet<&str>> = params
         .attributes_to_retrieve
@@ -113,7 +673,7 @@ async fn get_all_documents(
         .map(|a| a.split(',').collect());
 
     let mut documents = Vec::new();
-    for document_id in documents_ids? {
+    for document_id in documents_ids {
         if let Ok(Some(document)) =
             index.document::<Document>(&reader, attributes.as_ref(), document_id)
         {
@@ -124,19 +684,150 @@ async fn get_all_documents(
     Ok(HttpResponse::Ok().json(documents))
 }
 
-fn find_primary_key(document: &IndexMap<String, Value>) -> Option<String> {
-    for key in document.keys() {
-        if key.to_lowercase().contains("id") {
+/// Returns the ids of the documents that can match the filter, in order, the ones found in the
+/// facet index when the filter can be resolved by it, every document otherwise.
+fn filter_candidates<'txn>(
+    reader: &'txn MainReader,
+    index: &Index,
+    filter: Option<&Filter>,
+) -> MResult<Box<dyn Iterator<Item = MResult<DocumentId>> + 'txn>> {
+    match filter.map(|filter| filter.candidates(reader, index)).transpose()?.flatten() {
+        Some(candidates) => Ok(Box::new(candidates.into_vec().into_iter().map(Ok))),
+        None => Ok(Box::new(index.documents_fields_counts.documents_ids(reader)?)),
+    }
+}
+
+/// The names tried in order, after `id` and `{indexUid}Id`, to infer the primary key.
+const PRIMARY_KEY_NAMES: [&str; 4] = ["pk", "_id", "uuid", "key"];
+
+fn primary_key_candidates(index_uid: &str) -> Vec<String> {
+    let mut candidates = vec!["id".to_string(), format!("{}Id", index_uid)];
+    candidates.extend(PRIMARY_KEY_NAMES.iter().map(|name| name.to_string()));
+    candidates
+}
+
+fn find_primary_key(index_uid: &str, document: &IndexMap<String, Value>) -> Option<String> {
+    for candidate in primary_key_candidates(index_uid) {
+        if let Some(key) = document.keys().find(|key| key.eq_ignore_ascii_case(&candidate)) {
             return Some(key.to_string());
         }
     }
-    None
+
+    // falls back to the first field that looks like an identifier
+    document.keys().find(|key| key.to_lowercase().contains("id")).cloned()
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct UpdateDocumentsQuery {
     primary_key: Option<String>,
+    /// What to do with the invalid documents: `abort` (the default), `skip` or `fail`.
+    #[param(value_type = Option<String>)]
+    document_error_strategy: Option<update::DocumentErrorStrategy>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct DocumentError {
+    document_position: usize,
+    message: String,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct DryRunResponse {
+    processed_documents: usize,
+    errors: Vec<DocumentError>,
+}
+
+const DRY_RUN_HEADER: &str = "X-Meili-Dry-Run";
+
+fn is_dry_run(req: &HttpRequest) -> bool {
+    req.headers()
+        .get(DRY_RUN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| value.eq_ignore_ascii_case("true"))
+}
+
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Returns the API key of the request along with its idempotency key, if any.
+fn idempotency_key(req: &HttpRequest) -> Option<(Option<String>, String)> {
+    let key = req
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())?;
+
+    let api_key = req
+        .headers()
+        .get("X-Meili-API-Key")
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+
+    Some((api_key, key.to_string()))
+}
+
+/// The hash of everything an update of the documents depends on, two requests
+/// with the same idempotency key must have the same one.
+fn update_request_hash(
+    index_uid: &str,
+    params: &UpdateDocumentsQuery,
+    documents: &[Document],
+    is_partial: bool,
+) -> Result<u64, ResponseError> {
+    let request = (index_uid, &params.primary_key, params.document_error_strategy, is_partial, documents);
+    let request = serde_json::to_vec(&request).map_err(Error::internal)?;
+
+    let mut hasher = SipHasher::new();
+    hasher.write(&request);
+    Ok(hasher.finish())
+}
+
+/// Sets the primary key of the schema, the one given or the one inferred from the first
+/// document, when it does not have one yet, returns the primary key if the schema was changed.
+/// The schema is not saved, a detected primary key is given to the index by the update.
+pub(crate) fn set_primary_key(
+    schema: &mut Schema,
+    index_uid: &str,
+    primary_key: Option<&str>,
+    documents: &[Document],
+) -> Result<Option<String>, ResponseError> {
+    if schema.primary_key().is_some() {
+        return Ok(None);
+    }
+
+    let id = match primary_key {
+        Some(id) => id.to_string(),
+        None => match documents.first().and_then(|document| find_primary_key(index_uid, document)) {
+            Some(id) => id,
+            None => {
+                let fields: Vec<_> = documents
+                    .first()
+                    .into_iter()
+                    .flat_map(|document| document.keys())
+                    .map(|field| format!("`{}`", field))
+                    .collect();
+                let names: Vec<_> = primary_key_candidates(index_uid)
+                    .iter()
+                    .map(|name| format!("`{}`", name))
+                    .collect();
+                return Err(Error::bad_request(format!(
+                    "Could not infer a primary key; the candidates were the fields of the first document, [{}], and none of them is named {} or contains `id`",
+                    fields.join(", "),
+                    names.join(", "),
+                )).into());
+            }
+        },
+    };
+
+    schema
+        .set_primary_key(&id)
+        .map_err(Error::bad_request)?;
+
+    Ok(Some(id))
 }
 
 async fn update_multiple_documents(
@@ -145,12 +836,26 @@ async fn update_multiple_documents(
     params: web::Query<UpdateDocumentsQuery>,
     body: web::Json<Vec<Document>>,
     is_partial: bool,
+    dry_run: bool,
+    idempotency_key: Option<(Option<String>, String)>,
 ) -> Result<HttpResponse, ResponseError> {
     let index = data
         .db
         .open_index(&path.index_uid)
         .ok_or(Error::index_not_found(&path.index_uid))?;
 
+    // a dry run does not enqueue anything, there is nothing to send again
+    let reserved_key = match idempotency_key.filter(|_| !dry_run) {
+        Some((api_key, key)) => {
+            let request_hash = update_request_hash(&path.index_uid, &params, &body, is_partial)?;
+            match data.idempotency_keys.reserve(api_key.as_deref(), &key, request_hash)? {
+                Reservation::Done(response) => return Ok(HttpResponse::Accepted().json(response)),
+                Reservation::Reserved(reserved_key) => Some(reserved_key),
+            }
+        }
+        None => None,
+    };
+
     let reader = data.db.main_read_txn()?;
 
     let mut schema = index
@@ -158,20 +863,34 @@ async fn update_multiple_documents(
         .schema(&reader)?
         .ok_or(Error::internal("Impossible to retrieve the schema"))?;
 
-    if schema.primary_key().is_none() {
-        let id = match &params.primary_key {
-            Some(id) => id.to_string(),
-            None => body
-                .first()
-                .and_then(find_primary_key)
-                .ok_or(Error::bad_request("Could not infer a primary key"))?,
-        };
+    // the primary key given is saved right away, the one detected is given to the index by the update
+    let new_primary_key = set_primary_key(&mut schema, &path.index_uid, params.primary_key.as_deref(), &body)?;
+    let detected_primary_key = match new_primary_key {
+        Some(_) if params.primary_key.is_some() => {
+            if !dry_run {
+                data.db.main_write(|w| index.main.put_schema(w, &schema))?;
+            }
+            None
+        }
+        detected_primary_key => detected_primary_key,
+    };
 
-        schema
-            .set_primary_key(&id)
-            .map_err(Error::bad_request)?;
+    let mut documents = body.into_inner();
+    inject_referenced_documents(&data.db, &reader, &index, &mut documents)?;
 
-        data.db.main_write(|w| index.main.put_schema(w, &schema))?;
+    if dry_run {
+        let errors = update::check_documents_addition(&reader, &index, schema, &documents)?
+            .into_iter()
+            .map(|(document_position, error)| DocumentError {
+                document_position,
+                message: error.to_string(),
+            })
+            .collect();
+
+        return Ok(HttpResponse::Ok().json(DryRunResponse {
+            processed_documents: documents.len(),
+            errors,
+        }));
     }
 
     let mut document_addition = if is_partial {
@@ -180,35 +899,161 @@ async fn update_multiple_documents(
         index.documents_addition()
     };
 
-    for document in body.into_inner() {
+    for document in documents {
         document_addition.update_document(document);
     }
 
+    if let Some(error_strategy) = params.document_error_strategy {
+        document_addition.set_error_strategy(error_strategy);
+    }
+
+    if let Some(primary_key) = detected_primary_key {
+        document_addition.set_detected_primary_key(primary_key);
+    }
+
     let update_id = data.db.update_write(|w| document_addition.finalize(w))?;
+    let response = IndexUpdateResponse::with_id(update_id);
 
-    Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
+    if let Some(reserved_key) = reserved_key {
+        reserved_key.complete(response.clone());
+    }
+
+    Ok(HttpResponse::Accepted().json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/documents",
+    tag = "Documents",
+    params(
+        IndexParam,
+        UpdateDocumentsQuery,
+        ("X-Meili-Dry-Run" = Option<bool>, Header, description = "Only validate the documents"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Returns the same response to the same request sent again within 24 hours"),
+    ),
+    request_body = Vec<Object>,
+    responses(
+        (status = 202, description = "The documents will be added or replaced", body = IndexUpdateResponse),
+        (status = 200, description = "The result of the validation of the documents", body = DryRunResponse),
+        (status = 400, description = "The primary key could not be inferred", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+        (status = 409, description = "The idempotency key was used for a different request", body = ErrorResponseBody),
+    )
+)]
 #[post("/indexes/{index_uid}/documents", wrap = "Authentication::Private")]
 async fn add_documents(
     data: web::Data<Data>,
+    req: HttpRequest,
     path: web::Path<IndexParam>,
     params: web::Query<UpdateDocumentsQuery>,
     body: web::Json<Vec<Document>>,
 ) -> Result<HttpResponse, ResponseError> {
-    update_multiple_documents(data, path, params, body, false).await
+    let dry_run = is_dry_run(&req);
+    update_multiple_documents(data, path, params, body, false, dry_run, idempotency_key(&req)).await
 }
 
+#[utoipa::path(
+    put,
+    path = "/indexes/{index_uid}/documents",
+    tag = "Documents",
+    params(
+        IndexParam,
+        UpdateDocumentsQuery,
+        ("X-Meili-Dry-Run" = Option<bool>, Header, description = "Only validate the documents"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Returns the same response to the same request sent again within 24 hours"),
+    ),
+    request_body = Vec<Object>,
+    responses(
+        (status = 202, description = "The documents will be added or updated", body = IndexUpdateResponse),
+        (status = 200, description = "The result of the validation of the documents", body = DryRunResponse),
+        (status = 400, description = "The primary key could not be inferred", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+        (status = 409, description = "The idempotency key was used for a different request", body = ErrorResponseBody),
+    )
+)]
 #[put("/indexes/{index_uid}/documents", wrap = "Authentication::Private")]
 async fn update_documents(
     data: web::Data<Data>,
+    req: HttpRequest,
     path: web::Path<IndexParam>,
     params: web::Query<UpdateDocumentsQuery>,
     body: web::Json<Vec<Document>>,
 ) -> Result<HttpResponse, ResponseError> {
-    update_multiple_documents(data, path, params, body, true).await
+    let dry_run = is_dry_run(&req);
+    update_multiple_documents(data, path, params, body, true, dry_run, idempotency_key(&req)).await
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+enum UpdateStrategy {
+    /// The fields of the existing document are kept, unless the new document overwrites them.
+    Merge,
+    /// The existing document is replaced by the new one.
+    Replace,
+}
+
+impl Default for UpdateStrategy {
+    fn default() -> UpdateStrategy {
+        UpdateStrategy::Replace
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct UpsertBody {
+    #[serde(default)]
+    update_strategy: UpdateStrategy,
+    #[schema(value_type = Vec<Object>)]
+    documents: Vec<Document>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/documents/upsert",
+    tag = "Documents",
+    params(
+        IndexParam,
+        UpdateDocumentsQuery,
+        ("X-Meili-Dry-Run" = Option<bool>, Header, description = "Only validate the documents"),
+        ("Idempotency-Key" = Option<String>, Header, description = "Returns the same response to the same request sent again within 24 hours"),
+    ),
+    request_body = UpsertBody,
+    responses(
+        (status = 202, description = "The documents will be added, or merged with or replace the existing ones", body = IndexUpdateResponse),
+        (status = 200, description = "The result of the validation of the documents", body = DryRunResponse),
+        (status = 400, description = "The primary key could not be inferred", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+        (status = 409, description = "The idempotency key was used for a different request", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/documents/upsert", wrap = "Authentication::Private")]
+async fn upsert_documents(
+    data: web::Data<Data>,
+    req: HttpRequest,
+    path: web::Path<IndexParam>,
+    params: web::Query<UpdateDocumentsQuery>,
+    body: web::Json<UpsertBody>,
+) -> Result<HttpResponse, ResponseError> {
+    let dry_run = is_dry_run(&req);
+    let UpsertBody { update_strategy, documents } = body.into_inner();
+    let is_partial = match update_strategy {
+        UpdateStrategy::Merge => true,
+        UpdateStrategy::Replace => false,
+    };
+    update_multiple_documents(data, path, params, web::Json(documents), is_partial, dry_run, idempotency_key(&req)).await
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/documents/delete-batch",
+    tag = "Documents",
+    params(IndexParam),
+    request_body = Vec<String>,
+    responses(
+        (status = 202, description = "The deletion has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[post(
     "/indexes/{index_uid}/documents/delete-batch",
     wrap = "Authentication::Private"
@@ -236,6 +1081,133 @@ async fn delete_documents(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct TransformResponse {
+    #[schema(value_type = Vec<Object>)]
+    documents: Vec<Document>,
+    /// The documents the transformer failed on, they are returned as they are.
+    warnings: Vec<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/documents/transform",
+    tag = "Documents",
+    params(IndexParam),
+    request_body = Vec<Object>,
+    responses(
+        (status = 200, description = "The documents as the transformer of the index would store them", body = TransformResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[post(
+    "/indexes/{index_uid}/documents/transform",
+    wrap = "Authentication::Private"
+)]
+async fn transform_documents(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    body: web::Json<Vec<Document>>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+    let transformer = match index.main.transformer(&reader)? {
+        Some(transformer) => Some(transformer.parse::<Transformer>().map_err(Error::internal)?),
+        None => None,
+    };
+
+    let mut documents = body.into_inner();
+    let mut warnings = Vec::new();
+    if let Some(transformer) = transformer {
+        for (position, document) in documents.iter_mut().enumerate() {
+            if let Err(e) = transformer.apply(document) {
+                warnings.push(format!("document {}: the transformer failed, {}", position, e));
+            }
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(TransformResponse { documents, warnings }))
+}
+
+/// The maximum number of ids checked by a request.
+const MAX_EXISTS_IDS: usize = 10_000;
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct DocumentsExistBody {
+    #[schema(value_type = Vec<String>)]
+    ids: Vec<Value>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct DocumentsExistResponse {
+    existing: Vec<String>,
+    missing: Vec<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/documents/exists",
+    tag = "Documents",
+    params(IndexParam),
+    request_body = DocumentsExistBody,
+    responses(
+        (status = 200, description = "The ids of the documents of the index and the ones it does not have", body = DocumentsExistResponse),
+        (status = 400, description = "There are too many ids", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[post(
+    "/indexes/{index_uid}/documents/exists",
+    wrap = "Authentication::Public"
+)]
+async fn documents_exist(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    body: web::Json<DocumentsExistBody>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    if body.ids.len() > MAX_EXISTS_IDS {
+        return Err(Error::bad_parameter("ids", format!("at most {} ids can be checked at once", MAX_EXISTS_IDS)).into());
+    }
+
+    // the ids are only looked up, the documents are not read
+    let reader = data.db.main_read_txn()?;
+    let external_docids = index.main.external_docids(&reader)?;
+    let mut existing = Vec::new();
+    let mut missing = Vec::new();
+    for document_id in &body.ids {
+        let document_id = update::value_to_string(document_id);
+        if external_docids.get(&document_id).is_some() {
+            existing.push(document_id);
+        } else {
+            missing.push(document_id);
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(DocumentsExistResponse { existing, missing }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/documents",
+    tag = "Documents",
+    params(IndexParam),
+    responses(
+        (status = 202, description = "The deletion has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[delete("/indexes/{index_uid}/documents", wrap = "Authentication::Private")]
 async fn clear_all_documents(
     data: web::Data<Data>,
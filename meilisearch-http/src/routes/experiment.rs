@@ -0,0 +1,174 @@
+use actix_web::{web, HttpResponse};
+use actix_web_macros::{delete, get, post};
+use meilisearch_core::settings::RankingRule;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use crate::error::{Error, ErrorResponseBody, ResponseError};
+use crate::helpers::experiments::GroupMetrics;
+use crate::helpers::Authentication;
+use crate::routes::IndexParam;
+use crate::Data;
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_experiment).service(get_experiment_results).service(delete_experiment);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(create_experiment, get_experiment_results, delete_experiment))]
+pub struct ExperimentApi;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+struct ExperimentParam {
+    index_uid: String,
+    name: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ExperimentBody {
+    name: String,
+    /// The ranking rules of the control group.
+    control: Vec<String>,
+    /// The ranking rules of the treatment group.
+    treatment: Vec<String>,
+    /// The percentage, from 0 to 100, of the searches in the treatment group.
+    traffic_split: u8,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct GroupResults {
+    searches: usize,
+    zero_result_rate: Option<f64>,
+    clicks: usize,
+    /// The average position, starting at 0, of the clicked hits.
+    average_rank: Option<f64>,
+}
+
+impl From<GroupMetrics> for GroupResults {
+    fn from(metrics: GroupMetrics) -> GroupResults {
+        GroupResults {
+            searches: metrics.searches,
+            zero_result_rate: metrics.zero_result_rate(),
+            clicks: metrics.clicks,
+            average_rank: metrics.average_rank(),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct ExperimentResults {
+    name: String,
+    active: bool,
+    traffic_split: u8,
+    control: GroupResults,
+    treatment: GroupResults,
+}
+
+fn parse_ranking_rules(name: &str, rules: &[String]) -> Result<Vec<RankingRule>, Error> {
+    RankingRule::from_iter(rules).map_err(|e| Error::bad_parameter(name, e))
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/experiments",
+    tag = "Experiments",
+    params(IndexParam),
+    request_body = ExperimentBody,
+    responses(
+        (status = 201, description = "The experiment has been created and is the active one of the index", body = ExperimentResults),
+        (status = 400, description = "The ranking rules or the traffic split are invalid", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/experiments", wrap = "Authentication::Private")]
+async fn create_experiment(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    body: web::Json<ExperimentBody>,
+) -> Result<HttpResponse, ResponseError> {
+    data.db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    if body.traffic_split > 100 {
+        return Err(Error::bad_parameter("trafficSplit", "must be between 0 and 100").into());
+    }
+
+    let control = parse_ranking_rules("control", &body.control)?;
+    let treatment = parse_ranking_rules("treatment", &body.treatment)?;
+
+    if !data.experiments.create(&path.index_uid, &body.name, control, treatment, body.traffic_split) {
+        return Err(Error::bad_parameter("name", format!("the experiment {} already exists", body.name)).into());
+    }
+
+    Ok(HttpResponse::Created().json(ExperimentResults {
+        name: body.name.clone(),
+        active: true,
+        traffic_split: body.traffic_split,
+        control: GroupMetrics::default().into(),
+        treatment: GroupMetrics::default().into(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/experiments/{name}/results",
+    tag = "Experiments",
+    params(ExperimentParam),
+    responses(
+        (status = 200, description = "The metrics of both groups of the experiment", body = ExperimentResults),
+        (status = 404, description = "Index or experiment not found", body = ErrorResponseBody),
+    )
+)]
+#[get("/indexes/{index_uid}/experiments/{name}/results", wrap = "Authentication::Private")]
+async fn get_experiment_results(
+    data: web::Data<Data>,
+    path: web::Path<ExperimentParam>,
+) -> Result<HttpResponse, ResponseError> {
+    data.db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let experiment = data
+        .experiments
+        .get(&path.index_uid, &path.name)
+        .ok_or(Error::not_found(format!("Experiment {}", path.name)))?;
+
+    Ok(HttpResponse::Ok().json(ExperimentResults {
+        name: experiment.name,
+        active: experiment.active,
+        traffic_split: experiment.traffic_split,
+        control: experiment.control_metrics.into(),
+        treatment: experiment.treatment_metrics.into(),
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/experiments/{name}",
+    tag = "Experiments",
+    params(ExperimentParam),
+    responses(
+        (status = 204, description = "The experiment and its metrics have been deleted"),
+        (status = 404, description = "Index or experiment not found", body = ErrorResponseBody),
+    )
+)]
+#[delete("/indexes/{index_uid}/experiments/{name}", wrap = "Authentication::Private")]
+async fn delete_experiment(
+    data: web::Data<Data>,
+    path: web::Path<ExperimentParam>,
+) -> Result<HttpResponse, ResponseError> {
+    data.db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    if !data.experiments.delete(&path.index_uid, &path.name) {
+        return Err(Error::not_found(format!("Experiment {}", path.name)).into());
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,204 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use actix_web::{web, HttpResponse};
+use actix_web_macros::{get, post};
+use chrono::Utc;
+use meilisearch_core::store::ClickEvent;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::error::{Error, ErrorResponseBody, ResponseError};
+use crate::helpers::query_id::parse_query_id;
+use crate::helpers::Authentication;
+use crate::routes::IndexParam;
+use crate::Data;
+
+/// The number of days the clicks are kept for.
+const CLICKS_RETENTION_DAYS: i64 = 7;
+
+/// The minimum time between two removals of the expired clicks of an index.
+const CLICKS_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The maximum number of documents returned in the feedback statistics.
+const STATS_DOCUMENTS_LIMIT: usize = 100;
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(post_click).service(get_feedback_stats);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(post_click, get_feedback_stats))]
+pub struct FeedbackApi;
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ClickBody {
+    /// The `queryId` of the search response the document was clicked in.
+    query_id: String,
+    #[schema(value_type = Object)]
+    document_id: Value,
+    /// The position of the document in the hits, starting at 0.
+    position: usize,
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/feedback/click",
+    tag = "Feedback",
+    params(IndexParam),
+    request_body = ClickBody,
+    responses(
+        (status = 204, description = "The click has been recorded"),
+        (status = 400, description = "The query id or the document id is invalid", body = ErrorResponseBody),
+        (status = 404, description = "Index or document not found", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/feedback/click", wrap = "Authentication::Private")]
+async fn post_click(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    body: web::Json<ClickBody>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let query_id = parse_query_id(&body.query_id)
+        .ok_or(Error::bad_parameter("queryId", "must be a UUID given in a search response"))?;
+
+    let document_id = match &body.document_id {
+        Value::String(id) => id.clone(),
+        Value::Number(id) => id.to_string(),
+        _ => return Err(Error::bad_parameter("documentId", "must be a string or a number").into()),
+    };
+
+    let reader = data.db.main_read_txn()?;
+    if index.main.external_to_internal_docid(&reader, &document_id)?.is_none() {
+        return Err(Error::document_not_found(&document_id).into());
+    }
+
+    let click = ClickEvent {
+        document_id,
+        position: body.position,
+        clicked_at: Utc::now(),
+    };
+
+    let must_prune = {
+        let mut pruned_at = data.clicks_pruned_at.lock().unwrap();
+        match pruned_at.get(&path.index_uid) {
+            Some(instant) if instant.elapsed() < CLICKS_PRUNE_INTERVAL => false,
+            _ => {
+                pruned_at.insert(path.index_uid.clone(), Instant::now());
+                true
+            }
+        }
+    };
+
+    data.db.update_write::<_, _, ResponseError>(|writer| {
+        index.clicks.put_click(writer, &query_id, &click).map_err(meilisearch_core::Error::from)?;
+        if must_prune {
+            let expired_before = Utc::now() - chrono::Duration::days(CLICKS_RETENTION_DAYS);
+            index.clicks.remove_clicked_before(writer, expired_before).map_err(meilisearch_core::Error::from)?;
+        }
+        Ok(())
+    })?;
+
+    data.experiments.record_click(&path.index_uid, &body.query_id, body.position);
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct PositionStats {
+    position: usize,
+    clicks: usize,
+    /// The share of the clicked searches in which this position was clicked.
+    ctr: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct DocumentStats {
+    document_id: String,
+    clicks: usize,
+    /// The share of the clicked searches in which this document was clicked.
+    ctr: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct FeedbackStatsResponse {
+    total_clicks: usize,
+    clicked_queries: usize,
+    positions: Vec<PositionStats>,
+    /// The most clicked documents.
+    documents: Vec<DocumentStats>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/feedback/stats",
+    tag = "Feedback",
+    params(IndexParam),
+    responses(
+        (status = 200, description = "The clicks of the last days aggregated by position and by document", body = FeedbackStatsResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[get("/indexes/{index_uid}/feedback/stats", wrap = "Authentication::Private")]
+async fn get_feedback_stats(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let expired_before = Utc::now() - chrono::Duration::days(CLICKS_RETENTION_DAYS);
+    let update_reader = data.db.update_read_txn()?;
+
+    let mut total_clicks = 0;
+    let mut queries = HashSet::new();
+    let mut positions = HashMap::new();
+    let mut documents = HashMap::new();
+
+    for result in index.clicks.iter(&update_reader).map_err(meilisearch_core::Error::from)? {
+        let (query_id, click) = result.map_err(meilisearch_core::Error::from)?;
+        if click.clicked_at < expired_before {
+            continue;
+        }
+
+        total_clicks += 1;
+        queries.insert(query_id);
+        *positions.entry(click.position).or_insert(0) += 1;
+        *documents.entry(click.document_id).or_insert(0) += 1;
+    }
+
+    let clicked_queries = queries.len();
+    let ctr = |clicks: usize| clicks as f64 / clicked_queries as f64;
+
+    let mut positions: Vec<_> = positions
+        .into_iter()
+        .map(|(position, clicks)| PositionStats { position, clicks, ctr: ctr(clicks) })
+        .collect();
+    positions.sort_unstable_by_key(|stats| stats.position);
+
+    let mut documents: Vec<_> = documents
+        .into_iter()
+        .map(|(document_id, clicks)| DocumentStats { document_id, clicks, ctr: ctr(clicks) })
+        .collect();
+    documents.sort_unstable_by(|a, b| b.clicks.cmp(&a.clicks).then_with(|| a.document_id.cmp(&b.document_id)));
+    documents.truncate(STATS_DOCUMENTS_LIMIT);
+
+    Ok(HttpResponse::Ok().json(FeedbackStatsResponse {
+        total_clicks,
+        clicked_queries,
+        positions,
+        documents,
+    }))
+}
@@ -1,8 +1,10 @@
 use actix_web::{web, HttpResponse};
 use actix_web_macros::{get, put};
 use serde::Deserialize;
+use serde_json::json;
+use utoipa::{OpenApi, ToSchema};
 
-use crate::error::{Error, ResponseError};
+use crate::error::{Error, ErrorResponseBody, ResponseError};
 use crate::helpers::Authentication;
 use crate::Data;
 
@@ -10,13 +12,26 @@ pub fn services(cfg: &mut web::ServiceConfig) {
     cfg.service(get_health).service(change_healthyness);
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(get_health, change_healthyness))]
+pub struct HealthApi;
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "Health",
+    responses(
+        (status = 200, description = "The server is healthy, tells whether it is in read-only mode"),
+        (status = 503, description = "The server is in maintenance", body = ErrorResponseBody),
+    )
+)]
 #[get("/health", wrap = "Authentication::Private")]
 async fn get_health(data: web::Data<Data>) -> Result<HttpResponse, ResponseError> {
     let reader = data.db.main_read_txn()?;
     if let Ok(Some(_)) = data.db.get_health(&reader) {
         return Err(Error::Maintenance.into());
     }
-    Ok(HttpResponse::Ok().finish())
+    Ok(HttpResponse::Ok().json(json!({ "readOnly": data.read_only })))
 }
 
 async fn set_healthy(data: web::Data<Data>) -> Result<HttpResponse, ResponseError> {
@@ -29,11 +44,20 @@ async fn set_unhealthy(data: web::Data<Data>) -> Result<HttpResponse, ResponseEr
     Ok(HttpResponse::Ok().finish())
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Clone, ToSchema)]
 struct HealthBody {
     health: bool,
 }
 
+#[utoipa::path(
+    put,
+    path = "/health",
+    tag = "Health",
+    request_body = HealthBody,
+    responses(
+        (status = 200, description = "The health of the server has been changed"),
+    )
+)]
 #[put("/health", wrap = "Authentication::Private")]
 async fn change_healthyness(
     data: web::Data<Data>,
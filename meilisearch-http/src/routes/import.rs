@@ -0,0 +1,72 @@
+use actix_web::{web, HttpResponse};
+use actix_web_macros::post;
+use serde::Deserialize;
+use utoipa::{OpenApi, ToSchema};
+
+use crate::error::{Error, ErrorResponseBody, ResponseError};
+use crate::helpers::imports::ImportSource;
+use crate::helpers::public_hosts::check_public_url;
+use crate::helpers::Authentication;
+use crate::routes::{IndexParam, IndexUpdateResponse};
+use crate::Data;
+
+use meilisearch_core::update::{DocumentErrorStrategy, DocumentsImport, ImportFormat};
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(import_documents);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(import_documents))]
+pub struct ImportApi;
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ImportBody {
+    /// An `http(s)://` URL, or the `s3://bucket/key` or `gs://bucket/key` URL of an object, downloaded
+    /// with the key of its storage given to the server, or without authentication when there is none.
+    url: String,
+    /// `ndjson`, one JSON document per line, or `json`, a JSON array of documents.
+    #[schema(value_type = String)]
+    format: ImportFormat,
+    primary_key: Option<String>,
+    /// What to do with the invalid documents: `abort` (the default), `skip` or `fail`.
+    #[schema(value_type = Option<String>)]
+    document_error_strategy: Option<DocumentErrorStrategy>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/documents/import",
+    tag = "Documents",
+    params(IndexParam),
+    request_body = ImportBody,
+    responses(
+        (status = 202, description = "The file will be downloaded, and its documents added or replaced, by the update", body = IndexUpdateResponse),
+        (status = 400, description = "The URL is invalid or its host is not public", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/documents/import", wrap = "Authentication::Private")]
+async fn import_documents(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    body: web::Json<ImportBody>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let source = ImportSource::parse(&body.url).map_err(|e| Error::bad_parameter("url", e))?;
+    if let (ImportSource::Http(url), false) = (&source, data.allow_private_import_urls) {
+        check_public_url(url).map_err(|e| Error::bad_parameter("url", e))?;
+    }
+
+    let body = body.into_inner();
+    let import = DocumentsImport::new(body.url, body.format, body.primary_key);
+    let error_strategy = body.document_error_strategy.unwrap_or_default();
+    let update_id = data.db.update_write(|w| index.documents_import(w, import, error_strategy))?;
+
+    Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
+}
@@ -1,11 +1,16 @@
+use std::time::Instant;
+
 use actix_web::{web, HttpResponse};
 use actix_web_macros::{delete, get, post, put};
 use chrono::{DateTime, Utc};
-use log::error;
+use log::{error, info};
+use meilisearch_core::warm::{warm, WarmStrategy};
+use meilisearch_core::{Index, UpdateStatus};
 use rand::seq::SliceRandom;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
-use crate::error::{Error, ResponseError};
+use crate::error::{Error, ErrorResponseBody, ResponseError};
 use crate::helpers::Authentication;
 use crate::routes::IndexParam;
 use crate::Data;
@@ -16,8 +21,36 @@ pub fn services(cfg: &mut web::ServiceConfig) {
         .service(create_index)
         .service(update_index)
         .service(delete_index)
+        .service(warm_index)
         .service(get_update_status)
-        .service(get_all_updates_status);
+        .service(get_all_updates_status)
+        .service(get_index_tasks);
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    list_indexes,
+    get_index,
+    create_index,
+    update_index,
+    delete_index,
+    warm_index,
+    get_update_status,
+    get_all_updates_status,
+    get_index_tasks,
+))]
+pub struct IndexApi;
+
+/// The maximum length of the uid of an index.
+const MAX_INDEX_UID_LENGTH: usize = 128;
+
+/// An index uid is composed of alphanumeric characters, hyphens and underscores,
+/// and does not start with a digit.
+fn is_valid_index_uid(uid: &str) -> bool {
+    !uid.is_empty()
+        && uid.len() <= MAX_INDEX_UID_LENGTH
+        && !uid.starts_with(|c: char| c.is_ascii_digit())
+        && uid.chars().all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '_')
 }
 
 fn generate_uid() -> String {
@@ -29,16 +62,27 @@ fn generate_uid() -> String {
         .collect()
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct IndexResponse {
     name: String,
     uid: String,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
+    /// The last time documents were added or the settings changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_successful_indexing_at: Option<DateTime<Utc>>,
     primary_key: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes",
+    tag = "Indexes",
+    responses(
+        (status = 200, description = "The list of all the indexes", body = Vec<IndexResponse>),
+    )
+)]
 #[get("/indexes", wrap = "Authentication::Private")]
 async fn list_indexes(data: web::Data<Data>) -> Result<HttpResponse, ResponseError> {
     let reader = data.db.main_read_txn()?;
@@ -64,6 +108,7 @@ async fn list_indexes(data: web::Data<Data>) -> Result<HttpResponse, ResponseErr
                     .ok_or(Error::internal(
                             "Impossible to get the last update date of an index",
                     ))?;
+                let last_successful_indexing_at = index.main.last_successful_indexing_at(&reader)?;
 
                 let primary_key = match index.main.schema(&reader) {
                     Ok(Some(schema)) => match schema.primary_key() {
@@ -78,6 +123,7 @@ async fn list_indexes(data: web::Data<Data>) -> Result<HttpResponse, ResponseErr
                     uid: index_uid,
                     created_at,
                     updated_at,
+                    last_successful_indexing_at,
                     primary_key,
                 };
                 indexes.push(index_response);
@@ -92,6 +138,16 @@ async fn list_indexes(data: web::Data<Data>) -> Result<HttpResponse, ResponseErr
     Ok(HttpResponse::Ok().json(indexes))
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}",
+    tag = "Indexes",
+    params(IndexParam),
+    responses(
+        (status = 200, description = "The index", body = IndexResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get("/indexes/{index_uid}", wrap = "Authentication::Private")]
 async fn get_index(
     data: web::Data<Data>,
@@ -118,6 +174,7 @@ async fn get_index(
         .ok_or(Error::internal(
                 "Impossible to get the last update date of an index",
         ))?;
+    let last_successful_indexing_at = index.main.last_successful_indexing_at(&reader)?;
 
     let primary_key = match index.main.schema(&reader) {
         Ok(Some(schema)) => match schema.primary_key() {
@@ -131,13 +188,14 @@ async fn get_index(
         uid: path.index_uid.clone(),
         created_at,
         updated_at,
+        last_successful_indexing_at,
         primary_key,
     };
 
     Ok(HttpResponse::Ok().json(index_response))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct IndexCreateRequest {
     name: Option<String>,
@@ -145,6 +203,16 @@ struct IndexCreateRequest {
     primary_key: Option<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes",
+    tag = "Indexes",
+    request_body = IndexCreateRequest,
+    responses(
+        (status = 201, description = "The index has been created", body = IndexResponse),
+        (status = 400, description = "The index uid is invalid or already exists", body = ErrorResponseBody),
+    )
+)]
 #[post("/indexes", wrap = "Authentication::Private")]
 async fn create_index(
     data: web::Data<Data>,
@@ -158,10 +226,7 @@ async fn create_index(
 
     let uid = match &body.uid {
         Some(uid) => {
-            if uid
-                .chars()
-                .all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '_')
-            {
+            if is_valid_index_uid(uid) {
                 uid.to_owned()
             } else {
                 return Err(Error::InvalidIndexUid.into());
@@ -169,7 +234,7 @@ async fn create_index(
         }
         None => loop {
             let uid = generate_uid();
-            if data.db.open_index(&uid).is_none() {
+            if is_valid_index_uid(&uid) && data.db.open_index(&uid).is_none() {
                 break uid;
             }
         },
@@ -210,6 +275,7 @@ async fn create_index(
             uid,
             created_at,
             updated_at,
+            last_successful_indexing_at: None,
             primary_key: body.primary_key.clone(),
         };
         Ok(index_response)
@@ -235,6 +301,18 @@ struct UpdateIndexResponse {
     primary_key: Option<String>,
 }
 
+#[utoipa::path(
+    put,
+    path = "/indexes/{index_uid}",
+    tag = "Indexes",
+    params(IndexParam),
+    request_body = IndexCreateRequest,
+    responses(
+        (status = 200, description = "The index has been updated", body = IndexResponse),
+        (status = 400, description = "The primary key cannot be updated", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[put("/indexes/{index_uid}", wrap = "Authentication::Private")]
 async fn update_index(
     data: web::Data<Data>,
@@ -286,6 +364,7 @@ async fn update_index(
         .ok_or(Error::internal(
                 "Impossible to get the last update date of an index",
         ))?;
+    let last_successful_indexing_at = index.main.last_successful_indexing_at(&reader)?;
 
     let primary_key = match index.main.schema(&reader) {
         Ok(Some(schema)) => match schema.primary_key() {
@@ -300,12 +379,23 @@ async fn update_index(
         uid: path.index_uid.clone(),
         created_at,
         updated_at,
+        last_successful_indexing_at,
         primary_key,
     };
 
     Ok(HttpResponse::Ok().json(index_response))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}",
+    tag = "Indexes",
+    params(IndexParam),
+    responses(
+        (status = 204, description = "The index has been deleted"),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[delete("/indexes/{index_uid}", wrap = "Authentication::Private")]
 async fn delete_index(
     data: web::Data<Data>,
@@ -316,12 +406,90 @@ async fn delete_index(
     Ok(HttpResponse::NoContent().finish())
 }
 
-#[derive(Deserialize)]
+/// The number of terms preloaded by the `frequent-terms` strategy when no limit is given.
+const DEFAULT_WARM_TERMS: usize = 1000;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct WarmQuery {
+    /// Either `full` or `frequent-terms`, defaults to `full`.
+    strategy: Option<String>,
+    /// The number of terms preloaded by the `frequent-terms` strategy.
+    limit: Option<usize>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/warm",
+    tag = "Indexes",
+    params(IndexParam, WarmQuery),
+    responses(
+        (status = 202, description = "The index is being loaded in the page cache"),
+        (status = 400, description = "The strategy is unknown", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+        (status = 429, description = "Too many indexes are waiting to be warmed", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/warm", wrap = "Authentication::Private")]
+async fn warm_index(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    params: web::Query<WarmQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let strategy = match params.strategy.as_deref() {
+        None | Some("full") => WarmStrategy::Full,
+        Some("frequent-terms") => WarmStrategy::FrequentTerms(params.limit.unwrap_or(DEFAULT_WARM_TERMS)),
+        Some(_) => return Err(Error::bad_parameter("strategy", "must be full or frequent-terms").into()),
+    };
+
+    // the index is already being warmed, it will be loaded by the pending warm
+    let index_uid = path.into_inner().index_uid;
+    if !data.warming_indexes.lock().unwrap().insert(index_uid.clone()) {
+        return Ok(HttpResponse::Accepted().finish());
+    }
+
+    let job_data = data.clone();
+    let job_index_uid = index_uid.clone();
+    let pushed = data.warm_worker.push(move || {
+        let start = Instant::now();
+        match job_data.db.main_read_txn().and_then(|reader| warm(&reader, &index, strategy)) {
+            Ok(bytes) => info!("index {} warmed in {:.02?}, {} bytes read", job_index_uid, start.elapsed(), bytes),
+            Err(e) => error!("impossible to warm the index {}; {}", job_index_uid, e),
+        }
+        job_data.warming_indexes.lock().unwrap().remove(&job_index_uid);
+    });
+
+    if !pushed {
+        data.warming_indexes.lock().unwrap().remove(&index_uid);
+        return Err(Error::too_many_requests("too many indexes are waiting to be warmed").into());
+    }
+
+    Ok(HttpResponse::Accepted().finish())
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
 struct UpdateParam {
     index_uid: String,
     update_id: u64,
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/updates/{update_id}",
+    tag = "Updates",
+    params(UpdateParam),
+    responses(
+        (status = 200, description = "The status of the update", body = Object),
+        (status = 404, description = "Index or update not found", body = ErrorResponseBody),
+    )
+)]
 #[get(
     "/indexes/{index_uid}/updates/{update_id}",
     wrap = "Authentication::Private"
@@ -348,19 +516,118 @@ async fn get_update_status(
     }
 }
 
+/// The number of updates returned in a page when no limit is given.
+const DEFAULT_UPDATES_LIMIT: usize = 20;
+
+/// The maximum number of updates that can be returned in a page.
+const MAX_UPDATES_LIMIT: usize = 1000;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct UpdatesPageQuery {
+    /// The id of the first update of the page.
+    from: Option<u64>,
+    /// The maximum number of updates of the page, from 1 to 1000 and 20 by default.
+    limit: Option<usize>,
+    /// Only returns the updates with this status, `enqueued`, `processed` or `failed`.
+    status: Option<String>,
+    /// Only returns the updates of this type, e.g. `DocumentsAddition`.
+    #[serde(rename = "type")]
+    update_type: Option<String>,
+}
+
+impl UpdatesPageQuery {
+    fn is_empty(&self) -> bool {
+        self.from.is_none() && self.limit.is_none() && self.status.is_none() && self.update_type.is_none()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdatesPage {
+    results: Vec<UpdateStatus>,
+    /// The number of updates of the index, whatever the status and type filters.
+    total: u64,
+    limit: usize,
+    from: u64,
+    next: Option<u64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/updates",
+    tag = "Updates",
+    params(IndexParam, UpdatesPageQuery),
+    responses(
+        (status = 200, description = "The status of all the updates of the index, or a page of them when a parameter is given", body = Vec<Object>),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get("/indexes/{index_uid}/updates", wrap = "Authentication::Private")]
 async fn get_all_updates_status(
     data: web::Data<Data>,
     path: web::Path<IndexParam>,
+    params: web::Query<UpdatesPageQuery>,
 ) -> Result<HttpResponse, ResponseError> {
     let index = data
         .db
         .open_index(&path.index_uid)
         .ok_or(Error::index_not_found(&path.index_uid))?;
 
-    let reader = data.db.update_read_txn()?;
+    if params.is_empty() {
+        let reader = data.db.update_read_txn()?;
+        let response = index.all_updates_status(&reader)?;
+        return Ok(HttpResponse::Ok().json(response));
+    }
 
-    let response = index.all_updates_status(&reader)?;
+    updates_page(&data, &index, &params)
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/tasks",
+    tag = "Updates",
+    params(IndexParam, UpdatesPageQuery),
+    responses(
+        (status = 200, description = "A page of the updates of the index", body = Object),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[get("/indexes/{index_uid}/tasks", wrap = "Authentication::Private")]
+async fn get_index_tasks(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    params: web::Query<UpdatesPageQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    updates_page(&data, &index, &params)
+}
 
-    Ok(HttpResponse::Ok().json(response))
+fn updates_page(data: &Data, index: &Index, params: &UpdatesPageQuery) -> Result<HttpResponse, ResponseError> {
+    let reader = data.db.update_read_txn()?;
+
+    let from = params.from.unwrap_or(0);
+    let limit = params.limit.unwrap_or(DEFAULT_UPDATES_LIMIT).min(MAX_UPDATES_LIMIT);
+    // an empty page would point to itself as the next one
+    if limit == 0 {
+        return Err(Error::bad_parameter("limit", "must be greater than 0").into());
+    }
+    let filter = |status: &UpdateStatus| {
+        params.status.as_deref().map_or(true, |name| status.name() == name)
+            && params.update_type.as_deref().map_or(true, |name| status.update_type().name() == name)
+    };
+    let page = index.updates_status_from(&reader, from, limit, filter)?;
+
+    Ok(HttpResponse::Ok().json(UpdatesPage {
+        results: page.updates,
+        total: index.updates_count(&reader)?,
+        limit,
+        from,
+        next: page.next,
+    }))
 }
@@ -2,7 +2,9 @@ use actix_web::web;
 use actix_web::HttpResponse;
 use actix_web_macros::get;
 use serde::Serialize;
+use utoipa::{OpenApi, ToSchema};
 
+use crate::error::ErrorResponseBody;
 use crate::helpers::Authentication;
 use crate::Data;
 
@@ -10,12 +12,25 @@ pub fn services(cfg: &mut web::ServiceConfig) {
     cfg.service(list);
 }
 
-#[derive(Serialize)]
+#[derive(OpenApi)]
+#[openapi(paths(list))]
+pub struct KeyApi;
+
+#[derive(Serialize, ToSchema)]
 struct KeysResponse {
     private: Option<String>,
     public: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/keys",
+    tag = "Keys",
+    responses(
+        (status = 200, description = "The private and public API keys", body = KeysResponse),
+        (status = 401, description = "The master key is invalid", body = ErrorResponseBody),
+    )
+)]
 #[get("/keys", wrap = "Authentication::Admin")]
 async fn list(data: web::Data<Data>) -> HttpResponse {
     let api_keys = data.api_keys.clone();
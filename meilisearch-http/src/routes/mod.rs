@@ -1,22 +1,31 @@
 use actix_web::{get, HttpResponse};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
+pub mod analytics;
+pub mod batch;
+pub mod debug;
 pub mod document;
+pub mod experiment;
+pub mod feedback;
 pub mod health;
+pub mod import;
 pub mod index;
 pub mod key;
+pub mod openapi;
 pub mod search;
 pub mod setting;
 pub mod stats;
 pub mod stop_words;
 pub mod synonym;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Path)]
 pub struct IndexParam {
     index_uid: String,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct IndexUpdateResponse {
     pub update_id: u64,
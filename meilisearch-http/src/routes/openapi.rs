@@ -0,0 +1,46 @@
+use actix_web::{web, HttpResponse};
+use actix_web_macros::get;
+use utoipa::OpenApi;
+
+use crate::routes::{analytics, batch, debug, document, experiment, feedback, health, import, index, key, search, setting, stats, stop_words, synonym};
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(get_openapi).service(get_docs);
+}
+
+#[derive(OpenApi)]
+#[openapi(info(title = "MeiliSearch", description = "The MeiliSearch HTTP API"))]
+struct ApiDoc;
+
+/// Gathers the documentation of every routes module into a single OpenAPI document.
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    let mut openapi = ApiDoc::openapi();
+    openapi.merge(analytics::AnalyticsApi::openapi());
+    openapi.merge(batch::BatchApi::openapi());
+    openapi.merge(debug::DebugApi::openapi());
+    openapi.merge(document::DocumentApi::openapi());
+    openapi.merge(experiment::ExperimentApi::openapi());
+    openapi.merge(feedback::FeedbackApi::openapi());
+    openapi.merge(health::HealthApi::openapi());
+    openapi.merge(import::ImportApi::openapi());
+    openapi.merge(index::IndexApi::openapi());
+    openapi.merge(key::KeyApi::openapi());
+    openapi.merge(search::SearchApi::openapi());
+    openapi.merge(setting::SettingApi::openapi());
+    openapi.merge(stats::StatsApi::openapi());
+    openapi.merge(stop_words::StopWordsApi::openapi());
+    openapi.merge(synonym::SynonymApi::openapi());
+    openapi
+}
+
+#[get("/openapi.json")]
+async fn get_openapi() -> HttpResponse {
+    HttpResponse::Ok().json(openapi())
+}
+
+#[get("/docs")]
+async fn get_docs() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(include_str!("../../public/swagger.html").to_string())
+}
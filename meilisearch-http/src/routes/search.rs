@@ -1,58 +1,607 @@
-use std::collections::{HashSet, HashMap};
+use std::collections::{BTreeMap, HashSet, HashMap};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter;
+use std::mem;
+use std::time::Instant;
 
-use log::warn;
+use log::{info, warn};
+use actix_web::http::header::{ETAG, IF_NONE_MATCH, LINK};
 use actix_web::web;
-use actix_web::HttpResponse;
-use actix_web_macros::get;
-use serde::Deserialize;
-use serde_json::Value;
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web_macros::{get, post};
+use bytes::Bytes;
+use futures::channel::{mpsc, oneshot};
+use futures::future;
+use futures::stream::{self, Stream, StreamExt};
+use indexmap::IndexMap;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use siphasher::sip::SipHasher;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
-use crate::error::{Error, FacetCountError, ResponseError};
-use crate::helpers::meilisearch::IndexSearchExt;
+use crate::error::{Error, ErrorResponseBody, FacetCountError, ResponseError};
+use crate::helpers::embeddings::embed_documents_lazily;
+use crate::helpers::meilisearch::{
+    AggregationResult, CropSide, FacetDistribution, FallbackStrategy, IndexSearchExt, PendingHits, SearchBuilder, SearchHit,
+    SearchResult,
+};
+use crate::helpers::query_id::query_id_from_hash;
 use crate::helpers::Authentication;
 use crate::routes::IndexParam;
 use crate::Data;
 
 use meilisearch_core::facets::FacetFilter;
+use meilisearch_core::query_parser::analyze_query;
+use meilisearch_core::settings::{EmbeddingStrategy, RankingRule, DEFAULT_RANKING_RULES};
+use meilisearch_core::spellcheck;
+use meilisearch_core::{Index, MainReader};
 use meilisearch_schema::{Schema, FieldId};
 
 pub fn services(cfg: &mut web::ServiceConfig) {
-    cfg.service(search_with_url_query);
+    cfg.service(search_with_url_query)
+        .service(search_with_post)
+        .service(rerank)
+        .service(spellcheck_query);
 }
 
-#[derive(Deserialize)]
+#[derive(OpenApi)]
+#[openapi(paths(search_with_url_query, search_with_post, rerank, spellcheck_query))]
+pub struct SearchApi;
+
+const PROCESSING_TIME_HEADER: &str = "X-Meili-Processing-Time-Ms";
+
+/// The maximum number of hits of each group when the hits are grouped.
+const DEFAULT_GROUP_LIMIT: usize = 3;
+
+/// The number of hits of each aggregation when it has no limit.
+const DEFAULT_AGGREGATION_LIMIT: usize = 20;
+
+/// The maximum number of aggregations of a search, each one is another search.
+const MAX_AGGREGATIONS: usize = 10;
+
+/// The maximum number of hits of an aggregation.
+const MAX_AGGREGATION_LIMIT: usize = 100;
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct SearchQuery {
     q: String,
     offset: Option<usize>,
     limit: Option<usize>,
-    attributes_to_retrieve: Option<String>,
-    attributes_to_crop: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_string_list")]
+    attributes_to_retrieve: Option<Vec<String>>,
+    /// An alias of `attributesToRetrieve`, both lists are merged when they are given.
+    #[serde(default, deserialize_with = "deserialize_string_list")]
+    fields: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "deserialize_string_list")]
+    attributes_to_crop: Option<Vec<String>>,
     crop_length: Option<usize>,
-    attributes_to_highlight: Option<String>,
+    /// Keeps the values no longer than their crop length as they are, only the longer ones are cropped.
+    adaptive_crop: Option<bool>,
+    /// The side of the match the context of the cropped values is taken on: `left`, `right` or `both`, the default.
+    #[param(value_type = Option<String>)]
+    #[schema(value_type = Option<String>)]
+    snippet_ellipsis_side: Option<CropSide>,
+    #[serde(default, deserialize_with = "deserialize_string_list")]
+    attributes_to_highlight: Option<Vec<String>>,
+    /// Wraps the highlighted matches in `**` instead of `<em>` tags.
+    markdown_highlight: Option<bool>,
     filters: Option<String>,
     matches: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_facet_filters")]
     facet_filters: Option<String>,
-    facets_distribution: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_facets")]
+    #[param(value_type = Option<Vec<String>>)]
+    #[schema(value_type = Option<Vec<String>>)]
+    facets_distribution: Option<Value>,
+    /// The maximum number of values returned for these facets of the distribution, the most frequent first.
+    #[param(value_type = Option<Object>)]
+    #[schema(value_type = Option<Object>)]
+    facet_values_limits: Option<HashMap<String, usize>>,
+    /// The order of the values of these facets in the distribution, by count by default.
+    #[param(value_type = Option<Object>)]
+    #[schema(value_type = Option<Object>)]
+    facet_sort_order: Option<HashMap<String, FacetSortOrder>>,
+    /// Whether the values of the facets that no hit has are returned, with a count of 0, true by default.
+    show_all_facet_values: Option<bool>,
+    /// The facets of the distribution whose values are counted as if their own facet filters were not applied.
+    #[serde(default, deserialize_with = "deserialize_string_list")]
+    disjunctive_facets: Option<Vec<String>>,
+    /// The similarity, between 0.0 and 1.0, the words must have with the query words,
+    /// by default the typos allowed only depend on the length of the words.
+    min_similarity: Option<f32>,
+    /// Adds the stop words skipped and the stems searched to the response.
+    return_query_analysis: Option<bool>,
+    /// Adds to each hit the words of the query found in the document.
+    return_matched_words: Option<bool>,
+    /// Groups the hits by the value of this attribute.
+    group_by_attribute: Option<String>,
+    /// The maximum number of hits of each group, 3 by default.
+    group_limit: Option<usize>,
+    /// The 64-bit perceptual hash of an image, the hits with the closest image come first.
+    image_hash: Option<u64>,
+    /// Only returns the number of hits, the documents are not retrieved.
+    count_only: Option<bool>,
+    /// Adds to each hit the criteria of the ranking rules that ranked it at its position.
+    show_hit_explanation: Option<bool>,
+    /// What is searched when the query finds no hit: `none`, the default, `typo-relax` or `all`.
+    #[param(value_type = Option<String>)]
+    #[schema(value_type = Option<String>)]
+    zero_results_fallback: Option<FallbackStrategy>,
+    /// Other filters and sorts applied to all the results of the query, their first hits are returned by name.
+    #[param(value_type = Option<Vec<Object>>)]
+    #[schema(value_type = Option<Vec<Object>>)]
+    aggregations: Option<Vec<Aggregation>>,
 }
 
+/// The hits of an aggregation are the results of the query also matching its filter,
+/// ranked by its sort first.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Aggregation {
+    name: String,
+    filter: Option<String>,
+    /// `attribute:asc` or `attribute:desc`, the attribute must be one of an asc or desc ranking rule.
+    sort: Option<String>,
+    limit: Option<usize>,
+}
+
+/// How the values of a facet are ordered in the distribution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum FacetSortOrder {
+    /// In alphabetical order.
+    Alpha,
+    /// The most frequent first, the values with the same count in alphabetical order.
+    Count,
+}
+
+impl SearchQuery {
+    /// Returns the attribute to group the hits by and the maximum number of hits per group.
+    fn grouping(&self) -> Option<(String, usize)> {
+        let attribute = self.group_by_attribute.clone()?;
+        Some((attribute, self.group_limit.unwrap_or(DEFAULT_GROUP_LIMIT)))
+    }
+
+    /// Returns the attributes to retrieve, merged with the `fields` alias.
+    fn attributes_to_retrieve(&self) -> Option<HashSet<&str>> {
+        match (&self.attributes_to_retrieve, &self.fields) {
+            (None, None) => None,
+            (attributes, fields) => {
+                let attributes = attributes.iter().chain(fields).flatten();
+                Some(attributes.map(String::as_str).collect())
+            }
+        }
+    }
+}
+
+/// Deserializes a list of strings given either as a single comma-separated string,
+/// as repeated `key[]` query parameters or as a JSON array.
+fn deserialize_string_list<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where D: Deserializer<'de>,
+{
+    struct StringListVisitor;
+
+    impl<'de> Visitor<'de> for StringListVisitor {
+        type Value = Vec<String>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a comma-separated string or a list of strings")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            Ok(value.split(',').map(str::to_string).collect())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut values = Vec::new();
+            while let Some(value) = seq.next_element()? {
+                values.push(value);
+            }
+            Ok(values)
+        }
+    }
+
+    deserializer.deserialize_any(StringListVisitor).map(Some)
+}
+
+/// Deserializes the facets given either as a JSON array in a single string,
+/// as repeated `key[]` query parameters or as a JSON array.
+fn deserialize_facets<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where D: Deserializer<'de>,
+{
+    struct FacetsVisitor;
+
+    impl<'de> Visitor<'de> for FacetsVisitor {
+        type Value = Value;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a JSON array in a string or a list")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            Ok(Value::String(value.to_string()))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut values = Vec::new();
+            while let Some(value) = seq.next_element()? {
+                values.push(value);
+            }
+            Ok(Value::Array(values))
+        }
+    }
+
+    deserializer.deserialize_any(FacetsVisitor).map(Some)
+}
+
+/// Deserializes the facet filters given either as a JSON array in a single string, as a list
+/// or as nested `key[0][0]` query parameters, the inner arrays are alternatives (OR) and the
+/// outer array holds the filters that must all match (AND).
+fn deserialize_facet_filters<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where D: Deserializer<'de>,
+{
+    struct FacetFiltersVisitor;
+
+    impl<'de> Visitor<'de> for FacetFiltersVisitor {
+        type Value = String;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a JSON array in a string or a list")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            Ok(value.to_string())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut values = Vec::new();
+            while let Some(value) = seq.next_element::<Value>()? {
+                values.push(value);
+            }
+            Ok(Value::Array(values).to_string())
+        }
+    }
+
+    deserializer.deserialize_any(FacetFiltersVisitor).map(Some)
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/search",
+    tag = "Search",
+    params(IndexParam, SearchQuery),
+    responses(
+        (status = 200, description = "The documents matching the query", body = SearchResult),
+        (status = 304, description = "The results did not change since the ones of the If-None-Match ETag"),
+        (status = 400, description = "A search parameter is invalid", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get("/indexes/{index_uid}/search", wrap = "Authentication::Public")]
 async fn search_with_url_query(
     data: web::Data<Data>,
     path: web::Path<IndexParam>,
-    params: web::Query<SearchQuery>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, ResponseError> {
+    // The lists can be given as repeated `key[]` parameters, even with encoded brackets.
+    let params: SearchQuery = serde_qs::Config::new(5, false)
+        .deserialize_str(req.query_string())
+        .map_err(Error::bad_request)?;
+
+    let etag_hash = search_etag(&data, &path.index_uid, req.query_string(), &params.q)?;
+    let etag = etag_hash.map(|hash| format!("\"{:x}\"", hash));
+    if let Some(etag) = &etag {
+        if if_none_match(&req, etag) {
+            return Ok(HttpResponse::NotModified().header(ETAG, etag.as_str()).finish());
+        }
+    }
+
+    let grouping = params.grouping();
+    let count_only = params.count_only == Some(true);
+    let stream_hits = grouping.is_none() && !count_only;
+    let SearchResponse { result: mut search_result, hits } = blocking_search(&data, &path.index_uid, params, stream_hits).await?;
+    // the results that are not modified must have the same query id
+    if let Some(hash) = etag_hash {
+        search_result.query_id = query_id_from_hash(hash);
+    }
+
+    let mut response = HttpResponse::Ok();
+    response.header(PROCESSING_TIME_HEADER, search_result.processing_time_ms.to_string());
+    if let Some(etag) = etag {
+        response.header(ETAG, etag);
+    }
+
+    // the clients are told to prefetch the next page, as long as there is one
+    let next_offset = search_result.offset + search_result.limit;
+    if data.enable_http2_push && !count_only && next_offset < search_result.nb_hits {
+        let link = format!(
+            "</indexes/{}/search?{}>; rel=prefetch",
+            path.index_uid,
+            next_page_query_string(req.query_string(), next_offset),
+        );
+        response.header(LINK, link);
+    }
+
+    if count_only {
+        return Ok(response.json(json!({ "nbHits": search_result.nb_hits })));
+    }
+
+    match (grouping, hits) {
+        (Some((attribute, limit)), _) => Ok(response.json(group_hits(search_result, &attribute, limit))),
+        (None, Some(hits)) => Ok(response.content_type("application/json").streaming(stream_results(&search_result, hits)?)),
+        (None, None) => Ok(response.json(search_result)),
+    }
+}
+
+/// Returns the query string with its offset replaced by this one.
+fn next_page_query_string(query_string: &str, offset: usize) -> String {
+    let mut pairs: Vec<_> = query_string
+        .split('&')
+        .filter(|pair| !pair.is_empty() && !pair.starts_with("offset="))
+        .map(str::to_string)
+        .collect();
+    pairs.push(format!("offset={}", offset));
+    pairs.join("&")
+}
+
+/// Returns the hash of the ETag of the results of this query string, it changes whenever the index
+/// is updated. There is no ETag while an experiment is active on the index, as every search of the
+/// experiment must be counted in its group which a not modified response would not be.
+fn search_etag(data: &Data, index_uid: &str, query: &str, q: &str) -> Result<Option<u64>, ResponseError> {
     let index = data
         .db
-        .open_index(&path.index_uid)
-        .ok_or(Error::index_not_found(&path.index_uid))?;
+        .open_index(index_uid)
+        .ok_or(Error::index_not_found(index_uid))?;
+
+    if data.experiments.assign(index_uid, q).is_some() {
+        return Ok(None);
+    }
 
     let reader = data.db.main_read_txn()?;
+    let updated_at = match index.main.updated_at(&reader)? {
+        Some(updated_at) => updated_at,
+        None => return Ok(None),
+    };
+
+    let mut hasher = SipHasher::new();
+    query.hash(&mut hasher);
+    Ok(Some(hasher.finish() ^ updated_at.timestamp_nanos() as u64))
+}
+
+/// Returns whether one of the ETags of the `If-None-Match` header is this one.
+fn if_none_match(req: &HttpRequest, etag: &str) -> bool {
+    req.headers()
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| {
+            value.split(',').map(str::trim).any(|tag| tag == etag || tag == "*")
+        })
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/search",
+    tag = "Search",
+    params(IndexParam),
+    request_body = SearchQuery,
+    responses(
+        (status = 200, description = "The documents matching the query", body = SearchResult),
+        (status = 400, description = "The facets are invalid", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/search", wrap = "Authentication::Public")]
+async fn search_with_post(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    body: web::Json<SearchQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    let grouping = body.grouping();
+    let count_only = body.count_only == Some(true);
+    let stream_hits = grouping.is_none() && !count_only;
+    let SearchResponse { result: search_result, hits } = blocking_search(&data, &path.index_uid, body.into_inner(), stream_hits).await?;
+
+    let mut response = HttpResponse::Ok();
+    response.header(PROCESSING_TIME_HEADER, search_result.processing_time_ms.to_string());
+
+    if count_only {
+        return Ok(response.json(json!({ "nbHits": search_result.nb_hits })));
+    }
+
+    match (grouping, hits) {
+        (Some((attribute, limit)), _) => Ok(response.json(group_hits(search_result, &attribute, limit))),
+        (None, Some(hits)) => Ok(response.content_type("application/json").streaming(stream_results(&search_result, hits)?)),
+        (None, None) => Ok(response.json(search_result)),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HitsGroup {
+    group_value: Value,
+    hits: Vec<SearchHit>,
+}
+
+/// Replaces the hits of the response by the groups of hits sharing the same value for the attribute,
+/// ordered by their best hit. The hits past the limit of their group are counted in `ungroupedHits`.
+fn group_hits(mut result: SearchResult, attribute: &str, limit: usize) -> Value {
+    let mut groups: Vec<HitsGroup> = Vec::new();
+    let mut ungrouped_hits = 0;
+
+    for hit in mem::take(&mut result.hits) {
+        let value = hit.document.get(attribute).cloned().unwrap_or(Value::Null);
+        match groups.iter_mut().find(|group| group.group_value == value) {
+            Some(group) if group.hits.len() >= limit => ungrouped_hits += 1,
+            Some(group) => group.hits.push(hit),
+            None => groups.push(HitsGroup { group_value: value, hits: vec![hit] }),
+        }
+    }
+
+    let mut response = json!(result);
+    if let Some(object) = response.as_object_mut() {
+        object.remove("hits");
+        object.insert("groups".to_string(), json!(groups));
+        object.insert("ungroupedHits".to_string(), json!(ungrouped_hits));
+    }
+
+    response
+}
+
+/// The hits of streamed results, each one serialized, preceded by a comma when it is not the first.
+type HitsReceiver = mpsc::UnboundedReceiver<Result<Bytes, ResponseError>>;
+
+/// The results of a search, without their hits when they are streamed.
+struct SearchResponse {
+    result: SearchResult,
+    hits: Option<HitsReceiver>,
+}
+
+/// Runs the search on the threads of the blocking tasks, the request can then be cancelled
+/// by its `X-Meili-Request-Timeout` while the search goes on until it is done.
+///
+/// When the hits are streamed, the results are returned as soon as the documents are ranked and
+/// the same task then retrieves the hits, that are sent as they are while the response is sent.
+async fn blocking_search(
+    data: &Data,
+    index_uid: &str,
+    params: SearchQuery,
+    stream_hits: bool,
+) -> Result<SearchResponse, ResponseError> {
+    let data = data.clone();
+    let index_uid = index_uid.to_string();
+    let (result_sender, result_receiver) = oneshot::channel();
+    let (hits_sender, hits) = match stream_hits {
+        true => {
+            let (sender, receiver) = mpsc::unbounded();
+            (Some(sender), Some(receiver))
+        }
+        false => (None, None),
+    };
+
+    let task = web::block(move || {
+        send_search(&data, &index_uid, params, result_sender, hits_sender);
+        Ok::<_, ()>(())
+    });
+    actix_rt::spawn(async move {
+        let _ = task.await;
+    });
+
+    match result_receiver.await {
+        Ok(result) => Ok(SearchResponse { result: result?, hits }),
+        Err(_) => Err(Error::internal("the search was canceled").into()),
+    }
+}
+
+/// Sends the results of the search, then each of their hits when they are streamed. The hits are
+/// no longer retrieved once the response is dropped, and the stream ends at the first error.
+fn send_search(
+    data: &Data,
+    index_uid: &str,
+    params: SearchQuery,
+    result_sender: oneshot::Sender<Result<SearchResult, ResponseError>>,
+    hits_sender: Option<mpsc::UnboundedSender<Result<Bytes, ResponseError>>>,
+) {
+    let index = match data.db.open_index(index_uid) {
+        Some(index) => index,
+        None => {
+            let _ = result_sender.send(Err(Error::index_not_found(index_uid).into()));
+            return;
+        }
+    };
+
+    let reader = match data.db.main_read_txn() {
+        Ok(reader) => reader,
+        Err(e) => {
+            let _ = result_sender.send(Err(e.into()));
+            return;
+        }
+    };
+
+    let (result, mut pending_hits) = match search(data, &index, &reader, index_uid, params) {
+        Ok(search) => search,
+        Err(e) => {
+            let _ = result_sender.send(Err(e));
+            return;
+        }
+    };
+
+    let hits_sender = match hits_sender {
+        Some(hits_sender) => hits_sender,
+        None => {
+            let hits = iter::from_fn(|| pending_hits.next_hit(&reader)).collect::<Result<_, _>>();
+            let _ = result_sender.send(hits.map(|hits| SearchResult { hits, ..result }));
+            return;
+        }
+    };
+
+    if result_sender.send(Ok(result)).is_err() {
+        return;
+    }
+
+    let mut first = true;
+    while let Some(hit) = pending_hits.next_hit(&reader) {
+        let bytes = hit.and_then(|hit| {
+            let mut bytes = if first { Vec::new() } else { vec![b','] };
+            serde_json::to_writer(&mut bytes, &hit).map_err(Error::internal)?;
+            Ok(Bytes::from(bytes))
+        });
+        first = false;
+
+        let failed = bytes.is_err();
+        if hits_sender.unbounded_send(bytes).is_err() || failed {
+            break;
+        }
+    }
+}
+
+/// How the serialized results start when they have no hits, the streamed hits go in the brackets.
+const EMPTY_HITS_PREFIX: &[u8] = br#"{"hits":[]"#;
+
+/// Streams the results serialized like `serde_json` does, but with the hits sent as they are retrieved.
+fn stream_results(
+    result: &SearchResult,
+    hits: HitsReceiver,
+) -> Result<impl Stream<Item = Result<Bytes, ResponseError>>, ResponseError> {
+    let serialized = serde_json::to_vec(result).map_err(Error::internal)?;
+    // the hits are the first field of the results
+    let rest = match serialized.strip_prefix(EMPTY_HITS_PREFIX) {
+        Some(rest) if result.hits.is_empty() => rest,
+        _ => return Err(Error::internal("the streamed results must start with their empty hits").into()),
+    };
+
+    let head = Bytes::from_static(&EMPTY_HITS_PREFIX[..EMPTY_HITS_PREFIX.len() - 1]);
+    let mut tail = b"]".to_vec();
+    tail.extend_from_slice(rest);
+
+    Ok(stream::once(future::ok(head)).chain(hits).chain(stream::once(future::ok(Bytes::from(tail)))))
+}
+
+/// Prepares and runs the search, the hits of the results are left to the returned `PendingHits`.
+fn search<'a>(
+    data: &Data,
+    index: &'a Index,
+    reader: &MainReader,
+    index_uid: &str,
+    params: SearchQuery,
+) -> Result<(SearchResult, PendingHits<'a>), ResponseError> {
     let schema = index
         .main
-        .schema(&reader)?
+        .schema(reader)?
         .ok_or(Error::internal("Impossible to retrieve the schema"))?;
 
+    if let Some(attribute) = &params.group_by_attribute {
+        if schema.id(attribute).is_none() {
+            return Err(Error::bad_parameter("groupByAttribute", format!("{} is not an attribute", attribute)).into());
+        }
+        if params.group_limit == Some(0) {
+            return Err(Error::bad_parameter("groupLimit", "must be greater than 0").into());
+        }
+    }
+
     let mut search_builder = index.new_search(params.q.clone());
 
     if let Some(offset) = params.offset {
@@ -64,9 +613,8 @@ async fn search_with_url_query(
 
     let available_attributes = schema.displayed_name();
     let mut restricted_attributes: HashSet<&str>;
-    match &params.attributes_to_retrieve {
+    match params.attributes_to_retrieve() {
         Some(attributes_to_retrieve) => {
-            let attributes_to_retrieve: HashSet<&str> = attributes_to_retrieve.split(',').collect();
             if attributes_to_retrieve.contains("*") {
                 restricted_attributes = available_attributes.clone();
             } else {
@@ -76,9 +624,14 @@ async fn search_with_url_query(
                         restricted_attributes.insert(attr);
                         search_builder.add_retrievable_field(attr.to_string());
                     } else {
-                        warn!("The attributes {:?} present in attributesToCrop parameter doesn't exist", attr);
+                        warn!("The attributes {:?} present in attributesToRetrieve parameter doesn't exist", attr);
                     }
                 }
+                // None of the attributes exist, all the displayed attributes are returned
+                // and are the ones the wildcards of attributesToCrop and attributesToHighlight refer to.
+                if restricted_attributes.is_empty() {
+                    restricted_attributes = available_attributes.clone();
+                }
             }
         },
         None => {
@@ -86,17 +639,34 @@ async fn search_with_url_query(
         }
     }
 
+    // the hits are grouped by the value of the attribute in their returned document
+    if let Some(attribute) = &params.group_by_attribute {
+        if !restricted_attributes.contains(attribute.as_str()) {
+            let message = format!("{} is not a displayed attribute returned with the hits", attribute);
+            return Err(Error::bad_parameter("groupByAttribute", message).into());
+        }
+    }
+
     if let Some(ref facet_filters) = params.facet_filters {
-        let attrs = index.main.attributes_for_faceting(&reader)?;
+        let attrs = index.main.attributes_for_faceting(reader)?;
         if let Some(attrs) = attrs {
             search_builder.add_facet_filters(FacetFilter::from_str(facet_filters, &schema, &attrs)?);
         }
     }
 
     if let Some(facets) = &params.facets_distribution {
-        match index.main.attributes_for_faceting(&reader)? {
+        match index.main.attributes_for_faceting(reader)? {
             Some(ref attrs) => {
-                let field_ids = prepare_facet_list(&facets, &schema, attrs)?;
+                let mut field_ids = prepare_facet_list(&facets, &schema, attrs)?;
+                if let Some(levels) = index.main.hierarchical_facets(reader)? {
+                    let levels = ordered_facet_levels(&levels);
+                    // the first level is counted as a tree when it is asked for
+                    let root = levels.first().and_then(|name| schema.id(name));
+                    if let Some(position) = field_ids.iter().position(|(id, _)| Some(*id) == root) {
+                        field_ids.remove(position);
+                        search_builder.add_facet_tree(prepare_facet_levels(levels.into_iter(), &schema, attrs)?);
+                    }
+                }
                 search_builder.add_facets(field_ids);
             },
             None => return Err(FacetCountError::NoFacetSet.into()),
@@ -107,7 +677,7 @@ async fn search_with_url_query(
         let default_length = params.crop_length.unwrap_or(200);
         let mut final_attributes: HashMap<String, usize> = HashMap::new();
 
-        for attribute in attributes_to_crop.split(',') {
+        for attribute in attributes_to_crop {
             let mut attribute = attribute.split(':');
             let attr = attribute.next();
             let length = attribute.next().and_then(|s| s.parse().ok()).unwrap_or(default_length);
@@ -133,7 +703,8 @@ async fn search_with_url_query(
 
     if let Some(attributes_to_highlight) = &params.attributes_to_highlight {
         let mut final_attributes: HashSet<String> = HashSet::new();
-        for attribute in attributes_to_highlight.split(',') {
+        for attribute in attributes_to_highlight {
+            let attribute = attribute.as_str();
             if attribute == "*" {
                 for attr in &restricted_attributes {
                     final_attributes.insert(attr.to_string());
@@ -159,18 +730,461 @@ async fn search_with_url_query(
             search_builder.get_matches();
         }
     }
+
+    if params.return_matched_words == Some(true) {
+        search_builder.get_matched_words();
+    }
+
+    // without a minimum similarity, the typos allowed only depend on the length of the words
+    if let Some(min_similarity) = params.min_similarity {
+        if !(0.0..=1.0).contains(&min_similarity) {
+            return Err(Error::bad_parameter("minSimilarity", "must be between 0.0 and 1.0").into());
+        }
+        search_builder.min_similarity(min_similarity);
+    }
+    search_builder.max_wildcard_expansions(data.max_wildcard_expansions);
+
+    if let Some(image_hash) = params.image_hash {
+        search_builder.image_hash(image_hash);
+    }
+
+    if params.markdown_highlight == Some(true) {
+        search_builder.markdown_highlight();
+    }
+
+    if let Some(side) = params.snippet_ellipsis_side {
+        search_builder.crop_side(side);
+    }
+
+    if params.adaptive_crop == Some(true) {
+        search_builder.adaptive_crop();
+    }
+
+    if params.count_only == Some(true) {
+        search_builder.count_only();
+    }
+
+    if params.show_hit_explanation == Some(true) {
+        search_builder.explain_ranking();
+    }
+
+    // the aggregations are not ranked by the rules of the experiments
+    let aggregation_searches = match &params.aggregations {
+        Some(aggregations) if params.count_only != Some(true) => prepare_aggregations(
+            reader,
+            index,
+            &schema,
+            &search_builder,
+            params.filters.as_deref(),
+            aggregations,
+        )?,
+        _ => Vec::new(),
+    };
+
+    let experiment_group = match data.experiments.assign(index_uid, &params.q) {
+        Some((group, ranking_rules)) => {
+            search_builder.ranking_rules(ranking_rules);
+            Some(group)
+        }
+        None => None,
+    };
+
+    // the fallback search is prepared before the search consumes its builder
+    let fallback_search = match params.zero_results_fallback {
+        Some(FallbackStrategy::TypoRelax) => {
+            let mut fallback_search = search_builder.clone();
+            fallback_search.min_similarity(0.0);
+            Some((FallbackStrategy::TypoRelax, fallback_search))
+        }
+        Some(FallbackStrategy::All) => {
+            let mut fallback_search = search_builder.clone();
+            fallback_search.all_documents();
+            Some((FallbackStrategy::All, fallback_search))
+        }
+        Some(FallbackStrategy::None) | None => None,
+    };
+
+    // The processing time covers the query and the aggregations, the hits
+    // are retrieved and formatted afterwards, while they are sent.
+    let start = Instant::now();
+    let (mut search_result, mut hits) = search_builder.search_lazily(reader)?;
+    let no_results = search_result.nb_hits == 0;
+    if let (true, Some((strategy, fallback_search))) = (no_results, fallback_search) {
+        let (fallback_result, fallback_hits) = fallback_search.search_lazily(reader)?;
+        if fallback_result.nb_hits > 0 {
+            search_result = SearchResult { fallback_strategy: Some(strategy), ..fallback_result };
+            hits = fallback_hits;
+        }
+    }
+    if !aggregation_searches.is_empty() {
+        let mut aggregations = IndexMap::new();
+        for (name, mut aggregation_search) in aggregation_searches {
+            // the aggregations are applied to the results the fallback found
+            match search_result.fallback_strategy {
+                Some(FallbackStrategy::TypoRelax) => {
+                    aggregation_search.min_similarity(0.0);
+                }
+                Some(FallbackStrategy::All) => {
+                    aggregation_search.all_documents();
+                }
+                Some(FallbackStrategy::None) | None => (),
+            }
+            let result = aggregation_search.search(reader)?;
+            aggregations.insert(name, AggregationResult { hits: result.hits, nb_hits: result.nb_hits });
+        }
+        search_result.aggregations = Some(aggregations);
+    }
+    search_result.processing_time_ms = start.elapsed().as_millis() as usize;
+
+    // the queries are reported as finding nothing even when a fallback found hits
+    data.query_analytics.record(&search_result.query, no_results);
+
+    // with the lazy strategy, the documents are embedded once they are found by a search
+    let lazy_embedding = index.main.embedding_strategy(reader)? == Some(EmbeddingStrategy::Lazy);
+    if let Some(embedder) = index.main.embedder(reader)?.filter(|_| lazy_embedding) {
+        embed_documents_lazily(data, reader, index, index_uid, &embedder, &search_result.documents_ids)?;
+    }
+
+    if let (Some(facets), Some(distribution)) = (&params.disjunctive_facets, &mut search_result.facets_distribution) {
+        let attrs = index.main.attributes_for_faceting(reader)?.unwrap_or_default();
+        for facet in facets {
+            match distribution.get(facet) {
+                Some(FacetDistribution::Values(_)) => (),
+                _ => return Err(Error::bad_parameter("disjunctiveFacets", format!("{} is not in the facets distribution", facet)).into()),
+            }
+
+            // the facets are only counted again when they are filtered on
+            let facet_filters = match &params.facet_filters {
+                Some(facet_filters) if facet_filters_on(facet_filters, facet) => facet_filters_without(facet_filters, facet),
+                _ => continue,
+            };
+
+            let field_id = schema.id(facet).ok_or(Error::internal("Impossible to retrieve the facet"))?;
+            let mut facet_search = index.new_search(params.q.clone());
+            facet_search.count_only();
+            if let Some(min_similarity) = params.min_similarity {
+                facet_search.min_similarity(min_similarity);
+            }
+            facet_search.max_wildcard_expansions(data.max_wildcard_expansions);
+            if let Some(filters) = &params.filters {
+                facet_search.filters(filters.to_string());
+            }
+            if let Some(facet_filters) = facet_filters {
+                facet_search.add_facet_filters(FacetFilter::from_str(&facet_filters, &schema, &attrs)?);
+            }
+            facet_search.add_facets(vec![(field_id, facet.clone())]);
+
+            let facet_result = facet_search.search(reader)?;
+            if let Some(values) = facet_result.facets_distribution.and_then(|mut facets| facets.remove(facet)) {
+                distribution.insert(facet.clone(), values);
+            }
+        }
+    }
+
+    if let Some(distribution) = &mut search_result.facets_distribution {
+        let show_all_values = params.show_all_facet_values != Some(false);
+        sort_facet_values(
+            distribution,
+            params.facet_sort_order.as_ref(),
+            params.facet_values_limits.as_ref(),
+            show_all_values,
+        );
+    }
+
+    if params.return_query_analysis == Some(true) {
+        search_result.query_analysis = Some(analyze_query(reader, index, &params.q)?);
+    }
+
+    if let Some(group) = experiment_group {
+        data.experiments.record_search(index_uid, group, &search_result.query_id, search_result.nb_hits == 0);
+        search_result.experiment_group = Some(group);
+    }
+
+    if data.log_queries {
+        log_query(index_uid, &search_result, data.log_query_strings);
+    }
+
+    Ok((search_result, hits))
+}
+
+/// Prepares the search of each aggregation, the one of the query also matching the filter of the
+/// aggregation and ranked by its sort before the ranking rules of the index.
+fn prepare_aggregations<'a>(
+    reader: &MainReader,
+    index: &Index,
+    schema: &Schema,
+    search_builder: &SearchBuilder<'a>,
+    filters: Option<&str>,
+    aggregations: &[Aggregation],
+) -> Result<Vec<(String, SearchBuilder<'a>)>, ResponseError> {
+    if aggregations.len() > MAX_AGGREGATIONS {
+        let message = format!("there are more than {} aggregations", MAX_AGGREGATIONS);
+        return Err(Error::bad_parameter("aggregations", message).into());
+    }
+
+    let mut searches: Vec<(String, SearchBuilder)> = Vec::with_capacity(aggregations.len());
+    for aggregation in aggregations {
+        let limit = aggregation.limit.unwrap_or(DEFAULT_AGGREGATION_LIMIT);
+        if limit > MAX_AGGREGATION_LIMIT {
+            let message = format!("the limit of {} is greater than {}", aggregation.name, MAX_AGGREGATION_LIMIT);
+            return Err(Error::bad_parameter("aggregations", message).into());
+        }
+
+        if searches.iter().any(|(name, _)| *name == aggregation.name) {
+            let message = format!("{} is the name of several aggregations", aggregation.name);
+            return Err(Error::bad_parameter("aggregations", message).into());
+        }
+
+        let mut search = search_builder.clone();
+        search.offset(0);
+        search.limit(limit);
+
+        let filter = match (filters, &aggregation.filter) {
+            (Some(filters), Some(filter)) => Some(format!("({}) AND ({})", filters, filter)),
+            (filters, filter) => filters.map(str::to_string).or_else(|| filter.clone()),
+        };
+        if let Some(filter) = filter {
+            search.filters(filter);
+        }
+
+        if let Some(sort) = &aggregation.sort {
+            let mut ranking_rules = index.main.ranking_rules(reader)?.unwrap_or_else(|| DEFAULT_RANKING_RULES.to_vec());
+            ranking_rules.insert(0, parse_sort(schema, sort)?);
+            search.ranking_rules(ranking_rules);
+        }
+
+        searches.push((aggregation.name.clone(), search));
+    }
+
+    Ok(searches)
+}
+
+/// Parses a sort, `attribute:asc` or `attribute:desc`, the documents can only be sorted
+/// by the attributes of the asc and desc ranking rules.
+fn parse_sort(schema: &Schema, sort: &str) -> Result<RankingRule, ResponseError> {
+    let mut parts = sort.splitn(2, ':');
+    let attribute = parts.next().unwrap_or_default().trim();
+    if schema.id(attribute).map_or(true, |id| !schema.is_ranked(id)) {
+        return Err(Error::sort_attribute_not_sortable(attribute).into());
+    }
+
+    match parts.next().map(str::trim) {
+        Some("asc") => Ok(RankingRule::Asc(attribute.to_string())),
+        Some("desc") => Ok(RankingRule::Desc(attribute.to_string())),
+        _ => {
+            let message = format!("{} is not a sort, it must be attribute:asc or attribute:desc", sort);
+            Err(Error::bad_parameter("aggregations", message).into())
+        }
+    }
+}
+
+/// Whether the facet filter, either a `attribute:value` string or an array of them, is on the attribute.
+fn is_facet_filter_on(filter: &Value, attribute: &str) -> bool {
+    match filter {
+        Value::String(filter) => filter.split(':').next().map_or(false, |name| name.trim() == attribute),
+        Value::Array(alternatives) => alternatives.iter().any(|filter| is_facet_filter_on(filter, attribute)),
+        _ => false,
+    }
+}
+
+/// Whether one of the facet filters, a JSON array, is on the attribute.
+fn facet_filters_on(facet_filters: &str, attribute: &str) -> bool {
+    match serde_json::from_str(facet_filters) {
+        Ok(Value::Array(filters)) => filters.iter().any(|filter| is_facet_filter_on(filter, attribute)),
+        _ => false,
+    }
+}
+
+/// Returns the facet filters without the ones on the attribute, an array of alternatives is removed when
+/// one of them is on the attribute. Returns `None` when there is no filter left.
+fn facet_filters_without(facet_filters: &str, attribute: &str) -> Option<String> {
+    let filters = match serde_json::from_str(facet_filters) {
+        Ok(Value::Array(filters)) => filters,
+        _ => return None,
+    };
+
+    let filters: Vec<_> = filters.into_iter().filter(|filter| !is_facet_filter_on(filter, attribute)).collect();
+    if filters.is_empty() {
+        None
+    } else {
+        Some(Value::Array(filters).to_string())
+    }
+}
+
+/// Orders the values of each facet, by count unless another order is asked for, and only
+/// keeps the most frequent values of the facets that have a limit. The values that no hit
+/// has are removed when all the values are not asked for.
+fn sort_facet_values(
+    distribution: &mut HashMap<String, FacetDistribution>,
+    sort_orders: Option<&HashMap<String, FacetSortOrder>>,
+    limits: Option<&HashMap<String, usize>>,
+    show_all_values: bool,
+) {
+    for (facet, values) in distribution.iter_mut() {
+        // the values of the hierarchical facets are already ordered by count
+        let values = match values {
+            FacetDistribution::Values(values) => values,
+            FacetDistribution::Tree(_) => continue,
+        };
+
+        let mut sorted: Vec<_> = mem::take(values).into_iter().collect();
+        if !show_all_values {
+            sorted.retain(|(_, count)| *count > 0);
+        }
+        sorted.sort_unstable_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+
+        if let Some(&limit) = limits.and_then(|limits| limits.get(facet)) {
+            sorted.truncate(limit);
+        }
+
+        if sort_orders.and_then(|orders| orders.get(facet)) == Some(&FacetSortOrder::Alpha) {
+            sorted.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        }
+
+        values.extend(sorted);
+    }
+}
+
+/// The log target of the lines written by `log_query`.
+const QUERY_LOG_TARGET: &str = "query";
+
+/// Logs the shape of a search as a JSON line, the query string itself is only
+/// logged when asked for as it can contain sensitive data.
+fn log_query(index_uid: &str, result: &SearchResult, with_query_string: bool) {
+    let mut line = json!({
+        "type": "query",
+        "indexUid": index_uid,
+        "qLen": result.query.chars().count(),
+        "nbHits": result.nb_hits,
+        "processingTimeMs": result.processing_time_ms,
+    });
+
+    if with_query_string {
+        line["q"] = json!(result.query);
+    }
+
+    info!(target: QUERY_LOG_TARGET, "{}", line);
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct RerankBody {
+    q: String,
+    document_ids: Vec<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/rerank",
+    tag = "Search",
+    params(IndexParam),
+    request_body = RerankBody,
+    responses(
+        (status = 200, description = "The given documents matching the query, ranked", body = SearchResult),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/rerank", wrap = "Authentication::Public")]
+async fn rerank(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    body: web::Json<RerankBody>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+
+    // The unknown documents ids are ignored.
+    let mut candidates = Vec::with_capacity(body.document_ids.len());
+    for document_id in &body.document_ids {
+        if let Some(internal_id) = index.main.external_to_internal_docid(&reader, document_id)? {
+            candidates.push(internal_id);
+        }
+    }
+
+    let mut search_builder = index.new_search(body.q.clone());
+    search_builder.limit(candidates.len());
+    search_builder.candidates(candidates);
+
     let search_result = search_builder.search(&reader)?;
 
     Ok(HttpResponse::Ok().json(search_result))
 }
 
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct SpellcheckBody {
+    q: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/spellcheck",
+    tag = "Search",
+    params(IndexParam),
+    request_body = SpellcheckBody,
+    responses(
+        (status = 200, description = "The corrected query and the suggestions for the unknown words", body = Object),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/spellcheck", wrap = "Authentication::Public")]
+async fn spellcheck_query(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    body: web::Json<SpellcheckBody>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+    let result = spellcheck::spellcheck(&reader, &index, &body.q)?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// Returns the number ending the name of a level of a hierarchical facet, e.g. 10 for `lvl10`.
+pub fn facet_level_number(level: &str) -> Option<u64> {
+    let number = level.trim_start_matches(|c: char| !c.is_ascii_digit());
+    number.parse().ok()
+}
+
+/// Returns the attributes of the levels of a hierarchical facet, ordered by the number of their level.
+fn ordered_facet_levels(levels: &BTreeMap<String, String>) -> Vec<&String> {
+    let mut levels: Vec<_> = levels.iter().collect();
+    levels.sort_by_key(|(level, _)| facet_level_number(level));
+    levels.into_iter().map(|(_, attribute)| attribute).collect()
+}
+
+/// Returns the attributes of the levels of a hierarchical facet, they must all be facets.
+fn prepare_facet_levels<'a>(
+    levels: impl Iterator<Item = &'a String>,
+    schema: &Schema,
+    facet_attrs: &[FieldId],
+) -> Result<Vec<(FieldId, String)>, FacetCountError> {
+    levels
+        .map(|level| match schema.id(level) {
+            Some(id) if facet_attrs.contains(&id) => Ok((id, level.clone())),
+            _ => Err(FacetCountError::AttributeNotSet(level.clone())),
+        })
+        .collect()
+}
+
 /// Parses the incoming string into an array of attributes for which to return a count. It returns
 /// a Vec of attribute names ascociated with their id.
 ///
 /// An error is returned if the array is malformed, or if it contains attributes that are
 /// unexisting, or not set as facets.
-fn prepare_facet_list(facets: &str, schema: &Schema, facet_attrs: &[FieldId]) -> Result<Vec<(FieldId, String)>, FacetCountError> {
-    let json_array = serde_json::from_str(facets)?;
+fn prepare_facet_list(facets: &Value, schema: &Schema, facet_attrs: &[FieldId]) -> Result<Vec<(FieldId, String)>, FacetCountError> {
+    let json_array = match facets {
+        Value::String(facets) => serde_json::from_str(facets)?,
+        facets => facets.clone(),
+    };
     match json_array {
         Value::Array(vals) => {
             let wildcard = Value::String("*".to_string());
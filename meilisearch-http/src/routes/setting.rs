@@ -1,15 +1,25 @@
 use actix_web::{web, HttpResponse};
 use actix_web_macros::{delete, get, post};
-use meilisearch_core::settings::{Settings, SettingsUpdate, UpdateState, DEFAULT_RANKING_RULES};
+use meilisearch_core::settings::{Embedder, Settings, SettingsUpdate, UpdateState, DEFAULT_RANKING_RULES};
+use meilisearch_core::stemming::stemmer;
+use meilisearch_core::transformer::Transformer;
+use meilisearch_core::{Index, MainReader, UpdateStatus, UpdateType};
+use serde::Serialize;
+use serde_json::{json, Value};
 use std::collections::{BTreeMap, BTreeSet, HashSet};
+use utoipa::{OpenApi, ToSchema};
 
-use crate::error::{Error, ResponseError};
+use crate::error::{Error, ErrorResponseBody, ResponseError};
+use crate::helpers::public_hosts::check_public_url;
+use crate::helpers::references::references_cycle;
 use crate::helpers::Authentication;
+use crate::routes::search::facet_level_number;
 use crate::routes::{IndexParam, IndexUpdateResponse};
 use crate::Data;
 
 pub fn services(cfg: &mut web::ServiceConfig) {
     cfg.service(update_all)
+        .service(preview_all)
         .service(get_all)
         .service(delete_all)
         .service(get_rules)
@@ -28,6 +38,40 @@ pub fn services(cfg: &mut web::ServiceConfig) {
         .service(update_accept_new_fields);
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(
+    update_all,
+    preview_all,
+    get_all,
+    delete_all,
+    get_rules,
+    update_rules,
+    delete_rules,
+    get_distinct,
+    update_distinct,
+    delete_distinct,
+    get_searchable,
+    update_searchable,
+    delete_searchable,
+    get_displayed,
+    update_displayed,
+    delete_displayed,
+    get_accept_new_fields,
+    update_accept_new_fields,
+))]
+pub struct SettingApi;
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/settings",
+    tag = "Settings",
+    params(IndexParam),
+    request_body = Object,
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[post("/indexes/{index_uid}/settings", wrap = "Authentication::Private")]
 async fn update_all(
     data: web::Data<Data>,
@@ -39,6 +83,16 @@ async fn update_all(
         .open_index(&path.index_uid)
         .ok_or(Error::index_not_found(&path.index_uid))?;
 
+    check_stemming_language(&body)?;
+    check_proximity_window(&body)?;
+    check_pinned_hits(&body)?;
+    check_demoted_documents(&body)?;
+    check_transformer(&body)?;
+    check_webhook_url(&data, &body)?;
+    check_referenced_documents(&data, &path.index_uid, &body)?;
+    check_embedder(&body)?;
+    check_hierarchical_facets(&data, &index, &body)?;
+
     let update_id = data.db.update_write::<_, _, ResponseError>(|writer| {
         let settings = body
             .into_inner()
@@ -51,6 +105,302 @@ async fn update_all(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+fn check_stemming_language(settings: &Settings) -> Result<(), ResponseError> {
+    if let Some(Some(language)) = &settings.stemming_language {
+        if stemmer(language).is_none() {
+            return Err(Error::bad_parameter("stemmingLanguage", format!("{} is not a supported language", language)).into());
+        }
+    }
+    Ok(())
+}
+
+fn check_proximity_window(settings: &Settings) -> Result<(), ResponseError> {
+    if let Some(Some(0)) = settings.proximity_window {
+        return Err(Error::bad_parameter("proximityWindow", "must be greater than 0").into());
+    }
+    Ok(())
+}
+
+fn check_pinned_hits(settings: &Settings) -> Result<(), ResponseError> {
+    if let Some(Some(pinned_hits)) = &settings.pinned_hits {
+        if pinned_hits.iter().any(|hit| hit.position == Some(0)) {
+            return Err(Error::bad_parameter("pinnedHits", "the positions start at 1").into());
+        }
+    }
+    Ok(())
+}
+
+fn check_demoted_documents(settings: &Settings) -> Result<(), ResponseError> {
+    if let Some(Some(demoted_documents)) = &settings.demoted_documents {
+        if demoted_documents.iter().any(|document| !(0.0..=1.0).contains(&document.penalty_factor)) {
+            return Err(Error::bad_parameter("demotedDocuments", "the penalty factors must be between 0 and 1").into());
+        }
+    }
+    Ok(())
+}
+
+fn check_transformer(settings: &Settings) -> Result<(), ResponseError> {
+    if let Some(Some(transformer)) = &settings.transformer {
+        if let Err(e) = transformer.parse::<Transformer>() {
+            return Err(Error::bad_parameter("transformer", e).into());
+        }
+    }
+    Ok(())
+}
+
+fn check_webhook_url(data: &Data, settings: &Settings) -> Result<(), ResponseError> {
+    if let Some(Some(url)) = &settings.webhook_url {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(Error::bad_parameter("webhookUrl", "must be an http or https URL").into());
+        }
+        if !data.allow_private_webhook_urls {
+            check_public_url(url).map_err(|e| Error::bad_parameter("webhookUrl", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// The levels must be numbered, e.g. `lvl0` and `lvl1`, and their attributes must be
+/// attributes for faceting, once the settings are updated.
+fn check_hierarchical_facets(data: &Data, index: &Index, settings: &Settings) -> Result<(), ResponseError> {
+    if let Some(Some(levels)) = &settings.hierarchical_facets {
+        let mut numbers = HashSet::new();
+        if !levels.keys().all(|level| facet_level_number(level).map_or(false, |number| numbers.insert(number))) {
+            return Err(Error::bad_parameter("hierarchicalFacets", "the levels must end with distinct numbers, e.g. lvl0 and lvl1").into());
+        }
+
+        let attributes_for_faceting = match &settings.attributes_for_faceting {
+            Some(attributes) => attributes.clone().unwrap_or_default(),
+            None => {
+                let reader = data.db.main_read_txn()?;
+                match (index.main.schema(&reader)?, index.main.attributes_for_faceting(&reader)?) {
+                    (Some(schema), Some(attributes)) => {
+                        attributes.iter().filter_map(|id| schema.name(*id)).map(ToString::to_string).collect()
+                    }
+                    _ => Vec::new(),
+                }
+            }
+        };
+
+        if let Some(attribute) = levels.values().find(|attribute| !attributes_for_faceting.contains(attribute)) {
+            return Err(Error::bad_parameter("hierarchicalFacets", format!("{} is not an attribute for faceting", attribute)).into());
+        }
+    }
+    Ok(())
+}
+
+fn check_referenced_documents(data: &Data, index_uid: &str, settings: &Settings) -> Result<(), ResponseError> {
+    if let Some(Some(references)) = &settings.referenced_documents {
+        let reader = data.db.main_read_txn()?;
+        for reference in references {
+            if reference.field.is_empty() || reference.foreign_key.is_empty() {
+                return Err(Error::bad_parameter("referencedDocuments", "the fields and the foreign keys cannot be empty").into());
+            }
+            if reference.fields_to_inject.iter().any(|path| path.is_empty() || path.split('.').any(str::is_empty)) {
+                return Err(Error::bad_parameter("referencedDocuments", "the fields to inject must be valid paths").into());
+            }
+            if reference.foreign_index == index_uid {
+                return Err(Error::bad_parameter("referencedDocuments", "an index cannot reference itself").into());
+            }
+
+            // the foreign documents are only retrieved by their primary key
+            let primary_key = match data.db.open_index(&reference.foreign_index) {
+                Some(foreign_index) => foreign_index.main.schema(&reader)?.and_then(|schema| schema.primary_key().map(str::to_string)),
+                None => None,
+            };
+            if primary_key.map_or(false, |primary_key| primary_key != reference.foreign_key) {
+                let message = format!("the foreign key of {} must be the primary key of its documents", reference.foreign_index);
+                return Err(Error::bad_parameter("referencedDocuments", message).into());
+            }
+        }
+
+        if references_cycle(&data.db, &reader, index_uid, references)? {
+            return Err(Error::bad_parameter("referencedDocuments", "the references cannot form a cycle").into());
+        }
+    }
+    Ok(())
+}
+
+fn check_embedder(settings: &Settings) -> Result<(), ResponseError> {
+    if let Some(Some(embedder)) = &settings.embedder {
+        if embedder.model.is_empty() {
+            return Err(Error::bad_parameter("embedder", "the model cannot be empty").into());
+        }
+        if embedder.dimensions == 0 {
+            return Err(Error::bad_parameter("embedder", "the dimensions must be greater than 0").into());
+        }
+        if embedder.document_embedding_field.is_empty() || embedder.document_embedding_field.split('.').any(str::is_empty) {
+            return Err(Error::bad_parameter("embedder", "the document embedding field must be a valid path").into());
+        }
+        if embedder.api_key.as_deref().map_or(true, str::is_empty) {
+            return Err(Error::bad_parameter("embedder", "the API key of the source is missing").into());
+        }
+    }
+    Ok(())
+}
+
+/// The settings that make every document be reindexed when they are changed.
+const REINDEXING_SETTINGS: [&str; 10] = [
+    "rankingRules",
+    "searchableAttributes",
+    "attributesForFaceting",
+    "stopWords",
+    "phoneticMatching",
+    "phoneticAttributes",
+    "stemmingLanguage",
+    "decompoundTokens",
+    "imageHashField",
+    "languageDetectionField",
+];
+
+/// The settings holding a set of values, the order in which they are given does not matter.
+const UNORDERED_SETTINGS: [&str; 4] = ["displayedAttributes", "stopWords", "attributesForFaceting", "phoneticAttributes"];
+
+/// Returns the name of every setting along with its value, `None` when the setting is left untouched.
+fn settings_fields(settings: &Settings) -> [(&'static str, Option<Value>); 30] {
+    [
+        ("rankingRules", settings.ranking_rules.as_ref().map(|v| json!(v))),
+        ("distinctAttribute", settings.distinct_attribute.as_ref().map(|v| json!(v))),
+        ("searchableAttributes", settings.searchable_attributes.as_ref().map(|v| json!(v))),
+        ("displayedAttributes", settings.displayed_attributes.as_ref().map(|v| json!(v))),
+        ("stopWords", settings.stop_words.as_ref().map(|v| json!(v))),
+        ("synonyms", settings.synonyms.as_ref().map(|v| json!(v))),
+        ("acceptNewFields", settings.accept_new_fields.as_ref().map(|v| json!(v))),
+        ("attributesForFaceting", settings.attributes_for_faceting.as_ref().map(|v| json!(v))),
+        ("maxFieldsCount", settings.max_fields_count.as_ref().map(|v| json!(v))),
+        ("maxIndexSize", settings.max_index_size.as_ref().map(|v| json!(v))),
+        ("phoneticMatching", settings.phonetic_matching.as_ref().map(|v| json!(v))),
+        ("phoneticAttributes", settings.phonetic_attributes.as_ref().map(|v| json!(v))),
+        ("stemmingLanguage", settings.stemming_language.as_ref().map(|v| json!(v))),
+        ("decompoundTokens", settings.decompound_tokens.as_ref().map(|v| json!(v))),
+        ("proximityPrecision", settings.proximity_precision.as_ref().map(|v| json!(v))),
+        ("proximityWindow", settings.proximity_window.as_ref().map(|v| json!(v))),
+        ("pinnedHits", settings.pinned_hits.as_ref().map(|v| json!(v))),
+        ("demotedDocuments", settings.demoted_documents.as_ref().map(|v| json!(v))),
+        ("imageHashField", settings.image_hash_field.as_ref().map(|v| json!(v))),
+        ("languageDetectionField", settings.language_detection_field.as_ref().map(|v| json!(v))),
+        ("oneWaySynonyms", settings.one_way_synonyms.as_ref().map(|v| json!(v))),
+        ("equivalentSynonyms", settings.equivalent_synonyms.as_ref().map(|v| json!(v))),
+        ("hierarchicalFacets", settings.hierarchical_facets.as_ref().map(|v| json!(v))),
+        ("transformer", settings.transformer.as_ref().map(|v| json!(v))),
+        ("webhookUrl", settings.webhook_url.as_ref().map(|v| json!(v))),
+        ("crossFieldBoost", settings.cross_field_boost.as_ref().map(|v| json!(v))),
+        ("documentVersions", settings.document_versions.as_ref().map(|v| json!(v))),
+        ("referencedDocuments", settings.referenced_documents.as_ref().map(|v| json!(v))),
+        ("embedder", settings.embedder.as_ref().map(|v| json!(v))),
+        ("embeddingStrategy", settings.embedding_strategy.as_ref().map(|v| json!(v))),
+    ]
+}
+
+fn setting_changed(name: &str, proposed: &Value, current: &Value) -> bool {
+    match (proposed, current) {
+        (Value::Array(proposed), Value::Array(current)) if UNORDERED_SETTINGS.contains(&name) => {
+            let proposed: HashSet<_> = proposed.iter().map(Value::to_string).collect();
+            let current: HashSet<_> = current.iter().map(Value::to_string).collect();
+            proposed != current
+        }
+        (proposed, current) => proposed != current,
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct SettingsPreviewResponse {
+    documents_to_reindex: u64,
+    /// Estimated from the speed of the last documents addition, `null` when there is none.
+    estimated_duration_ms: Option<u64>,
+    affected_settings: Vec<&'static str>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/settings/preview",
+    tag = "Settings",
+    params(IndexParam),
+    request_body = Object,
+    responses(
+        (status = 200, description = "The settings that would change and the documents to reindex", body = SettingsPreviewResponse),
+        (status = 400, description = "The settings are invalid", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/settings/preview", wrap = "Authentication::Private")]
+async fn preview_all(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    body: web::Json<Settings>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    check_stemming_language(&body)?;
+    check_proximity_window(&body)?;
+    check_pinned_hits(&body)?;
+    check_demoted_documents(&body)?;
+    check_transformer(&body)?;
+    check_webhook_url(&data, &body)?;
+    check_referenced_documents(&data, &path.index_uid, &body)?;
+    check_embedder(&body)?;
+    check_hierarchical_facets(&data, &index, &body)?;
+    body.into_update().map_err(Error::bad_request)?;
+
+    let reader = data.db.main_read_txn()?;
+    let current = settings_fields(&current_settings(&reader, &index)?);
+
+    let mut affected_settings = Vec::new();
+    let mut must_reindex = false;
+    for ((name, proposed), (_, current)) in settings_fields(&body).iter().zip(current.iter()) {
+        let proposed = match proposed {
+            Some(proposed) => proposed,
+            None => continue,
+        };
+
+        if setting_changed(name, proposed, current.as_ref().unwrap_or(&Value::Null)) {
+            affected_settings.push(*name);
+            // clearing the attributes for faceting does not reindex the documents
+            let clears_facets = *name == "attributesForFaceting" && proposed.is_null();
+            must_reindex |= REINDEXING_SETTINGS.contains(name) && !clears_facets;
+        }
+    }
+
+    let documents_to_reindex = if must_reindex { index.main.number_of_documents(&reader)? } else { 0 };
+
+    let update_reader = data.db.update_read_txn()?;
+    let millis_per_document = index
+        .all_updates_status(&update_reader)?
+        .into_iter()
+        .rev()
+        .find_map(|status| match status {
+            UpdateStatus::Processed { content } => match content.update_type {
+                UpdateType::DocumentsAddition { number } if number > 0 => {
+                    Some(content.duration * 1000.0 / number as f64)
+                }
+                _ => None,
+            },
+            _ => None,
+        });
+
+    let estimated_duration_ms = millis_per_document.map(|millis| (millis * documents_to_reindex as f64).round() as u64);
+
+    Ok(HttpResponse::Ok().json(SettingsPreviewResponse {
+        documents_to_reindex,
+        estimated_duration_ms,
+        affected_settings,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/settings",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 200, description = "All the settings of the index", body = Object),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get("/indexes/{index_uid}/settings", wrap = "Authentication::Private")]
 async fn get_all(
     data: web::Data<Data>,
@@ -62,35 +412,41 @@ async fn get_all(
         .ok_or(Error::index_not_found(&path.index_uid))?;
 
     let reader = data.db.main_read_txn()?;
+    let settings = current_settings(&reader, &index)?;
 
+    Ok(HttpResponse::Ok().json(settings))
+}
+
+/// Returns the settings of the index as they are currently applied.
+fn current_settings(reader: &MainReader, index: &Index) -> Result<Settings, ResponseError> {
     let stop_words: BTreeSet<String> = index
         .main
-        .stop_words(&reader)?
+        .stop_words(reader)?
         .into_iter()
         .collect();
 
-    let synonyms_list = index.main.synonyms(&reader)?;
+    let synonyms_list = index.main.synonyms(reader)?;
 
     let mut synonyms = BTreeMap::new();
     let index_synonyms = &index.synonyms;
     for synonym in synonyms_list {
-        let list = index_synonyms.synonyms(&reader, synonym.as_bytes())?;
+        let list = index_synonyms.synonyms(reader, synonym.as_bytes())?;
         synonyms.insert(synonym, list);
     }
 
     let ranking_rules = index
         .main
-        .ranking_rules(&reader)?
+        .ranking_rules(reader)?
         .unwrap_or(DEFAULT_RANKING_RULES.to_vec())
         .into_iter()
         .map(|r| r.to_string())
         .collect();
 
-    let distinct_attribute = index.main.distinct_attribute(&reader)?;
+    let distinct_attribute = index.main.distinct_attribute(reader)?;
 
-    let schema = index.main.schema(&reader)?;
+    let schema = index.main.schema(reader)?;
 
-    let attributes_for_faceting = match (&schema, &index.main.attributes_for_faceting(&reader)?) {
+    let attributes_for_faceting = match (&schema, &index.main.attributes_for_faceting(reader)?) {
         (Some(schema), Some(attrs)) => {
             Some(attrs
                 .iter()
@@ -117,7 +473,31 @@ async fn get_all(
 
     let accept_new_fields = schema.map(|s| s.accept_new_fields());
 
-    let settings = Settings {
+    let max_fields_count = index.main.max_fields_count(reader)?;
+    let max_index_size = index.main.max_index_size(reader)?;
+    let phonetic_matching = index.main.phonetic_matching(reader)?;
+    let phonetic_attributes = index.main.phonetic_attributes(reader)?;
+    let stemming_language = index.main.stemming_language(reader)?;
+    let decompound_tokens = index.main.decompound_tokens(reader)?;
+    let proximity_precision = index.main.proximity_precision(reader)?;
+    let proximity_window = index.main.proximity_window(reader)?;
+    let pinned_hits = index.main.pinned_hits(reader)?;
+    let demoted_documents = index.main.demoted_documents(reader)?;
+    let image_hash_field = index.main.image_hash_field(reader)?;
+    let language_detection_field = index.main.language_detection_field(reader)?;
+    let one_way_synonyms = index.main.one_way_synonyms(reader)?;
+    let equivalent_synonyms = index.main.equivalent_synonyms(reader)?;
+    let hierarchical_facets = index.main.hierarchical_facets(reader)?;
+    let transformer = index.main.transformer(reader)?;
+    let webhook_url = index.main.webhook_url(reader)?;
+    let cross_field_boost = index.main.cross_field_boost(reader)?;
+    let document_versions = index.main.document_versions(reader)?;
+    let referenced_documents = index.main.referenced_documents(reader)?;
+    // the API key is a secret, it is never returned
+    let embedder = index.main.embedder(reader)?.map(|embedder| Embedder { api_key: None, ..embedder });
+    let embedding_strategy = index.main.embedding_strategy(reader)?;
+
+    Ok(Settings {
         ranking_rules: Some(Some(ranking_rules)),
         distinct_attribute: Some(distinct_attribute),
         searchable_attributes: Some(searchable_attributes),
@@ -126,11 +506,41 @@ async fn get_all(
         synonyms: Some(Some(synonyms)),
         accept_new_fields: Some(accept_new_fields),
         attributes_for_faceting: Some(attributes_for_faceting),
-    };
-
-    Ok(HttpResponse::Ok().json(settings))
+        max_fields_count: max_fields_count.map(Some),
+        max_index_size: max_index_size.map(Some),
+        phonetic_matching: phonetic_matching.map(Some),
+        phonetic_attributes: phonetic_attributes.map(Some),
+        stemming_language: stemming_language.map(Some),
+        decompound_tokens: decompound_tokens.map(Some),
+        proximity_precision: proximity_precision.map(Some),
+        proximity_window: proximity_window.map(Some),
+        pinned_hits: pinned_hits.map(Some),
+        demoted_documents: demoted_documents.map(Some),
+        image_hash_field: image_hash_field.map(Some),
+        language_detection_field: language_detection_field.map(Some),
+        one_way_synonyms: one_way_synonyms.map(Some),
+        equivalent_synonyms: equivalent_synonyms.map(Some),
+        hierarchical_facets: hierarchical_facets.map(Some),
+        transformer: transformer.map(Some),
+        webhook_url: webhook_url.map(Some),
+        cross_field_boost: cross_field_boost.map(Some),
+        document_versions: document_versions.map(Some),
+        referenced_documents: referenced_documents.map(Some),
+        embedder: embedder.map(Some),
+        embedding_strategy: embedding_strategy.map(Some),
+    })
 }
 
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/settings",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[delete("/indexes/{index_uid}/settings", wrap = "Authentication::Private")]
 async fn delete_all(
     data: web::Data<Data>,
@@ -151,6 +561,28 @@ async fn delete_all(
         synonyms: UpdateState::Clear,
         accept_new_fields: UpdateState::Clear,
         attributes_for_faceting: UpdateState::Clear,
+        max_fields_count: UpdateState::Clear,
+        max_index_size: UpdateState::Clear,
+        phonetic_matching: UpdateState::Clear,
+        phonetic_attributes: UpdateState::Clear,
+        stemming_language: UpdateState::Clear,
+        decompound_tokens: UpdateState::Clear,
+        proximity_precision: UpdateState::Clear,
+        proximity_window: UpdateState::Clear,
+        pinned_hits: UpdateState::Clear,
+        demoted_documents: UpdateState::Clear,
+        image_hash_field: UpdateState::Clear,
+        language_detection_field: UpdateState::Clear,
+        one_way_synonyms: UpdateState::Clear,
+        equivalent_synonyms: UpdateState::Clear,
+        hierarchical_facets: UpdateState::Clear,
+        transformer: UpdateState::Clear,
+        webhook_url: UpdateState::Clear,
+        cross_field_boost: UpdateState::Clear,
+        document_versions: UpdateState::Clear,
+        referenced_documents: UpdateState::Clear,
+        embedder: UpdateState::Clear,
+        embedding_strategy: UpdateState::Clear,
     };
 
     let update_id = data.db.update_write(|w| index.settings_update(w, settings))?;
@@ -158,6 +590,16 @@ async fn delete_all(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/settings/ranking-rules",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 200, description = "The ranking rules of the index", body = Vec<String>),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get(
     "/indexes/{index_uid}/settings/ranking-rules",
     wrap = "Authentication::Private"
@@ -183,6 +625,17 @@ async fn get_rules(
     Ok(HttpResponse::Ok().json(ranking_rules))
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/settings/ranking-rules",
+    tag = "Settings",
+    params(IndexParam),
+    request_body = Option<Vec<String>>,
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[post(
     "/indexes/{index_uid}/settings/ranking-rules",
     wrap = "Authentication::Private"
@@ -208,6 +661,16 @@ async fn update_rules(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/settings/ranking-rules",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[delete(
     "/indexes/{index_uid}/settings/ranking-rules",
     wrap = "Authentication::Private"
@@ -231,6 +694,16 @@ async fn delete_rules(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/settings/distinct-attribute",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 200, description = "The distinct attribute of the index", body = Option<String>),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get(
     "/indexes/{index_uid}/settings/distinct-attribute",
     wrap = "Authentication::Private"
@@ -249,6 +722,17 @@ async fn get_distinct(
     Ok(HttpResponse::Ok().json(distinct_attribute))
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/settings/distinct-attribute",
+    tag = "Settings",
+    params(IndexParam),
+    request_body = Option<String>,
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[post(
     "/indexes/{index_uid}/settings/distinct-attribute",
     wrap = "Authentication::Private"
@@ -274,6 +758,16 @@ async fn update_distinct(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/settings/distinct-attribute",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[delete(
     "/indexes/{index_uid}/settings/distinct-attribute",
     wrap = "Authentication::Private"
@@ -297,6 +791,16 @@ async fn delete_distinct(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/settings/searchable-attributes",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 200, description = "The searchable attributes of the index", body = Option<Vec<String>>),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get(
     "/indexes/{index_uid}/settings/searchable-attributes",
     wrap = "Authentication::Private"
@@ -317,6 +821,17 @@ async fn get_searchable(
     Ok(HttpResponse::Ok().json(searchable_attributes))
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/settings/searchable-attributes",
+    tag = "Settings",
+    params(IndexParam),
+    request_body = Option<Vec<String>>,
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[post(
     "/indexes/{index_uid}/settings/searchable-attributes",
     wrap = "Authentication::Private"
@@ -343,6 +858,16 @@ async fn update_searchable(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/settings/searchable-attributes",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[delete(
     "/indexes/{index_uid}/settings/searchable-attributes",
     wrap = "Authentication::Private"
@@ -366,6 +891,16 @@ async fn delete_searchable(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/settings/displayed-attributes",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 200, description = "The displayed attributes of the index", body = Option<Vec<String>>),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get(
     "/indexes/{index_uid}/settings/displayed-attributes",
     wrap = "Authentication::Private"
@@ -388,6 +923,17 @@ async fn get_displayed(
     Ok(HttpResponse::Ok().json(displayed_attributes))
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/settings/displayed-attributes",
+    tag = "Settings",
+    params(IndexParam),
+    request_body = Option<Vec<String>>,
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[post(
     "/indexes/{index_uid}/settings/displayed-attributes",
     wrap = "Authentication::Private"
@@ -413,6 +959,16 @@ async fn update_displayed(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/settings/displayed-attributes",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[delete(
     "/indexes/{index_uid}/settings/displayed-attributes",
     wrap = "Authentication::Private"
@@ -436,6 +992,16 @@ async fn delete_displayed(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/settings/accept-new-fields",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 200, description = "Whether new fields are accepted", body = Option<bool>),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get(
     "/indexes/{index_uid}/settings/accept-new-fields",
     wrap = "Authentication::Private"
@@ -457,6 +1023,17 @@ async fn get_accept_new_fields(
     Ok(HttpResponse::Ok().json(accept_new_fields))
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/settings/accept-new-fields",
+    tag = "Settings",
+    params(IndexParam),
+    request_body = Option<bool>,
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[post(
     "/indexes/{index_uid}/settings/accept-new-fields",
     wrap = "Authentication::Private"
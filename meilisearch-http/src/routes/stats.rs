@@ -1,36 +1,66 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 
 use actix_web::web;
 use actix_web::HttpResponse;
-use actix_web_macros::get;
+use actix_web_macros::{get, post};
 use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
 use log::error;
 use pretty_bytes::converter::convert;
 use serde::Serialize;
+use serde_json::Value;
 use sysinfo::{NetworkExt, ProcessExt, ProcessorExt, System, SystemExt};
+use utoipa::{OpenApi, ToSchema};
 use walkdir::WalkDir;
 
-use crate::error::{Error, ResponseError};
+use crate::error::{Error, ErrorResponseBody, ResponseError};
 use crate::helpers::Authentication;
+use crate::routes::document::Document;
 use crate::routes::IndexParam;
 use crate::Data;
 
 pub fn services(cfg: &mut web::ServiceConfig) {
     cfg.service(index_stats)
+        .service(analyze_documents)
         .service(get_stats)
+        .service(get_status)
         .service(get_version)
         .service(get_sys_info)
         .service(get_sys_info_pretty);
 }
 
-#[derive(Serialize)]
+#[derive(OpenApi)]
+#[openapi(paths(
+    index_stats,
+    analyze_documents,
+    get_stats,
+    get_status,
+    get_version,
+    get_sys_info,
+    get_sys_info_pretty,
+))]
+pub struct StatsApi;
+
+#[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct IndexStatsResponse {
     number_of_documents: u64,
     is_indexing: bool,
+    field_count: usize,
     fields_distribution: HashMap<String, usize>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/stats",
+    tag = "Stats",
+    params(IndexParam),
+    responses(
+        (status = 200, description = "The stats of the index", body = IndexStatsResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get("/indexes/{index_uid}/stats", wrap = "Authentication::Private")]
 async fn index_stats(
     data: web::Data<Data>,
@@ -47,6 +77,8 @@ async fn index_stats(
 
     let fields_distribution = index.main.fields_distribution(&reader)?.unwrap_or_default();
 
+    let field_count = index.main.schema(&reader)?.map_or(0, |s| s.fields_count());
+
     let update_reader = data.db.update_read_txn()?;
 
     let is_indexing =
@@ -58,11 +90,133 @@ async fn index_stats(
     Ok(HttpResponse::Ok().json(IndexStatsResponse {
         number_of_documents,
         is_indexing,
+        field_count,
         fields_distribution,
     }))
 }
 
-#[derive(Serialize)]
+/// The maximum number of documents of a sample to analyze.
+const MAX_ANALYZED_DOCUMENTS: usize = 1000;
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct FieldStatistics {
+    name: String,
+    /// `number`, `string`, `boolean`, `array` or `object`, `null` when the field is
+    /// always null and `mixed` when its values are of different types.
+    #[serde(rename = "type")]
+    field_type: &'static str,
+    /// The smallest of the numbers, if the field has some.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<f64>)]
+    min: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<f64>)]
+    max: Option<Value>,
+    /// The percentage of the documents where the field is missing or null.
+    #[serde(rename = "null%")]
+    null_percentage: f64,
+    /// The number of distinct values, null excluded.
+    unique: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct AnalyzeResponse {
+    fields: Vec<FieldStatistics>,
+}
+
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Returns the statistics of the fields of the documents, in the order they are first seen.
+fn field_statistics(documents: &[Document]) -> Vec<FieldStatistics> {
+    let mut fields: IndexMap<&str, Vec<&Value>> = IndexMap::new();
+    for document in documents {
+        for (name, value) in document {
+            let values = fields.entry(name.as_str()).or_insert_with(Vec::new);
+            if !value.is_null() {
+                values.push(value);
+            }
+        }
+    }
+
+    let compare = |a: &&&Value, b: &&&Value| a.as_f64().partial_cmp(&b.as_f64()).unwrap_or(Ordering::Equal);
+
+    fields
+        .into_iter()
+        .map(|(name, values)| {
+            let mut types: Vec<_> = values.iter().map(|value| value_type(value)).collect();
+            types.sort_unstable();
+            types.dedup();
+
+            let field_type = match types.as_slice() {
+                [] => "null",
+                [field_type] => *field_type,
+                _ => "mixed",
+            };
+
+            let numbers = values.iter().filter(|value| value.is_number());
+            let min = numbers.clone().min_by(compare).map(|value| (*value).clone());
+            let max = numbers.max_by(compare).map(|value| (*value).clone());
+
+            let nulls = documents.len() - values.len();
+            let null_percentage = (nulls as f64 * 1000.0 / documents.len() as f64).round() / 10.0;
+            let unique = values.iter().map(|value| value.to_string()).collect::<HashSet<_>>().len();
+
+            FieldStatistics {
+                name: name.to_string(),
+                field_type,
+                min,
+                max,
+                null_percentage,
+                unique,
+            }
+        })
+        .collect()
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/analyze",
+    tag = "Stats",
+    params(IndexParam),
+    request_body = Vec<Object>,
+    responses(
+        (status = 200, description = "The statistics of the fields of the documents, which are not stored", body = AnalyzeResponse),
+        (status = 400, description = "There are too many documents", body = ErrorResponseBody),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
+#[post("/indexes/{index_uid}/analyze", wrap = "Authentication::Private")]
+async fn analyze_documents(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    body: web::Json<Vec<Document>>,
+) -> Result<HttpResponse, ResponseError> {
+    data.db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    if body.len() > MAX_ANALYZED_DOCUMENTS {
+        let message = format!("at most {} documents can be analyzed", MAX_ANALYZED_DOCUMENTS);
+        return Err(Error::bad_parameter("documents", message).into());
+    }
+
+    Ok(HttpResponse::Ok().json(AnalyzeResponse {
+        fields: field_statistics(&body),
+    }))
+}
+
+#[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct StatsResult {
     database_size: u64,
@@ -70,6 +224,14 @@ struct StatsResult {
     indexes: HashMap<String, IndexStatsResponse>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/stats",
+    tag = "Stats",
+    responses(
+        (status = 200, description = "The stats of the database and of all the indexes", body = StatsResult),
+    )
+)]
 #[get("/stats", wrap = "Authentication::Private")]
 async fn get_stats(data: web::Data<Data>) -> Result<HttpResponse, ResponseError> {
     let mut index_list = HashMap::new();
@@ -86,6 +248,8 @@ async fn get_stats(data: web::Data<Data>) -> Result<HttpResponse, ResponseError>
 
                 let fields_distribution = index.main.fields_distribution(&reader)?.unwrap_or_default();
 
+                let field_count = index.main.schema(&reader)?.map_or(0, |s| s.fields_count());
+
                 let is_indexing = data.db.is_indexing(&update_reader, &index_uid)?.ok_or(
                     Error::internal("Impossible to know if the database is indexing"),
                 )?;
@@ -93,6 +257,7 @@ async fn get_stats(data: web::Data<Data>) -> Result<HttpResponse, ResponseError>
                 let response = IndexStatsResponse {
                     number_of_documents,
                     is_indexing,
+                    field_count,
                     fields_distribution,
                 };
                 index_list.insert(index_uid, response);
@@ -120,7 +285,75 @@ async fn get_stats(data: web::Data<Data>) -> Result<HttpResponse, ResponseError>
     }))
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+enum IndexingStatus {
+    /// Some updates of the index are waiting to be processed.
+    Indexing,
+    Idle,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct IndexStatus {
+    uid: String,
+    /// The number of enqueued updates, including the one being processed.
+    pending_tasks: u64,
+    status: IndexingStatus,
+    last_task_completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct StatusResponse {
+    indexes: Vec<IndexStatus>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/status",
+    tag = "Stats",
+    responses(
+        (status = 200, description = "The updates queue of every index", body = StatusResponse),
+    )
+)]
+#[get("/status", wrap = "Authentication::Admin")]
+async fn get_status(data: web::Data<Data>) -> Result<HttpResponse, ResponseError> {
+    let update_reader = data.db.update_read_txn()?;
+
+    let mut indexes_uids = data.db.indexes_uids();
+    indexes_uids.sort_unstable();
+
+    let mut indexes = Vec::with_capacity(indexes_uids.len());
+    for uid in indexes_uids {
+        let index = match data.db.open_index(&uid) {
+            Some(index) => index,
+            None => {
+                error!("Index {:?} is referenced in the indexes list but cannot be found", uid);
+                continue;
+            }
+        };
+
+        let pending_tasks = index.pending_updates_count(&update_reader)?;
+        let status = if pending_tasks > 0 { IndexingStatus::Indexing } else { IndexingStatus::Idle };
+        let last_task_completed_at = index
+            .updates_results
+            .last_update(&update_reader)
+            .map_err(meilisearch_core::Error::from)?
+            .map(|(_, result)| result.processed_at);
+
+        indexes.push(IndexStatus {
+            uid,
+            pending_tasks,
+            status,
+            last_task_completed_at,
+        });
+    }
+
+    Ok(HttpResponse::Ok().json(StatusResponse { indexes }))
+}
+
+#[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct VersionResponse {
     commit_sha: String,
@@ -128,6 +361,14 @@ struct VersionResponse {
     pkg_version: String,
 }
 
+#[utoipa::path(
+    get,
+    path = "/version",
+    tag = "Stats",
+    responses(
+        (status = 200, description = "The version of the server", body = VersionResponse),
+    )
+)]
 #[get("/version", wrap = "Authentication::Private")]
 async fn get_version() -> HttpResponse {
     HttpResponse::Ok().json(VersionResponse {
@@ -137,7 +378,7 @@ async fn get_version() -> HttpResponse {
     })
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct SysGlobal {
     total_memory: u64,
@@ -161,7 +402,7 @@ impl SysGlobal {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct SysProcess {
     memory: u64,
@@ -177,7 +418,7 @@ impl SysProcess {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct SysInfo {
     memory_usage: f64,
@@ -197,6 +438,14 @@ impl SysInfo {
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/sys-info",
+    tag = "Stats",
+    responses(
+        (status = 200, description = "The usage of the system resources", body = SysInfo),
+    )
+)]
 #[get("/sys-info", wrap = "Authentication::Private")]
 async fn get_sys_info(data: web::Data<Data>) -> HttpResponse {
     let mut sys = System::new();
@@ -232,7 +481,7 @@ async fn get_sys_info(data: web::Data<Data>) -> HttpResponse {
     HttpResponse::Ok().json(info)
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct SysGlobalPretty {
     total_memory: String,
@@ -256,7 +505,7 @@ impl SysGlobalPretty {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct SysProcessPretty {
     memory: String,
@@ -272,7 +521,7 @@ impl SysProcessPretty {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct SysInfoPretty {
     memory_usage: String,
@@ -292,6 +541,14 @@ impl SysInfoPretty {
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/sys-info/pretty",
+    tag = "Stats",
+    responses(
+        (status = 200, description = "The usage of the system resources, formatted for humans", body = SysInfoPretty),
+    )
+)]
 #[get("/sys-info/pretty", wrap = "Authentication::Private")]
 async fn get_sys_info_pretty(data: web::Data<Data>) -> HttpResponse {
     let mut sys = System::new();
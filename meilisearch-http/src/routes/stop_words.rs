@@ -2,8 +2,9 @@ use actix_web::{web, HttpResponse};
 use actix_web_macros::{delete, get, post};
 use meilisearch_core::settings::{SettingsUpdate, UpdateState};
 use std::collections::BTreeSet;
+use utoipa::OpenApi;
 
-use crate::error::{Error, ResponseError};
+use crate::error::{Error, ErrorResponseBody, ResponseError};
 use crate::helpers::Authentication;
 use crate::routes::{IndexParam, IndexUpdateResponse};
 use crate::Data;
@@ -12,6 +13,24 @@ pub fn services(cfg: &mut web::ServiceConfig) {
     cfg.service(get).service(update).service(delete);
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(
+    get,
+    update,
+    delete,
+))]
+pub struct StopWordsApi;
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/settings/stop-words",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 200, description = "The stop words of the index", body = Vec<String>),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get(
     "/indexes/{index_uid}/settings/stop-words",
     wrap = "Authentication::Private"
@@ -30,6 +49,17 @@ async fn get(
     Ok(HttpResponse::Ok().json(stop_words))
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/settings/stop-words",
+    tag = "Settings",
+    params(IndexParam),
+    request_body = Vec<String>,
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[post(
     "/indexes/{index_uid}/settings/stop-words",
     wrap = "Authentication::Private"
@@ -54,6 +84,16 @@ async fn update(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/settings/stop-words",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[delete(
     "/indexes/{index_uid}/settings/stop-words",
     wrap = "Authentication::Private"
@@ -1,19 +1,56 @@
 use std::collections::BTreeMap;
 
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use actix_web_macros::{delete, get, post};
 use indexmap::IndexMap;
 use meilisearch_core::settings::{SettingsUpdate, UpdateState};
+use meilisearch_core::normalize_synonym;
+use meilisearch_core::update::SynonymsEdit;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
-use crate::error::{Error, ResponseError};
+use crate::error::{Error, ErrorResponseBody, ResponseError};
 use crate::helpers::Authentication;
 use crate::routes::{IndexParam, IndexUpdateResponse};
 use crate::Data;
 
 pub fn services(cfg: &mut web::ServiceConfig) {
-    cfg.service(get).service(update).service(delete);
+    cfg.service(get)
+        .service(update)
+        .service(delete)
+        .service(get_word)
+        .service(delete_word)
+        .service(import);
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(
+    get,
+    update,
+    delete,
+    get_word,
+    delete_word,
+    import,
+))]
+pub struct SynonymApi;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Path)]
+struct SynonymParam {
+    index_uid: String,
+    word: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/settings/synonyms",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 200, description = "The synonyms of the index", body = BTreeMap<String, Vec<String>>),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[get(
     "/indexes/{index_uid}/settings/synonyms",
     wrap = "Authentication::Private"
@@ -41,6 +78,17 @@ async fn get(
     Ok(HttpResponse::Ok().json(synonyms))
 }
 
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/settings/synonyms",
+    tag = "Settings",
+    params(IndexParam),
+    request_body = BTreeMap<String, Vec<String>>,
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[post(
     "/indexes/{index_uid}/settings/synonyms",
     wrap = "Authentication::Private"
@@ -65,6 +113,16 @@ async fn update(
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/settings/synonyms",
+    tag = "Settings",
+    params(IndexParam),
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+    )
+)]
 #[delete(
     "/indexes/{index_uid}/settings/synonyms",
     wrap = "Authentication::Private"
@@ -87,3 +145,220 @@ async fn delete(
 
     Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
 }
+
+#[utoipa::path(
+    get,
+    path = "/indexes/{index_uid}/synonyms/{word}",
+    tag = "Settings",
+    params(SynonymParam),
+    responses(
+        (status = 200, description = "The synonyms of the word", body = Vec<String>),
+        (status = 404, description = "Index or word not found", body = ErrorResponseBody),
+    )
+)]
+#[get(
+    "/indexes/{index_uid}/synonyms/{word}",
+    wrap = "Authentication::Private"
+)]
+async fn get_word(
+    data: web::Data<Data>,
+    path: web::Path<SynonymParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+
+    // the synonyms are stored normalized
+    let word = normalize_synonym(&path.word);
+    if !index.main.contains_synonym(&reader, &word)? {
+        return Err(Error::not_found(format!("Synonyms of {}", path.word)).into());
+    }
+
+    let synonyms = index.synonyms.synonyms(&reader, word.as_bytes())?;
+
+    Ok(HttpResponse::Ok().json(synonyms))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/indexes/{index_uid}/synonyms/{word}",
+    tag = "Settings",
+    params(SynonymParam),
+    responses(
+        (status = 202, description = "The update has been enqueued", body = IndexUpdateResponse),
+        (status = 404, description = "Index or word not found", body = ErrorResponseBody),
+    )
+)]
+#[delete(
+    "/indexes/{index_uid}/synonyms/{word}",
+    wrap = "Authentication::Private"
+)]
+async fn delete_word(
+    data: web::Data<Data>,
+    path: web::Path<SynonymParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let reader = data.db.main_read_txn()?;
+
+    let word = normalize_synonym(&path.word);
+    if !index.main.contains_synonym(&reader, &word)? {
+        return Err(Error::not_found(format!("Synonyms of {}", path.word)).into());
+    }
+
+    // the other words are not touched, the edit is applied by the update loop
+    let edit = SynonymsEdit::DeleteWord(word);
+    let update_id = data.db.update_write(|w| index.synonyms_edit(w, edit))?;
+
+    Ok(HttpResponse::Accepted().json(IndexUpdateResponse::with_id(update_id)))
+}
+
+/// The synonyms read from a CSV file, one row per group of synonyms.
+#[derive(Debug, Default, PartialEq)]
+struct SynonymsImport {
+    synonyms: BTreeMap<String, Vec<String>>,
+    malformed_rows: Vec<u64>,
+}
+
+/// The column separating the word from its synonyms in a one-way row, e.g. `nyc,→,new york`.
+fn is_arrow(field: &str) -> bool {
+    field == "→" || field == "->"
+}
+
+/// Reads the synonyms of the CSV rows. The words of a row are all synonyms of each other,
+/// unless its second column is an arrow, then the first word only is expanded.
+fn read_synonyms_csv(csv: &[u8]) -> SynonymsImport {
+    let mut synonyms_import = SynonymsImport::default();
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(csv);
+
+    // the lines are counted from the offsets, the records start with the empty lines before them
+    let line_at = |position: &csv::Position| {
+        let mut offset = (position.byte() as usize).min(csv.len());
+        while csv.get(offset).map_or(false, |b| *b == b'\n' || *b == b'\r') {
+            offset += 1;
+        }
+        csv[..offset].iter().filter(|b| **b == b'\n').count() as u64 + 1
+    };
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                if let Some(position) = e.position() {
+                    synonyms_import.malformed_rows.push(line_at(position));
+                }
+                continue;
+            }
+        };
+
+        let line = record.position().map_or(0, line_at);
+        let fields: Vec<&str> = record.iter().filter(|field| !field.is_empty()).collect();
+
+        let pairs: Vec<(&str, Vec<&str>)> = match fields.as_slice() {
+            [word, arrow, targets @ ..] if is_arrow(arrow) && !targets.is_empty() => {
+                if targets.iter().any(|target| is_arrow(target)) {
+                    synonyms_import.malformed_rows.push(line);
+                    continue;
+                }
+                vec![(word, targets.to_vec())]
+            }
+            words if words.len() >= 2 && !words.iter().any(|word| is_arrow(word)) => words
+                .iter()
+                .map(|word| (*word, words.iter().filter(|other| *other != word).copied().collect()))
+                .collect(),
+            _ => {
+                synonyms_import.malformed_rows.push(line);
+                continue;
+            }
+        };
+
+        // the duplicates are kept, they are counted when the synonyms are merged
+        for (word, targets) in pairs {
+            let entry = synonyms_import.synonyms.entry(word.to_string()).or_default();
+            entry.extend(targets.into_iter().map(str::to_string));
+        }
+    }
+
+    synonyms_import
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct SynonymsImportResponse {
+    /// The update merging the synonyms with the ones of the index,
+    /// its warnings give the number of synonyms imported and skipped.
+    update_id: u64,
+    /// The line numbers of the rows that could not be read.
+    malformed_rows: Vec<u64>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/indexes/{index_uid}/synonyms/import",
+    tag = "Settings",
+    params(IndexParam),
+    request_body(content = String, content_type = "text/csv"),
+    responses(
+        (status = 202, description = "The update has been enqueued", body = SynonymsImportResponse),
+        (status = 404, description = "Index not found", body = ErrorResponseBody),
+        (status = 415, description = "The body is not a CSV file", body = ErrorResponseBody),
+    )
+)]
+#[post(
+    "/indexes/{index_uid}/synonyms/import",
+    wrap = "Authentication::Private"
+)]
+async fn import(
+    data: web::Data<Data>,
+    path: web::Path<IndexParam>,
+    req: HttpRequest,
+    body: String,
+) -> Result<HttpResponse, ResponseError> {
+    if req.content_type() != "text/csv" {
+        return Err(Error::UnsupportedMediaType.into());
+    }
+
+    let index = data
+        .db
+        .open_index(&path.index_uid)
+        .ok_or(Error::index_not_found(&path.index_uid))?;
+
+    let SynonymsImport { synonyms, malformed_rows } = read_synonyms_csv(body.as_bytes());
+
+    let edit = SynonymsEdit::Merge(synonyms);
+    let update_id = data.db.update_write(|w| index.synonyms_edit(w, edit))?;
+
+    Ok(HttpResponse::Accepted().json(SynonymsImportResponse { update_id, malformed_rows }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_bidirectional_and_one_way_rows() {
+        let csv = "TV,television\nnyc,→,new york,big apple\n\nlonely\nnyc,->,new york\n\"lake\ntahoe\",tahoe\n\"broken,row\n";
+        let synonyms_import = read_synonyms_csv(csv.as_bytes());
+
+        let mut expected = BTreeMap::new();
+        expected.insert("TV".to_string(), vec!["television".to_string()]);
+        expected.insert("lake\ntahoe".to_string(), vec!["tahoe".to_string()]);
+        expected.insert("nyc".to_string(), vec!["new york".to_string(), "big apple".to_string(), "new york".to_string()]);
+        expected.insert("tahoe".to_string(), vec!["lake\ntahoe".to_string()]);
+        expected.insert("television".to_string(), vec!["TV".to_string()]);
+
+        assert_eq!(synonyms_import.synonyms, expected);
+        assert_eq!(synonyms_import.malformed_rows, vec![4, 8]);
+    }
+}
@@ -3,7 +3,7 @@
 use serde_json::{json, Value};
 use std::time::Duration;
 
-use actix_web::{http::StatusCode, test};
+use actix_web::{http::HeaderMap, http::StatusCode, middleware, test};
 use meilisearch_core::DatabaseOptions;
 use meilisearch_http::data::Data;
 use meilisearch_http::option::Opt;
@@ -18,11 +18,15 @@ pub struct Server {
 
 impl Server {
     pub fn with_uid(uid: &str) -> Server {
+        Server::with_uid_and_options(uid, |_| ())
+    }
+
+    pub fn with_uid_and_options(uid: &str, customize: impl FnOnce(&mut Opt)) -> Server {
         let tmp_dir = TempDir::new("meilisearch").unwrap();
 
         let default_db_options = DatabaseOptions::default();
 
-        let opt = Opt {
+        let mut opt = Opt {
             db_path: tmp_dir.path().to_str().unwrap().to_string(),
             http_addr: "127.0.0.1:7700".to_owned(),
             master_key: None,
@@ -31,8 +35,10 @@ impl Server {
             main_map_size: default_db_options.main_map_size,
             update_map_size: default_db_options.update_map_size,
             http_payload_size_limit: 10000000,
+            max_wildcard_expansions: 1000,
             ..Opt::default()
         };
+        customize(&mut opt);
 
         let data = Data::new(opt.clone());
 
@@ -115,7 +121,7 @@ impl Server {
             let (response, status_code) = self.get_update_status(update_id).await;
             assert_eq!(status_code, 200);
 
-            if response["status"] == "processed" || response["status"] == "error" {
+            if response["status"] == "processed" || response["status"] == "failed" || response["status"] == "error" {
                 eprintln!("{:#?}", response);
                 return;
             }
@@ -140,6 +146,51 @@ impl Server {
         (response, status_code)
     }
 
+    pub async fn get_request_with_header(
+        &mut self,
+        url: &str,
+        header: (&str, &str),
+    ) -> (Value, StatusCode) {
+        eprintln!("get_request_with_header: {}", url);
+
+        let mut app = test::init_service(meilisearch_http::create_app(&self.data).wrap(NormalizePath)).await;
+
+        let req = test::TestRequest::get()
+            .uri(url)
+            .header(header.0, header.1)
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        let status_code = res.status().clone();
+
+        let body = test::read_body(res).await;
+        let response = serde_json::from_slice(&body).unwrap_or_default();
+        (response, status_code)
+    }
+
+    pub async fn get_request_with_headers(
+        &mut self,
+        url: &str,
+        headers: &[(&str, &str)],
+    ) -> (Value, StatusCode, HeaderMap) {
+        eprintln!("get_request_with_headers: {}", url);
+
+        // the responses are compressed like they are by the server
+        let app = meilisearch_http::create_app(&self.data).wrap(middleware::Compress::default()).wrap(NormalizePath);
+        let mut app = test::init_service(app).await;
+
+        let mut req = test::TestRequest::get().uri(url);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let res = test::call_service(&mut app, req.to_request()).await;
+        let status_code = res.status().clone();
+        let response_headers = res.headers().clone();
+
+        let body = test::read_body(res).await;
+        let response = serde_json::from_slice(&body).unwrap_or_default();
+        (response, status_code, response_headers)
+    }
+
     pub async fn post_request(&mut self, url: &str, body: Value) -> (Value, StatusCode) {
         eprintln!("post_request: {}", url);
 
@@ -157,6 +208,29 @@ impl Server {
         (response, status_code)
     }
 
+    pub async fn post_request_with_header(
+        &mut self,
+        url: &str,
+        body: Value,
+        header: (&str, &str),
+    ) -> (Value, StatusCode) {
+        eprintln!("post_request_with_header: {}", url);
+
+        let mut app = test::init_service(meilisearch_http::create_app(&self.data).wrap(NormalizePath)).await;
+
+        let req = test::TestRequest::post()
+            .uri(url)
+            .header(header.0, header.1)
+            .set_json(&body)
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        let status_code = res.status().clone();
+
+        let body = test::read_body(res).await;
+        let response = serde_json::from_slice(&body).unwrap_or_default();
+        (response, status_code)
+    }
+
     pub async fn post_request_async(&mut self, url: &str, body: Value) -> (Value, StatusCode) {
         eprintln!("post_request_async: {}", url);
 
@@ -197,6 +271,34 @@ impl Server {
         (response, status_code)
     }
 
+    pub async fn patch_request(&mut self, url: &str, body: Value) -> (Value, StatusCode) {
+        eprintln!("patch_request: {}", url);
+
+        let mut app = test::init_service(meilisearch_http::create_app(&self.data).wrap(NormalizePath)).await;
+
+        let req = test::TestRequest::patch()
+            .uri(url)
+            .set_json(&body)
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        let status_code = res.status().clone();
+
+        let body = test::read_body(res).await;
+        let response = serde_json::from_slice(&body).unwrap_or_default();
+        (response, status_code)
+    }
+
+    pub async fn patch_request_async(&mut self, url: &str, body: Value) -> (Value, StatusCode) {
+        eprintln!("patch_request_async: {}", url);
+
+        let (response, status_code) = self.patch_request(url, body).await;
+        assert!(response["updateId"].as_u64().is_some());
+        assert_eq!(status_code, 202);
+        self.wait_update_id(response["updateId"].as_u64().unwrap())
+            .await;
+        (response, status_code)
+    }
+
     pub async fn delete_request(&mut self, url: &str) -> (Value, StatusCode) {
         eprintln!("delete_request: {}", url);
 
@@ -257,6 +359,21 @@ impl Server {
         self.get_request(&url).await
     }
 
+    pub async fn search_post(&mut self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/search", self.uid);
+        self.post_request(&url, body).await
+    }
+
+    pub async fn rerank(&mut self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/rerank", self.uid);
+        self.post_request(&url, body).await
+    }
+
+    pub async fn spellcheck(&mut self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/spellcheck", self.uid);
+        self.post_request(&url, body).await
+    }
+
     pub async fn get_all_updates_status(&mut self) -> (Value, StatusCode) {
         let url = format!("/indexes/{}/updates", self.uid);
         self.get_request(&url).await
@@ -285,6 +402,14 @@ impl Server {
         self.post_request(&url, body).await
     }
 
+    pub async fn add_or_replace_multiple_documents_dry_run(
+        &mut self,
+        body: Value,
+    ) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents", self.uid);
+        self.post_request_with_header(&url, body, ("X-Meili-Dry-Run", "true")).await
+    }
+
     pub async fn add_or_update_multiple_documents(&mut self, body: Value) {
         let url = format!("/indexes/{}/documents", self.uid);
         self.put_request_async(&url, body).await;
@@ -304,6 +429,16 @@ impl Server {
         self.get_request(&url).await
     }
 
+    pub async fn get_similar_documents(&mut self, document_id: impl ToString, query: &str) -> (Value, StatusCode) {
+        let url = format!(
+            "/indexes/{}/documents/{}/similar-content?{}",
+            self.uid,
+            document_id.to_string(),
+            query
+        );
+        self.get_request(&url).await
+    }
+
     pub async fn delete_document(&mut self, document_id: impl ToString) -> (Value, StatusCode) {
         let url = format!(
             "/indexes/{}/documents/{}",
@@ -423,6 +558,28 @@ impl Server {
         self.post_request_async(&url, body).await;
     }
 
+    pub async fn import_synonyms_csv(&mut self, csv: &str) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/synonyms/import", self.uid);
+        eprintln!("import_synonyms_csv: {}", url);
+
+        let mut app = test::init_service(meilisearch_http::create_app(&self.data).wrap(NormalizePath)).await;
+
+        let req = test::TestRequest::post()
+            .uri(&url)
+            .header("Content-Type", "text/csv")
+            .set_payload(csv.to_string())
+            .to_request();
+        let res = test::call_service(&mut app, req).await;
+        let status_code = res.status().clone();
+
+        let body = test::read_body(res).await;
+        let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+        if let Some(update_id) = response["updateId"].as_u64() {
+            self.wait_update_id(update_id).await;
+        }
+        (response, status_code)
+    }
+
     pub async fn delete_synonyms(&mut self) -> (Value, StatusCode) {
         let url = format!("/indexes/{}/settings/synonyms", self.uid);
         self.delete_request_async(&url).await
@@ -448,6 +605,11 @@ impl Server {
         self.get_request(&url).await
     }
 
+    pub async fn parse_query_debug(&mut self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/debug/parse-query", self.uid);
+        self.post_request(&url, body).await
+    }
+
     pub async fn list_keys(&mut self) -> (Value, StatusCode) {
         self.get_request("/keys").await
     }
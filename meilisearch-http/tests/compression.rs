@@ -0,0 +1,26 @@
+use serde_json::json;
+
+mod common;
+
+#[actix_rt::test]
+async fn only_the_responses_larger_than_the_threshold_are_compressed() {
+    let mut server = common::Server::with_uid_and_options("test", |opt| opt.compress_response_threshold = 100);
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+
+    let documents: Vec<_> = (0..20).map(|id| json!({ "id": id, "title": "a pair of red shoes" })).collect();
+    server.add_or_replace_multiple_documents(json!(documents)).await;
+
+    let accept_gzip = [("Accept-Encoding", "gzip")];
+
+    let (_response, status_code, headers) = server.get_request_with_headers("/indexes/test/documents", &accept_gzip).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(headers.get("Content-Encoding").unwrap(), "gzip");
+
+    let (_response, _status_code, headers) = server.get_request_with_headers("/health", &accept_gzip).await;
+    assert!(headers.get("Content-Encoding").is_none());
+
+    // the clients that do not accept a compressed response get the plain one
+    let (response, _status_code, headers) = server.get_request_with_headers("/indexes/test/documents", &[]).await;
+    assert!(headers.get("Content-Encoding").is_none());
+    assert_eq!(response.as_array().unwrap().len(), 20);
+}
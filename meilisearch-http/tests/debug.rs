@@ -0,0 +1,174 @@
+use assert_json_diff::assert_json_eq;
+use serde_json::json;
+
+mod common;
+
+#[actix_rt::test]
+async fn parse_query_returns_tokens_and_filters() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let body = json!({
+        "q": "Captain Marvel",
+        "filters": "popularity > 10 AND NOT title = Escape",
+    });
+
+    let (response, status_code) = server.parse_query_debug(body).await;
+    assert_eq!(status_code, 200);
+
+    let expected_tokens = json!([
+        {
+            "original": "Captain",
+            "word": "captain",
+            "kind": "word",
+            "wordIndex": 0,
+            "charIndex": 0,
+            "modified": true,
+        },
+        {
+            "original": "Marvel",
+            "word": "marvel",
+            "kind": "word",
+            "wordIndex": 1,
+            "charIndex": 8,
+            "modified": true,
+        },
+    ]);
+    assert_json_eq!(expected_tokens, response["query"]["tokens"].clone(), ordered: true);
+
+    let expected_filters = json!({
+        "and": [
+            { "condition": { "attribute": "popularity", "operator": ">", "value": "10" } },
+            { "not": { "condition": { "attribute": "title", "operator": "=", "value": "Escape" } } },
+        ]
+    });
+    assert_json_eq!(expected_filters, response["filters"].clone(), ordered: true);
+}
+
+#[actix_rt::test]
+async fn parse_query_with_invalid_filters() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let body = json!({
+        "q": "Captain",
+        "filters": "popularity >",
+    });
+
+    let (_response, status_code) = server.parse_query_debug(body).await;
+    assert_eq!(status_code, 400);
+}
+
+#[actix_rt::test]
+async fn analytics_describes_the_collected_data() {
+    let mut server = common::Server::with_uid("movies");
+
+    let (response, status_code) = server.get_request("/debug/analytics").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["enabled"], false);
+
+    let fields: Vec<_> = response["collectedData"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["name"].as_str().unwrap())
+        .collect();
+    assert!(fields.contains(&"event_properties.number_of_documents"));
+    assert!(fields.contains(&"user_properties.env"));
+}
+
+#[actix_rt::test]
+async fn inverted_index_stats_of_a_field() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let url = "/indexes/movies/diagnostic/inverted-index-stats?field=title";
+    let (response, status_code) = server.get_request(url).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["field"], "title");
+    assert_eq!(response["partial"], false);
+
+    let unique_terms = response["uniqueTerms"].as_u64().unwrap();
+    let total_postings = response["totalPostings"].as_u64().unwrap();
+    assert!(unique_terms > 0);
+    assert!(total_postings >= unique_terms);
+    assert!(response["maxPostingsPerTerm"]["count"].as_u64().unwrap() >= 1);
+
+    let url = "/indexes/movies/diagnostic/inverted-index-stats?field=unknown";
+    let (response, status_code) = server.get_request(url).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn schema_lists_the_fields() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let body = json!({
+        "attributesForFaceting": ["genres"],
+        "displayedAttributes": ["title", "genres"],
+    });
+    server.update_all_settings(body).await;
+
+    let (response, status_code) = server.get_request("/indexes/movies/debug/schema").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["primaryKey"], "id");
+
+    let fields = response["fields"].as_array().unwrap();
+    let field = |name: &str| fields.iter().find(|field| field["name"] == name).unwrap().clone();
+
+    assert_eq!(field("genres")["filterable"], true);
+    assert_eq!(field("genres")["displayed"], true);
+    assert_eq!(field("title")["filterable"], false);
+    assert_eq!(field("title")["searchable"], true);
+    assert_eq!(field("overview")["displayed"], false);
+    assert_eq!(field("popularity")["sortable"], true);
+}
+
+#[actix_rt::test]
+async fn posting_list_of_a_term() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+
+    let body = json!([
+        { "id": "doc-1", "title": "red shoe and blue shoe", "description": "a shoe" },
+        { "id": "doc-2", "title": "boots" },
+        { "id": "doc-3", "title": "Shoe" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let (response, status_code) = server.get_request("/indexes/test/debug/posting-list?term=Shoe").await;
+    assert_eq!(status_code, 200, "{}", response);
+
+    let expected = json!({
+        "term": "shoe",
+        "documentCount": 2,
+        "documents": [
+            {
+                "id": "doc-1",
+                "fields": [
+                    { "name": "title", "positions": [1, 4] },
+                    { "name": "description", "positions": [1] },
+                ],
+            },
+            { "id": "doc-3", "fields": [{ "name": "title", "positions": [0] }] },
+        ],
+        "nextCursor": null,
+    });
+    assert_eq!(response, expected);
+
+    // the pages follow each other with the cursor
+    let (response, _status_code) = server.get_request("/indexes/test/debug/posting-list?term=shoe&limit=1").await;
+    assert_eq!(response["documents"][0]["id"], "doc-1");
+    let cursor = response["nextCursor"].as_u64().unwrap();
+
+    let url = format!("/indexes/test/debug/posting-list?term=shoe&limit=1&cursor={}", cursor);
+    let (response, _status_code) = server.get_request(&url).await;
+    assert_eq!(response["documents"], json!([{ "id": "doc-3", "fields": [{ "name": "title", "positions": [0] }] }]));
+    assert_eq!(response["nextCursor"], json!(null));
+
+    let (response, _status_code) = server.get_request("/indexes/test/debug/posting-list?term=sandal").await;
+    assert_eq!(response["documentCount"], 0);
+    assert_eq!(response["documents"], json!([]));
+}
@@ -1,7 +1,26 @@
-use serde_json::json;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::time::delay_for;
 
 mod common;
 
+/// Adds the documents to the authors index and waits for them to be processed,
+/// the server waits for the updates of the books index.
+async fn add_authors(server: &mut common::Server, authors: Value) {
+    let (response, status_code) = server.post_request("/indexes/authors/documents", authors).await;
+    assert_eq!(status_code, 202);
+
+    let url = format!("/indexes/authors/updates/{}", response["updateId"]);
+    loop {
+        let (response, _status_code) = server.get_request(&url).await;
+        if response["status"] == "processed" {
+            return;
+        }
+        delay_for(Duration::from_millis(100)).await;
+    }
+}
+
 // Test issue https://github.com/meilisearch/MeiliSearch/issues/519
 #[actix_rt::test]
 async fn check_add_documents_with_primary_key_param() {
@@ -175,3 +194,684 @@ async fn check_add_documents_with_nested_sequence() {
     assert_eq!(status_code, 200);
     assert_eq!(response["hits"], body);
 }
+
+#[actix_rt::test]
+async fn check_add_documents_dry_run() {
+    let mut server = common::Server::with_uid("movies");
+
+    let body = json!({
+        "uid": "movies",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    // 1 - Validate documents without persisting them
+
+    let body = json!([
+        { "id": 1, "title": "Carol" },
+        { "title": "Missing identifier" },
+        { "id": { "invalid": true }, "title": "Invalid identifier" },
+    ]);
+
+    let (response, status_code) = server.add_or_replace_multiple_documents_dry_run(body).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["processedDocuments"], 3);
+
+    let errors = response["errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0]["documentPosition"], 1);
+    assert_eq!(errors[1]["documentPosition"], 2);
+
+    // 2 - Check that nothing has been written
+
+    let (response, status_code) = server.get_index_stats().await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["numberOfDocuments"], 0);
+
+    let (response, status_code) = server.get_all_updates_status().await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response.as_array().unwrap().len(), 0);
+}
+
+#[actix_rt::test]
+async fn documents_addition_exceeding_the_max_index_size() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "maxIndexSize": 1000,
+    });
+    server.update_all_settings(body).await;
+
+    // 1 - A small document fits in the index
+
+    let (response, status_code) = server
+        .add_or_replace_multiple_documents_sync(json!([{ "id": 1, "title": "small" }]))
+        .await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert_eq!(response["status"], "processed");
+
+    // 2 - A document that would make the index too big is refused
+
+    let (response, status_code) = server
+        .add_or_replace_multiple_documents_sync(json!([{ "id": 2, "title": "big ".repeat(500) }]))
+        .await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["errorCode"], "index_size_limit_exceeded");
+
+    // nothing the failed update indexed is kept
+    let (_response, status_code) = server.get_document(2).await;
+    assert_eq!(status_code, 404);
+    let (response, _status_code) = server.get_document(1).await;
+    assert_eq!(response, json!({ "id": 1, "title": "small" }));
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["maxIndexSize"], json!(1000));
+}
+
+#[actix_rt::test]
+async fn documents_batch_is_committed_as_a_single_update() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies" })).await;
+
+    let (response, status_code) = server.post_request("/indexes/movies/batch/start", json!(null)).await;
+    assert_eq!(status_code, 201);
+    let batch_id = response["batchId"].as_str().unwrap().to_string();
+
+    let url = format!("/indexes/movies/batch/{}/documents", batch_id);
+    let (response, status_code) = server.post_request(&url, json!([{ "id": 1, "title": "Carol" }])).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["documentsCount"], 1);
+
+    let (response, _status_code) = server.post_request(&url, json!([{ "id": 2, "title": "Wonder Woman" }])).await;
+    assert_eq!(response["documentsCount"], 2);
+
+    // nothing is indexed until the batch is committed
+    let (response, _status_code) = server.get_all_documents().await;
+    assert_eq!(response.as_array().unwrap().len(), 0);
+
+    let url = format!("/indexes/movies/batch/{}/commit", batch_id);
+    let (response, status_code) = server.post_request(&url, json!(null)).await;
+    assert_eq!(status_code, 202);
+    server.wait_update_id(response["updateId"].as_u64().unwrap()).await;
+
+    let (response, _status_code) = server.get_all_documents().await;
+    assert_eq!(response.as_array().unwrap().len(), 2);
+
+    // the batch does not exist anymore once committed
+    let (_response, status_code) = server.post_request(&url, json!(null)).await;
+    assert_eq!(status_code, 404);
+}
+
+#[actix_rt::test]
+async fn documents_batch_rollback() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies" })).await;
+
+    let (response, _status_code) = server.post_request("/indexes/movies/batch/start", json!(null)).await;
+    let batch_id = response["batchId"].as_str().unwrap().to_string();
+
+    let url = format!("/indexes/movies/batch/{}/documents", batch_id);
+    server.post_request(&url, json!([{ "id": 1, "title": "Carol" }])).await;
+
+    let url = format!("/indexes/movies/batch/{}/rollback", batch_id);
+    let (_response, status_code) = server.post_request(&url, json!(null)).await;
+    assert_eq!(status_code, 204);
+
+    let url = format!("/indexes/movies/batch/{}/commit", batch_id);
+    let (response, status_code) = server.post_request(&url, json!(null)).await;
+    assert_eq!(status_code, 404);
+    assert_eq!(response["errorCode"], "not_found");
+}
+
+#[actix_rt::test]
+async fn documents_are_transformed_before_being_stored() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies", "primaryKey": "id" })).await;
+
+    let body = json!({ "transformer": r#"doc.title = doc.title.to_lower(); doc.site = domain(doc.url); doc.remove("notes");"# });
+    server.update_all_settings(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "Carol", "url": "https://www.example.com/carol", "notes": "internal" },
+        { "id": 2, "title": "Wonder Woman", "url": 42, "notes": "internal" },
+    ]);
+    let (response, status_code) = server.add_or_replace_multiple_documents_sync(body).await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert_eq!(response["status"], "processed");
+    let warnings = response["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].as_str().unwrap().starts_with("document `2`"));
+
+    let (response, _status_code) = server.get_document(1).await;
+    assert_eq!(response, json!({ "id": 1, "title": "carol", "url": "https://www.example.com/carol", "site": "example.com" }));
+
+    // the document the transformer failed on is stored as it is
+    let (response, _status_code) = server.get_document(2).await;
+    assert_eq!(response["title"], "Wonder Woman");
+    assert_eq!(response["notes"], "internal");
+
+    let url = "/indexes/movies/documents/transform";
+    let (response, status_code) = server.post_request(url, json!([{ "id": 3, "title": "Joker", "url": "http://joker.com" }])).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["documents"], json!([{ "id": 3, "title": "joker", "url": "http://joker.com", "site": "joker.com" }]));
+    assert_eq!(response["warnings"], json!([]));
+
+    let url = "/indexes/movies/settings";
+    let (response, status_code) = server.post_request(url, json!({ "transformer": "doc.title = reverse(" })).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+/// Serves the body once over HTTP and returns the URL of the file, the second half
+/// of the body is only sent once the receiver is unblocked.
+fn serve_file_once(body: &'static str, blocked: std::sync::mpsc::Receiver<()>) -> String {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/movies.ndjson", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0; 1024];
+        let _ = stream.read(&mut request).unwrap();
+        let (first, second) = body.split_at(body.len() / 2);
+        let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), first);
+        stream.write_all(response.as_bytes()).unwrap();
+        stream.flush().unwrap();
+        let _ = blocked.recv();
+        stream.write_all(second.as_bytes()).unwrap();
+    });
+    url
+}
+
+#[actix_rt::test]
+async fn documents_are_imported_from_a_url() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies" })).await;
+
+    // the files cannot be imported from the private addresses by default
+    let body = json!({ "url": "http://127.0.0.1:7700/movies.ndjson", "format": "ndjson" });
+    let (response, status_code) = server.post_request("/indexes/movies/documents/import", body).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+
+    let mut server = common::Server::with_uid_and_options("movies", |opt| opt.allow_private_import_urls = true);
+    server.create_index(json!({ "uid": "movies" })).await;
+
+    let file = "{ \"id\": 1, \"title\": \"Carol\" }\n\n{ \"id\": 2, \"title\": \"Wonder Woman\" }\n";
+    let (unblock, blocked) = std::sync::mpsc::channel();
+    let url = serve_file_once(file, blocked);
+    let (response, status_code) = server.post_request("/indexes/movies/documents/import", json!({ "url": url, "format": "ndjson" })).await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+
+    // the progress of the download is in the details of the update
+    let details = loop {
+        let (response, status_code) = server.get_update_status(update_id).await;
+        assert_eq!(status_code, 200);
+        assert_eq!(response["status"], "enqueued", "{:#?}", response);
+        assert_eq!(response["type"]["name"], "DocumentsImport");
+        if response["details"]["downloadedBytes"].as_u64().map_or(false, |bytes| bytes > 0) {
+            break response["details"].clone();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    };
+    assert_eq!(details["totalBytes"], file.len());
+    unblock.send(()).unwrap();
+
+    server.wait_update_id(update_id).await;
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert_eq!(response["status"], "processed", "{:#?}", response);
+    assert_eq!(response["type"], json!({ "name": "DocumentsImport", "number": 2 }));
+
+    let (response, _status_code) = server.get_all_documents().await;
+    assert_eq!(response.as_array().unwrap().len(), 2);
+
+    // the files that cannot be downloaded fail their update
+    let body = json!({ "url": "http://127.0.0.1:1/movies.ndjson", "format": "ndjson" });
+    let (response, status_code) = server.post_request("/indexes/movies/documents/import", body).await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["errorCode"], "import_failed");
+
+    let body = json!({ "url": "ftp://example.com/movies.ndjson", "format": "ndjson" });
+    let (response, status_code) = server.post_request("/indexes/movies/documents/import", body).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn invalid_documents_follow_the_document_error_strategy() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies", "primaryKey": "id" })).await;
+
+    let documents = json!([
+        { "id": 1, "title": "Carol" },
+        { "id": "not valid!", "title": "Wonder Woman" },
+        { "id": 3, "title": "Mad Max" },
+    ]);
+
+    // 1 - abort, the default, fails at the first invalid document
+
+    let (response, status_code) = server.post_request("/indexes/movies/documents", documents.clone()).await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert_eq!(response["status"], "failed");
+
+    // 2 - fail checks all the documents before failing
+
+    let (response, status_code) = server
+        .post_request("/indexes/movies/documents?documentErrorStrategy=fail", documents.clone())
+        .await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert_eq!(response["status"], "failed");
+    assert_eq!(response["errorCode"], "invalid_document");
+
+    let (response, _status_code) = server.get_all_documents().await;
+    assert_eq!(response.as_array().unwrap().len(), 0);
+
+    // 3 - skip adds the valid documents and reports the other ones
+
+    let (response, status_code) = server
+        .post_request("/indexes/movies/documents?documentErrorStrategy=skip", documents)
+        .await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert_eq!(response["status"], "processed");
+    assert_eq!(response["skippedDocuments"].as_array().unwrap().len(), 1);
+    assert_eq!(response["skippedDocuments"][0]["id"], "not valid!");
+
+    let (response, _status_code) = server.get_all_documents().await;
+    assert_eq!(response.as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn skipped_documents_are_capped_and_counted() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies", "primaryKey": "id" })).await;
+
+    let documents: Vec<_> = (0..150).map(|i| json!({ "id": format!("not valid {}", i) })).collect();
+
+    let (response, status_code) = server
+        .post_request("/indexes/movies/documents?documentErrorStrategy=skip", json!(documents))
+        .await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert_eq!(response["status"], "processed");
+    assert_eq!(response["skippedDocuments"].as_array().unwrap().len(), 100);
+    assert_eq!(response["skippedDocumentsCount"], 150);
+
+    let (response, status_code) = server
+        .post_request("/indexes/movies/documents?documentErrorStrategy=fail", json!(documents))
+        .await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert_eq!(response["status"], "failed");
+    let message = response["error"].as_str().unwrap();
+    assert!(message.starts_with("150 documents are invalid;"));
+    assert!(message.ends_with("and 50 other documents"));
+}
+
+#[actix_rt::test]
+async fn documents_are_upserted_with_the_update_strategy() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies", "primaryKey": "id" })).await;
+    server
+        .add_or_replace_multiple_documents(json!([{ "id": 1, "title": "Carol", "genre": "drama" }]))
+        .await;
+
+    // 1 - merge keeps the fields the new document does not have
+
+    let body = json!({
+        "updateStrategy": "merge",
+        "documents": [{ "id": 1, "title": "Carol (2015)" }, { "id": 2, "title": "Mad Max" }],
+    });
+    let (response, status_code) = server.post_request("/indexes/movies/documents/upsert", body).await;
+    assert_eq!(status_code, 202);
+    server.wait_update_id(response["updateId"].as_u64().unwrap()).await;
+
+    let (response, _status_code) = server.get_document(1).await;
+    assert_eq!(response, json!({ "id": 1, "title": "Carol (2015)", "genre": "drama" }));
+    let (response, _status_code) = server.get_document(2).await;
+    assert_eq!(response, json!({ "id": 2, "title": "Mad Max" }));
+
+    // 2 - replace, the default, drops them
+
+    let body = json!({ "documents": [{ "id": 1, "title": "Carol" }] });
+    let (response, status_code) = server.post_request("/indexes/movies/documents/upsert", body).await;
+    assert_eq!(status_code, 202);
+    server.wait_update_id(response["updateId"].as_u64().unwrap()).await;
+
+    let (response, _status_code) = server.get_document(1).await;
+    assert_eq!(response, json!({ "id": 1, "title": "Carol" }));
+
+    let body = json!({ "updateStrategy": "patch", "documents": [] });
+    let (_response, status_code) = server.post_request("/indexes/movies/documents/upsert", body).await;
+    assert_eq!(status_code, 400);
+}
+
+#[actix_rt::test]
+async fn documents_are_counted() {
+    let mut server = common::Server::with_uid("products");
+    server.create_index(json!({ "uid": "products", "primaryKey": "id" })).await;
+
+    let body = json!([
+        { "id": 1, "category": "shoes" },
+        { "id": 2, "category": "shirts" },
+        { "id": 3, "category": "shoes" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let (response, status_code) = server.get_request("/indexes/products/documents/count").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response, json!({ "count": 3 }));
+
+    let (response, status_code) = server.get_request("/indexes/products/documents/count?filter=category%3Dshoes").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response, json!({ "count": 2 }));
+
+    let (_response, status_code) = server.get_request("/indexes/products/documents/count?filter=category%3D").await;
+    assert_eq!(status_code, 400);
+
+    // the equalities on the attributes for faceting are resolved by the facet index
+    server.update_all_settings(json!({ "attributesForFaceting": ["category"] })).await;
+
+    let (response, _status_code) = server.get_request("/indexes/products/documents/count?filter=category%3DShoes").await;
+    assert_eq!(response, json!({ "count": 2 }));
+
+    let filter = "category%3Dshoes%20AND%20id%20%3E%201";
+    let (response, _status_code) = server.get_request(&format!("/indexes/products/documents/count?filter={}", filter)).await;
+    assert_eq!(response, json!({ "count": 1 }));
+
+    let filter = "category%3Dshoes%20OR%20category%3Dshirts";
+    let (response, _status_code) = server.get_request(&format!("/indexes/products/documents/count?filter={}", filter)).await;
+    assert_eq!(response, json!({ "count": 3 }));
+
+    let filter = "category%3Dshoes%20OR%20id%3D2";
+    let (response, _status_code) = server.get_request(&format!("/indexes/products/documents/count?filter={}", filter)).await;
+    assert_eq!(response, json!({ "count": 3 }));
+}
+
+#[actix_rt::test]
+async fn documents_are_browsed_with_a_filter() {
+    let mut server = common::Server::with_uid("tasks");
+    server.create_index(json!({ "uid": "tasks", "primaryKey": "id" })).await;
+
+    let body = json!([
+        { "id": 1, "status": "pending" },
+        { "id": 2, "status": "done" },
+        { "id": 3, "status": "pending" },
+        { "id": 4, "status": "pending" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let (response, status_code) = server.get_request("/indexes/tasks/documents?filter=status%3Dpending").await;
+    assert_eq!(status_code, 200);
+    let ids: Vec<_> = response.as_array().unwrap().iter().map(|document| document["id"].clone()).collect();
+    assert_eq!(ids, vec![json!(1), json!(3), json!(4)]);
+
+    let (response, status_code) = server
+        .get_request("/indexes/tasks/documents?filter=status%3Dpending&offset=1&limit=1")
+        .await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response, json!([{ "id": 3, "status": "pending" }]));
+
+    // the documents found in the facet index are returned in the same order
+    server.update_all_settings(json!({ "attributesForFaceting": ["status"] })).await;
+
+    let (response, _status_code) = server
+        .get_request("/indexes/tasks/documents?filter=status%3Dpending&offset=1&limit=1")
+        .await;
+    assert_eq!(response, json!([{ "id": 3, "status": "pending" }]));
+}
+
+#[actix_rt::test]
+async fn a_single_document_is_replaced_by_its_id() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies", "primaryKey": "id" })).await;
+    server.add_or_replace_multiple_documents(json!([{ "id": 1, "title": "Carol", "year": 2015 }])).await;
+
+    server
+        .put_request_async("/indexes/movies/documents/1", json!({ "id": 1, "title": "Carol" }))
+        .await;
+
+    let (response, status_code) = server.get_document(1).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response, json!({ "id": 1, "title": "Carol" }));
+
+    // the primary key of the document must match the id of the route
+    let (_, status_code) = server
+        .put_request("/indexes/movies/documents/1", json!({ "id": 2, "title": "Wonder Woman" }))
+        .await;
+    assert_eq!(status_code, 400);
+
+    let (_, status_code) = server
+        .put_request("/indexes/movies/documents/2?upsert=false", json!({ "id": 2, "title": "Wonder Woman" }))
+        .await;
+    assert_eq!(status_code, 404);
+}
+
+#[actix_rt::test]
+async fn a_single_document_is_patched_by_its_id() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies", "primaryKey": "id" })).await;
+    let body = json!([{
+        "id": 1,
+        "title": "Carol",
+        "year": 2015,
+        "director": { "name": "Todd Haynes", "born": 1961 },
+    }]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    server
+        .patch_request_async(
+            "/indexes/movies/documents/1",
+            json!({ "title": "Carol (2015)", "year": null, "director": { "born": null } }),
+        )
+        .await;
+
+    let (response, status_code) = server.get_document(1).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response, json!({ "id": 1, "title": "Carol (2015)", "director": { "name": "Todd Haynes" } }));
+
+    server
+        .patch_request_async("/indexes/movies/documents/1?conflictsOnNull=preserve", json!({ "title": null }))
+        .await;
+
+    let (response, status_code) = server.get_document(1).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response, json!({ "id": 1, "title": null, "director": { "name": "Todd Haynes" } }));
+
+    // the patch is applied to the document as the updates enqueued before it left it
+    server.put_request("/indexes/movies/documents", json!([{ "id": 1, "year": 2016 }])).await;
+    let (response, _status_code) = server
+        .patch_request_async("/indexes/movies/documents/1", json!({ "title": "Carol" }))
+        .await;
+
+    let (status, _status_code) = server.get_update_status(response["updateId"].as_u64().unwrap()).await;
+    assert_eq!(status["status"], json!("processed"), "{}", status);
+    assert_eq!(status["type"], json!({ "name": "DocumentPatch" }));
+
+    let (response, _status_code) = server.get_document(1).await;
+    assert_eq!(response, json!({ "id": 1, "title": "Carol", "year": 2016, "director": { "name": "Todd Haynes" } }));
+
+    // the primary key cannot be removed nor changed
+    let (_, status_code) = server.patch_request("/indexes/movies/documents/1", json!({ "id": 2 })).await;
+    assert_eq!(status_code, 400);
+
+    let (_, status_code) = server.patch_request("/indexes/movies/documents/1", json!([{ "id": 1 }])).await;
+    assert_eq!(status_code, 400);
+
+    let (_, status_code) = server.patch_request("/indexes/movies/documents/2", json!({ "title": "Wonder Woman" })).await;
+    assert_eq!(status_code, 404);
+}
+
+#[actix_rt::test]
+async fn replaced_documents_are_versioned() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies", "primaryKey": "id" })).await;
+    server.update_all_settings(json!({ "documentVersions": 2 })).await;
+
+    for title in &["Carol", "Carol (2015)", "Carol, by Todd Haynes", "Carol, by Todd Haynes (2015)"] {
+        server.add_or_replace_multiple_documents(json!([{ "id": 1, "title": title }])).await;
+    }
+
+    // only the two newest of the three previous versions are kept
+    let (response, status_code) = server.get_request("/indexes/movies/documents/1/versions").await;
+    assert_eq!(status_code, 200);
+    let versions = response.as_array().unwrap();
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions[0]["version"], json!(2));
+    assert_eq!(versions[0]["document"], json!({ "id": 1, "title": "Carol (2015)" }));
+    assert_eq!(versions[1]["version"], json!(3));
+    assert!(versions[1]["archivedAt"].is_string());
+
+    let (_, status_code) = server.get_request("/indexes/movies/documents/1/versions/1").await;
+    assert_eq!(status_code, 404);
+
+    let (response, status_code) = server.get_request("/indexes/movies/documents/1/versions/3").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["document"], json!({ "id": 1, "title": "Carol, by Todd Haynes" }));
+
+    let (response, status_code) = server.post_request("/indexes/movies/documents/1/restore/2", json!(null)).await;
+    assert_eq!(status_code, 202);
+    server.wait_update_id(response["updateId"].as_u64().unwrap()).await;
+
+    let (response, _) = server.get_document(1).await;
+    assert_eq!(response, json!({ "id": 1, "title": "Carol (2015)" }));
+
+    // the restored version replaced the newest one, which is archived in turn
+    let (response, _) = server.get_request("/indexes/movies/documents/1/versions").await;
+    let versions: Vec<_> = response.as_array().unwrap().iter().map(|version| version["version"].clone()).collect();
+    assert_eq!(versions, vec![json!(3), json!(4)]);
+}
+
+#[actix_rt::test]
+async fn referenced_documents_are_injected() {
+    let mut server = common::Server::with_uid("books");
+    server.create_index(json!({ "uid": "authors", "primaryKey": "id" })).await;
+    server.create_index(json!({ "uid": "books", "primaryKey": "id" })).await;
+
+    let authors = json!([
+        { "id": 1, "name": "Ursula K. Le Guin", "photo": "leguin.jpg" },
+        { "id": 2, "name": "Terry Pratchett", "photo": "pratchett.jpg" },
+    ]);
+    add_authors(&mut server, authors).await;
+
+    server.update_all_settings(json!({
+        "referencedDocuments": [{
+            "field": "authorId",
+            "foreignIndex": "authors",
+            "foreignKey": "id",
+            "fieldsToInject": ["author.name", "author.photo"],
+        }],
+    })).await;
+
+    let books = json!([
+        { "id": 1, "title": "The Dispossessed", "authorId": 1 },
+        { "id": 2, "title": "Good Omens", "authorId": [2, 3] },
+    ]);
+    server.add_or_replace_multiple_documents(books).await;
+
+    let (response, _) = server.get_document(1).await;
+    assert_eq!(response["author"], json!({ "name": "Ursula K. Le Guin", "photo": "leguin.jpg" }));
+
+    // the unknown references are injected as null
+    let (response, _) = server.get_document(2).await;
+    assert_eq!(response["author"], json!({ "name": ["Terry Pratchett", null], "photo": ["pratchett.jpg", null] }));
+
+    // the injected fields are searchable
+    let (response, _) = server.search("q=guin").await;
+    assert_eq!(response["hits"][0]["id"], json!(1));
+
+    // the documents are refreshed once the referenced documents are updated
+    let author = json!([{ "id": 1, "name": "Ursula Le Guin", "photo": "leguin.jpg" }]);
+    add_authors(&mut server, author).await;
+
+    let mut name = json!(null);
+    for _ in 0..10 {
+        let (response, _) = server.get_document(1).await;
+        name = response["author"]["name"].clone();
+        if name == json!("Ursula Le Guin") {
+            break;
+        }
+        delay_for(Duration::from_secs(1)).await;
+    }
+    assert_eq!(name, json!("Ursula Le Guin"));
+
+    // the references cannot form a cycle and must use the primary key of the foreign documents
+    let reference = |foreign_index: &str, foreign_key: &str| json!({
+        "referencedDocuments": [{
+            "field": "bookId",
+            "foreignIndex": foreign_index,
+            "foreignKey": foreign_key,
+            "fieldsToInject": ["book.title"],
+        }],
+    });
+
+    let (_, status_code) = server.post_request("/indexes/authors/settings", reference("books", "id")).await;
+    assert_eq!(status_code, 400);
+    let (_, status_code) = server.post_request("/indexes/books/settings", reference("books", "id")).await;
+    assert_eq!(status_code, 400);
+    let (_, status_code) = server.post_request("/indexes/books/settings", reference("authors", "name")).await;
+    assert_eq!(status_code, 400);
+}
+
+#[actix_rt::test]
+async fn add_documents_with_idempotency_key() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+
+    let key = ("Idempotency-Key", "5b6c3a0e-4f1d-4a8e-9c2b-7d3e1f0a9b8c");
+    let body = json!([{ "id": 1, "title": "hello" }]);
+    let (first, status_code) = server.post_request_with_header("/indexes/test/documents", body.clone(), key).await;
+    assert_eq!(status_code, 202);
+
+    // the same request is not enqueued again
+    let (second, status_code) = server.post_request_with_header("/indexes/test/documents", body, key).await;
+    assert_eq!(status_code, 202);
+    assert_eq!(second["updateId"], first["updateId"]);
+
+    // the key cannot be used for a different request
+    let body = json!([{ "id": 2, "title": "world" }]);
+    let (response, status_code) = server.post_request_with_header("/indexes/test/documents", body, key).await;
+    assert_eq!(status_code, 409);
+    assert_eq!(response["errorCode"], "idempotency_key_reused");
+}
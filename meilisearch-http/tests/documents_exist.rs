@@ -0,0 +1,26 @@
+use serde_json::json;
+
+mod common;
+
+#[actix_rt::test]
+async fn existing_and_missing_documents() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+    server.add_or_replace_multiple_documents(json!([{ "id": 1, "title": "shoe" }, { "id": "a-2", "title": "boot" }])).await;
+
+    let body = json!({ "ids": ["1", "unknown", "a-2", 3] });
+    let (response, status_code) = server.post_request("/indexes/test/documents/exists", body).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response, json!({ "existing": ["1", "a-2"], "missing": ["unknown", "3"] }));
+}
+
+#[actix_rt::test]
+async fn too_many_ids_to_check() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+
+    let ids: Vec<_> = (0..10_001).map(|id| id.to_string()).collect();
+    let (response, status_code) = server.post_request("/indexes/test/documents/exists", json!({ "ids": ids })).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
@@ -0,0 +1,31 @@
+use serde_json::json;
+
+mod common;
+
+#[actix_rt::test]
+async fn get_unknown_document() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+    server.add_or_replace_multiple_documents(json!([{ "id": 1, "title": "shoe" }])).await;
+
+    let (response, status_code) = server.get_document(2).await;
+    assert_eq!(status_code, 404);
+    assert_eq!(response, json!({
+        "message": "Document 2 not found",
+        "errorCode": "document_not_found",
+        "errorType": "invalid_request_error",
+        "errorLink": "https://docs.meilisearch.com/error/document_not_found",
+    }));
+}
+
+#[actix_rt::test]
+async fn get_document_of_empty_index() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test" })).await;
+
+    let (response, status_code) = server.get_document("unknown").await;
+    assert_eq!(status_code, 404);
+    assert_eq!(response["message"], "Document unknown not found");
+    assert_eq!(response["errorCode"], "document_not_found");
+    assert_eq!(response["errorType"], "invalid_request_error");
+}
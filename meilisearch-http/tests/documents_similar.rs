@@ -0,0 +1,47 @@
+use serde_json::json;
+
+mod common;
+
+#[actix_rt::test]
+async fn similar_documents_by_jaccard_score() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "searchableAttributes": ["title"],
+        "displayedAttributes": ["id", "title"],
+    });
+    server.update_all_settings(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "the quick brown fox" },
+        { "id": 2, "title": "the quick brown dog" },
+        { "id": 3, "title": "the quick rabbit" },
+        { "id": 4, "title": "a lazy cat" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let (response, status_code) = server.get_similar_documents(1, "limit=10").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["nbHits"], json!(2));
+
+    let expected = json!([
+        { "id": 2, "title": "the quick brown dog", "_jaccardScore": 0.6 },
+        { "id": 3, "title": "the quick rabbit", "_jaccardScore": 0.4 },
+    ]);
+    assert_eq!(response["hits"], expected);
+}
+
+#[actix_rt::test]
+async fn similar_documents_of_unknown_document() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (_response, status_code) = server.get_similar_documents(1, "").await;
+    assert_eq!(status_code, 404);
+}
@@ -0,0 +1,82 @@
+use serde_json::json;
+
+mod common;
+
+#[actix_rt::test]
+async fn searches_are_split_between_the_groups() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (response, _status_code) = server.search("q=captain").await;
+    assert_eq!(response.get("_experimentGroup"), None);
+
+    let body = json!({
+        "name": "popularity",
+        "control": ["typo", "words", "proximity", "attribute", "wordsPosition", "exactness"],
+        "treatment": ["typo", "words", "desc(popularity)"],
+        "trafficSplit": 100,
+    });
+    let (response, status_code) = server.post_request("/indexes/movies/experiments", body.clone()).await;
+    assert_eq!(status_code, 201);
+    assert_eq!(response["active"], json!(true));
+
+    let (_response, status_code) = server.post_request("/indexes/movies/experiments", body).await;
+    assert_eq!(status_code, 400);
+
+    let (response, _status_code) = server.search("q=captain").await;
+    assert_eq!(response["_experimentGroup"], json!("treatment"));
+
+    let body = json!({ "queryId": response["queryId"].clone(), "documentId": 299537, "position": 2 });
+    let (_response, status_code) = server.post_request("/indexes/movies/feedback/click", body).await;
+    assert_eq!(status_code, 204);
+
+    server.search("q=xyzzyqwerty").await;
+
+    let (response, status_code) = server.get_request("/indexes/movies/experiments/popularity/results").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["control"]["searches"], json!(0));
+    assert_eq!(response["control"]["averageRank"], json!(null));
+    assert_eq!(
+        response["treatment"],
+        json!({ "searches": 2, "zeroResultRate": 0.5, "clicks": 1, "averageRank": 2.0 })
+    );
+}
+
+#[actix_rt::test]
+async fn invalid_experiments() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let body = json!({ "name": "split", "control": ["typo"], "treatment": ["typo"], "trafficSplit": 101 });
+    let (response, status_code) = server.post_request("/indexes/movies/experiments", body).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+
+    let body = json!({ "name": "split", "control": ["typo"], "treatment": ["unknown"], "trafficSplit": 50 });
+    let (_response, status_code) = server.post_request("/indexes/movies/experiments", body).await;
+    assert_eq!(status_code, 400);
+
+    let (_response, status_code) = server.get_request("/indexes/movies/experiments/split/results").await;
+    assert_eq!(status_code, 404);
+}
+
+#[actix_rt::test]
+async fn deleted_experiments_stop_splitting_the_searches() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let body = json!({ "name": "split", "control": ["typo"], "treatment": ["words"], "trafficSplit": 100 });
+    let (_response, status_code) = server.post_request("/indexes/movies/experiments", body).await;
+    assert_eq!(status_code, 201);
+
+    let (_response, status_code) = server.delete_request("/indexes/movies/experiments/split").await;
+    assert_eq!(status_code, 204);
+
+    let (response, _status_code) = server.search("q=captain").await;
+    assert_eq!(response.get("_experimentGroup"), None);
+
+    let (_response, status_code) = server.get_request("/indexes/movies/experiments/split/results").await;
+    assert_eq!(status_code, 404);
+    let (_response, status_code) = server.delete_request("/indexes/movies/experiments/split").await;
+    assert_eq!(status_code, 404);
+}
@@ -0,0 +1,58 @@
+use serde_json::json;
+
+mod common;
+
+#[actix_rt::test]
+async fn clicks_are_aggregated_by_position_and_document() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (first, _status_code) = server.search("q=captain").await;
+    let (second, _status_code) = server.search("q=avengers").await;
+    let first_id = first["queryId"].as_str().unwrap().to_string();
+    let second_id = second["queryId"].as_str().unwrap().to_string();
+    assert_ne!(first_id, second_id);
+
+    let clicks = [(&first_id, 299537, 0), (&first_id, 299536, 2), (&second_id, 299536, 0)];
+    for (query_id, document_id, position) in clicks.iter() {
+        let body = json!({ "queryId": query_id, "documentId": document_id, "position": position });
+        let (_response, status_code) = server.post_request("/indexes/movies/feedback/click", body).await;
+        assert_eq!(status_code, 204);
+    }
+
+    let (response, status_code) = server.get_request("/indexes/movies/feedback/stats").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["totalClicks"], json!(3));
+    assert_eq!(response["clickedQueries"], json!(2));
+    assert_eq!(
+        response["positions"],
+        json!([
+            { "position": 0, "clicks": 2, "ctr": 1.0 },
+            { "position": 2, "clicks": 1, "ctr": 0.5 },
+        ])
+    );
+    assert_eq!(response["documents"][0], json!({ "documentId": "299536", "clicks": 2, "ctr": 1.0 }));
+}
+
+#[actix_rt::test]
+async fn click_with_an_invalid_query_id() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let body = json!({ "queryId": "not-a-uuid", "documentId": 299537, "position": 0 });
+    let (response, status_code) = server.post_request("/indexes/movies/feedback/click", body).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn click_on_an_unknown_document() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (response, _status_code) = server.search("q=captain").await;
+    let body = json!({ "queryId": response["queryId"].clone(), "documentId": 42, "position": 0 });
+    let (response, status_code) = server.post_request("/indexes/movies/feedback/click", body).await;
+    assert_eq!(status_code, 404);
+    assert_eq!(response["errorCode"], "document_not_found");
+}
@@ -36,3 +36,43 @@ async fn test_healthyness() {
     let (_response, status_code) = server.get_health().await;
     assert_eq!(status_code, 200);
 }
+
+#[actix_rt::test]
+async fn read_only_mode() {
+    let mut server = common::Server::with_uid_and_options("movies", |opt| opt.read_only = true);
+
+    let (response, status_code) = server.get_health().await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["readOnly"], true);
+
+    // The write routes are refused
+
+    let (response, status_code) = server.create_index(json!({ "uid": "movies" })).await;
+    assert_eq!(status_code, 405);
+    assert_eq!(response["errorCode"], "read_only_mode");
+
+    let (response, status_code) = server.update_health(json!({ "health": false })).await;
+    assert_eq!(status_code, 405);
+    assert_eq!(response["errorCode"], "read_only_mode");
+
+    // The read routes keep working
+
+    let (_response, status_code) = server.list_indexes().await;
+    assert_eq!(status_code, 200);
+
+    let (response, status_code) = server.search_post(json!({ "q": "carol" })).await;
+    assert_eq!(status_code, 404);
+    assert_eq!(response["errorCode"], "index_not_found");
+
+    // the POST routes that only read are not refused either, the other ones are
+    let read_routes = ["rerank", "spellcheck", "analyze", "documents/exists", "documents/transform", "settings/preview"];
+    for route in &read_routes {
+        let url = format!("/indexes/movies/{}", route);
+        let (response, _status_code) = server.post_request(&url, json!({})).await;
+        assert_ne!(response["errorCode"], "read_only_mode", "{} is refused", route);
+    }
+
+    let (response, status_code) = server.post_request("/indexes/movies/documents", json!([])).await;
+    assert_eq!(status_code, 405);
+    assert_eq!(response["errorCode"], "read_only_mode");
+}
@@ -470,7 +470,7 @@ async fn create_index_with_invalid_uid() {
     assert_eq!(status_code, 400);
     let message = response["message"].as_str().unwrap();
     assert_eq!(response.as_object().unwrap().len(), 4);
-    assert_eq!(message, "Index must have a valid uid; Index uid can be of type integer or string only composed of alphanumeric characters, hyphens (-) and underscores (_).");
+    assert_eq!(message, "Index must have a valid uid; Index uid must be 1 to 128 characters long, only composed of alphanumeric characters, hyphens (-) and underscores (_), and cannot start with a digit.");
 
     // 2 - Create the index with invalid uid
 
@@ -483,7 +483,7 @@ async fn create_index_with_invalid_uid() {
     assert_eq!(status_code, 400);
     let message = response["message"].as_str().unwrap();
     assert_eq!(response.as_object().unwrap().len(), 4);
-    assert_eq!(message, "Index must have a valid uid; Index uid can be of type integer or string only composed of alphanumeric characters, hyphens (-) and underscores (_).");
+    assert_eq!(message, "Index must have a valid uid; Index uid must be 1 to 128 characters long, only composed of alphanumeric characters, hyphens (-) and underscores (_), and cannot start with a digit.");
 
     // 3 - Create the index with invalid uid
 
@@ -496,7 +496,7 @@ async fn create_index_with_invalid_uid() {
     assert_eq!(status_code, 400);
     let message = response["message"].as_str().unwrap();
     assert_eq!(response.as_object().unwrap().len(), 4);
-    assert_eq!(message, "Index must have a valid uid; Index uid can be of type integer or string only composed of alphanumeric characters, hyphens (-) and underscores (_).");
+    assert_eq!(message, "Index must have a valid uid; Index uid must be 1 to 128 characters long, only composed of alphanumeric characters, hyphens (-) and underscores (_), and cannot start with a digit.");
 
     // 4 - Create the index with invalid uid
 
@@ -509,7 +509,18 @@ async fn create_index_with_invalid_uid() {
     assert_eq!(status_code, 400);
     let message = response["message"].as_str().unwrap();
     assert_eq!(response.as_object().unwrap().len(), 4);
-    assert_eq!(message, "Index must have a valid uid; Index uid can be of type integer or string only composed of alphanumeric characters, hyphens (-) and underscores (_).");
+    assert_eq!(message, "Index must have a valid uid; Index uid must be 1 to 128 characters long, only composed of alphanumeric characters, hyphens (-) and underscores (_), and cannot start with a digit.");
+
+    // 5 - Create the index with a uid starting with a digit, too long or empty
+
+    for uid in &["1movies".to_string(), "a".repeat(129), String::new()] {
+        let (response, status_code) = server.create_index(json!({ "uid": uid })).await;
+        assert_eq!(status_code, 400);
+        assert_eq!(response["errorCode"], "invalid_index_uid");
+    }
+
+    let (_response, status_code) = server.create_index(json!({ "uid": "a".repeat(128) })).await;
+    assert_eq!(status_code, 201);
 }
 
 // Test that it's possible to add primary_key if it's not already set on index creation
@@ -654,10 +665,56 @@ async fn check_add_documents_without_primary_key() {
 
     let message = response["message"].as_str().unwrap();
     assert_eq!(response.as_object().unwrap().len(), 4);
-    assert_eq!(message, "Could not infer a primary key");
+    assert_eq!(
+        message,
+        "Could not infer a primary key; the candidates were the fields of the first document, [`title`, `comment`], and none of them is named `id`, `moviesId`, `pk`, `_id`, `uuid`, `key` or contains `id`"
+    );
     assert_eq!(status_code, 400);
 }
 
+#[actix_rt::test]
+async fn check_add_documents_detects_the_primary_key() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies" })).await;
+
+    // the primary key detected is not given to the index when the update fails
+    let body = json!([{ "title": "Carol", "uuid": "not a valid id" }]);
+    let (response, status_code) = server.post_request("/indexes/movies/documents", body).await;
+    assert_eq!(status_code, 202);
+    assert!(response.get("detectedPrimaryKey").is_none());
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert_eq!(response["status"], "failed");
+    assert!(response.get("detectedPrimaryKey").is_none());
+    let (response, _status_code) = server.get_index().await;
+    assert_eq!(response["primaryKey"], json!(null));
+
+    // `uuid` is tried before the fields that only contain `id`
+    let body = json!([{ "title": "Carol", "movie_ref_id": 12, "uuid": "a1" }]);
+    let (response, status_code) = server.post_request("/indexes/movies/documents", body).await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert_eq!(response["status"], "processed");
+    assert_eq!(response["detectedPrimaryKey"], "uuid");
+    let (response, _status_code) = server.get_index().await;
+    assert_eq!(response["primaryKey"], "uuid");
+
+    // the primary key is only detected once
+    let body = json!([{ "title": "Wonder Woman", "uuid": "a2" }]);
+    let (response, status_code) = server.post_request("/indexes/movies/documents", body).await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+
+    let (response, _status_code) = server.get_update_status(update_id).await;
+    assert!(response.get("detectedPrimaryKey").is_none());
+}
+
 #[actix_rt::test]
 async fn check_first_update_should_bring_up_processed_status_after_first_docs_addition() {
     let mut server = common::Server::with_uid("movies");
@@ -685,3 +742,158 @@ async fn check_first_update_should_bring_up_processed_status_after_first_docs_ad
     assert_eq!(status_code, 200);
     assert_eq!(response[0]["status"], "processed");
 }
+
+#[actix_rt::test]
+async fn warm_index() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (_response, status_code) = server.post_request("/indexes/movies/warm", json!(null)).await;
+    assert_eq!(status_code, 202);
+
+    let url = "/indexes/movies/warm?strategy=frequent-terms&limit=10";
+    let (_response, status_code) = server.post_request(url, json!(null)).await;
+    assert_eq!(status_code, 202);
+
+    let url = "/indexes/movies/warm?strategy=unknown";
+    let (response, status_code) = server.post_request(url, json!(null)).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+
+    let (_response, status_code) = server.post_request("/indexes/unknown/warm", json!(null)).await;
+    assert_eq!(status_code, 404);
+}
+
+#[actix_rt::test]
+async fn paginate_updates_status() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (response, status_code) = server.get_request("/indexes/movies/updates?limit=1").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["updateId"], 0);
+    assert_eq!(response["total"], 2);
+    assert_eq!(response["limit"], 1);
+    assert_eq!(response["from"], 0);
+    assert_eq!(response["next"], 1);
+
+    let (response, status_code) = server.get_request("/indexes/movies/updates?from=1&limit=1").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["results"][0]["updateId"], 1);
+    assert_eq!(response["next"], json!(null));
+
+    let (response, status_code) = server.get_request("/indexes/movies/updates?from=1").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["limit"], 20);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+
+    let (response, status_code) = server.get_request("/indexes/movies/updates?limit=0").await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn index_tasks_are_filtered() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (response, status_code) = server.get_request("/indexes/movies/tasks").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+    assert_eq!(response["limit"], 20);
+
+    let (response, status_code) = server.get_request("/indexes/movies/tasks?type=DocumentsAddition").await;
+    assert_eq!(status_code, 200);
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["type"]["name"], "DocumentsAddition");
+
+    let (response, _status_code) = server.get_request("/indexes/movies/tasks?status=failed").await;
+    assert_eq!(response["results"].as_array().unwrap().len(), 0);
+
+    let (response, _status_code) = server.get_request("/indexes/movies/updates?status=processed&limit=1").await;
+    assert_eq!(response["results"][0]["updateId"], 0);
+    assert_eq!(response["next"], 1);
+
+    let (_response, status_code) = server.get_request("/indexes/unknown/tasks").await;
+    assert_eq!(status_code, 404);
+}
+
+#[actix_rt::test]
+async fn analyze_a_sample_of_documents() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies" })).await;
+
+    let body = json!([
+        { "id": 1, "price": 0, "title": "Carol", "tags": null },
+        { "id": 2, "price": 999, "title": "Mad Max" },
+        { "id": 3, "price": 10.5, "title": "Carol", "tags": ["drama"] },
+        { "id": 4, "price": "free" },
+    ]);
+    let (response, status_code) = server.post_request("/indexes/movies/analyze", body).await;
+    assert_eq!(status_code, 200);
+
+    let expected = json!({
+        "fields": [
+            { "name": "id", "type": "number", "min": 1, "max": 4, "null%": 0.0, "unique": 4 },
+            { "name": "price", "type": "mixed", "min": 0, "max": 999, "null%": 0.0, "unique": 4 },
+            { "name": "title", "type": "string", "null%": 25.0, "unique": 2 },
+            { "name": "tags", "type": "array", "null%": 75.0, "unique": 1 },
+        ]
+    });
+    assert_eq!(response, expected);
+
+    // the documents are not stored
+    let (response, _status_code) = server.get_all_documents().await;
+    assert_eq!(response, json!([]));
+
+    let body = json!(vec![json!({ "id": 1 }); 1001]);
+    let (response, status_code) = server.post_request("/indexes/movies/analyze", body).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn index_keeps_its_last_successful_indexing_date() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies", "primaryKey": "id" })).await;
+
+    let (response, _status_code) = server.get_index().await;
+    assert!(response.get("lastSuccessfulIndexingAt").is_none());
+
+    server.add_or_replace_multiple_documents(json!([{ "id": 1, "title": "Carol" }])).await;
+
+    let (response, _status_code) = server.get_index().await;
+    let indexed_at = response["lastSuccessfulIndexingAt"].clone();
+    assert!(indexed_at.is_string());
+
+    // deleting documents is not indexing new data
+    server.delete_request_async("/indexes/movies/documents/1").await;
+
+    let (response, _status_code) = server.get_index().await;
+    assert_eq!(response["lastSuccessfulIndexingAt"], indexed_at);
+}
+
+#[actix_rt::test]
+async fn status_shows_the_updates_queue_of_the_indexes() {
+    let mut server = common::Server::with_uid("movies");
+    server.create_index(json!({ "uid": "movies", "primaryKey": "id" })).await;
+    server.create_index(json!({ "uid": "books", "primaryKey": "id" })).await;
+
+    server.add_or_replace_multiple_documents(json!([{ "id": 1, "title": "Carol" }])).await;
+
+    let (response, status_code) = server.get_request("/status").await;
+    assert_eq!(status_code, 200);
+
+    let indexes = response["indexes"].as_array().unwrap();
+    assert_eq!(indexes.len(), 2);
+    assert_eq!(indexes[0]["uid"], "books");
+    assert_eq!(indexes[0]["pendingTasks"], 0);
+    assert_eq!(indexes[0]["status"], "idle");
+    assert_eq!(indexes[0]["lastTaskCompletedAt"], json!(null));
+    assert_eq!(indexes[1]["uid"], "movies");
+    assert_eq!(indexes[1]["pendingTasks"], 0);
+    assert_eq!(indexes[1]["status"], "idle");
+    assert!(indexes[1]["lastTaskCompletedAt"].is_string());
+}
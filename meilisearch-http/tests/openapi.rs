@@ -0,0 +1,19 @@
+mod common;
+
+#[actix_rt::test]
+async fn openapi_spec_is_served() {
+    let mut server = common::Server::with_uid("movies");
+
+    let (response, status_code) = server.get_request("/openapi.json").await;
+    assert_eq!(status_code, 200);
+    assert!(response["openapi"].as_str().unwrap().starts_with("3.1"));
+
+    let paths = response["paths"].as_object().unwrap();
+    assert!(paths.contains_key("/indexes"));
+    assert!(paths.contains_key("/indexes/{index_uid}/search"));
+    assert!(paths.contains_key("/indexes/{index_uid}/settings"));
+    assert!(response["components"]["schemas"]["ErrorResponseBody"].is_object());
+
+    let (_response, status_code) = server.get_request("/docs").await;
+    assert_eq!(status_code, 200);
+}
@@ -0,0 +1,25 @@
+use serde_json::json;
+
+mod common;
+
+#[actix_rt::test]
+async fn top_and_zero_results_queries() {
+    let mut server = common::Server::with_uid_and_options("movies", |opt| {
+        opt.query_analytics_size = 100;
+    });
+    server.populate_movies().await;
+
+    server.search("q=Captain").await;
+    server.search("q=captain").await;
+    server.search("q=Avengers").await;
+    server.search("q=xyzzyqwerty").await;
+
+    let (response, status_code) = server.get_request("/analytics/queries").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response[0], json!({ "query": "captain", "count": 2 }));
+    assert_eq!(response.as_array().unwrap().len(), 3);
+
+    let (response, status_code) = server.get_request("/analytics/queries/zero-results").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response, json!([{ "query": "xyzzyqwerty", "count": 1 }]));
+}
@@ -0,0 +1,28 @@
+mod common;
+
+#[actix_rt::test]
+async fn request_timeout_header() {
+    let mut server = common::Server::with_uid("movies");
+
+    // 1 - A request completing before the deadline is answered normally
+
+    let (_response, status_code) = server
+        .get_request_with_header("/health", ("X-Meili-Request-Timeout", "5000"))
+        .await;
+    assert_eq!(status_code, 200);
+
+    // 2 - A deadline above the maximum is capped instead of being rejected
+
+    let (_response, status_code) = server
+        .get_request_with_header("/health", ("X-Meili-Request-Timeout", "3600000"))
+        .await;
+    assert_eq!(status_code, 200);
+
+    // 3 - An invalid deadline is rejected
+
+    let (response, status_code) = server
+        .get_request_with_header("/health", ("X-Meili-Request-Timeout", "soon"))
+        .await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
@@ -196,6 +196,52 @@ async fn search_with_attribute_to_highlight_wildcard() {
     assert_json_eq!(expected, response["hits"].clone(), ordered: false);
 }
 
+// Search with attribute to highlight wildcard and only unknown attributes to retrieve
+// q: Captain
+// limit: 1
+// attributesToRetrieve: [unknown]
+// attributeToHighlight: *
+#[actix_rt::test]
+async fn search_with_attribute_to_highlight_wildcard_and_unknown_attributes_to_retrieve() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let query = "q=captain&limit=1&attributesToRetrieve=unknown&attributesToHighlight=*";
+
+    let (response, _status_code) = server.search(query).await;
+    let formatted = &response["hits"][0]["_formatted"];
+    assert_eq!(formatted["title"], json!("<em>Captain</em> Marvel"));
+    assert_eq!(formatted["director"], json!("Ryan Fleck"));
+    assert!(formatted["overview"].as_str().unwrap().contains("<em>Captain</em> Marvel"));
+}
+
+// Search with attribute to highlight wildcard and attributes to retrieve
+// q: Captain
+// limit: 1
+// attributesToRetrieve: [title,tagline]
+// attributeToHighlight: *
+#[actix_rt::test]
+async fn search_with_attribute_to_highlight_wildcard_and_attributes_to_retrieve() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let query = "q=captain&limit=1&attributesToRetrieve=title,tagline&attributesToHighlight=*";
+
+    let expected = json!([
+      {
+        "title": "Captain Marvel",
+        "tagline": "Higher. Further. Faster.",
+        "_formatted": {
+          "title": "<em>Captain</em> Marvel",
+          "tagline": "Higher. Further. Faster."
+        }
+      }
+    ]);
+
+    let (response, _status_code) = server.search(query).await;
+    assert_json_eq!(expected, response["hits"].clone(), ordered: false);
+}
+
 // Search with attribute to highlight title
 // q: Captain
 // limit: 1
@@ -1414,3 +1460,1391 @@ async fn highlight_cropped_text() {
         .unwrap()
         , &Value::String(expected_response.to_owned()));
 }
+
+#[actix_rt::test]
+async fn search_with_phonetic_matching() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "phoneticMatching": true,
+        "phoneticAttributes": ["name"],
+    });
+    server.update_all_settings(body).await;
+
+    let body = json!([
+        { "id": 1, "name": "Catherine" },
+        { "id": 2, "name": "Kathryn" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let (response, _status_code) = server.search("q=kathryn").await;
+
+    let expected = json!([
+        { "id": 2, "name": "Kathryn" },
+        { "id": 1, "name": "Catherine", "_phoneticMatch": true },
+    ]);
+    assert_json_eq!(expected, response["hits"].clone(), ordered: false);
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["phoneticMatching"], json!(true));
+    assert_eq!(response["phoneticAttributes"], json!(["name"]));
+}
+
+#[actix_rt::test]
+async fn rerank_only_the_given_documents() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let body = json!({
+        "q": "captain",
+        "documentIds": ["1771", "512200", "299537", "unknown"],
+    });
+    let (response, status_code) = server.rerank(body).await;
+    assert_eq!(status_code, 200);
+
+    let ids: Vec<_> = response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].clone())
+        .collect();
+    assert_eq!(ids, vec![json!(299537), json!(1771)]);
+    assert_eq!(response["nbHits"], json!(2));
+}
+
+#[actix_rt::test]
+async fn spellcheck_unknown_words() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let body = json!({ "q": "captian marvel" });
+    let (response, status_code) = server.spellcheck(body).await;
+    assert_eq!(status_code, 200);
+
+    assert_eq!(response["corrected"], json!("captain marvel"));
+    assert_eq!(response["words"][0]["original"], json!("captian"));
+    assert_eq!(response["words"][0]["suggestions"][0], json!("captain"));
+    assert_eq!(response["words"].as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn search_with_min_similarity() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (response, _status_code) = server.search("q=captian").await;
+    assert!(!response["hits"].as_array().unwrap().is_empty());
+
+    let (response, _status_code) = server.search("q=captian&minSimilarity=1.0").await;
+    assert!(response["hits"].as_array().unwrap().is_empty());
+
+    let (_response, status_code) = server.search("q=captian&minSimilarity=1.5").await;
+    assert_eq!(status_code, 400);
+}
+
+#[actix_rt::test]
+async fn search_with_stemming_language() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "stemmingLanguage": "english",
+    });
+    server.update_all_settings(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "run" },
+        { "id": 2, "title": "running" },
+        { "id": 3, "title": "walk" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let (response, _status_code) = server.search("q=running").await;
+    let expected = json!([
+        { "id": 2, "title": "running" },
+        { "id": 1, "title": "run" },
+    ]);
+    assert_json_eq!(expected, response["hits"].clone(), ordered: true);
+
+    let (response, _status_code) = server.search("q=runs").await;
+    let expected = json!([
+        { "id": 1, "title": "run" },
+        { "id": 2, "title": "running" },
+    ]);
+    assert_json_eq!(expected, response["hits"].clone(), ordered: false);
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["stemmingLanguage"], json!("english"));
+}
+
+#[actix_rt::test]
+async fn search_with_decompound_tokens() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "decompoundTokens": true,
+    });
+    server.update_all_settings(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "Fußball" },
+        { "id": 2, "title": "Training" },
+        { "id": 3, "title": "Fußballtraining" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let (response, _status_code) = server.search("q=training").await;
+    let expected = json!([
+        { "id": 2, "title": "Training" },
+        { "id": 3, "title": "Fußballtraining" },
+    ]);
+    assert_json_eq!(expected, response["hits"].clone(), ordered: false);
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["decompoundTokens"], json!(true));
+}
+
+#[actix_rt::test]
+async fn search_with_proximity_settings() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "rankingRules": ["proximity", "asc(rank)"],
+    });
+    server.update_all_settings(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "alpha one two three four beta", "rank": 2 },
+        { "id": 2, "title": "alpha beta", "rank": 3 },
+        { "id": 3, "title": "alpha", "overview": "beta", "rank": 1 },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let ids = |response: &Value| -> Vec<Value> {
+        response["hits"].as_array().unwrap().iter().map(|hit| hit["id"].clone()).collect()
+    };
+
+    let (response, _status_code) = server.search("q=alpha%20beta").await;
+    assert_eq!(ids(&response), vec![json!(2), json!(1), json!(3)]);
+
+    // past the window, the words are as far as the ones in different attributes
+    server.update_all_settings(json!({ "proximityWindow": 4 })).await;
+    let (response, _status_code) = server.search("q=alpha%20beta").await;
+    assert_eq!(ids(&response), vec![json!(2), json!(3), json!(1)]);
+
+    // the words in the same attribute are all near each other
+    server.update_all_settings(json!({ "proximityPrecision": "byAttribute" })).await;
+    let (response, _status_code) = server.search("q=alpha%20beta").await;
+    assert_eq!(ids(&response), vec![json!(1), json!(2), json!(3)]);
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["proximityPrecision"], json!("byAttribute"));
+    assert_eq!(response["proximityWindow"], json!(4));
+
+    let url = "/indexes/test/settings";
+    let (response, status_code) = server.post_request(url, json!({ "proximityWindow": 0 })).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn search_with_ranking_rule_expression() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "rankingRules": ["desc(price * discount_factor)"],
+    });
+    server.update_all_settings(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "shoes", "price": 100, "discount_factor": 0.5 },
+        { "id": 2, "title": "shoes", "price": 80, "discount_factor": 0.9 },
+        { "id": 3, "title": "shoes", "price": 60, "discount_factor": 1 },
+        { "id": 4, "title": "shoes", "price": 200 },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let (response, _status_code) = server.search("q=shoes").await;
+    let ids: Vec<_> = response["hits"].as_array().unwrap().iter().map(|hit| hit["id"].clone()).collect();
+    // the documents without one of the attributes are ranked last
+    assert_eq!(ids, vec![json!(2), json!(3), json!(1), json!(4)]);
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["rankingRules"], json!(["desc(price * discount_factor)"]));
+
+    let url = "/indexes/test/settings";
+    let (response, status_code) = server.post_request(url, json!({ "rankingRules": ["asc(price *)"] })).await;
+    assert_eq!(status_code, 400);
+    assert!(response["message"].as_str().unwrap().contains("invalid ranking rule expression"));
+
+    // the whole rule must be the expression sorted in one direction
+    for rule in &["xdesc(price)", "desc(price)x", "desc()"] {
+        let (_response, status_code) = server.post_request(url, json!({ "rankingRules": [rule] })).await;
+        assert_eq!(status_code, 400, "{} is accepted", rule);
+    }
+}
+
+#[actix_rt::test]
+async fn search_with_pinned_hits() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "rankingRules": ["asc(rank)"],
+        "pinnedHits": [
+            { "id": "3", "position": 1, "query": "Shoes" },
+            { "id": "4", "position": null },
+            { "id": "unknown", "position": 2 },
+        ],
+    });
+    server.update_all_settings(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "shoes", "rank": 1 },
+        { "id": 2, "title": "shoes", "rank": 2 },
+        { "id": 3, "title": "shoes", "rank": 3 },
+        { "id": 4, "title": "boots", "rank": 4 },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let ids = |response: &Value| -> Vec<Value> {
+        response["hits"].as_array().unwrap().iter().map(|hit| hit["id"].clone()).collect()
+    };
+
+    let (response, _status_code) = server.search("q=shoes").await;
+    assert_eq!(ids(&response), vec![json!(3), json!(1), json!(2), json!(4)]);
+    assert_eq!(response["nbHits"], json!(4));
+
+    let (response, _status_code) = server.search("q=shoes&offset=2&limit=1").await;
+    assert_eq!(ids(&response), vec![json!(2)]);
+
+    // the pinned documents must match the filters too
+    let (response, _status_code) = server.search("q=shoes&filters=rank%20%3C%204").await;
+    assert_eq!(ids(&response), vec![json!(3), json!(1), json!(2)]);
+    assert_eq!(response["nbHits"], json!(3));
+
+    // the first document is only pinned for the shoes
+    let (response, _status_code) = server.search("q=boots").await;
+    assert_eq!(ids(&response), vec![json!(4)]);
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["pinnedHits"][0], json!({ "id": "3", "position": 1, "query": "Shoes" }));
+
+    let url = "/indexes/test/settings";
+    let body = json!({ "pinnedHits": [{ "id": "1", "position": 0 }] });
+    let (response, status_code) = server.post_request(url, body).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn search_with_demoted_documents() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "rankingRules": ["asc(rank)"],
+        "demotedDocuments": [
+            { "id": "1", "penaltyFactor": 0.5 },
+            { "id": "2", "penaltyFactor": 0.0, "query": "buy shoes" },
+        ],
+    });
+    server.update_all_settings(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "buy shoes", "rank": 1 },
+        { "id": 2, "title": "buy shoes", "rank": 2 },
+        { "id": 3, "title": "buy shoes", "rank": 3 },
+        { "id": 4, "title": "buy shoes", "rank": 4 },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let ids = |response: &Value| -> Vec<Value> {
+        response["hits"].as_array().unwrap().iter().map(|hit| hit["id"].clone()).collect()
+    };
+
+    let (response, _status_code) = server.search("q=buy%20shoes").await;
+    assert_eq!(ids(&response), vec![json!(3), json!(1), json!(4)]);
+    assert_eq!(response["nbHits"], json!(3));
+
+    // the second document is only removed for the buy shoes query
+    let (response, _status_code) = server.search("q=shoes").await;
+    assert_eq!(ids(&response), vec![json!(2), json!(1), json!(3), json!(4)]);
+
+    let url = "/indexes/test/settings";
+    let body = json!({ "demotedDocuments": [{ "id": "1", "penaltyFactor": 2 }] });
+    let (response, status_code) = server.post_request(url, body).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn search_with_image_hash() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "shoes", "image_hash": 255 },
+        { "id": 2, "title": "shoes", "image_hash": 1 },
+        { "id": 3, "title": "shoes", "image_hash": 7 },
+        { "id": 4, "title": "shoes" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let (response, status_code) = server.search("q=shoes&imageHash=0").await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+
+    server.update_all_settings(json!({ "imageHashField": "image_hash" })).await;
+
+    let (response, _status_code) = server.search("q=shoes&imageHash=0").await;
+    let hits = response["hits"].as_array().unwrap();
+    let ids: Vec<_> = hits.iter().map(|hit| hit["id"].clone()).collect();
+    assert_eq!(ids, vec![json!(2), json!(3), json!(1), json!(4)]);
+
+    let distances: Vec<_> = hits.iter().map(|hit| hit["_imageDistance"].clone()).collect();
+    assert_eq!(distances, vec![json!(1), json!(3), json!(8), Value::Null]);
+
+    let (response, _status_code) = server.search("q=shoes").await;
+    assert!(response["hits"][0].get("_imageDistance").is_none());
+}
+
+#[actix_rt::test]
+async fn search_with_language_detection_field() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "Tage", "lang": "de" },
+        { "id": 2, "title": "running", "lang": "en" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let (response, _status_code) = server.search("q=tags").await;
+    assert_eq!(response["hits"].as_array().unwrap().len(), 0);
+
+    server.update_all_settings(json!({ "languageDetectionField": "lang" })).await;
+
+    let (response, _status_code) = server.search("q=tags").await;
+    let expected = json!([{ "id": 1, "title": "Tage", "lang": "de" }]);
+    assert_json_eq!(expected, response["hits"].clone(), ordered: true);
+
+    let (response, _status_code) = server.search("q=runs").await;
+    let expected = json!([{ "id": 2, "title": "running", "lang": "en" }]);
+    assert_json_eq!(expected, response["hits"].clone(), ordered: true);
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["languageDetectionField"], json!("lang"));
+}
+
+#[actix_rt::test]
+async fn search_with_one_way_and_equivalent_synonyms() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "oneWaySynonyms": { "nyc": ["new york"] },
+        "equivalentSynonyms": [["tv", "television"]],
+    });
+    server.update_all_settings(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "new york" },
+        { "id": 2, "title": "nyc" },
+        { "id": 3, "title": "tv" },
+        { "id": 4, "title": "television" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let ids = |response: Value| -> Vec<Value> {
+        response["hits"].as_array().unwrap().iter().map(|hit| hit["id"].clone()).collect()
+    };
+
+    let (response, _status_code) = server.search("q=nyc").await;
+    let mut found = ids(response);
+    found.sort_by_key(|id| id.as_u64());
+    assert_eq!(found, vec![json!(1), json!(2)]);
+
+    let (response, _status_code) = server.search("q=new%20york").await;
+    assert_eq!(ids(response), vec![json!(1)]);
+
+    let (response, _status_code) = server.search("q=tv").await;
+    let mut found = ids(response);
+    found.sort_by_key(|id| id.as_u64());
+    assert_eq!(found, vec![json!(3), json!(4)]);
+
+    let (response, _status_code) = server.search("q=television").await;
+    let mut found = ids(response);
+    found.sort_by_key(|id| id.as_u64());
+    assert_eq!(found, vec![json!(3), json!(4)]);
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["oneWaySynonyms"], json!({ "nyc": ["new york"] }));
+    assert_eq!(response["equivalentSynonyms"], json!([["tv", "television"]]));
+}
+
+#[actix_rt::test]
+async fn search_with_count_only() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (response, _status_code) = server.search("q=captain").await;
+    let nb_hits = response["nbHits"].clone();
+    assert!(nb_hits.as_u64().unwrap() > 0);
+
+    let (response, status_code) = server.search("q=captain&countOnly=true").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response, json!({ "nbHits": nb_hits }));
+
+    let (response, status_code) = server.search_post(json!({ "q": "captain", "countOnly": true })).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response, json!({ "nbHits": nb_hits }));
+}
+
+#[actix_rt::test]
+async fn search_with_bracketed_facet_filters() {
+    let mut server = common::Server::test_server().await;
+
+    let body = json!({
+        "attributesForFaceting": ["color", "tags"]
+    });
+    server.update_all_settings(body).await;
+
+    // [["color:blue", "color:green"], ["tags:bug"]]
+    let query = "q=a&facetFilters=%5B%5B%22color%3Ablue%22,%22color%3Agreen%22%5D,%5B%22tags%3Abug%22%5D%5D";
+    let (expected, _status_code) = server.search(query).await;
+    assert!(!expected["hits"].as_array().unwrap().is_empty());
+
+    let query = "q=a&facetFilters[0][0]=color:blue&facetFilters[0][1]=color:green&facetFilters[1][0]=tags:bug";
+    let (response, status_code) = server.search(query).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["hits"], expected["hits"]);
+    assert!(response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|value| (value["color"] == "blue" || value["color"] == "green")
+            && value["tags"].as_array().unwrap().contains(&json!("bug"))));
+
+    let query = "q=a&facetFilters%5B0%5D%5B0%5D=color:blue&facetFilters%5B0%5D%5B1%5D=color:green&facetFilters%5B1%5D%5B0%5D=tags:bug";
+    let (response, _status_code) = server.search(query).await;
+    assert_eq!(response["hits"], expected["hits"]);
+}
+
+#[actix_rt::test]
+async fn search_with_facet_values_limits() {
+    let mut server = common::Server::test_server().await;
+
+    let body = json!({
+        "attributesForFaceting": ["color", "tags"]
+    });
+    server.update_all_settings(body).await;
+
+    let query = "q=a&facetsDistribution=%5B%22color%22,%22tags%22%5D";
+    let (response, _status_code) = server.search(query).await;
+    let colors = response["facetsDistribution"]["color"].as_object().unwrap().clone();
+    let tags = response["facetsDistribution"]["tags"].clone();
+    assert!(colors.len() > 1);
+
+    let query = "q=a&facetsDistribution=%5B%22color%22,%22tags%22%5D&facetValuesLimits[color]=1";
+    let (response, status_code) = server.search(query).await;
+    assert_eq!(status_code, 200);
+
+    let limited = response["facetsDistribution"]["color"].as_object().unwrap();
+    assert_eq!(limited.len(), 1);
+    let max_count = colors.values().filter_map(Value::as_u64).max();
+    assert_eq!(limited.values().next().unwrap().as_u64(), max_count);
+    assert_eq!(response["facetsDistribution"]["tags"], tags);
+}
+
+#[actix_rt::test]
+async fn search_with_facet_sort_order() {
+    let mut server = common::Server::test_server().await;
+
+    let body = json!({
+        "attributesForFaceting": ["color", "tags"]
+    });
+    server.update_all_settings(body).await;
+
+    let query = "q=a&facetsDistribution=%5B%22color%22,%22tags%22%5D&facetSortOrder[color]=alpha";
+    let (response, status_code) = server.search(query).await;
+    assert_eq!(status_code, 200);
+
+    let colors: Vec<_> = response["facetsDistribution"]["color"].as_object().unwrap().keys().cloned().collect();
+    let mut sorted_colors = colors.clone();
+    sorted_colors.sort();
+    assert_eq!(colors, sorted_colors);
+
+    let tags: Vec<_> = response["facetsDistribution"]["tags"]
+        .as_object()
+        .unwrap()
+        .values()
+        .filter_map(Value::as_u64)
+        .collect();
+    assert!(tags.windows(2).all(|pair| pair[0] >= pair[1]));
+
+    let body = json!({
+        "q": "a",
+        "facetsDistribution": ["color"],
+        "facetSortOrder": { "color": "count" }
+    });
+    let (response, status_code) = server.search_post(body).await;
+    assert_eq!(status_code, 200);
+
+    let colors: Vec<_> = response["facetsDistribution"]["color"]
+        .as_object()
+        .unwrap()
+        .values()
+        .filter_map(Value::as_u64)
+        .collect();
+    assert!(colors.windows(2).all(|pair| pair[0] >= pair[1]));
+
+    let body = json!({
+        "q": "a",
+        "facetsDistribution": ["color"],
+        "facetSortOrder": { "color": "random" }
+    });
+    let (_response, status_code) = server.search_post(body).await;
+    assert_eq!(status_code, 400);
+}
+
+#[actix_rt::test]
+async fn search_with_show_all_facet_values() {
+    let mut server = common::Server::test_server().await;
+
+    let body = json!({
+        "attributesForFaceting": ["color", "tags"]
+    });
+    server.update_all_settings(body).await;
+
+    let body = json!({
+        "q": "a",
+        "facetFilters": ["color:green"],
+        "facetsDistribution": ["color"],
+        "showAllFacetValues": false
+    });
+    let (response, status_code) = server.search_post(body).await;
+    assert_eq!(status_code, 200);
+
+    let colors = response["facetsDistribution"]["color"].as_object().unwrap();
+    assert_eq!(colors.keys().collect::<Vec<_>>(), vec!["green"]);
+
+    // all the values are returned by default
+    let body = json!({
+        "q": "a",
+        "facetFilters": ["color:green"],
+        "facetsDistribution": ["color"]
+    });
+    let (response, status_code) = server.search_post(body).await;
+    assert_eq!(status_code, 200);
+
+    let colors = response["facetsDistribution"]["color"].as_object().unwrap();
+    assert_eq!(colors.len(), 3);
+    assert_eq!(colors.keys().next().unwrap(), "green");
+    assert_eq!(colors["blue"], json!(0));
+    assert_eq!(colors["brown"], json!(0));
+}
+
+#[actix_rt::test]
+async fn search_with_hierarchical_facets() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "attributesForFaceting": ["category", "subcategory", "brand"],
+        "hierarchicalFacets": { "lvl0": "category", "lvl1": "subcategory" },
+    });
+    server.update_all_settings(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "phone", "category": "Electronics", "subcategory": "Phones", "brand": "acme" },
+        { "id": 2, "title": "phone", "category": "Electronics", "subcategory": "Phones", "brand": "acme" },
+        { "id": 3, "title": "phone", "category": "Electronics", "subcategory": "Chargers", "brand": "zorg" },
+        { "id": 4, "title": "phone", "category": "Toys", "subcategory": "Puppets", "brand": "zorg" },
+        { "id": 5, "title": "book", "category": "Books", "subcategory": "Novels", "brand": "acme" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let body = json!({
+        "q": "phone",
+        "facetsDistribution": ["category", "brand"],
+    });
+    let (response, status_code) = server.search_post(body).await;
+    assert_eq!(status_code, 200);
+
+    let expected = json!({
+        "electronics": {
+            "_count": 3,
+            "subcategory": {
+                "phones": { "_count": 2 },
+                "chargers": { "_count": 1 },
+            },
+        },
+        "toys": {
+            "_count": 1,
+            "subcategory": {
+                "puppets": { "_count": 1 },
+            },
+        },
+    });
+    assert_eq!(response["facetsDistribution"]["category"], expected);
+    assert_eq!(response["facetsDistribution"]["brand"], json!({ "acme": 2, "zorg": 2 }));
+
+    // the levels below the first one are counted as flat lists
+    let body = json!({
+        "q": "phone",
+        "facetsDistribution": ["subcategory"],
+    });
+    let (response, _status_code) = server.search_post(body).await;
+    assert_eq!(response["facetsDistribution"]["subcategory"], json!({ "phones": 2, "chargers": 1, "puppets": 1, "novels": 0 }));
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["hierarchicalFacets"], json!({ "lvl0": "category", "lvl1": "subcategory" }));
+
+    // the levels are ordered by their number, not by their name
+    server.update_all_settings(json!({ "hierarchicalFacets": { "lvl9": "category", "lvl10": "subcategory" } })).await;
+    let body = json!({
+        "q": "phone",
+        "facetsDistribution": ["category"],
+    });
+    let (response, _status_code) = server.search_post(body).await;
+    assert_eq!(response["facetsDistribution"]["category"], expected);
+
+    // the levels must be numbered and be attributes for faceting
+    let body = json!({ "hierarchicalFacets": { "top": "category", "lvl1": "subcategory" } });
+    let (_response, status_code) = server.post_request("/indexes/test/settings", body).await;
+    assert_eq!(status_code, 400);
+
+    let body = json!({ "hierarchicalFacets": { "lvl0": "category", "lvl1": "title" } });
+    let (_response, status_code) = server.post_request("/indexes/test/settings", body).await;
+    assert_eq!(status_code, 400);
+
+    // nor be previewed
+    let body = json!({ "hierarchicalFacets": { "lvl0": "category", "lvl1": "title" } });
+    let (_response, status_code) = server.post_request("/indexes/test/settings/preview", body).await;
+    assert_eq!(status_code, 400);
+
+    let body = json!({ "attributesForFaceting": ["category", "title"], "hierarchicalFacets": { "lvl0": "category", "lvl1": "title" } });
+    let (_response, status_code) = server.post_request("/indexes/test/settings", body).await;
+    assert_eq!(status_code, 202);
+}
+
+#[actix_rt::test]
+async fn search_with_cross_field_boost() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+
+    let body = json!([
+        { "id": 1, "title": "apple", "description": "a fruit", "tags": "fruit" },
+        { "id": 2, "title": "pie", "description": "an apple pie", "tags": "apple" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    // the document matching in the first attribute comes first
+    let (response, _status_code) = server.search("q=apple&attributesToRetrieve=id").await;
+    assert_eq!(response["hits"], json!([{ "id": 1 }, { "id": 2 }]));
+
+    server.update_all_settings(json!({ "crossFieldBoost": true })).await;
+
+    // the document matching in two fields comes first
+    let (response, _status_code) = server.search("q=apple&attributesToRetrieve=id").await;
+    assert_eq!(response["hits"], json!([{ "id": 2 }, { "id": 1 }]));
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["crossFieldBoost"], json!(true));
+}
+
+#[actix_rt::test]
+async fn search_with_disjunctive_facets() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+    server.update_all_settings(json!({ "attributesForFaceting": ["brand", "color"] })).await;
+
+    let body = json!([
+        { "id": 1, "name": "running shoes", "brand": "Nike", "color": "red" },
+        { "id": 2, "name": "running shoes", "brand": "Nike", "color": "blue" },
+        { "id": 3, "name": "running shoes", "brand": "Adidas", "color": "red" },
+        { "id": 4, "name": "running shoes", "brand": "Puma", "color": "blue" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let query = json!({
+        "q": "shoes",
+        "facetFilters": ["brand:Nike"],
+        "facetsDistribution": ["brand", "color"],
+    });
+    let (response, status_code) = server.search_post(query).await;
+    assert_eq!(status_code, 200);
+    // the values of the other documents are counted as zero
+    assert_eq!(response["facetsDistribution"]["brand"], json!({ "nike": 2, "adidas": 0, "puma": 0 }));
+
+    // the brands are counted without the brand filter, the colors with it
+    let query = json!({
+        "q": "shoes",
+        "facetFilters": ["brand:Nike"],
+        "facetsDistribution": ["brand", "color"],
+        "disjunctiveFacets": ["brand"],
+    });
+    let (response, status_code) = server.search_post(query).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["nbHits"], json!(2));
+    assert_eq!(response["facetsDistribution"]["brand"], json!({ "nike": 2, "adidas": 1, "puma": 1 }));
+    assert_eq!(response["facetsDistribution"]["color"], json!({ "red": 1, "blue": 1 }));
+
+    // the disjunctive facets must be in the distribution
+    let query = json!({
+        "q": "shoes",
+        "facetsDistribution": ["color"],
+        "disjunctiveFacets": ["brand"],
+    });
+    let (_response, status_code) = server.search_post(query).await;
+    assert_eq!(status_code, 400);
+}
+
+#[actix_rt::test]
+async fn search_with_hit_explanation() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+
+    let body = json!([
+        { "id": 1, "title": "apple", "description": "a fruit" },
+        { "id": 2, "title": "pie", "description": "an apple pie" },
+        { "id": 3, "title": "aple", "description": "a typo" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let (response, status_code) = server.search("q=apple&attributesToRetrieve=id").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["hits"], json!([{ "id": 1 }, { "id": 2 }, { "id": 3 }]));
+
+    let (response, status_code) = server.search("q=apple&attributesToRetrieve=id&showHitExplanation=true").await;
+    assert_eq!(status_code, 200);
+
+    let expected = json!([
+        { "id": 1, "_explanation": "Ranked #1 by: typo (0 typos), then attribute (title match)" },
+        { "id": 2, "_explanation": "Ranked #2 by: typo (0 typos), then attribute (description match)" },
+        { "id": 3, "_explanation": "Ranked #3 by: typo (1 typo)" },
+    ]);
+    assert_eq!(response["hits"], expected);
+
+    // the position takes the offset into account
+    let (response, _status_code) = server.search("q=apple&attributesToRetrieve=id&showHitExplanation=true&offset=2").await;
+    assert_eq!(response["hits"], json!([{ "id": 3, "_explanation": "Ranked #3 by: typo (1 typo)" }]));
+}
+
+#[actix_rt::test]
+async fn search_links_the_next_page_to_prefetch() {
+    let mut server = common::Server::with_uid_and_options("test", |opt| opt.enable_http2_push = true);
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+
+    let body = json!([
+        { "id": 1, "title": "shoe" },
+        { "id": 2, "title": "shoe" },
+        { "id": 3, "title": "shoe" },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let url = "/indexes/test/search?q=shoe&limit=2&offset=0";
+    let (_response, status_code, headers) = server.get_request_with_headers(url, &[]).await;
+    assert_eq!(status_code, 200);
+    let link = headers.get("Link").unwrap().to_str().unwrap();
+    assert_eq!(link, "</indexes/test/search?q=shoe&limit=2&offset=2>; rel=prefetch");
+
+    // the last page has no next page
+    let url = "/indexes/test/search?q=shoe&limit=2&offset=2";
+    let (_response, _status_code, headers) = server.get_request_with_headers(url, &[]).await;
+    assert!(headers.get("Link").is_none());
+
+    // the link is not added unless it is enabled
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+    server.add_or_replace_multiple_documents(json!([{ "id": 1, "title": "shoe" }, { "id": 2, "title": "shoe" }])).await;
+    let (_response, _status_code, headers) = server.get_request_with_headers("/indexes/test/search?q=shoe&limit=1", &[]).await;
+    assert!(headers.get("Link").is_none());
+}
+
+#[actix_rt::test]
+async fn search_with_markdown_highlight() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+    server.add_or_replace_multiple_documents(json!([{ "id": 1, "title": "red shoe" }])).await;
+
+    let (response, _status_code) = server.search("q=shoe&attributesToHighlight=title&markdownHighlight=true").await;
+    assert_eq!(response["hits"][0]["_formatted"]["title"], "red **shoe**");
+
+    let (response, _status_code) = server.search("q=shoe&attributesToHighlight=title").await;
+    assert_eq!(response["hits"][0]["_formatted"]["title"], "red <em>shoe</em>");
+}
+
+#[actix_rt::test]
+async fn search_with_snippet_ellipsis_side() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+    let body = json!([{ "id": 1, "text": "see you at the station tomorrow morning" }]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let (response, _status_code) = server.search("q=station&attributesToCrop=text&cropLength=5&snippetEllipsisSide=left").await;
+    assert_eq!(response["hits"][0]["_formatted"]["text"], "at the station");
+
+    let (response, _status_code) = server.search("q=station&attributesToCrop=text&cropLength=5&snippetEllipsisSide=right").await;
+    assert_eq!(response["hits"][0]["_formatted"]["text"], "station tomorrow");
+
+    let (_response, status_code) = server.search("q=station&attributesToCrop=text&cropLength=5&snippetEllipsisSide=top").await;
+    assert_eq!(status_code, 400);
+}
+
+#[actix_rt::test]
+async fn search_with_zero_results_fallback() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+    server.add_or_replace_multiple_documents(json!([{ "id": 2, "title": "shoe" }, { "id": 1, "title": "boot" }])).await;
+
+    let (response, _status_code) = server.search("q=shoos&attributesToRetrieve=id").await;
+    assert_eq!(response["hits"], json!([]));
+    assert!(response.get("fallbackStrategy").is_none());
+
+    // the query is searched again with two typos allowed
+    let (response, _status_code) = server.search("q=shoos&attributesToRetrieve=id&zeroResultsFallback=typo-relax").await;
+    assert_eq!(response["hits"], json!([{ "id": 2 }]));
+    assert_eq!(response["fallbackStrategy"], "typo-relax");
+
+    // all the documents are returned in the order they were added
+    let (response, _status_code) = server.search("q=sandal&attributesToRetrieve=id&zeroResultsFallback=all").await;
+    assert_eq!(response["hits"], json!([{ "id": 2 }, { "id": 1 }]));
+    assert_eq!(response["nbHits"], 2);
+    assert_eq!(response["fallbackStrategy"], "all");
+
+    // there is no fallback when the query finds hits
+    let (response, _status_code) = server.search("q=boot&attributesToRetrieve=id&zeroResultsFallback=all").await;
+    assert_eq!(response["hits"], json!([{ "id": 1 }]));
+    assert!(response.get("fallbackStrategy").is_none());
+
+    let (response, _status_code) = server.search("q=sandal&zeroResultsFallback=none").await;
+    assert_eq!(response["hits"], json!([]));
+}
+
+#[actix_rt::test]
+async fn facet_errors_have_their_own_codes() {
+    let mut server = common::Server::test_server().await;
+
+    let query = "q=a&facetsDistribution=%5B%22color%22%5D";
+    let (response, status_code) = server.search(query).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "facets_not_set");
+
+    server.update_all_settings(json!({ "attributesForFaceting": ["color"] })).await;
+
+    let query = "q=a&facetsDistribution=%5B%22gender%22%5D";
+    let (response, status_code) = server.search(query).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "facet_attribute_not_set");
+
+    let query = "q=a&facetsDistribution=%5B%22color%22,%20true%5D";
+    let (response, status_code) = server.search(query).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "invalid_facet");
+
+    let query = "q=a&facetFilters=%5B%22gender%3Amale%22%5D";
+    let (response, status_code) = server.search(query).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "facet_attribute_not_set");
+
+    let query = "q=a&filters=age%20%3E";
+    let (response, status_code) = server.search(query).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "invalid_filter");
+}
+
+#[actix_rt::test]
+async fn search_with_aggregations() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+    server.update_all_settings(json!({
+        "rankingRules": ["typo", "words", "proximity", "attribute", "wordsPosition", "exactness", "desc(price)"],
+    })).await;
+    server.add_or_replace_multiple_documents(json!([
+        { "id": 1, "title": "red shoe", "brand": "acme", "category": "shoes", "price": 30 },
+        { "id": 2, "title": "blue shoe", "brand": "acme", "category": "shoes", "price": 10 },
+        { "id": 3, "title": "green shoe", "brand": "acme", "category": "shoes", "price": 20 },
+        { "id": 4, "title": "red hat", "brand": "acme", "category": "hats", "price": 15 },
+        { "id": 5, "title": "red scarf", "brand": "acme", "category": "scarves", "price": 5 },
+    ])).await;
+
+    let body = json!({
+        "q": "acme",
+        "limit": 1,
+        "aggregations": [
+            { "name": "byCategory", "filter": "category = 'shoes'", "sort": "price:asc", "limit": 2 },
+            { "name": "cheapest", "sort": "price:asc", "limit": 1 },
+        ],
+    });
+    let (response, status_code) = server.search_post(body).await;
+    assert_eq!(status_code, 200);
+    // the aggregations are not limited to the paginated hits
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+
+    let ids = |hits: &Value| -> Vec<Value> { hits.as_array().unwrap().iter().map(|hit| hit["id"].clone()).collect() };
+    assert_eq!(ids(&response["aggregations"]["byCategory"]["hits"]), vec![json!(2), json!(3)]);
+    // like the one of the search, nbHits is counted before the filter is applied
+    assert_eq!(response["aggregations"]["byCategory"]["nbHits"], 5);
+    assert_eq!(ids(&response["aggregations"]["cheapest"]["hits"]), vec![json!(5)]);
+
+    // the filters of the query also apply to the aggregations
+    let body = json!({
+        "q": "red",
+        "filters": "price > 10",
+        "aggregations": [{ "name": "shoes", "filter": "category = shoes" }],
+    });
+    let (response, _status_code) = server.search_post(body).await;
+    assert_eq!(ids(&response["aggregations"]["shoes"]["hits"]), vec![json!(1)]);
+
+    // only the attributes of the asc and desc ranking rules can be sorted by
+    let body = json!({ "q": "acme", "aggregations": [{ "name": "byTitle", "sort": "title:asc" }] });
+    let (response, status_code) = server.search_post(body).await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "sort_attribute_not_sortable");
+
+    let body = json!({ "q": "acme", "aggregations": [{ "name": "a" }, { "name": "a" }] });
+    let (_response, status_code) = server.search_post(body).await;
+    assert_eq!(status_code, 400);
+
+    // the number of aggregations and their limits are bounded
+    let aggregations: Vec<_> = (0..11).map(|i| json!({ "name": format!("a{}", i) })).collect();
+    let body = json!({ "q": "acme", "aggregations": aggregations });
+    let (_response, status_code) = server.search_post(body).await;
+    assert_eq!(status_code, 400);
+
+    let body = json!({ "q": "acme", "aggregations": [{ "name": "a", "limit": 101 }] });
+    let (_response, status_code) = server.search_post(body).await;
+    assert_eq!(status_code, 400);
+}
+
+#[actix_rt::test]
+async fn search_results_are_streamed() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+    server.add_or_replace_multiple_documents(json!([{ "id": 1, "title": "shoe" }, { "id": 2, "title": "shoe" }])).await;
+
+    let (response, status_code, headers) = server.get_request_with_headers("/indexes/test/search?q=shoe", &[]).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(headers.get("Content-Type").unwrap(), "application/json");
+    assert_eq!(response["hits"], json!([{ "id": 1, "title": "shoe" }, { "id": 2, "title": "shoe" }]));
+    assert_eq!(response["nbHits"], 2);
+
+    // the fields are in the same order as when the results are not streamed
+    let fields: Vec<_> = response.as_object().unwrap().keys().map(String::as_str).collect();
+    let expected = ["hits", "offset", "limit", "nbHits", "exhaustiveNbHits", "processingTimeMs", "query", "queryId"];
+    assert_eq!(fields, expected);
+
+    // the results without hits are streamed too
+    let (response, status_code) = server.search("q=boot").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["hits"], json!([]));
+    assert_eq!(response["nbHits"], 0);
+
+    let (response, status_code) = server.search("q=shoe&attributesToHighlight=title&offset=1").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["hits"], json!([{ "id": 2, "title": "shoe", "_formatted": { "id": 2, "title": "<em>shoe</em>" } }]));
+}
+
+#[actix_rt::test]
+async fn search_with_repeated_list_parameters() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let expected = json!([
+      {
+        "title": "Captain Marvel",
+        "director": "Ryan Fleck",
+        "_formatted": {
+          "title": "<em>Captain</em> Marvel"
+        }
+      }
+    ]);
+
+    let query = "q=captain&limit=1&attributesToRetrieve[]=title&attributesToRetrieve[]=director&attributesToHighlight[]=title";
+    let (response, status_code) = server.search(query).await;
+    assert_eq!(status_code, 200);
+    assert_json_eq!(expected.clone(), response["hits"].clone(), ordered: false);
+
+    let query = "q=captain&limit=1&attributesToRetrieve%5B%5D=title&attributesToRetrieve%5B%5D=director&attributesToHighlight%5B%5D=title";
+    let (response, status_code) = server.search(query).await;
+    assert_eq!(status_code, 200);
+    assert_json_eq!(expected, response["hits"].clone(), ordered: false);
+}
+
+#[actix_rt::test]
+async fn search_with_post_body() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let body = json!({
+        "q": "captain",
+        "limit": 1,
+        "attributesToRetrieve": ["title", "director"],
+        "attributesToHighlight": ["title"],
+    });
+
+    let expected = json!([
+      {
+        "title": "Captain Marvel",
+        "director": "Ryan Fleck",
+        "_formatted": {
+          "title": "<em>Captain</em> Marvel"
+        }
+      }
+    ]);
+
+    let (response, status_code) = server.search_post(body).await;
+    assert_eq!(status_code, 200);
+    assert_json_eq!(expected, response["hits"].clone(), ordered: false);
+
+    let (response, status_code) = server.search_post(json!({ "q": "captain", "unknown": true })).await;
+    assert_eq!(status_code, 400);
+    assert!(response.get("message").is_some());
+}
+
+#[actix_rt::test]
+async fn facets_distribution_with_repeated_parameters() {
+    let mut server = common::Server::test_server().await;
+
+    let body = json!({
+        "attributesForFaceting": ["color", "tags"]
+    });
+    server.update_all_settings(body).await;
+
+    let query = "q=a&facetsDistribution[]=color&facetsDistribution[]=tags";
+    let (response, _status_code) = server.search(query).await;
+    let facets = response.get("facetsDistribution").unwrap().as_object().unwrap();
+    assert_eq!(facets.values().count(), 2);
+}
+
+#[actix_rt::test]
+async fn search_with_fields_alias() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let query = "q=captain&limit=1&fields=title,producer";
+    let expected = json!([
+      {
+        "title": "Captain Marvel",
+        "producer": "Kevin Feige"
+      }
+    ]);
+    let (response, _status_code) = server.search(query).await;
+    assert_json_eq!(expected, response["hits"].clone(), ordered: false);
+
+    let query = "q=captain&limit=1&fields=title&attributesToRetrieve=director";
+    let expected = json!([
+      {
+        "title": "Captain Marvel",
+        "director": "Ryan Fleck"
+      }
+    ]);
+    let (response, _status_code) = server.search(query).await;
+    assert_json_eq!(expected, response["hits"].clone(), ordered: false);
+
+    let query = "q=captain&limit=1&fieldz=title";
+    let (_response, status_code) = server.search(query).await;
+    assert_eq!(status_code, 400);
+}
+
+#[actix_rt::test]
+async fn search_etag_and_if_none_match() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let url = "/indexes/movies/search?q=captain";
+    let (response, status_code, headers) = server.get_request_with_headers(url, &[]).await;
+    assert_eq!(status_code, 200);
+    assert!(response["hits"].as_array().is_some());
+    let etag = headers.get("ETag").unwrap().to_str().unwrap().to_string();
+
+    // The same query on the same index is not modified
+    let (_response, status_code, headers) = server
+        .get_request_with_headers(url, &[("If-None-Match", etag.as_str())])
+        .await;
+    assert_eq!(status_code, 304);
+    assert_eq!(headers.get("ETag").unwrap().to_str().unwrap(), etag);
+
+    // The results with the same ETag have the same query id
+    let (other_response, _status_code, _headers) = server.get_request_with_headers(url, &[]).await;
+    assert_eq!(other_response["queryId"], response["queryId"]);
+
+    // Another query has another ETag
+    let other = "/indexes/movies/search?q=marvel";
+    let (_response, status_code, headers) = server
+        .get_request_with_headers(other, &[("If-None-Match", etag.as_str())])
+        .await;
+    assert_eq!(status_code, 200);
+    assert_ne!(headers.get("ETag").unwrap().to_str().unwrap(), etag);
+
+    // Updating the index changes the ETag
+    server.add_or_replace_multiple_documents(json!([{ "id": 1, "title": "Captain Planet" }])).await;
+    let (_response, status_code, _headers) = server
+        .get_request_with_headers(url, &[("If-None-Match", etag.as_str())])
+        .await;
+    assert_eq!(status_code, 200);
+
+    // There is no ETag while an experiment is active
+    let body = json!({
+        "name": "popularity",
+        "control": ["typo", "words", "proximity", "attribute", "wordsPosition", "exactness"],
+        "treatment": ["typo", "words", "desc(popularity)"],
+        "trafficSplit": 50,
+    });
+    let (_response, status_code) = server.post_request("/indexes/movies/experiments", body).await;
+    assert_eq!(status_code, 201);
+    let (_response, status_code, headers) = server.get_request_with_headers(url, &[]).await;
+    assert_eq!(status_code, 200);
+    assert!(headers.get("ETag").is_none());
+}
+
+#[actix_rt::test]
+async fn search_processing_time_header() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let url = "/indexes/movies/search?q=captain";
+    let (response, status_code, headers) = server.get_request_with_headers(url, &[]).await;
+    assert_eq!(status_code, 200);
+
+    let header = headers.get("X-Meili-Processing-Time-Ms").unwrap().to_str().unwrap();
+    let processing_time_ms: u64 = header.parse().unwrap();
+    assert_eq!(response["processingTimeMs"], json!(processing_time_ms));
+}
+
+#[actix_rt::test]
+async fn search_with_query_analysis() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    server.update_all_settings(json!({ "stopWords": ["the"], "stemmingLanguage": "english" })).await;
+
+    let (response, status_code) = server.search("q=the%20running%20man").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response.get("queryAnalysis"), None);
+
+    let (response, status_code) = server.search("q=the%20running%20man&returnQueryAnalysis=true").await;
+    assert_eq!(status_code, 200);
+
+    let expected = json!({
+        "originalQuery": "the running man",
+        "processedQuery": "running man",
+        "stoppedWords": ["the"],
+        "stemmedTokens": [{ "original": "running", "stemmed": "run" }],
+    });
+    assert_eq!(response["queryAnalysis"], expected);
+}
+
+#[actix_rt::test]
+async fn search_grouped_by_attribute() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (response, _status_code) = server.search("q=captain&limit=50").await;
+    let hits = response["hits"].as_array().unwrap().clone();
+
+    let (response, status_code) = server.search("q=captain&limit=50&groupByAttribute=director&groupLimit=1").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response.get("hits"), None);
+
+    let groups = response["groups"].as_array().unwrap();
+    let grouped_hits: usize = groups.iter().map(|group| group["hits"].as_array().unwrap().len()).sum();
+    assert_eq!(grouped_hits + response["ungroupedHits"].as_u64().unwrap() as usize, hits.len());
+
+    // the groups are ordered by their best hit and only keep it
+    let mut directors = Vec::new();
+    for hit in &hits {
+        if !directors.contains(&hit["director"]) {
+            directors.push(hit["director"].clone());
+        }
+    }
+    assert_eq!(groups.len(), directors.len());
+    for (group, director) in groups.iter().zip(&directors) {
+        assert_eq!(&group["groupValue"], director);
+        assert_eq!(group["hits"].as_array().unwrap().len(), 1);
+        assert_eq!(&group["hits"][0]["director"], director);
+    }
+
+    let (response, status_code) = server.search("q=captain&groupByAttribute=unknown").await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+
+    // the hits can only be grouped by an attribute they are returned with
+    let (response, status_code) = server.search("q=captain&attributesToRetrieve=title&groupByAttribute=director").await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+
+    server.update_displayed_attributes(json!(["id", "title"])).await;
+    let (response, status_code) = server.search("q=captain&groupByAttribute=director").await;
+    assert_eq!(status_code, 400);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn search_with_matched_words() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (response, status_code) = server.search("q=captain%20marvl&limit=1").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["hits"][0].get("_matchedWords"), None);
+
+    // the last word matches with a typo, as a prefix
+    let (response, status_code) = server.search("q=captain%20marvl&limit=1&returnMatchedWords=true").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["hits"][0]["title"], json!("Captain Marvel"));
+    assert_eq!(response["hits"][0]["_matchedWords"], json!(["captain", "marvl"]));
+}
+
+#[actix_rt::test]
+async fn search_with_excluded_words() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let has_captain_marvel = |response: &Value| {
+        response["hits"].as_array().unwrap().iter().any(|hit| hit["id"] == json!(299537))
+    };
+
+    let (response, status_code) = server.search("q=captain&limit=50").await;
+    assert_eq!(status_code, 200);
+    assert!(has_captain_marvel(&response));
+
+    let (response, status_code) = server.search("q=captain%20-marvel&limit=50").await;
+    assert_eq!(status_code, 200);
+    assert!(!response["hits"].as_array().unwrap().is_empty());
+    assert!(!has_captain_marvel(&response));
+
+    // the excluded words are typo tolerant, and only exclude the words they are a prefix of with a `*`
+    let (response, status_code) = server.search("q=captain%20-marve&limit=50").await;
+    assert_eq!(status_code, 200);
+    assert!(!has_captain_marvel(&response));
+
+    let (response, status_code) = server.search("q=captain%20-marv&limit=50").await;
+    assert_eq!(status_code, 200);
+    assert!(has_captain_marvel(&response));
+
+    let (response, status_code) = server.search("q=captain%20-marv*&limit=50").await;
+    assert_eq!(status_code, 200);
+    assert!(!has_captain_marvel(&response));
+
+    let (response, status_code) = server.search("q=captain%20-marvl&limit=50").await;
+    assert_eq!(status_code, 200);
+    assert!(!has_captain_marvel(&response));
+
+    // a double negation searches the word
+    let (response, status_code) = server.search("q=--marvel&limit=50").await;
+    assert_eq!(status_code, 200);
+    assert!(has_captain_marvel(&response));
+
+    // a query made of excluded words only matches nothing
+    let (response, status_code) = server.search("q=-marvel%20-captain").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["hits"], json!([]));
+}
+
+#[actix_rt::test]
+async fn search_with_excluded_words_and_filters() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    // director = "Anthony Russo" AND (title = "Captain America: Civil War" OR title = "Captain America: The Winter Soldier")
+    let filters = "director%20%3D%20%22anthony%20russo%22%20AND%20%20(title%20%3D%20%22captain%20america%3A%20civil%20war%22%20OR%20title%20%3D%20%22Captain%20America%3A%20The%20Winter%20Soldier%22)";
+
+    let (response, status_code) = server.search(&format!("q=captain&filters={}", filters)).await;
+    assert_eq!(status_code, 200);
+    let mut ids: Vec<_> = response["hits"].as_array().unwrap().iter().map(|hit| hit["id"].clone()).collect();
+    ids.sort_by_key(|id| id.as_u64());
+    assert_eq!(ids, vec![json!(100402), json!(271110)]);
+
+    let (response, status_code) = server.search(&format!("q=captain%20-civil&filters={}", filters)).await;
+    assert_eq!(status_code, 200);
+    let ids: Vec<_> = response["hits"].as_array().unwrap().iter().map(|hit| hit["id"].clone()).collect();
+    assert_eq!(ids, vec![json!(100402)]);
+}
+
+#[actix_rt::test]
+async fn search_with_wildcard() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (response, status_code) = server.search("q=capt*&limit=50").await;
+    assert_eq!(status_code, 200);
+    assert!(response["hits"].as_array().unwrap().iter().any(|hit| hit["id"] == json!(299537)));
+    assert_eq!(response.get("warnings"), None);
+
+    // leading wildcards are ignored
+    let (response, status_code) = server.search("q=*captain&limit=1").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["hits"][0]["title"], json!("Captain Marvel"));
+}
+
+#[actix_rt::test]
+async fn search_with_truncated_wildcard() {
+    let mut server = common::Server::with_uid_and_options("movies", |opt| opt.max_wildcard_expansions = 1);
+    server.populate_movies().await;
+
+    let (response, status_code) = server.search("q=capt*").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["warnings"], json!(["capt* matches too many words, only the first ones are searched"]));
+}
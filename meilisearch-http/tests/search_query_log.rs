@@ -0,0 +1,69 @@
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde_json::Value;
+
+mod common;
+
+/// Keeps the lines logged with the `query` target.
+struct QueryLogger {
+    lines: Mutex<Vec<Value>>,
+}
+
+impl QueryLogger {
+    fn take(&self) -> Vec<Value> {
+        std::mem::take(&mut *self.lines.lock().unwrap())
+    }
+}
+
+impl Log for QueryLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Info && metadata.target() == "query"
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let line = serde_json::from_str(&record.args().to_string()).unwrap();
+            self.lines.lock().unwrap().push(line);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[actix_rt::test]
+async fn queries_are_logged_without_their_string() {
+    let logger: &'static QueryLogger = Box::leak(Box::new(QueryLogger { lines: Mutex::new(Vec::new()) }));
+    log::set_logger(logger).unwrap();
+    log::set_max_level(LevelFilter::Info);
+
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    server.search("q=captain").await;
+    assert!(logger.take().is_empty());
+
+    let mut server = common::Server::with_uid_and_options("movies", |opt| opt.log_queries = true);
+    server.populate_movies().await;
+
+    let (response, _status_code) = server.search("q=captain").await;
+    let lines = logger.take();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["type"], "query");
+    assert_eq!(lines[0]["indexUid"], "movies");
+    assert_eq!(lines[0]["qLen"], 7);
+    assert_eq!(lines[0]["nbHits"], response["nbHits"]);
+    assert!(lines[0]["processingTimeMs"].is_number());
+    assert!(lines[0].get("q").is_none());
+
+    let mut server = common::Server::with_uid_and_options("movies", |opt| {
+        opt.log_queries = true;
+        opt.log_query_strings = true;
+    });
+    server.populate_movies().await;
+
+    server.search("q=captain").await;
+    let lines = logger.take();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["q"], "captain");
+}
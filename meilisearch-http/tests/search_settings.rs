@@ -268,58 +268,59 @@ async fn search_with_settings_synonyms() {
 
     server.update_all_settings(config).await;
 
+    // the synonyms match without typo, they are ranked before the documents matching `avengers`
     let query = "q=avangers&limit=3";
     let expect = json!([
       {
-        "id": 299536,
-        "popularity": 65.013,
-        "vote_average": 8.3,
-        "title": "Avengers: Infinity War",
-        "tagline": "An entire universe. Once and for all.",
-        "overview": "As the Avengers and their allies have continued to protect the world from threats too large for any one hero to handle, a new danger has emerged from the cosmic shadows: Thanos. A despot of intergalactic infamy, his goal is to collect all six Infinity Stones, artifacts of unimaginable power, and use them to inflict his twisted will on all of reality. Everything the Avengers have fought for has led up to this moment - the fate of Earth and existence itself has never been more uncertain.",
-        "director": "Anthony Russo",
-        "producer": "Kevin Feige",
+        "id": 1726,
+        "popularity": 40.212,
+        "vote_average": 7.6,
+        "title": "Iron Man",
+        "tagline": "Heroes aren't born. They're built.",
+        "overview": "After being held captive in an Afghan cave, billionaire engineer Tony Stark creates a unique weaponized suit of armor to fight evil.",
+        "director": "Jon Favreau",
+        "producer": "Avi Arad",
         "genres": [
-          "Adventure",
           "Action",
-          "Science Fiction"
+          "Science Fiction",
+          "Adventure"
         ],
-        "vote_count": 16056,
-        "poster_path": "https://image.tmdb.org/t/p/w500/7WsyChQLEftFiDOVTGkv3hFpyyt.jpg"
+        "poster_path": "https://image.tmdb.org/t/p/w500/78lPtwv72eTNqFW9COBYI0dWDJa.jpg",
+        "vote_count": 17015
       },
       {
-        "id": 299534,
-        "popularity": 38.659,
-        "vote_average": 8.3,
-        "title": "Avengers: Endgame",
-        "tagline": "Part of the journey is the end.",
-        "overview": "After the devastating events of Avengers: Infinity War, the universe is in ruins due to the efforts of the Mad Titan, Thanos. With the help of remaining allies, the Avengers must assemble once more in order to undo Thanos' actions and restore order to the universe once and for all, no matter what consequences may be in store.",
+        "id": 271110,
+        "popularity": 37.431,
+        "vote_average": 7.4,
+        "title": "Captain America: Civil War",
+        "tagline": "Divided We Fall",
+        "overview": "Following the events of Age of Ultron, the collective governments of the world pass an act designed to regulate all superhuman activity. This polarizes opinion amongst the Avengers, causing two factions to side with Iron Man or Captain America, which causes an epic battle between former allies.",
         "director": "Anthony Russo",
         "producer": "Kevin Feige",
         "genres": [
           "Adventure",
-          "Science Fiction",
-          "Action"
+          "Action",
+          "Science Fiction"
         ],
-        "vote_count": 10497,
-        "poster_path": "https://image.tmdb.org/t/p/w500/or06FN3Dka5tukK1e9sl16pB3iy.jpg"
+        "poster_path": "https://image.tmdb.org/t/p/w500/kSBXou5Ac7vEqKd97wotJumyJvU.jpg",
+        "vote_count": 15079
       },
       {
-        "id": 99861,
-        "popularity": 33.938,
-        "vote_average": 7.3,
-        "title": "Avengers: Age of Ultron",
-        "tagline": "A New Age Has Come.",
-        "overview": "When Tony Stark tries to jumpstart a dormant peacekeeping program, things go awry and Earth’s Mightiest Heroes are put to the ultimate test as the fate of the planet hangs in the balance. As the villainous Ultron emerges, it is up to The Avengers to stop him from enacting his terrible plans, and soon uneasy alliances and unexpected action pave the way for an epic and unique global adventure.",
-        "director": "Joss Whedon",
+        "id": 10138,
+        "popularity": 26.929,
+        "vote_average": 6.8,
+        "title": "Iron Man 2",
+        "tagline": "It's not the armor that makes the hero, but the man inside.",
+        "overview": "With the world now aware of his dual life as the armored superhero Iron Man, billionaire inventor Tony Stark faces pressure from the government, the press and the public to share his technology with the military. Unwilling to let go of his invention, Stark, with Pepper Potts and James 'Rhodey' Rhodes at his side, must forge new alliances – and confront powerful enemies.",
+        "director": "Jon Favreau",
         "producer": "Kevin Feige",
         "genres": [
-          "Action",
           "Adventure",
+          "Action",
           "Science Fiction"
         ],
-        "vote_count": 14661,
-        "poster_path": "https://image.tmdb.org/t/p/w500/t90Y3G8UGQp0f0DrP60wRu9gfrH.jpg"
+        "poster_path": "https://image.tmdb.org/t/p/w500/1LoT5WsN4Lc1aR7A18ciSA1LoMy.jpg",
+        "vote_count": 13395
       }
     ]);
 
@@ -677,3 +678,46 @@ async fn search_with_settings_searchable_attributes_2() {
     let (response, _status_code) = server.search(query).await;
     assert_json_eq!(expect, response["hits"].clone(), ordered: false);
 }
+
+#[actix_rt::test]
+async fn search_never_returns_the_attributes_not_displayed() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!([
+        { "id": 1, "title": "Blue shoes", "price": 42 },
+    ]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    let config = json!({
+        "displayedAttributes": ["title"],
+    });
+    server.update_all_settings(config).await;
+
+    let queries = [
+        "q=shoes",
+        "q=shoes&attributesToRetrieve=*",
+        "q=shoes&attributesToRetrieve=title,price",
+        "q=shoes&attributesToHighlight=*&attributesToCrop=*&matches=true",
+        "q=shoes&attributesToRetrieve=title&attributesToHighlight=price",
+    ];
+
+    for query in queries.iter() {
+        let (response, status_code) = server.search(query).await;
+        assert_eq!(status_code, 200);
+        let hit = &response["hits"][0];
+        assert_eq!(hit["title"], json!("Blue shoes"), "{}", query);
+        assert!(hit.get("price").is_none(), "{}", query);
+        if let Some(formatted) = hit.get("_formatted") {
+            assert!(formatted.get("price").is_none(), "{}", query);
+        }
+        if let Some(matches_info) = hit.get("_matchesInfo") {
+            assert!(matches_info.get("price").is_none(), "{}", query);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+mod common;
+
+/// Keeps the warnings that are logged, to check what they say.
+struct WarningsLogger {
+    warnings: Mutex<Vec<String>>,
+}
+
+impl WarningsLogger {
+    fn contains(&self, needle: &str) -> bool {
+        self.warnings.lock().unwrap().iter().any(|warning| warning.contains(needle))
+    }
+}
+
+impl Log for WarningsLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.warnings.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[actix_rt::test]
+async fn unknown_attributes_warnings_name_their_parameter() {
+    let logger: &'static WarningsLogger = Box::leak(Box::new(WarningsLogger { warnings: Mutex::new(Vec::new()) }));
+    log::set_logger(logger).unwrap();
+    log::set_max_level(LevelFilter::Warn);
+
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    server.search("q=captain&attributesToRetrieve=unknown_retrieve").await;
+    assert!(logger.contains(r#""unknown_retrieve" present in attributesToRetrieve parameter"#));
+
+    server.search("q=captain&attributesToCrop=unknown_crop").await;
+    assert!(logger.contains(r#""unknown_crop" present in attributesToCrop parameter"#));
+
+    server.search("q=captain&attributesToHighlight=unknown_highlight").await;
+    assert!(logger.contains(r#""unknown_highlight" present in attributesToHighlight parameter"#));
+}
@@ -436,3 +436,314 @@ async fn write_setting_and_update_partial() {
 
     assert_json_eq!(expected, response, ordered: false);
 }
+
+#[actix_rt::test]
+async fn max_fields_count_limits_new_fields() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    // 1 - Limit the schema to two fields
+
+    let body = json!({
+        "maxFieldsCount": 2,
+    });
+    server.update_all_settings(body).await;
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["maxFieldsCount"], json!(2));
+
+    // 2 - Add a document with more fields than allowed
+
+    let body = json!([{
+        "id": 1,
+        "title": "Carol",
+        "description": "ignored",
+        "color": "ignored",
+    }]);
+    server.add_or_replace_multiple_documents(body).await;
+
+    // 3 - Check that only the first fields have been kept
+
+    let (response, status_code) = server.get_index_stats().await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["fieldCount"], json!(2));
+
+    let (response, status_code) = server.get_document(1).await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response, json!({ "id": 1, "title": "Carol" }));
+
+    // 4 - Remove the limit
+
+    server.delete_all_settings().await;
+
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response.get("maxFieldsCount"), None);
+}
+
+#[actix_rt::test]
+async fn preview_settings_does_not_persist() {
+    let mut server = common::Server::with_uid("movies");
+    server.populate_movies().await;
+
+    let (settings_before, _status_code) = server.get_all_settings().await;
+    let (stats, _status_code) = server.get_index_stats().await;
+
+    // 1 - Preview a setting that reindexes the documents and one that does not
+
+    let body = json!({
+        "stopWords": ["the", "of"],
+        "distinctAttribute": "id",
+    });
+    let (response, status_code) = server
+        .post_request("/indexes/movies/settings/preview", body)
+        .await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["documentsToReindex"], stats["numberOfDocuments"]);
+    assert!(response["estimatedDurationMs"].is_u64());
+    assert_json_eq!(response["affectedSettings"].clone(), json!(["distinctAttribute", "stopWords"]), ordered: false);
+
+    // 2 - Values equal to the current ones are not affected
+
+    let body = json!({ "distinctAttribute": settings_before["distinctAttribute"].clone() });
+    let (response, status_code) = server
+        .post_request("/indexes/movies/settings/preview", body)
+        .await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response["documentsToReindex"], json!(0));
+    assert_eq!(response["affectedSettings"], json!([]));
+
+    // 3 - Nothing has been enqueued nor changed
+
+    let (settings_after, _status_code) = server.get_all_settings().await;
+    assert_json_eq!(settings_before, settings_after, ordered: false);
+
+    let (updates, _status_code) = server.get_all_updates_status().await;
+    assert_eq!(updates.as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn synonyms_are_normalized() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "TV": ["television", "Television", "TELEVISION"],
+        "tv": ["telly"],
+    });
+    server.update_synonyms(body).await;
+
+    let (response, _status_code) = server.get_synonyms().await;
+    assert_json_eq!(response, json!({ "tv": ["television", "telly"] }), ordered: false);
+
+    let (response, _status_code) = server.get_all_updates_status().await;
+    let update = response.as_array().unwrap().last().unwrap();
+    assert_eq!(update["warnings"], json!(["synonyms: the synonyms of `TV`, `tv` were merged under `tv`"]));
+}
+
+#[actix_rt::test]
+async fn get_and_delete_the_synonyms_of_a_word() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    let body = json!({
+        "nyc": ["new york", "big apple"],
+        "tv": ["television"],
+    });
+    server.update_synonyms(body).await;
+
+    let (response, status_code) = server.get_request("/indexes/test/synonyms/NYC").await;
+    assert_eq!(status_code, 200);
+    assert_eq!(response, json!(["big apple", "new york"]));
+
+    let (_response, status_code) = server.get_request("/indexes/test/synonyms/paris").await;
+    assert_eq!(status_code, 404);
+
+    let (_response, status_code) = server.delete_request_async("/indexes/test/synonyms/nyc").await;
+    assert_eq!(status_code, 202);
+
+    let (response, _status_code) = server.get_synonyms().await;
+    assert_eq!(response, json!({ "tv": ["television"] }));
+
+    let (_response, status_code) = server.delete_request("/indexes/test/synonyms/nyc").await;
+    assert_eq!(status_code, 404);
+}
+
+#[actix_rt::test]
+async fn import_synonyms_from_csv() {
+    let mut server = common::Server::with_uid("test");
+
+    let body = json!({
+        "uid": "test",
+        "primaryKey": "id",
+    });
+    server.create_index(body).await;
+
+    server.update_synonyms(json!({ "tv": ["television"] })).await;
+
+    let (_response, status_code) = server.post_request("/indexes/test/synonyms/import", json!({})).await;
+    assert_eq!(status_code, 415);
+
+    let csv = "tv,television\nnyc,→,new york,big apple\nlonely\n";
+    let (response, status_code) = server.import_synonyms_csv(csv).await;
+    assert_eq!(status_code, 202);
+    assert_eq!(response["malformedRows"], json!([3]));
+
+    let (response, _status_code) = server.get_update_status(response["updateId"].as_u64().unwrap()).await;
+    assert_eq!(response["status"], "processed", "{}", response);
+    assert_eq!(response["warnings"], json!(["synonyms: 3 synonyms imported, 1 duplicates skipped"]));
+
+    let (response, _status_code) = server.get_synonyms().await;
+    let expected = json!({
+        "nyc": ["big apple", "new york"],
+        "television": ["tv"],
+        "tv": ["television"],
+    });
+    assert_json_eq!(expected, response, ordered: false);
+}
+
+#[actix_rt::test]
+async fn processed_updates_are_sent_to_the_webhook() {
+    use std::io::{Read, Write};
+
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+
+    let (response, status_code) = server.post_request("/indexes/test/settings", json!({ "webhookUrl": "ftp://example.com" })).await;
+    assert_eq!(status_code, 400, "{}", response);
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/updates", listener.local_addr().unwrap());
+
+    // the webhooks of the local network are refused unless they are allowed
+    let (response, status_code) = server.post_request("/indexes/test/settings", json!({ "webhookUrl": url })).await;
+    assert_eq!(status_code, 400, "{}", response);
+
+    let mut server = common::Server::with_uid_and_options("test", |opt| opt.allow_private_webhook_urls = true);
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            // reads the request until its whole body, as given by its Content-Length, is received
+            loop {
+                let read = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let length = text[..end]
+                        .lines()
+                        .find(|line| line.to_lowercase().starts_with("content-length:"))
+                        .and_then(|line| line[15..].trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if read == 0 || text.len() >= end + 4 + length {
+                        let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+                        let _ = sender.send(text[end + 4..].to_string());
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    server.update_all_settings(json!({ "webhookUrl": url })).await;
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["webhookUrl"], json!(url));
+
+    let (response, status_code) = server.post_request("/indexes/test/documents", json!([{ "id": 1, "title": "Carol" }])).await;
+    assert_eq!(status_code, 202);
+    let update_id = response["updateId"].as_u64().unwrap();
+    server.wait_update_id(update_id).await;
+
+    // the settings update that set the webhook is also sent to it
+    let body = loop {
+        let body = receiver.recv_timeout(std::time::Duration::from_secs(10)).unwrap();
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        if body["updateId"] == json!(update_id) {
+            break body;
+        }
+    };
+    assert_eq!(body["status"], json!("processed"));
+    assert_eq!(body["indexUid"], json!("test"));
+}
+
+#[actix_rt::test]
+async fn embedder_is_set_without_returning_its_api_key() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+
+    let embedder = json!({ "source": "openai", "model": "text-embedding-3-small", "dimensions": 0, "apiKey": "sk-test" });
+    let (response, status_code) = server.post_request("/indexes/test/settings", json!({ "embedder": embedder })).await;
+    assert_eq!(status_code, 400, "{}", response);
+
+    let embedder = json!({ "source": "openai", "model": "text-embedding-3-small", "dimensions": 1536 });
+    let (response, status_code) = server.post_request("/indexes/test/settings", json!({ "embedder": embedder })).await;
+    assert_eq!(status_code, 400, "{}", response);
+
+    let embedder = json!({ "source": "openai", "model": "text-embedding-3-small", "dimensions": 1536, "apiKey": "sk-test" });
+    server.update_all_settings(json!({ "embedder": embedder })).await;
+
+    let expected = json!({
+        "source": "openai",
+        "model": "text-embedding-3-small",
+        "dimensions": 1536,
+        "documentEmbeddingField": "_vectors.default",
+        "apiKey": null,
+    });
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["embedder"], expected);
+
+    // the API key is neither in the updates nor sent to the webhook
+    let (response, _status_code) = server.get_all_updates_status().await;
+    assert!(!response.to_string().contains("sk-test"), "{}", response);
+
+    // the embeddings are neither searchable nor displayed
+    server.post_request("/indexes/test/settings", json!({ "embeddingStrategy": "lazy" })).await;
+    server.add_or_replace_multiple_documents(json!([{ "id": 1, "title": "hello", "_vectors": { "default": [42] } }])).await;
+
+    let (response, _status_code) = server.get_document(1).await;
+    assert_eq!(response, json!({ "id": 1, "title": "hello" }));
+
+    let (response, _status_code) = server.search("q=42").await;
+    assert_eq!(response["hits"], json!([]));
+
+    server.delete_all_settings().await;
+    let (response, _status_code) = server.get_all_settings().await;
+    assert!(response.get("embedder").is_none());
+}
+
+#[actix_rt::test]
+async fn embedding_strategy_is_eager_or_lazy() {
+    let mut server = common::Server::with_uid("test");
+    server.create_index(json!({ "uid": "test", "primaryKey": "id" })).await;
+
+    let (response, status_code) = server.post_request("/indexes/test/settings", json!({ "embeddingStrategy": "sometimes" })).await;
+    assert_eq!(status_code, 400, "{}", response);
+
+    let embedder = json!({ "source": "openai", "model": "text-embedding-3-small", "dimensions": 1536, "apiKey": "sk-test" });
+    server.update_all_settings(json!({ "embedder": embedder, "embeddingStrategy": "lazy" })).await;
+    let (response, _status_code) = server.get_all_settings().await;
+    assert_eq!(response["embeddingStrategy"], json!("lazy"));
+
+    // the documents are not embedded when they are added
+    let body = json!([{ "id": 1, "title": "Carol" }]);
+    server.add_or_replace_multiple_documents(body).await;
+    let (response, _status_code) = server.get_document(1).await;
+    assert_eq!(response, json!({ "id": 1, "title": "Carol" }));
+}
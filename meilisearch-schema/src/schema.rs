@@ -83,6 +83,10 @@ impl Schema {
         self.fields_map.iter().map(|(k, _)| k.as_ref())
     }
 
+    pub fn fields_count(&self) -> usize {
+        self.fields_map.len()
+    }
+
     pub fn contains(&self, name: &str) -> bool {
         self.fields_map.id(name).is_some()
     }